@@ -1,8 +1,10 @@
 use criterion::{criterion_group, criterion_main};
 
 mod benchmarks;
+use benchmarks::propagation_loop::propagation_loop_benchmarks;
 use benchmarks::rate_functions::constant_rate_benchmarks;
 
 criterion_group!(rate_benches, constant_rate_benchmarks,);
+criterion_group!(propagation_benches, propagation_loop_benchmarks,);
 
-criterion_main!(rate_benches);
+criterion_main!(rate_benches, propagation_benches);
@@ -1 +1,3 @@
+pub mod propagation_loop;
 pub mod rate_functions;
+pub mod setup;
@@ -0,0 +1,41 @@
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput};
+use epi_isolation::infectiousness_manager::get_cumulative_infections;
+use std::hint::black_box;
+
+use super::setup::setup_propagation_context;
+
+fn run_propagation_loop(population_size: usize) -> usize {
+    let mut context = setup_propagation_context(population_size);
+    context.execute();
+    get_cumulative_infections(&context)
+}
+
+pub fn propagation_loop_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("infection_propagation_loop::full_loop");
+    // Each iteration runs a whole simulation, so fewer samples keep the benchmark quick
+    group.sample_size(10);
+    for population_size in [1_000, 5_000, 20_000] {
+        // The seed is fixed, so every iteration of a population size has this many infections and
+        // the throughput is reported in infections per second
+        let infections = run_propagation_loop(population_size);
+        group.throughput(Throughput::Elements(
+            u64::try_from(infections).expect("Infection count fits in a u64"),
+        ));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(population_size),
+            &population_size,
+            |b, &population_size| {
+                // Only the propagation loop is timed, not building the population
+                b.iter_batched(
+                    || setup_propagation_context(population_size),
+                    |mut context| {
+                        context.execute();
+                        black_box(get_cumulative_infections(&context))
+                    },
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
@@ -0,0 +1,69 @@
+use epi_isolation::{
+    infection_propagation_loop,
+    parameters::{
+        CoreSettingsTypes, GlobalParams, ItinerarySpecificationType, Params, RateFnType,
+        SeedingMode,
+    },
+    settings::{
+        self, CensusTract, ContextSettingExt, Home, ItineraryEntry, SettingId, SettingProperties,
+        Workplace,
+    },
+};
+use ixa::{Context, ContextGlobalPropertiesExt, ContextPeopleExt, ContextRandomExt};
+
+const HOME_SIZE: usize = 4;
+const WORKPLACE_SIZE: usize = 20;
+const CENSUS_TRACT_SIZE: usize = 1_000;
+
+/// The simulated time over which the propagation loop runs in each benchmark iteration
+pub const PROPAGATION_HORIZON: f64 = 30.0;
+
+/// Build a context with `population_size` people who are each a member of a home, a workplace,
+/// and a census tract, ready to run the infection propagation loop. The seed is fixed so that
+/// every context of the same size produces the same epidemic.
+pub fn setup_propagation_context(population_size: usize) -> Context {
+    let mut context = Context::new();
+    let setting_properties = |alpha: f64, ratio: f64| SettingProperties {
+        alpha,
+        itinerary_specification: Some(ItinerarySpecificationType::Constant { ratio }),
+    };
+    context
+        .set_global_property_value(
+            GlobalParams,
+            Params {
+                seed: 42,
+                max_time: PROPAGATION_HORIZON,
+                infectiousness_rate_fn: RateFnType::Constant {
+                    rate: 0.2,
+                    duration: 5.0,
+                },
+                initial_infection_seeding: Some(SeedingMode::ExactCount(10)),
+                settings_properties: [
+                    (CoreSettingsTypes::Home, setting_properties(0.5, 0.5)),
+                    (CoreSettingsTypes::Workplace, setting_properties(0.5, 0.3)),
+                    (CoreSettingsTypes::CensusTract, setting_properties(0.1, 0.2)),
+                ]
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            },
+        )
+        .expect("Valid benchmark parameters");
+    context.init_random(42);
+    settings::init(&mut context).expect("Settings can be initialized");
+    for i in 0..population_size {
+        let person = context.add_person(()).expect("Person can be added");
+        context
+            .add_itinerary(
+                person,
+                vec![
+                    ItineraryEntry::new(SettingId::new(Home, i / HOME_SIZE), 0.5),
+                    ItineraryEntry::new(SettingId::new(Workplace, i / WORKPLACE_SIZE), 0.3),
+                    ItineraryEntry::new(SettingId::new(CensusTract, i / CENSUS_TRACT_SIZE), 0.2),
+                ],
+            )
+            .expect("Itinerary can be added");
+    }
+    infection_propagation_loop::init(&mut context).expect("Propagation loop can be initialized");
+    context
+}
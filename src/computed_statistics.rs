@@ -41,8 +41,24 @@
 //! ```
 //!
 
-use ixa::profiling::{
-    add_computed_statistic, CustomStatisticComputer, CustomStatisticPrinter, ProfilingData,
+use ixa::{
+    profiling::{
+        add_computed_statistic, CustomStatisticComputer, CustomStatisticPrinter, ProfilingData,
+    },
+    Context, ContextPeopleExt,
+};
+
+use crate::{
+    hospitalizations::{get_hospital_admission_times, get_peak_hospital_census},
+    infectiousness_manager::{
+        get_age_transmission_matrix, get_cumulative_infections, get_infection_times,
+        get_infector_setting_counts, get_longest_transmission_chain, get_offspring_distribution,
+        get_setting_attack_rates, get_setting_peak_infectious, get_setting_rt,
+    },
+    policies::previous_guidance::{
+        get_time_from_infection_to_isolation_delays, get_time_to_isolation_delays,
+    },
+    utils::{doubling_time, linear_interpolation},
 };
 
 /// The name of the distinguished accepted infection label. You don't need to make a constant
@@ -94,3 +110,268 @@ pub fn init() {
     // Use the free function in the `profiling` module to register the statistic.
     add_computed_statistic(label, description, computer, printer);
 }
+
+/// The quantiles of the delay from symptom onset to isolation that are summarized at the end of
+/// the simulation.
+pub const TIME_TO_ISOLATION_QUANTILES: [f64; 5] = [0.0, 0.25, 0.5, 0.75, 1.0];
+
+/// Computes the `TIME_TO_ISOLATION_QUANTILES` of the delays from symptom onset to isolation,
+/// linearly interpolating between the sorted delays. Returns `None` if nobody isolated.
+///
+/// Unlike the statistics above, this is computed from data collected on the context rather than
+/// from `ProfilingData`, so it is not registered with `add_computed_statistic`.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub fn time_to_isolation_quantiles(delays: &[f64]) -> Option<Vec<(f64, f64)>> {
+    if delays.is_empty() {
+        return None;
+    }
+    let mut sorted = delays.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let max_index = (sorted.len() - 1) as f64;
+    Some(
+        TIME_TO_ISOLATION_QUANTILES
+            .iter()
+            .map(|&q| {
+                let position = q * max_index;
+                let lower = position.floor();
+                let upper = position.ceil();
+                // Returns the (equal) sorted value when the position lands exactly on an index
+                let value = linear_interpolation(
+                    lower,
+                    upper,
+                    sorted[lower as usize],
+                    sorted[upper as usize],
+                    position,
+                );
+                (q, value)
+            })
+            .collect(),
+    )
+}
+
+/// Prints the summary quantiles of the delays from symptom onset and from infection to isolation.
+/// This is called from the `main` function in `src/main.rs` once the simulation has finished.
+pub fn print_time_to_isolation_summary(context: &Context) {
+    if let Some(quantiles) = time_to_isolation_quantiles(get_time_to_isolation_delays(context)) {
+        println!("Time from Symptom Onset to Isolation:");
+        for (q, value) in quantiles {
+            println!("  {:>3.0}th percentile: {:.2}", q * 100.0, value);
+        }
+    }
+    // The full detection lag, which also includes the incubation period
+    if let Some(quantiles) =
+        time_to_isolation_quantiles(get_time_from_infection_to_isolation_delays(context))
+    {
+        println!("Time from Infection to Isolation:");
+        for (q, value) in quantiles {
+            println!("  {:>3.0}th percentile: {:.2}", q * 100.0, value);
+        }
+    }
+}
+
+/// Prints the peak hospital census (the largest number of people in the hospital at the same
+/// time) and when it was first reached. This is called from the `main` function in `src/main.rs`
+/// once the simulation has finished.
+pub fn print_peak_hospital_census_summary(context: &Context) {
+    if let Some((peak, peak_time)) = get_peak_hospital_census(context) {
+        println!("Peak Hospital Census: {peak} at time {peak_time:.2}");
+    }
+}
+
+/// Prints the doubling times of infections and hospital admissions, each fit over its own growth
+/// phase, if their times were tracked (see the `doubling_time_summary` parameter). Admissions lag
+/// infections, so the two differ while the dynamics are changing but agree during steady
+/// exponential growth. This is called from the `main` function in `src/main.rs` once the
+/// simulation has finished.
+pub fn print_doubling_time_summary(context: &Context) {
+    let infection_doubling_time = doubling_time(get_infection_times(context));
+    let hospitalization_doubling_time = doubling_time(get_hospital_admission_times(context));
+    if infection_doubling_time.is_none() && hospitalization_doubling_time.is_none() {
+        return;
+    }
+    println!("Doubling Time:");
+    for (series, doubling_time) in [
+        ("Infections", infection_doubling_time),
+        ("Hospitalizations", hospitalization_doubling_time),
+    ] {
+        match doubling_time {
+            Some(doubling_time) => println!("  {series}: {doubling_time:.2}"),
+            None => println!("  {series}: not growing"),
+        }
+    }
+}
+
+/// Prints the within-setting Rt of each setting type, if transmission by setting type was tracked
+/// (see the `setting_rt_summary` parameter). This is called from the `main` function in
+/// `src/main.rs` once the simulation has finished.
+pub fn print_setting_rt_summary(context: &Context) {
+    let setting_rt = get_setting_rt(context);
+    if setting_rt.is_empty() {
+        return;
+    }
+    println!("Within-Setting Rt:");
+    for (setting_type, rt) in setting_rt {
+        println!("  {setting_type}: {rt:.2}");
+    }
+}
+
+/// Prints the distribution of the number of distinct settings infectors were active in at the
+/// time of each transmission, which characterizes how much infectors mix across settings. This is
+/// called from the `main` function in `src/main.rs` once the simulation has finished.
+pub fn print_infector_setting_count_summary(context: &Context) {
+    let counts = get_infector_setting_counts(context);
+    if counts.is_empty() {
+        return;
+    }
+    println!("Distinct Active Settings of Infectors at Transmission:");
+    for (settings, transmissions) in counts {
+        println!("  {settings} setting(s): {transmissions} transmission(s)");
+    }
+}
+
+/// Prints the realized attack rate of each setting type, the proportion of its members who were
+/// ever infected. This is called from the `main` function in `src/main.rs` once the simulation has
+/// finished.
+pub fn print_setting_attack_rate_summary(context: &Context) {
+    let attack_rates = get_setting_attack_rates(context);
+    if attack_rates.is_empty() {
+        return;
+    }
+    println!("Attack Rate by Setting Type:");
+    for (setting_type, attack_rate) in attack_rates {
+        println!("  {setting_type}: {:.2}%", attack_rate * 100.0);
+    }
+}
+
+/// The number of settings with the highest peaks printed in the setting peak infectious summary
+pub const SETTING_PEAK_INFECTIOUS_COUNT: usize = 10;
+
+/// Prints the settings with the most simultaneously infectious members, with the peak number of
+/// infectious members and when it was first reached, if peaks were tracked (see the
+/// `setting_peak_infectious_summary` parameter). This is called from the `main` function in
+/// `src/main.rs` once the simulation has finished.
+pub fn print_setting_peak_infectious_summary(context: &Context) {
+    let peaks = get_setting_peak_infectious(context);
+    if peaks.is_empty() {
+        return;
+    }
+    println!("Peak Concurrent Infectious Members by Setting:");
+    for (setting_type, setting_id, peak, peak_time) in
+        peaks.into_iter().take(SETTING_PEAK_INFECTIOUS_COUNT)
+    {
+        println!("  {setting_type} {setting_id}: {peak} at time {peak_time:.2}");
+    }
+}
+
+/// The width in years of the age groups of the printed age-by-age transmission matrix
+pub const AGE_TRANSMISSION_MATRIX_GROUP_WIDTH: u8 = 10;
+
+/// Prints the number of transmissions by the age group of the infectee (rows) and the age group
+/// of the infector (columns), if transmissions by age were tracked (see the
+/// `age_transmission_matrix` parameter). This is called from the `main` function in
+/// `src/main.rs` once the simulation has finished.
+pub fn print_age_transmission_matrix_summary(context: &Context) {
+    let matrix = get_age_transmission_matrix(context);
+    if matrix.is_empty() {
+        return;
+    }
+    let group = |age: u8| usize::from(age / AGE_TRANSMISSION_MATRIX_GROUP_WIDTH);
+    let num_groups = group(u8::MAX) + 1;
+    let mut counts = vec![vec![0usize; num_groups]; num_groups];
+    for (infectee_age, infector_age, count) in matrix {
+        counts[group(infectee_age)][group(infector_age)] += count;
+    }
+    // Only print the age groups from the youngest to the oldest with any transmissions
+    let has_transmissions =
+        |g: usize| counts[g].iter().any(|&c| c > 0) || counts.iter().any(|row| row[g] > 0);
+    let groups: Vec<usize> = (0..num_groups).filter(|&g| has_transmissions(g)).collect();
+    let (first, last) = (groups[0], groups[groups.len() - 1]);
+    let label = |g: usize| {
+        let width = usize::from(AGE_TRANSMISSION_MATRIX_GROUP_WIDTH);
+        format!("{}-{}", g * width, (g + 1) * width - 1)
+    };
+    println!("Transmissions by Infectee Age (rows) and Infector Age (columns):");
+    let header: String = (first..=last).map(|g| format!("{:>8}", label(g))).collect();
+    println!("  {:>8}{header}", "");
+    for (infectee_group, infectee_counts) in counts.iter().enumerate().take(last + 1).skip(first) {
+        let row: String = infectee_counts[first..=last]
+            .iter()
+            .map(|count| format!("{count:>8}"))
+            .collect();
+        println!("  {:>8}{row}", label(infectee_group));
+    }
+}
+
+/// Prints the offspring distribution, the number of infections that caused each number of
+/// secondary cases, and its mean, if secondary cases were tracked (see the
+/// `offspring_distribution_summary` parameter). This is called from the `main` function in
+/// `src/main.rs` once the simulation has finished.
+pub fn print_offspring_distribution_summary(context: &Context) {
+    let distribution = get_offspring_distribution(context);
+    if distribution.is_empty() {
+        return;
+    }
+    let infections: usize = distribution.iter().map(|&(_, count)| count).sum();
+    let secondary_cases: usize = distribution
+        .iter()
+        .map(|&(cases, count)| cases * count)
+        .sum();
+    println!("Offspring Distribution (Secondary Cases per Infection):");
+    for (cases, count) in distribution {
+        println!("  {cases} secondary case(s): {count} infection(s)");
+    }
+    println!("  Mean: {:.2}", secondary_cases as f64 / infections as f64);
+}
+
+/// The z-score of the 95% confidence interval of the final attack rate
+const ATTACK_RATE_CONFIDENCE_Z: f64 = 1.96;
+
+/// Computes the final attack rate, the proportion of the population ever infected, with a 95%
+/// confidence interval from the normal approximation to the binomial distribution, clamped to
+/// [0, 1]. This treats each person's infection in a single stochastic run as an independent
+/// trial, so it reflects the uncertainty from the finite population size only. Returns `None` if
+/// the population is empty.
+#[allow(clippy::cast_precision_loss)]
+pub fn attack_rate_confidence_interval(
+    infections: usize,
+    population: usize,
+) -> Option<(f64, f64, f64)> {
+    if population == 0 {
+        return None;
+    }
+    let attack_rate = infections as f64 / population as f64;
+    let half_width =
+        ATTACK_RATE_CONFIDENCE_Z * (attack_rate * (1.0 - attack_rate) / population as f64).sqrt();
+    Some((
+        attack_rate,
+        (attack_rate - half_width).max(0.0),
+        (attack_rate + half_width).min(1.0),
+    ))
+}
+
+/// Prints the final size of the epidemic, the proportion of the population ever infected with a
+/// 95% confidence interval, and the length of the longest transmission chain. This is called from
+/// the `main` function in `src/main.rs` once the simulation has finished.
+pub fn print_final_size_summary(context: &Context) {
+    let Some((attack_rate, lower, upper)) = attack_rate_confidence_interval(
+        get_cumulative_infections(context),
+        context.get_current_population(),
+    ) else {
+        return;
+    };
+    println!("Final Size:");
+    println!(
+        "  Attack rate: {:.2}% (95% CI {:.2}%-{:.2}%)",
+        attack_rate * 100.0,
+        lower * 100.0,
+        upper * 100.0
+    );
+    println!(
+        "  Longest transmission chain: {} generation(s)",
+        get_longest_transmission_chain(context)
+    );
+}
@@ -1,20 +1,102 @@
 use ixa::prelude::*;
-use ixa::{trace, PersonPropertyChangeEvent};
-use rand_distr::Exp;
+use ixa::{define_data_plugin, trace, PersonPropertyChangeEvent};
+use rand_distr::{Exp, Gamma, LogNormal};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    parameters::{ContextParametersExt, GlobalParams, Params},
-    population_loader::Age,
-    symptom_progression::PresentingWithSymptoms,
+    parameters::{ContextParametersExt, DelayDistribution, GlobalParams, Params},
+    population_loader::{Age, Alive},
+    settings::{ContextSettingExt, Hospital, SettingId},
+    symptom_progression::{PresentingWithSymptoms, SymptomRecord},
 };
 
 define_person_property_with_default!(Hospitalized, bool, false);
+// Whether a person's hospital arrival has been planned but has not happened yet
+define_person_property_with_default!(HospitalArrivalPlanned, bool, false);
+
+// The running hospital census and the first time it reached its peak
+#[derive(Default)]
+struct HospitalCensusContainer {
+    census: usize,
+    peak: usize,
+    peak_time: f64,
+}
+
+define_data_plugin!(
+    HospitalCensusPlugin,
+    HospitalCensusContainer,
+    HospitalCensusContainer::default()
+);
+
+/// Get the peak number of people in the hospital at the same time and the first time the peak was
+/// reached. Returns `None` if nobody has been hospitalized.
+pub fn get_peak_hospital_census(context: &Context) -> Option<(usize, f64)> {
+    let container = context.get_data(HospitalCensusPlugin);
+    if container.peak == 0 {
+        return None;
+    }
+    Some((container.peak, container.peak_time))
+}
+
+fn update_hospital_census(context: &mut Context, admitted: bool) {
+    let current_time = context.get_current_time();
+    let container = context.get_data_mut(HospitalCensusPlugin);
+    if admitted {
+        container.census += 1;
+        if container.census > container.peak {
+            container.peak = container.census;
+            container.peak_time = current_time;
+        }
+    } else {
+        container.census -= 1;
+    }
+}
+
+// The census is admissions minus discharges minus deaths of people in the hospital
+fn track_hospital_census(context: &mut Context) {
+    context.subscribe_to_event(|context, event: PersonPropertyChangeEvent<Hospitalized>| {
+        // People who died in the hospital have already been removed from the census
+        if context.get_person_property(event.person_id, Alive) {
+            update_hospital_census(context, event.current);
+        }
+    });
+    context.subscribe_to_event(|context, event: PersonPropertyChangeEvent<Alive>| {
+        if !event.current && context.get_person_property(event.person_id, Hospitalized) {
+            update_hospital_census(context, false);
+        }
+    });
+}
+
+// The time of every hospital admission
+define_data_plugin!(HospitalAdmissionTimesPlugin, Vec<f64>, Vec::new());
+
+fn track_hospital_admission_times(context: &mut Context) {
+    context.subscribe_to_event(|context, event: PersonPropertyChangeEvent<Hospitalized>| {
+        if event.current {
+            let current_time = context.get_current_time();
+            context
+                .get_data_mut(HospitalAdmissionTimesPlugin)
+                .push(current_time);
+        }
+    });
+}
+
+/// Get the time of every hospital admission in the order they occurred, if admission times were
+/// tracked (see the `doubling_time_summary` parameter).
+pub fn get_hospital_admission_times(context: &Context) -> &[f64] {
+    context.get_data(HospitalAdmissionTimesPlugin)
+}
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
 pub struct HospitalAgeGroups {
     pub min: u8,
     pub probability: f64,
+    /// The distribution of the delay from symptom onset to hospitalization for this age group. If
+    /// not specified, the delay is exponential with mean `mean_delay_to_hospitalization`.
+    pub delay_to_hospitalization: Option<DelayDistribution>,
+    /// The distribution of the duration of hospitalization for this age group. If not specified,
+    /// the duration is exponential with mean `mean_duration_of_hospitalization`.
+    pub duration_of_hospitalization: Option<DelayDistribution>,
 }
 
 define_rng!(HospitalizationRng);
@@ -43,8 +125,27 @@ define_derived_property!(
 );
 
 trait ContextHospitalizationInternalExt:
-    PluginContext + ContextRandomExt + ContextPeopleExt + ContextParametersExt + ContextRandomExt
+    PluginContext
+    + ContextRandomExt
+    + ContextPeopleExt
+    + ContextParametersExt
+    + ContextRandomExt
+    + ContextSettingExt
 {
+    fn sample_delay(&self, distribution: DelayDistribution) -> f64 {
+        // Parameters are validated when they are loaded
+        match distribution {
+            DelayDistribution::Exponential { mean } => {
+                self.sample_distr(HospitalizationRng, Exp::new(1.0 / mean).unwrap())
+            }
+            DelayDistribution::Gamma { shape, scale } => {
+                self.sample_distr(HospitalizationRng, Gamma::new(shape, scale).unwrap())
+            }
+            DelayDistribution::LogNormal { mu, sigma } => {
+                self.sample_distr(HospitalizationRng, LogNormal::new(mu, sigma).unwrap())
+            }
+        }
+    }
     fn plan_hospital_arrival(&mut self, person_id: PersonId) -> Result<(), ixa::IxaError> {
         // get hospital parameters
         // evaluate hospitalization risk
@@ -53,13 +154,20 @@ trait ContextHospitalizationInternalExt:
             .get_params()
             .hospitalization_parameters
             .mean_delay_to_hospitalization;
-        let exp = Exp::new(1.0 / mean_delay_to_hospitalization).unwrap();
-        let duration = self.sample_distr(HospitalizationRng, exp);
+        let distribution = self
+            .get_person_property(person_id, HospitalAgeGroup)
+            .delay_to_hospitalization
+            .unwrap_or(DelayDistribution::Exponential {
+                mean: mean_delay_to_hospitalization,
+            });
+        let duration = self.scale_duration(self.sample_delay(distribution));
         trace!(
             "Planning hospital arrival for person {person_id} at {}",
             self.get_current_time() + duration
         );
+        self.set_person_property(person_id, HospitalArrivalPlanned, true);
         self.add_plan(self.get_current_time() + duration, move |context| {
+            context.set_person_property(person_id, HospitalArrivalPlanned, false);
             context.set_person_property(person_id, Hospitalized, true);
         });
         Ok(())
@@ -73,14 +181,39 @@ trait ContextHospitalizationInternalExt:
             .get_params()
             .hospitalization_parameters
             .mean_duration_of_hospitalization;
-        let exp = Exp::new(1.0 / mean_duration_of_hospitalization).unwrap();
-        let duration = self.sample_distr(HospitalizationRng, exp);
+        let distribution = self
+            .get_person_property(person_id, HospitalAgeGroup)
+            .duration_of_hospitalization
+            .unwrap_or(DelayDistribution::Exponential {
+                mean: mean_duration_of_hospitalization,
+            });
+        let duration = self.scale_duration(self.sample_delay(distribution));
         self.add_plan(self.get_current_time() + duration, move |context| {
             context.set_person_property(person_id, Hospitalized, false);
         });
         Ok(())
     }
 
+    /// Move a newly admitted person into the hospital ward, if one is specified, so that they are
+    /// no longer active in their home, workplace, or other settings during their stay.
+    fn admit_to_hospital_ward(&mut self, person_id: PersonId) -> Result<(), ixa::IxaError> {
+        if let Some(ward_id) = self.get_params().hospital_ward_id {
+            trace!("Person {person_id} is moving into hospital ward {ward_id}");
+            self.move_to_setting(person_id, SettingId::new(Hospital, ward_id))?;
+        }
+        Ok(())
+    }
+
+    /// Return a discharged person to their other settings, applying any itinerary modifier (e.g.,
+    /// isolation) that is still in place
+    fn discharge_from_hospital_ward(&mut self, person_id: PersonId) -> Result<(), ixa::IxaError> {
+        if self.get_params().hospital_ward_id.is_some() {
+            trace!("Person {person_id} is leaving the hospital ward");
+            self.return_from_setting(person_id)?;
+        }
+        Ok(())
+    }
+
     fn evaluate_hospitalization_risk(&mut self, person_id: PersonId) -> bool {
         // Evaluate the risk of hospitalization using the age group probabilities
         let p = self
@@ -98,11 +231,37 @@ trait ContextHospitalizationInternalExt:
                 }
             },
         );
-        // Subscribe to individuals being hospitalized to plan when they leave the hospital
+        // Symptoms that escalate from mild to moderate are evaluated again as if they had just
+        // started, unless the person is already in or on their way to the hospital
+        self.subscribe_to_event(
+            move |context, event: PersonPropertyChangeEvent<SymptomRecord>| {
+                if let (Some(previous), Some(current)) = (event.previous, event.current) {
+                    // Escalation marks the ongoing episode severe without changing its start time
+                    #[allow(clippy::float_cmp)]
+                    let escalated = !previous.severe
+                        && current.severe
+                        && previous.symptom_start == current.symptom_start;
+                    if escalated
+                        && !context.get_person_property(event.person_id, Hospitalized)
+                        && !context.get_person_property(event.person_id, HospitalArrivalPlanned)
+                        && context.evaluate_hospitalization_risk(event.person_id)
+                    {
+                        context.plan_hospital_arrival(event.person_id).unwrap();
+                    }
+                }
+            },
+        );
+        // Subscribe to individuals being hospitalized to plan when they leave the hospital, and
+        // to move them into and out of the hospital ward
         self.subscribe_to_event(
             move |context, event: PersonPropertyChangeEvent<Hospitalized>| {
                 if event.current {
                     context.plan_hospital_departure(event.person_id).unwrap();
+                    context.admit_to_hospital_ward(event.person_id).unwrap();
+                } else {
+                    context
+                        .discharge_from_hospital_ward(event.person_id)
+                        .unwrap();
                 }
             },
         );
@@ -118,6 +277,10 @@ pub fn init(context: &mut Context) {
         .any(|grp| grp.probability > 0.0);
     if initialization_check {
         context.setup_hospitalization_event_sequence();
+        track_hospital_census(context);
+        if context.get_params().doubling_time_summary.unwrap_or(false) {
+            track_hospital_admission_times(context);
+        }
     } else {
         trace!(
             "All hospitalization probabilities are zero. Hospitalizations module is not initialized."
@@ -127,20 +290,29 @@ pub fn init(context: &mut Context) {
 
 #[cfg(test)]
 mod test {
-    use super::Hospitalized;
+    use super::{get_peak_hospital_census, Hospitalized};
     use crate::{
         hospitalizations::{HospitalAgeGroup, HospitalAgeGroups},
-        parameters::{GlobalParams, HospitalizationParameters, ProgressionLibraryType},
+        parameters::{
+            CoreSettingsTypes, DelayDistribution, GlobalParams, HospitalizationParameters,
+            ItinerarySpecificationType, ProgressionLibraryType,
+        },
         population_loader::Age,
         rate_fns::load_rate_fns,
-        symptom_progression::{PresentingWithSymptoms, SymptomValue, Symptoms},
+        settings::{
+            ContextSettingExt, Home, Hospital, ItineraryEntry, ItineraryModifiers, SettingId,
+            SettingProperties, Workplace,
+        },
+        symptom_progression::{
+            PresentingWithSymptoms, SymptomRecord, SymptomRecordValue, SymptomValue, Symptoms,
+        },
         Params,
     };
     use std::{cell::RefCell, path::PathBuf, rc::Rc};
 
     use ixa::{
         define_person_property_with_default, Context, ContextGlobalPropertiesExt, ContextPeopleExt,
-        ContextRandomExt, PersonPropertyChangeEvent,
+        ContextRandomExt, HashMap, PersonId, PersonPropertyChangeEvent,
     };
 
     use ixa::assert_almost_eq;
@@ -186,14 +358,20 @@ mod test {
             HospitalAgeGroups {
                 min: 0,
                 probability: 1.0,
+                delay_to_hospitalization: None,
+                duration_of_hospitalization: None,
             },
             HospitalAgeGroups {
                 min: 19,
                 probability: 1.0,
+                delay_to_hospitalization: None,
+                duration_of_hospitalization: None,
             },
             HospitalAgeGroups {
                 min: 65,
                 probability: 1.0,
+                delay_to_hospitalization: None,
+                duration_of_hospitalization: None,
             },
         ]
         .to_vec();
@@ -279,14 +457,20 @@ mod test {
             HospitalAgeGroups {
                 min: 0,
                 probability: 0.25,
+                delay_to_hospitalization: None,
+                duration_of_hospitalization: None,
             },
             HospitalAgeGroups {
                 min: 19,
                 probability: 0.5,
+                delay_to_hospitalization: None,
+                duration_of_hospitalization: None,
             },
             HospitalAgeGroups {
                 min: 65,
                 probability: 0.75,
+                delay_to_hospitalization: None,
+                duration_of_hospitalization: None,
             },
         ]
         .to_vec();
@@ -348,4 +532,311 @@ mod test {
         assert_almost_eq!(adult_hospitalization_rate, age_groups[1].probability, 0.01);
         assert_almost_eq!(eldery_hospitalization_rate, age_groups[2].probability, 0.01);
     }
+
+    #[test]
+    fn test_hospital_stay_duration_by_age_group() {
+        let young_duration = DelayDistribution::Exponential { mean: 3.0 };
+        let elderly_duration = DelayDistribution::Gamma {
+            shape: 4.0,
+            scale: 3.0,
+        };
+        let age_groups = vec![
+            HospitalAgeGroups {
+                min: 0,
+                probability: 1.0,
+                delay_to_hospitalization: None,
+                duration_of_hospitalization: Some(young_duration),
+            },
+            HospitalAgeGroups {
+                min: 65,
+                probability: 1.0,
+                delay_to_hospitalization: Some(DelayDistribution::LogNormal {
+                    mu: 0.0,
+                    sigma: 0.5,
+                }),
+                duration_of_hospitalization: Some(elderly_duration),
+            },
+        ];
+        let mut context = setup_context(1.0, 5.0, age_groups);
+        context.init_random(0);
+        let mut people = Vec::new();
+        for _ in 0..2000 {
+            people.push(context.add_person((Age, 30u8)).unwrap());
+            people.push(context.add_person((Age, 75u8)).unwrap());
+        }
+        crate::symptom_progression::init(&mut context).unwrap();
+        super::init(&mut context);
+        for person in people {
+            context.set_person_property(person, Symptoms, Some(SymptomValue::Presymptomatic));
+        }
+
+        define_person_property_with_default!(AdmissionTime, f64, 0.0);
+        let durations = Rc::new(RefCell::new(HashMap::<u8, Vec<f64>>::default()));
+        let durations_clone = Rc::clone(&durations);
+        context.subscribe_to_event::<PersonPropertyChangeEvent<Hospitalized>>(
+            move |context, event| {
+                if event.current {
+                    context.set_person_property(
+                        event.person_id,
+                        AdmissionTime,
+                        context.get_current_time(),
+                    );
+                } else {
+                    let min_age = context
+                        .get_person_property(event.person_id, HospitalAgeGroup)
+                        .min;
+                    durations_clone
+                        .borrow_mut()
+                        .entry(min_age)
+                        .or_default()
+                        .push(
+                            context.get_current_time()
+                                - context.get_person_property(event.person_id, AdmissionTime),
+                        );
+                }
+            },
+        );
+        context.execute();
+
+        let durations = durations.borrow();
+        #[allow(clippy::cast_precision_loss)]
+        let mean = |durations: &Vec<f64>| durations.iter().sum::<f64>() / durations.len() as f64;
+        let young_mean = mean(&durations[&0]);
+        let elderly_mean = mean(&durations[&65]);
+        assert_almost_eq!(young_mean, 3.0, 0.3);
+        assert_almost_eq!(elderly_mean, 12.0, 0.6);
+        assert!(elderly_mean > young_mean);
+    }
+
+    #[test]
+    fn test_peak_hospital_census_matches_manual_computation() {
+        let age_groups = vec![HospitalAgeGroups {
+            min: 0,
+            probability: 1.0,
+            delay_to_hospitalization: None,
+            duration_of_hospitalization: None,
+        }];
+        let mut context = setup_context(1.0, 5.0, age_groups);
+        context.init_random(0);
+        let cohort: Vec<_> = (0..50)
+            .map(|_| context.add_person((Age, 30u8)).unwrap())
+            .collect();
+        crate::symptom_progression::init(&mut context).unwrap();
+        super::init(&mut context);
+        assert!(get_peak_hospital_census(&context).is_none());
+        for person in cohort {
+            context.set_person_property(person, Symptoms, Some(SymptomValue::Presymptomatic));
+        }
+
+        // Record every admission (+1) and discharge (-1) with its time
+        let census_changes = Rc::new(RefCell::new(Vec::<(f64, i32)>::new()));
+        let census_changes_clone = Rc::clone(&census_changes);
+        context.subscribe_to_event::<PersonPropertyChangeEvent<Hospitalized>>(
+            move |context, event| {
+                let change = if event.current { 1 } else { -1 };
+                census_changes_clone
+                    .borrow_mut()
+                    .push((context.get_current_time(), change));
+            },
+        );
+        context.execute();
+
+        // Events are recorded in time order, so the peak is the maximum running sum
+        let mut census = 0;
+        let mut expected_peak = 0;
+        let mut expected_peak_time = 0.0;
+        for &(time, change) in census_changes.borrow().iter() {
+            census += change;
+            if census > expected_peak {
+                expected_peak = census;
+                expected_peak_time = time;
+            }
+        }
+        assert_eq!(census, 0);
+        let (peak, peak_time) = get_peak_hospital_census(&context).unwrap();
+        assert_eq!(peak, usize::try_from(expected_peak).unwrap());
+        assert_almost_eq!(peak_time, expected_peak_time, 0.0);
+        assert!(peak > 1);
+    }
+
+    #[test]
+    fn test_escalated_symptoms_are_evaluated_for_hospitalization() {
+        let age_groups = vec![HospitalAgeGroups {
+            min: 0,
+            probability: 1.0,
+            delay_to_hospitalization: None,
+            duration_of_hospitalization: None,
+        }];
+        let mut context = setup_context(1.0, 5.0, age_groups);
+        context.init_random(0);
+        let escalated = context.add_person((Age, 30u8)).unwrap();
+        let planned = context.add_person((Age, 30u8)).unwrap();
+        super::init(&mut context);
+
+        let admissions = Rc::new(RefCell::new(HashMap::<PersonId, usize>::default()));
+        let admissions_clone = Rc::clone(&admissions);
+        context.subscribe_to_event::<PersonPropertyChangeEvent<Hospitalized>>(
+            move |_context, event| {
+                if event.current {
+                    *admissions_clone
+                        .borrow_mut()
+                        .entry(event.person_id)
+                        .or_default() += 1;
+                }
+            },
+        );
+
+        let mild = SymptomRecordValue {
+            category: SymptomValue::Category2,
+            symptom_start: 0.0,
+            symptom_end: None,
+            severe: false,
+        };
+        let moderate = SymptomRecordValue {
+            category: SymptomValue::Category1,
+            severe: true,
+            ..mild
+        };
+        // Only the escalation can admit the first person, since they never start presenting with
+        // symptoms. The second person's arrival is already planned when their symptoms escalate.
+        context.set_person_property(planned, Symptoms, Some(SymptomValue::Category2));
+        for person in [escalated, planned] {
+            context.set_person_property(person, SymptomRecord, Some(mild));
+            context.set_person_property(person, SymptomRecord, Some(moderate));
+        }
+        context.execute();
+
+        let admissions = admissions.borrow();
+        assert_eq!(admissions.get(&escalated), Some(&1));
+        assert_eq!(admissions.get(&planned), Some(&1));
+    }
+
+    /// Set up a person with a home and a workplace who will be hospitalized in ward 3
+    fn setup_hospital_ward_context() -> (Context, PersonId) {
+        let mut context = Context::new();
+        let setting_properties = SettingProperties {
+            alpha: 1.0,
+            itinerary_specification: Some(ItinerarySpecificationType::Constant { ratio: 1.0 }),
+        };
+        let parameters = Params {
+            max_time: 100.0,
+            symptom_progression_library: Some(ProgressionLibraryType::EmpiricalFromFile {
+                file: PathBuf::from("./input/library_symptom_parameters.csv"),
+            }),
+            hospitalization_parameters: HospitalizationParameters {
+                mean_delay_to_hospitalization: 1.0,
+                mean_duration_of_hospitalization: 5.0,
+                age_groups: vec![HospitalAgeGroups {
+                    min: 0,
+                    probability: 1.0,
+                    delay_to_hospitalization: None,
+                    duration_of_hospitalization: None,
+                }],
+            },
+            hospital_ward_id: Some(3),
+            settings_properties: HashMap::from_iter([
+                (CoreSettingsTypes::Home, setting_properties),
+                (CoreSettingsTypes::Workplace, setting_properties),
+                (CoreSettingsTypes::Hospital, setting_properties),
+            ]),
+            ..Default::default()
+        };
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        context.init_random(0);
+        load_rate_fns(&mut context).unwrap();
+        crate::settings::init(&mut context).unwrap();
+        let person = context.add_person((Age, 30u8)).unwrap();
+        context
+            .add_itinerary(
+                person,
+                vec![
+                    ItineraryEntry::new(SettingId::new(Home, 0), 0.5),
+                    ItineraryEntry::new(SettingId::new(Workplace, 0), 0.5),
+                ],
+            )
+            .unwrap();
+        crate::symptom_progression::init(&mut context).unwrap();
+        super::init(&mut context);
+        (context, person)
+    }
+
+    #[test]
+    fn test_hospitalized_people_move_into_hospital_ward() {
+        let (mut context, person) = setup_hospital_ward_context();
+        context.set_person_property(person, Symptoms, Some(SymptomValue::Presymptomatic));
+
+        let stays = Rc::new(RefCell::new((0, 0)));
+        let stays_clone = Rc::clone(&stays);
+        context.subscribe_to_event::<PersonPropertyChangeEvent<Hospitalized>>(
+            move |context, event| {
+                let ward = SettingId::new(Hospital, 3);
+                let in_ward = context.is_setting_active(event.person_id, &ward);
+                let at_home = context.is_setting_active(event.person_id, &SettingId::new(Home, 0));
+                let at_work =
+                    context.is_setting_active(event.person_id, &SettingId::new(Workplace, 0));
+                if event.current {
+                    // During their stay, people are only in the hospital ward
+                    assert!(in_ward);
+                    assert!(context
+                        .get_setting_members(&ward)
+                        .unwrap()
+                        .contains(&event.person_id));
+                    assert!(!at_home);
+                    assert!(!at_work);
+                    stays_clone.borrow_mut().0 += 1;
+                } else {
+                    // After discharge, people return to their default itinerary
+                    assert!(!in_ward);
+                    assert!(at_home);
+                    assert!(at_work);
+                    stays_clone.borrow_mut().1 += 1;
+                }
+            },
+        );
+        context.execute();
+        assert_eq!(*stays.borrow(), (1, 1));
+    }
+
+    #[test]
+    fn test_discharged_people_resume_isolation() {
+        let (mut context, person) = setup_hospital_ward_context();
+        // The person is isolating at home when they are admitted
+        context
+            .modify_itinerary(
+                person,
+                ItineraryModifiers::RestrictTo {
+                    setting: &Home,
+                    setting_id: None,
+                },
+            )
+            .unwrap();
+        context.set_person_property(person, Symptoms, Some(SymptomValue::Presymptomatic));
+
+        let discharged = Rc::new(RefCell::new(false));
+        let discharged_clone = Rc::clone(&discharged);
+        context.subscribe_to_event::<PersonPropertyChangeEvent<Hospitalized>>(
+            move |context, event| {
+                let in_ward =
+                    context.is_setting_active(event.person_id, &SettingId::new(Hospital, 3));
+                let at_home = context.is_setting_active(event.person_id, &SettingId::new(Home, 0));
+                let at_work =
+                    context.is_setting_active(event.person_id, &SettingId::new(Workplace, 0));
+                if event.current {
+                    // The ward takes precedence over isolation
+                    assert!(in_ward);
+                    assert!(!at_home);
+                } else {
+                    // After discharge, the person is still isolating
+                    assert!(!in_ward);
+                    assert!(at_home);
+                    assert!(!at_work);
+                    *discharged_clone.borrow_mut() = true;
+                }
+            },
+        );
+        context.execute();
+        assert!(*discharged.borrow());
+    }
 }
@@ -1,50 +1,209 @@
 use core::f64;
 use rand_distr::{Binomial, Uniform};
+use std::time::Instant;
 
 use crate::computed_statistics::{ACCEPTED_INFECTION_LABEL, FORECASTED_INFECTION_LABEL};
 use crate::infectiousness_manager::{
-    evaluate_forecast, get_forecast, infection_attempt, Forecast, InfectionContextExt,
-    InfectionData, InfectionDataValue, InfectionStatus, InfectionStatusValue,
+    calibrate_transmission_scale, evaluate_forecast, forecast_infection_attempts,
+    get_cumulative_infections, get_forecast, track_age_transmission, track_infection_times,
+    track_infector_setting_counts, track_offspring_distribution, track_setting_peak_infectious,
+    track_setting_transmission, Forecast, InfectionContextExt, InfectionCount, InfectionData,
+    InfectionDataValue, InfectionStatus, InfectionStatusValue,
 };
-use crate::parameters::{ContextParametersExt, Params};
+use crate::interventions::ContextTransmissionModifierExt;
+use crate::parameters::{ContextParametersExt, Params, PriorInfectionHistory, SeedingMode};
+use crate::population_loader::{InitiallyImmune, Shielded};
 use crate::rate_fns::{load_rate_fns, InfectiousnessRateExt};
+use crate::settings::{ContextSettingExt, Home, SettingId};
+use crate::symptom_progression::Symptoms;
+use ixa::plan::PlanId;
 use ixa::profiling::{increment_named_count, open_span};
 use ixa::{
-    define_rng, trace, Context, ContextPeopleExt, ContextRandomExt, IxaError, PersonId,
-    PersonPropertyChangeEvent, PluginContext,
+    define_data_plugin, define_rng, info, trace, Context, ContextPeopleExt, ContextRandomExt,
+    ExecutionPhase, HashMap, IxaError, PersonId, PersonPropertyChangeEvent, PluginContext,
 };
 
 define_rng!(InfectionRng);
 
+// The pending forecast plan for each infectious person, so that it can be cancelled on recovery
+#[derive(Default)]
+struct ForecastPlanContainer {
+    plans: HashMap<PersonId, PlanId>,
+}
+
+define_data_plugin!(
+    ForecastPlanPlugin,
+    ForecastPlanContainer,
+    ForecastPlanContainer::default()
+);
+
 fn schedule_next_forecasted_infection(context: &mut Context, person: PersonId) {
-    if let Some(Forecast {
+    // With transmission disabled, infections only progress through their natural history.
+    if !context.get_params().transmission_enabled.unwrap_or(true) {
+        return;
+    }
+    // Shielded people never transmit, so there is no need to forecast their infection attempts.
+    if context.get_person_property(person, Shielded) {
+        return;
+    }
+    if let Some(forecast) = get_forecast(context, person) {
+        schedule_forecast(context, person, forecast);
+    }
+}
+
+fn schedule_forecast(context: &mut Context, person: PersonId, forecast: Forecast) {
+    let Forecast {
         next_time,
         forecasted_total_infectiousness,
-    }) = get_forecast(context, person)
+    } = forecast;
+    let plan_id = context.add_plan(next_time, move |context| {
+        let _span = open_span("evaluate and schedule next forecast");
+        // This plan is now running, so there is nothing left to cancel on recovery.
+        context
+            .get_data_mut(ForecastPlanPlugin)
+            .plans
+            .remove(&person);
+        // Forecasts are cancelled on recovery, but we double check in case a forecast ties
+        // with recovery or the person was recovered outside of the recovery plan.
+        if context.get_person_property(person, InfectionStatus) != InfectionStatusValue::Infectious
+        {
+            return;
+        }
+        increment_named_count(FORECASTED_INFECTION_LABEL);
+        if evaluate_forecast(context, person, forecasted_total_infectiousness) {
+            increment_named_count(ACCEPTED_INFECTION_LABEL);
+            let _ = forecast_infection_attempts(context, person);
+        }
+        // Continue scheduling forecasts until the person recovers.
+        schedule_next_forecasted_infection(context, person);
+    });
+    context
+        .get_data_mut(ForecastPlanPlugin)
+        .plans
+        .insert(person, plan_id);
+}
+
+/// Cancel a person's pending forecast, if they have one, so that no infection attempts can
+/// happen after they recover.
+fn remove_forecast_plan(context: &mut Context, person: PersonId) {
+    if let Some(plan_id) = context
+        .get_data_mut(ForecastPlanPlugin)
+        .plans
+        .remove(&person)
     {
-        context.add_plan(next_time, move |context| {
-            let _span = open_span("evaluate and schedule next forecast");
-            increment_named_count(FORECASTED_INFECTION_LABEL);
-            if evaluate_forecast(context, person, forecasted_total_infectiousness) {
-                increment_named_count(ACCEPTED_INFECTION_LABEL);
-                let _ = infection_attempt(context, person);
-            }
-            // Continue scheduling forecasts until the person recovers.
-            schedule_next_forecasted_infection(context, person);
-        });
+        trace!("Person {person}: Cancelling pending forecast on recovery");
+        context.cancel_plan(&plan_id);
     }
 }
 
 fn schedule_recovery(context: &mut Context, person: PersonId) {
+    let infection_time = context.get_current_time();
     let infection_duration = context.get_person_rate_fn(person).infection_duration();
-    let recovery_time = context.get_current_time() + infection_duration;
+    let Some(asymptomatic_duration_factor) = context.get_params().asymptomatic_duration_factor
+    else {
+        plan_recovery(context, person, infection_time + infection_duration);
+        return;
+    };
+    // Whether the person is asymptomatic is only known once the symptom progression has reacted
+    // to their infection, so wait until everything else at the infection time has happened
+    context.add_plan_with_phase(
+        infection_time,
+        move |context| {
+            let infectious_duration = if context.get_person_property(person, Symptoms).is_none() {
+                trace!("Person {person} is asymptomatic and recovers early");
+                infection_duration * asymptomatic_duration_factor
+            } else {
+                infection_duration
+            };
+            plan_recovery(context, person, infection_time + infectious_duration);
+        },
+        ExecutionPhase::Last,
+    );
+}
+
+fn plan_recovery(context: &mut Context, person: PersonId, recovery_time: f64) {
     context.add_plan(recovery_time, move |context| {
         increment_named_count("recovery");
         trace!("Person {person} has recovered at {recovery_time}");
         context.recover_person(person);
+        remove_forecast_plan(context, person);
+        if context.get_params().stop_on_extinction.unwrap_or(false) {
+            schedule_extinction_check(context);
+        }
     });
 }
 
+/// How often, in simulated time, the wall clock budget is checked
+const WALL_CLOCK_CHECK_PERIOD: f64 = 1.0;
+
+/// Shut down the simulation at the first check after more than `max_wall_clock_seconds` have
+/// elapsed since `start`. Shutting down lets the simulation finish normally, so the reports hold
+/// every row recorded up to the time the simulation stopped and are flushed when the context is
+/// dropped. Like other periodic plans, the check stops once no other plans remain, so it does not
+/// keep a simulation that has nothing left to do running.
+fn schedule_wall_clock_check(context: &mut Context, start: Instant, max_wall_clock_seconds: f64) {
+    context.add_periodic_plan_with_phase(
+        WALL_CLOCK_CHECK_PERIOD,
+        move |context| {
+            // The first periodic plan runs at time zero, before any simulated day has ended
+            if context.get_current_time() <= 0.0 {
+                return;
+            }
+            let elapsed = start.elapsed().as_secs_f64();
+            if elapsed > max_wall_clock_seconds {
+                info!(
+                    "Stopping the simulation at time {} after {elapsed} seconds, exceeding the wall clock budget of {max_wall_clock_seconds} seconds",
+                    context.get_current_time()
+                );
+                context.shutdown();
+            }
+        },
+        ExecutionPhase::Last,
+    );
+}
+
+/// Shut down the simulation as soon as the fraction of the population that has ever been infected
+/// reaches `threshold`.
+fn subscribe_to_cumulative_incidence_threshold(context: &mut Context, threshold: f64) {
+    context.subscribe_to_event(
+        move |context, event: PersonPropertyChangeEvent<InfectionStatus>| {
+            if event.current != InfectionStatusValue::Infectious {
+                return;
+            }
+            #[allow(clippy::cast_precision_loss)]
+            let cumulative_incidence = get_cumulative_infections(context) as f64
+                / context.get_current_population() as f64;
+            if cumulative_incidence >= threshold {
+                info!(
+                    "The cumulative incidence reached {cumulative_incidence} at {}, so the simulation is stopping.",
+                    context.get_current_time()
+                );
+                context.shutdown();
+            }
+        },
+    );
+}
+
+/// Check for extinction once everything else at the current time has happened, so that people
+/// infected at the same time as a recovery are counted, and shut down the simulation if nobody
+/// is infectious.
+fn schedule_extinction_check(context: &mut Context) {
+    context.add_plan_with_phase(
+        context.get_current_time(),
+        |context| {
+            if context.query_people_count((InfectionStatus, InfectionStatusValue::Infectious)) == 0
+            {
+                info!(
+                    "Nobody is infectious at {}, so the simulation is stopping.",
+                    context.get_current_time()
+                );
+                context.shutdown();
+            }
+        },
+        ExecutionPhase::Last,
+    );
+}
+
 /// Takes susceptible people from the population and changes them according to a provided `seed_fn`.
 /// The total number of people seeded is distributed binomially according to the proportion to seed.
 /// The proportion to seed is calibrated to the population size, not the current number of susceptibles.
@@ -64,12 +223,22 @@ fn query_susceptibles_and_seed(
     trace!(
         "Altering {k} susceptibles with a seeding function using proportion {proportion_to_seed}."
     );
+    seed_susceptibles(context, k as usize, seed_fn);
+}
 
-    if k > 0 {
+/// Takes exactly `count` susceptible people, sampled without replacement, and changes them
+/// according to a provided `seed_fn`. If there are fewer than `count` susceptibles, all of them
+/// are seeded.
+fn seed_susceptibles(
+    context: &mut Context,
+    count: usize,
+    seed_fn: impl Fn(&mut Context, PersonId),
+) {
+    if count > 0 {
         let susceptibles = context.sample_people(
             InfectionRng,
             (InfectionStatus, InfectionStatusValue::Susceptible),
-            k as usize,
+            count,
         );
         for person in susceptibles {
             seed_fn(context, person);
@@ -77,6 +246,63 @@ fn query_susceptibles_and_seed(
     }
 }
 
+/// Takes `count` homes sampled without replacement and changes the susceptible members of each
+/// according to a provided `seed_fn`. Only `member_fraction` of the susceptible members of each
+/// home, rounded up and sampled without replacement, are seeded. If there are fewer than `count`
+/// homes, all of them are seeded, and homes without susceptible members seed nobody.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn seed_households(
+    context: &mut Context,
+    count: usize,
+    member_fraction: f64,
+    seed_fn: impl Fn(&mut Context, PersonId),
+) {
+    let mut homes = context.get_category_setting_ids(&Home);
+    if count > homes.len() {
+        trace!(
+            "Only {} homes are available to seed, fewer than the {count} requested.",
+            homes.len()
+        );
+    }
+    let count = count.min(homes.len());
+    sample_prefix_without_replacement(context, &mut homes, count);
+    for &home_id in &homes[..count] {
+        let mut members: Vec<PersonId> = context
+            .get_setting_members(&SettingId::new(Home, home_id))
+            .map(|members| {
+                members
+                    .iter()
+                    .copied()
+                    .filter(|&person| {
+                        context.get_person_property(person, InfectionStatus)
+                            == InfectionStatusValue::Susceptible
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let num_to_seed =
+            ((member_fraction * members.len() as f64).ceil() as usize).min(members.len());
+        sample_prefix_without_replacement(context, &mut members, num_to_seed);
+        trace!("Seeding {num_to_seed} member(s) of home {home_id}.");
+        for &person in &members[..num_to_seed] {
+            seed_fn(context, person);
+        }
+    }
+}
+
+/// Shuffle a uniformly random sample of `count` of the values to the start of `values` with a
+/// partial Fisher-Yates shuffle.
+fn sample_prefix_without_replacement<T>(context: &Context, values: &mut [T], count: usize) {
+    for i in 0..count {
+        let j = context.sample_range(InfectionRng, i..values.len());
+        values.swap(i, j);
+    }
+}
+
 trait InitializationContextExt: PluginContext + ContextPeopleExt + InfectiousnessRateExt {
     fn seed_infection(&mut self, person_id: PersonId) {
         // sample an offset for the individuals infectious period
@@ -90,55 +316,251 @@ trait InitializationContextExt: PluginContext + ContextPeopleExt + Infectiousnes
             context.infect_person(person_id, None, None, None);
         });
     }
+    /// Draw the infection and recovery times of a person who recovered before the current time.
+    fn sample_prior_infection(
+        &self,
+        person_id: PersonId,
+        history: PriorInfectionHistory,
+    ) -> (f64, f64) {
+        let PriorInfectionHistory::Uniform {
+            max_time_since_recovery,
+        } = history;
+        let uniform = Uniform::new_inclusive(-max_time_since_recovery, 0.0).unwrap();
+        let recovery_time = self.get_current_time() + self.sample_distr(InfectionRng, uniform);
+        let infection_time =
+            recovery_time - self.get_person_rate_fn(person_id).infection_duration();
+        (infection_time, recovery_time)
+    }
 }
 impl InitializationContextExt for Context {}
 
-fn seed_initial_infections(context: &mut Context, initial_incidence: f64) {
-    query_susceptibles_and_seed(context, initial_incidence, |context, person_id| {
-        trace!("Infecting person {person_id} as an initial infection.");
-        context.seed_infection(person_id);
-        context.add_plan(0.0, move |context| {
-            assert!(
-                context.get_person_property(person_id, InfectionStatus)
-                    == InfectionStatusValue::Infectious
-            );
-            schedule_next_forecasted_infection(context, person_id);
-        });
+fn seed_initial_infection(context: &mut Context, person_id: PersonId) {
+    trace!("Infecting person {person_id} as an initial infection.");
+    context.seed_infection(person_id);
+    context.add_plan(0.0, move |context| {
+        assert!(
+            context.get_person_property(person_id, InfectionStatus)
+                == InfectionStatusValue::Infectious
+        );
+        schedule_next_forecasted_infection(context, person_id);
     });
 }
 
+fn seed_initial_infections(context: &mut Context, initial_incidence: f64) {
+    query_susceptibles_and_seed(context, initial_incidence, seed_initial_infection);
+}
+
+/// Seed exactly `count` initial infections rather than a binomially distributed number
+fn seed_exact_initial_infections(context: &mut Context, count: usize) {
+    seed_susceptibles(context, count, seed_initial_infection);
+}
+
+fn seed_recovered(context: &mut Context, person_id: PersonId) {
+    trace!("Recovering person {person_id} as an initial recovered.");
+    // Without a prior infection history, we don't know when the person was infected or
+    // recovered, so these times are unknown.
+    let (infection_time, recovery_time) = match context.get_params().initial_recovered_history {
+        Some(history) => context.sample_prior_infection(person_id, history),
+        None => (f64::NAN, f64::NAN),
+    };
+    // Their prior infection makes any infection in the simulation a reinfection
+    context.set_person_property(person_id, InfectionCount, 1);
+    context.set_person_property(
+        person_id,
+        InfectionData,
+        InfectionDataValue::Recovered {
+            infection_time,
+            recovery_time,
+        },
+    );
+}
+
 fn seed_initial_recovered(context: &mut Context, initial_recovered: f64) {
     context.add_plan(0.0, move |context| {
-        query_susceptibles_and_seed(context, initial_recovered, |context, person_id| {
-            trace!("Recovering person {person_id} as an initial recovered.");
-            context.set_person_property(
-                person_id,
-                InfectionData,
-                InfectionDataValue::Recovered {
-                    // If we choose to seed the population with people who are in various levels of "recovered"
-                    // and include waning immunity based on that, we could changes these values to reflect
-                    // when prior to simulation start an individual was actually infected/recovered.
-                    infection_time: f64::NAN,
-                    recovery_time: f64::NAN,
-                },
-            );
-        });
+        query_susceptibles_and_seed(context, initial_recovered, seed_recovered);
+    });
+}
+
+/// Seed exactly `count` initial recovered rather than a binomially distributed number
+fn seed_exact_initial_recovered(context: &mut Context, count: usize) {
+    context.add_plan(0.0, move |context| {
+        seed_susceptibles(context, count, seed_recovered);
+    });
+}
+
+/// Seed the susceptible members of `count` homes as recovered, so that immunity is clustered
+/// within households as it would be after a prior wave
+fn seed_initial_recovered_households(context: &mut Context, count: usize, member_fraction: f64) {
+    context.add_plan(0.0, move |context| {
+        seed_households(context, count, member_fraction, seed_recovered);
+    });
+}
+
+/// Recover the people marked as initially immune by the initial immunity file when the
+/// population was loaded. Like the initial recovered, they have a prior infection history if
+/// `initial_recovered_history` is specified.
+fn seed_initial_immunity(context: &mut Context) {
+    let mut initially_immune = Vec::new();
+    context.with_query_results((InitiallyImmune, true), &mut |people| {
+        initially_immune.extend(people.iter().copied());
     });
+    for person_id in initially_immune {
+        seed_recovered(context, person_id);
+    }
+}
+
+/// Remove people from all of their settings when they recover
+fn subscribe_to_removing_recovered_from_settings(context: &mut Context) {
+    context.subscribe_to_event(
+        |context, event: PersonPropertyChangeEvent<InfectionStatus>| {
+            if event.current == InfectionStatusValue::Recovered {
+                trace!(
+                    "Person {} has recovered and left their settings",
+                    event.person_id
+                );
+                context.clear_itinerary(event.person_id);
+            }
+        },
+    );
+}
+
+/// Return recovered people to susceptible once their immunity wanes, `immunity_duration` after
+/// their recovery. People who were seeded as recovered without a prior infection history are
+/// treated as having recovered at the time they were seeded.
+fn subscribe_to_waning_immunity(context: &mut Context, immunity_duration: f64) {
+    context.subscribe_to_event(
+        move |context, event: PersonPropertyChangeEvent<InfectionStatus>| {
+            if event.current != InfectionStatusValue::Recovered {
+                return;
+            }
+            let person = event.person_id;
+            let InfectionDataValue::Recovered { recovery_time, .. } =
+                context.get_person_property(person, InfectionData)
+            else {
+                unreachable!("Person {person} should be recovered");
+            };
+            let current_time = context.get_current_time();
+            let recorded_recovery_time = recovery_time;
+            let recovery_time = if recovery_time.is_nan() {
+                current_time
+            } else {
+                recovery_time
+            };
+            let waning_time = (recovery_time + immunity_duration).max(current_time);
+            context.add_plan(waning_time, move |context| {
+                // People who were reinfected since this recovery have their immunity wane from
+                // their latest recovery instead
+                let InfectionDataValue::Recovered { recovery_time, .. } =
+                    context.get_person_property(person, InfectionData)
+                else {
+                    trace!("Person {person} was reinfected before their immunity waned");
+                    return;
+                };
+                if recovery_time.to_bits() != recorded_recovery_time.to_bits() {
+                    trace!("Person {person} recovered again before their immunity waned");
+                    return;
+                }
+                trace!("Person {person} has lost immunity at {waning_time}");
+                context.set_person_property(person, InfectionData, InfectionDataValue::Susceptible);
+            });
+        },
+    );
 }
 
 pub fn init(context: &mut Context) -> Result<(), IxaError> {
     let &Params {
         initial_incidence,
+        initial_infection_seeding,
         initial_recovered,
+        initial_recovered_seeding,
+        immunity_duration,
+        remove_recovered_from_settings,
+        recovered_susceptibility_factor,
+        setting_rt_summary,
+        age_transmission_matrix,
+        setting_peak_infectious_summary,
+        offspring_distribution_summary,
+        infector_setting_count_summary,
+        doubling_time_summary,
+        stop_at_cumulative_incidence,
+        max_wall_clock_seconds,
+        target_r0,
         ..
     } = context.get_params();
 
     load_rate_fns(context)?;
-    if initial_incidence > 0.0 {
-        seed_initial_infections(context, initial_incidence);
+    if let Some(target_r0) = target_r0 {
+        calibrate_transmission_scale(context, target_r0)?;
+    }
+    if setting_rt_summary.unwrap_or(false) {
+        track_setting_transmission(context);
+    }
+    if age_transmission_matrix.unwrap_or(false) {
+        track_age_transmission(context);
+    }
+    if setting_peak_infectious_summary.unwrap_or(false) {
+        track_setting_peak_infectious(context);
+    }
+    if offspring_distribution_summary.unwrap_or(false) {
+        track_offspring_distribution(context);
+    }
+    if infector_setting_count_summary.unwrap_or(false) {
+        track_infector_setting_counts(context);
+    }
+    if doubling_time_summary.unwrap_or(false) {
+        track_infection_times(context);
+    }
+    if let Some(threshold) = stop_at_cumulative_incidence {
+        subscribe_to_cumulative_incidence_threshold(context, threshold);
+    }
+    if let Some(max_wall_clock_seconds) = max_wall_clock_seconds {
+        schedule_wall_clock_check(context, Instant::now(), max_wall_clock_seconds);
+    }
+    if let Some(factor) = recovered_susceptibility_factor {
+        context.store_transmission_modifier_values(
+            InfectionStatusValue::Recovered,
+            InfectionStatus,
+            &[(InfectionStatusValue::Recovered, factor)],
+        )?;
+    }
+    // Subscribe before recovering the initially immune so that they also leave their settings
+    if remove_recovered_from_settings.unwrap_or(false) {
+        subscribe_to_removing_recovered_from_settings(context);
     }
-    if initial_recovered > 0.0 {
-        seed_initial_recovered(context, initial_recovered);
+    // Subscribe before recovering the initially immune so that their immunity also wanes
+    if let Some(immunity_duration) = immunity_duration {
+        subscribe_to_waning_immunity(context, context.scale_duration(immunity_duration));
+    }
+    // The initially immune are recovered first so that they cannot be seeded as infected
+    seed_initial_immunity(context);
+    match initial_infection_seeding {
+        Some(SeedingMode::ExactCount(count)) => seed_exact_initial_infections(context, count),
+        Some(SeedingMode::Households {
+            count,
+            member_fraction,
+        }) => seed_households(
+            context,
+            count,
+            member_fraction.unwrap_or(1.0),
+            seed_initial_infection,
+        ),
+        Some(SeedingMode::Binomial) | None => {
+            if initial_incidence > 0.0 {
+                seed_initial_infections(context, initial_incidence);
+            }
+        }
+    }
+    match initial_recovered_seeding {
+        Some(SeedingMode::ExactCount(count)) => seed_exact_initial_recovered(context, count),
+        Some(SeedingMode::Households {
+            count,
+            member_fraction,
+        }) => seed_initial_recovered_households(context, count, member_fraction.unwrap_or(1.0)),
+        Some(SeedingMode::Binomial) | None => {
+            if initial_recovered > 0.0 {
+                seed_initial_recovered(context, initial_recovered);
+            }
+        }
     }
 
     // Subscribe to the person becoming infectious to trigger the infection propagation loop
@@ -160,11 +582,12 @@ pub fn init(context: &mut Context) -> Result<(), IxaError> {
 #[cfg(test)]
 mod test {
     use serde::{Deserialize, Serialize};
-    use std::{cell::RefCell, rc::Rc};
+    use std::{cell::RefCell, path::PathBuf, rc::Rc};
 
     use ixa::{
         define_person_property_with_default, Context, ContextGlobalPropertiesExt, ContextPeopleExt,
-        ContextRandomExt, ExecutionPhase, HashMap, IxaError, PersonId, PersonPropertyChangeEvent,
+        ContextRandomExt, ExecutionPhase, HashMap, HashSet, HashSetExt, IxaError, PersonId,
+        PersonPropertyChangeEvent,
     };
 
     use ixa::assert_almost_eq;
@@ -172,23 +595,36 @@ mod test {
     use crate::{
         define_setting_category,
         infection_propagation_loop::{
-            init, schedule_next_forecasted_infection, schedule_recovery, seed_initial_infections,
-            seed_initial_recovered, InfectionStatus, InfectionStatusValue,
+            init, schedule_forecast, schedule_next_forecasted_infection, schedule_recovery,
+            seed_exact_initial_infections, seed_initial_infections, seed_initial_recovered,
+            seed_recovered, subscribe_to_waning_immunity, Forecast, ForecastPlanPlugin,
+            InfectionCount, InfectionStatus, InfectionStatusValue,
         },
         infectiousness_manager::{
-            max_total_infectiousness_multiplier, InfectionContextExt, InfectionData,
-            InfectionDataValue,
+            calibrate_transmission_scale, estimate_r0, get_age_transmission_matrix,
+            get_cumulative_infections, get_offspring_distribution, get_setting_attack_rates,
+            get_setting_rt, infection_attempt_in_setting, max_total_infectiousness_multiplier,
+            set_next_forecast_override, track_setting_transmission, InfectionContextExt,
+            InfectionData, InfectionDataValue,
         },
         interventions::ContextTransmissionModifierExt,
         parameters::{
-            ContextParametersExt, CoreSettingsTypes, GlobalParams, ItinerarySpecificationType,
-            Params, RateFnType,
+            AgeContactMultiplier, ContextParametersExt, CoreSettingsTypes, GlobalParams,
+            ItinerarySpecificationType, Params, PriorInfectionHistory, ProgressionLibraryType,
+            RateFnType, SeedingMode,
         },
+        population_loader::{Age, Shielded},
         rate_fns::{load_rate_fns, InfectiousnessRateExt},
+        reports::{
+            report_sink::{ContextReportSinkExt, ReportSink},
+            ReportParams,
+        },
         settings::{
-            CensusTract, ContextSettingExt, Home, ItineraryEntry, ItineraryModifiers, SettingId,
-            SettingProperties, Workplace,
+            CensusTract, ContextSettingExt, DailyActivityProfile, Home, ItineraryEntry,
+            ItineraryModifiers, SettingCategory, SettingContactRate, SettingId, SettingProperties,
+            Workplace,
         },
+        symptom_progression::{SymptomRecord, SymptomValue, Symptoms},
     };
 
     define_setting_category!(HomogeneousMixing);
@@ -274,6 +710,94 @@ mod test {
         context
     }
 
+    // Runs 200 index cases, infected at t = 0.1, through a single workplace of 20,000 workers with
+    // a constant rate function of the given duration. The remaining parameters are taken from
+    // `parameters`, and `configure` is called with the workers before the simulation starts so
+    // tests can vary the index cases. Returns the workers and the number of secondary cases caused
+    // by each infector.
+    fn run_workplace_index_cases(
+        duration: f64,
+        parameters: Params,
+        configure: impl FnOnce(&mut Context, &[PersonId]),
+    ) -> (Context, Vec<PersonId>, HashMap<PersonId, usize>) {
+        let mut context = Context::new();
+        context.init_random(0);
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 4.5,
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 1.0,
+                        duration,
+                    },
+                    settings_properties: [(
+                        CoreSettingsTypes::Workplace,
+                        SettingProperties {
+                            alpha: 0.0,
+                            itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                                ratio: 1.0,
+                            }),
+                        },
+                    )]
+                    .into_iter()
+                    .collect(),
+                    ..parameters
+                },
+            )
+            .unwrap();
+        crate::settings::init(&mut context).unwrap();
+        let mut workers = vec![];
+        for _ in 0..20_000 {
+            let person = context.add_person(()).unwrap();
+            context
+                .add_itinerary(
+                    person,
+                    vec![ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0)],
+                )
+                .unwrap();
+            workers.push(person);
+        }
+        init(&mut context).unwrap();
+        configure(&mut context, &workers);
+        let index_cases: Vec<PersonId> = workers[..200].to_vec();
+        context.add_plan(0.1, move |context| {
+            for &person in &index_cases {
+                context.infect_person(person, None, None, None);
+            }
+        });
+        let secondary_cases = Rc::new(RefCell::new(HashMap::default()));
+        let secondary_cases_clone = Rc::clone(&secondary_cases);
+        context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionData>>(
+            move |_context, event| {
+                if let InfectionDataValue::Infectious {
+                    infected_by: Some(infector),
+                    ..
+                } = event.current
+                {
+                    *secondary_cases_clone
+                        .borrow_mut()
+                        .entry(infector)
+                        .or_insert(0_usize) += 1;
+                }
+            },
+        );
+        context.execute();
+        (context, workers, secondary_cases.take())
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn mean_secondary_cases(
+        secondary_cases: &HashMap<PersonId, usize>,
+        infectors: &HashSet<PersonId>,
+    ) -> f64 {
+        infectors
+            .iter()
+            .map(|person| secondary_cases.get(person).copied().unwrap_or(0))
+            .sum::<usize>() as f64
+            / infectors.len() as f64
+    }
+
     #[test]
     fn test_seed_initial_conditions() {
         let mut context = setup_context(0, 1.0, 1.0, 5.0, 0.0);
@@ -299,6 +823,121 @@ mod test {
         });
     }
 
+    #[test]
+    fn test_seeded_recovered_prior_times_and_waning() {
+        let mut context = Context::new();
+        context.init_random(0);
+        let parameters = Params {
+            max_time: 100.0,
+            infectiousness_rate_fn: RateFnType::Constant {
+                rate: 1.0,
+                duration: 5.0,
+            },
+            initial_recovered_history: Some(PriorInfectionHistory::Uniform {
+                max_time_since_recovery: 10.0,
+            }),
+            immunity_duration: Some(20.0),
+            ..Default::default()
+        };
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        load_rate_fns(&mut context).unwrap();
+        for _ in 0..20 {
+            context.add_person(()).unwrap();
+        }
+        seed_initial_recovered(&mut context, 1.0);
+        subscribe_to_waning_immunity(&mut context, 20.0);
+
+        let recovery_times = Rc::new(RefCell::new(HashMap::<PersonId, f64>::default()));
+        let recovery_times_clone = Rc::clone(&recovery_times);
+        context.add_plan(0.0, move |context| {
+            for person in context.recovered_people() {
+                let InfectionDataValue::Recovered {
+                    infection_time,
+                    recovery_time,
+                } = context.get_person_property(person, InfectionData)
+                else {
+                    panic!("Person {person} should be recovered");
+                };
+                assert!(infection_time.is_finite() && recovery_time.is_finite());
+                assert!((-10.0..=0.0).contains(&recovery_time));
+                assert_almost_eq!(recovery_time - infection_time, 5.0, 1e-10);
+                recovery_times_clone
+                    .borrow_mut()
+                    .insert(person, recovery_time);
+            }
+        });
+
+        let num_waned = Rc::new(RefCell::new(0));
+        let num_waned_clone = Rc::clone(&num_waned);
+        let recovery_times_clone = Rc::clone(&recovery_times);
+        context.subscribe_to_event(
+            move |context, event: PersonPropertyChangeEvent<InfectionStatus>| {
+                if event.current == InfectionStatusValue::Susceptible {
+                    let recovery_time = recovery_times_clone.borrow()[&event.person_id];
+                    assert_almost_eq!(context.get_current_time(), recovery_time + 20.0, 1e-10);
+                    *num_waned_clone.borrow_mut() += 1;
+                }
+            },
+        );
+        context.execute();
+        assert_eq!(recovery_times.borrow().len(), 20);
+        assert_eq!(*num_waned.borrow(), 20);
+    }
+
+    #[test]
+    fn test_exact_count_seeds_one_person() {
+        for seed in 0..100 {
+            let mut context = setup_context(seed, 1.0, 1.0, 5.0, 0.0);
+            // Initial infections begin before time 0
+            context.set_start_time(-1000.);
+            load_rate_fns(&mut context).unwrap();
+            for _ in 0..10 {
+                context.add_person(()).unwrap();
+            }
+            seed_exact_initial_infections(&mut context, 1);
+            context.add_plan(0.0, |context| {
+                assert_eq!(
+                    context.query_people_count((InfectionStatus, InfectionStatusValue::Infectious)),
+                    1
+                );
+                context.shutdown();
+            });
+            context.execute();
+        }
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_next_forecast_override_is_used() {
+        let mut context = setup_context(0, 1.0, 1.0, 5.0, 0.0);
+        load_rate_fns(&mut context).unwrap();
+        let infector = context.add_person(()).unwrap();
+        let contact = context.add_person(()).unwrap();
+        for person in [infector, contact] {
+            set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+        }
+        context.infect_person(infector, None, None, None);
+
+        // The forecasted infectiousness is the infector's actual infectiousness, so the forecast
+        // is accepted and the only contact is infected at exactly the overridden time
+        set_next_forecast_override(&mut context, infector, 2.5);
+        schedule_next_forecasted_infection(&mut context, infector);
+        context.execute();
+
+        let InfectionDataValue::Infectious {
+            infection_time,
+            infected_by,
+            ..
+        } = context.get_person_property(contact, InfectionData)
+        else {
+            panic!("Person {contact} should be infectious");
+        };
+        assert_eq!(infection_time, 2.5);
+        assert_eq!(infected_by, Some(infector));
+    }
+
     #[test]
     fn test_seed_initial_conditions_empty() {
         let mut context = setup_context(0, 1.0, 1.0, 5.0, 0.0);
@@ -622,37 +1261,1877 @@ mod test {
         assert_almost_eq!(ks_stat, 0.0, 0.01);
     }
 
-    #[test]
-    fn test_schedule_recovery() {
-        // Create a simulation with an infected person and schedule their recovery.
-        let mut context = setup_context(0, 0.0, 1.0, 5.0, 0.0);
-        load_rate_fns(&mut context).unwrap();
-        let person = context.add_person(()).unwrap();
-        context.infect_person(person, None, None, None);
-        // For later, we need to get the recovery time from the rate function.
-        context.execute();
-        let recovery_time = context.get_person_rate_fn(person).infection_duration();
-        schedule_recovery(&mut context, person);
-        context.execute();
-        // Make sure person is recovered.
-        assert_eq!(
-            context.get_person_property(person, InfectionData),
-            InfectionDataValue::Recovered {
-                infection_time: 0.0,
-                recovery_time
+    #[allow(clippy::cast_precision_loss)]
+    fn mean_infections_in_one_time_unit(
+        parameters: &Params,
+        infector_age: u8,
+        num_sims: u64,
+    ) -> f64 {
+        // One infectious person in a homogeneous setting with one susceptible who is reset to
+        // susceptible upon infection, so there is no susceptible depletion. Count the number of
+        // infections that occur in 1.0 time units.
+        let num_infected = Rc::new(RefCell::new(0usize));
+        for seed in 0..num_sims {
+            let num_infected_clone = Rc::clone(&num_infected);
+            let mut context = Context::new();
+            let parameters = Params {
+                seed,
+                ..parameters.clone()
+            };
+            context.init_random(parameters.seed);
+            context
+                .set_global_property_value(GlobalParams, parameters)
+                .unwrap();
+            context
+                .register_setting_category(
+                    &HomogeneousMixing,
+                    SettingProperties {
+                        alpha: 1.0,
+                        itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                            ratio: 1.0,
+                        }),
+                    },
+                )
+                .unwrap();
+            context.add_plan_with_phase(1.0, ixa::Context::shutdown, ExecutionPhase::Last);
+            load_rate_fns(&mut context).unwrap();
+            if context.get_params().symptom_progression_library.is_some() {
+                crate::symptom_progression::init(&mut context).unwrap();
             }
-        );
-        // Make sure nothing has happened after person is recovered.
-        assert_almost_eq!(context.get_current_time(), recovery_time, 0.0);
-    }
-
-    #[test]
-    fn test_location_infections() {
-        // Does one infectious person generate the number of infections as expected in different
-        // settings? We're going to run many simulations that each start with one infectious and three
-        // susceptible person. Each susceptible person belongs in one of three setting types
-        // and the infectious person is in all three settings. The simulation ends after the
-        // first person is infected. The location of this infection is records. We compare the number of
+            let susceptible = context.add_person(()).unwrap();
+            set_homogeneous_mixing_itinerary(&mut context, susceptible).unwrap();
+            let infectious_person = context.add_person((Age, infector_age)).unwrap();
+            set_homogeneous_mixing_itinerary(&mut context, infectious_person).unwrap();
+            context.infect_person(infectious_person, None, None, None);
+            context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionStatus>>(
+                move |context, event| {
+                    if event.current == InfectionStatusValue::Infectious
+                        && event.person_id != infectious_person
+                    {
+                        *num_infected_clone.borrow_mut() += 1;
+                        context.set_person_property(
+                            event.person_id,
+                            InfectionData,
+                            InfectionDataValue::Susceptible,
+                        );
+                    }
+                },
+            );
+            schedule_next_forecasted_infection(&mut context, infectious_person);
+            context.execute();
+        }
+        let mean_infections = *num_infected.borrow() as f64 / num_sims as f64;
+        mean_infections
+    }
+
+    fn mean_infections_with_transmission_scale(transmission_scale: f64, num_sims: u64) -> f64 {
+        let parameters = Params {
+            max_time: 100.0,
+            infectiousness_rate_fn: RateFnType::Constant {
+                rate: 1.0,
+                duration: 5.0,
+            },
+            transmission_scale: Some(transmission_scale),
+            ..Default::default()
+        };
+        mean_infections_in_one_time_unit(&parameters, 30, num_sims)
+    }
+
+    #[test]
+    fn test_presymptomatic_infectiousness_factor_reduces_transmission() {
+        let num_sims = 10_000;
+        // All incubation periods in the symptom library are longer than 1.0, so the infector is
+        // presymptomatic for the whole time unit.
+        let parameters = Params {
+            max_time: 100.0,
+            infectiousness_rate_fn: RateFnType::Constant {
+                rate: 1.0,
+                duration: 5.0,
+            },
+            proportion_asymptomatic: 0.0,
+            symptom_progression_library: Some(ProgressionLibraryType::EmpiricalFromFile {
+                file: PathBuf::from("./input/library_symptom_parameters.csv"),
+            }),
+            presymptomatic_infectiousness_factor: Some(0.25),
+            ..Default::default()
+        };
+        let reduced = mean_infections_in_one_time_unit(&parameters, 30, num_sims);
+        // With one contact and a rate of 1.0, we expect 1.0 infections in 1.0 time units at full
+        // infectiousness, so presymptomatic transmission still happens at a quarter of the rate.
+        assert!(reduced > 0.0);
+        assert_almost_eq!(reduced, 0.25, 0.02);
+    }
+
+    #[test]
+    fn test_transmission_disabled_seeds_progress_through_natural_history() {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 200.0,
+                    initial_incidence: 0.1,
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 5.0,
+                        duration: 5.0,
+                    },
+                    proportion_asymptomatic: 0.0,
+                    symptom_progression_library: Some(ProgressionLibraryType::EmpiricalFromFile {
+                        file: PathBuf::from("./input/library_symptom_parameters.csv"),
+                    }),
+                    transmission_enabled: Some(false),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        context.set_start_time(-1000.0);
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 1.0,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 1.0,
+                    }),
+                },
+            )
+            .unwrap();
+        for _ in 0..100 {
+            let person = context.add_person(()).unwrap();
+            set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+        }
+        crate::symptom_progression::init(&mut context).unwrap();
+        init(&mut context).unwrap();
+
+        let seeds = Rc::new(RefCell::new(Vec::new()));
+        let secondary_cases = Rc::new(RefCell::new(0usize));
+        let seeds_clone = Rc::clone(&seeds);
+        let secondary_cases_clone = Rc::clone(&secondary_cases);
+        context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionData>>(move |_, event| {
+            if let InfectionDataValue::Infectious { infected_by, .. } = event.current {
+                if infected_by.is_some() {
+                    *secondary_cases_clone.borrow_mut() += 1;
+                } else {
+                    seeds_clone.borrow_mut().push(event.person_id);
+                }
+            }
+        });
+        context.execute();
+
+        // At a rate of 5.0 in a homogeneous setting, the seeds would otherwise infect many people.
+        assert!(!seeds.borrow().is_empty());
+        assert_eq!(*secondary_cases.borrow(), 0);
+        assert_eq!(
+            context.query_people_count((InfectionStatus, InfectionStatusValue::Susceptible)),
+            100 - seeds.borrow().len()
+        );
+        for &person in seeds.borrow().iter() {
+            assert_eq!(
+                context.get_person_property(person, InfectionStatus),
+                InfectionStatusValue::Recovered
+            );
+            let record = context
+                .get_person_property(person, SymptomRecord)
+                .expect("Every seed is symptomatic");
+            assert!(record.symptom_end.is_some());
+        }
+    }
+
+    #[test]
+    fn test_stop_at_cumulative_incidence_stops_after_crossing_threshold() {
+        let max_time = 200.0;
+        let threshold = 0.3;
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time,
+                    initial_infection_seeding: Some(SeedingMode::ExactCount(2)),
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 2.0,
+                        duration: 5.0,
+                    },
+                    stop_at_cumulative_incidence: Some(threshold),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        context.set_start_time(-1000.0);
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 1.0,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 1.0,
+                    }),
+                },
+            )
+            .unwrap();
+        for _ in 0..200 {
+            let person = context.add_person(()).unwrap();
+            set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+        }
+        init(&mut context).unwrap();
+        context.add_plan(max_time, ixa::Context::shutdown);
+        context.execute();
+
+        // Each forecast infects at most one person, so the simulation stops at the infection
+        // that crosses the threshold
+        assert!(context.get_current_time() < max_time);
+        #[allow(clippy::cast_precision_loss)]
+        let cumulative_incidence = get_cumulative_infections(&context) as f64 / 200.0;
+        assert!(cumulative_incidence >= threshold);
+        assert!(cumulative_incidence < threshold + 0.01);
+    }
+
+    #[test]
+    fn test_seed_households_clusters_seeds_within_homes() {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 100.0,
+                    initial_infection_seeding: Some(SeedingMode::Households {
+                        count: 3,
+                        member_fraction: None,
+                    }),
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 1.0,
+                        duration: 5.0,
+                    },
+                    transmission_enabled: Some(false),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        context.set_start_time(-1000.0);
+        context
+            .register_setting_category(
+                &Home,
+                SettingProperties {
+                    alpha: 0.0,
+                    itinerary_specification: None,
+                },
+            )
+            .unwrap();
+        // Twenty homes of one to four people
+        let mut homes = HashMap::default();
+        for home_id in 0..20 {
+            for _ in 0..=home_id % 4 {
+                let person = context.add_person(()).unwrap();
+                context
+                    .add_itinerary(
+                        person,
+                        vec![ItineraryEntry::new(SettingId::new(Home, home_id), 1.0)],
+                    )
+                    .unwrap();
+                homes.insert(person, home_id);
+            }
+        }
+        init(&mut context).unwrap();
+        context.add_plan(0.0, ixa::Context::shutdown);
+        context.execute();
+
+        let mut seeded_homes: HashMap<usize, usize> = HashMap::default();
+        context.with_query_results(
+            (InfectionStatus, InfectionStatusValue::Infectious),
+            &mut |people| {
+                for person in people {
+                    *seeded_homes.entry(homes[person]).or_insert(0) += 1;
+                }
+            },
+        );
+        assert_eq!(seeded_homes.len(), 3);
+        // Every member of each seeded home is infected
+        for (home_id, seeded_members) in seeded_homes {
+            assert_eq!(seeded_members, home_id % 4 + 1);
+        }
+    }
+
+    #[test]
+    fn test_seed_recovered_households_clusters_immunity_within_homes() {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 100.0,
+                    initial_recovered_seeding: Some(SeedingMode::Households {
+                        count: 10,
+                        member_fraction: None,
+                    }),
+                    transmission_enabled: Some(false),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        context
+            .register_setting_category(
+                &Home,
+                SettingProperties {
+                    alpha: 0.0,
+                    itinerary_specification: None,
+                },
+            )
+            .unwrap();
+        // Forty homes of four people
+        let mut homes = HashMap::default();
+        for home_id in 0..40 {
+            for _ in 0..4 {
+                let person = context.add_person(()).unwrap();
+                context
+                    .add_itinerary(
+                        person,
+                        vec![ItineraryEntry::new(SettingId::new(Home, home_id), 1.0)],
+                    )
+                    .unwrap();
+                homes.insert(person, home_id);
+            }
+        }
+        init(&mut context).unwrap();
+        context.add_plan(0.0, ixa::Context::shutdown);
+        context.execute();
+
+        let mut recovered_per_home: HashMap<usize, usize> = HashMap::default();
+        context.with_query_results(
+            (InfectionStatus, InfectionStatusValue::Recovered),
+            &mut |people| {
+                for person in people {
+                    *recovered_per_home.entry(homes[person]).or_insert(0) += 1;
+                }
+            },
+        );
+        // A quarter of the population is recovered, but rather than being spread across most
+        // homes as uniform seeding would, the recovered make up exactly a quarter of the homes
+        assert_eq!(recovered_per_home.len(), 10);
+        for recovered in recovered_per_home.into_values() {
+            assert_eq!(recovered, 4);
+        }
+    }
+
+    /// Infect half of a homogeneously mixing population of ten people without transmission, and
+    /// return the number of active members of the setting before and after they recover.
+    fn run_recoveries_in_setting(remove_recovered_from_settings: Option<bool>) -> (usize, usize) {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 100.0,
+                    transmission_enabled: Some(false),
+                    remove_recovered_from_settings,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 1.0,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 1.0,
+                    }),
+                },
+            )
+            .unwrap();
+        let people: Vec<PersonId> = (0..10).map(|_| context.add_person(()).unwrap()).collect();
+        for &person in &people {
+            set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+        }
+        init(&mut context).unwrap();
+        let setting = SettingId::new(HomogeneousMixing, 0);
+        let before = context.get_setting_members(&setting).unwrap().len();
+        for &person in &people[..5] {
+            context.add_plan(0.0, move |context| {
+                context.infect_person(person, None, None, None);
+            });
+        }
+        context.add_plan(100.0, ixa::Context::shutdown);
+        context.execute();
+        assert_eq!(
+            context.query_people_count((InfectionStatus, InfectionStatusValue::Recovered)),
+            5
+        );
+        let after = context.get_setting_members(&setting).unwrap().len();
+        (before, after)
+    }
+
+    #[test]
+    fn test_recovered_people_stay_in_settings_by_default() {
+        assert_eq!(run_recoveries_in_setting(None), (10, 10));
+        assert_eq!(run_recoveries_in_setting(Some(false)), (10, 10));
+    }
+
+    #[test]
+    fn test_remove_recovered_from_settings_shrinks_settings() {
+        assert_eq!(run_recoveries_in_setting(Some(true)), (10, 5));
+    }
+
+    #[test]
+    fn test_recovered_reinfected_at_reduced_rate() {
+        let factor = 0.3;
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 100.0,
+                    transmission_enabled: Some(false),
+                    recovered_susceptibility_factor: Some(factor),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 1.0,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 1.0,
+                    }),
+                },
+            )
+            .unwrap();
+        let infector = context.add_person(()).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, infector).unwrap();
+        let recovered = InfectionDataValue::Recovered {
+            infection_time: -10.0,
+            recovery_time: -5.0,
+        };
+        let mut recovered_people = HashSet::new();
+        for i in 0..100 {
+            let person = context.add_person(()).unwrap();
+            set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+            if i % 2 == 0 {
+                context.set_person_property(person, InfectionData, recovered);
+                recovered_people.insert(person);
+            }
+        }
+        init(&mut context).unwrap();
+        context.infect_person(infector, None, None, None);
+
+        let mut naive_infections = 0;
+        let mut recovered_infections = 0;
+        for _ in 0..10_000 {
+            let Some(contact) = infection_attempt_in_setting(&mut context, infector, None) else {
+                continue;
+            };
+            // Return the contact to their previous state so that the population is unchanged
+            if recovered_people.contains(&contact) {
+                recovered_infections += 1;
+                context.set_person_property(contact, InfectionData, recovered);
+            } else {
+                naive_infections += 1;
+                context.set_person_property(
+                    contact,
+                    InfectionData,
+                    InfectionDataValue::Susceptible,
+                );
+            }
+        }
+        let relative_rate = f64::from(recovered_infections) / f64::from(naive_infections);
+        assert!(
+            (relative_rate - factor).abs() < 0.03,
+            "Relative reinfection rate {relative_rate} is not close to {factor}"
+        );
+    }
+
+    #[test]
+    fn test_reinfection_before_immunity_wanes() {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 100.0,
+                    transmission_enabled: Some(false),
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 1.0,
+                        duration: 5.0,
+                    },
+                    immunity_duration: Some(10.0),
+                    recovered_susceptibility_factor: Some(1.0),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 1.0,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 1.0,
+                    }),
+                },
+            )
+            .unwrap();
+        let person = context.add_person(()).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+        init(&mut context).unwrap();
+        // Recover at 5.0, and be reinfected at 12.0 before the immunity from that recovery wanes
+        // at 15.0
+        context.add_plan(0.0, move |context| {
+            context.infect_person(person, None, None, None);
+        });
+        context.add_plan(12.0, move |context| {
+            assert_eq!(
+                context.get_person_property(person, InfectionStatus),
+                InfectionStatusValue::Recovered
+            );
+            context.infect_person(person, None, None, None);
+        });
+        for (time, status) in [
+            (16.0, InfectionStatusValue::Infectious),
+            (20.0, InfectionStatusValue::Recovered),
+            (28.0, InfectionStatusValue::Susceptible),
+        ] {
+            context.add_plan(time, move |context| {
+                assert_eq!(context.get_person_property(person, InfectionStatus), status);
+            });
+        }
+        context.add_plan(30.0, ixa::Context::shutdown);
+        context.execute();
+    }
+
+    #[test]
+    fn test_recovered_not_reinfected_without_susceptibility_factor() {
+        let mut context = setup_context(0, 1.0, 1.0, 5.0, 0.0);
+        load_rate_fns(&mut context).unwrap();
+        let infector = context.add_person(()).unwrap();
+        let contact = context.add_person(()).unwrap();
+        for person in [infector, contact] {
+            set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+        }
+        context.set_person_property(
+            contact,
+            InfectionData,
+            InfectionDataValue::Recovered {
+                infection_time: -10.0,
+                recovery_time: -5.0,
+            },
+        );
+        context.infect_person(infector, None, None, None);
+        for _ in 0..100 {
+            assert!(infection_attempt_in_setting(&mut context, infector, None).is_none());
+        }
+    }
+
+    #[test]
+    fn test_stop_on_extinction_ends_before_max_time() {
+        let max_time = 200.0;
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time,
+                    initial_infection_seeding: Some(SeedingMode::ExactCount(2)),
+                    // An expected 0.5 secondary cases per infection, so the epidemic fades out
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 0.1,
+                        duration: 5.0,
+                    },
+                    stop_on_extinction: Some(true),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        context.set_start_time(-1000.0);
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 1.0,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 1.0,
+                    }),
+                },
+            )
+            .unwrap();
+        for _ in 0..100 {
+            let person = context.add_person(()).unwrap();
+            set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+        }
+        init(&mut context).unwrap();
+        context.add_plan(max_time, ixa::Context::shutdown);
+        context.execute();
+
+        assert!(context.get_current_time() < max_time);
+        assert_eq!(
+            context.query_people_count((InfectionStatus, InfectionStatusValue::Infectious)),
+            0
+        );
+        assert!(
+            context.query_people_count((InfectionStatus, InfectionStatusValue::Recovered)) >= 2
+        );
+    }
+
+    #[test]
+    fn test_wall_clock_budget_stops_simulation_with_reports() {
+        let max_time = 200.0;
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time,
+                    initial_infection_seeding: Some(SeedingMode::ExactCount(2)),
+                    // Any real simulation exceeds a budget of a nanosecond by the first check
+                    max_wall_clock_seconds: Some(1e-9),
+                    prevalence_report: ReportParams {
+                        write: true,
+                        filename: Some("prevalence.csv".to_string()),
+                        period: Some(1.0),
+                        cumulative: false,
+                        by_setting_type: false,
+                        age_groups: None,
+                        compress: None,
+                        include_cumulative: false,
+                    },
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        context.set_start_time(-1000.0);
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 1.0,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 1.0,
+                    }),
+                },
+            )
+            .unwrap();
+        // The prevalence report is tabulated by age
+        for _ in 0..100 {
+            let person = context.add_person((Age, 30)).unwrap();
+            set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+        }
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink("prevalence.csv", ReportSink::Memory(Rc::clone(&buffer)));
+        init(&mut context).unwrap();
+        crate::reports::init(&mut context).unwrap();
+        context.add_plan(max_time, ixa::Context::shutdown);
+        context.execute();
+
+        assert!(context.get_current_time() < max_time);
+        std::mem::drop(context);
+        let buffer = buffer.borrow();
+        let mut reader = csv::Reader::from_reader(buffer.as_slice());
+        assert!(reader.records().count() > 0);
+    }
+
+    #[test]
+    fn test_offspring_distribution_is_right_skewed_under_superspreading() {
+        let num_seeds = 20;
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 200.0,
+                    initial_infection_seeding: Some(SeedingMode::ExactCount(num_seeds)),
+                    // An expected 0.5 secondary cases per infection for most people
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 0.1,
+                        duration: 5.0,
+                    },
+                    // One in ten people (those 65 and older) are superspreaders with twenty
+                    // times as many contacts
+                    age_contact_multipliers: Some(vec![
+                        AgeContactMultiplier {
+                            min: 0,
+                            multiplier: 1.0,
+                        },
+                        AgeContactMultiplier {
+                            min: 65,
+                            multiplier: 20.0,
+                        },
+                    ]),
+                    offspring_distribution_summary: Some(true),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        context.set_start_time(-1000.0);
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 1.0,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 1.0,
+                    }),
+                },
+            )
+            .unwrap();
+        for i in 0..1000 {
+            let age = if i % 10 == 0 { 70 } else { 30 };
+            let person = context.add_person((Age, age)).unwrap();
+            set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+        }
+        init(&mut context).unwrap();
+        context.add_plan(200.0, ixa::Context::shutdown);
+        context.execute();
+
+        let distribution = get_offspring_distribution(&context);
+        let infections: usize = distribution.iter().map(|&(_, count)| count).sum();
+        let secondary_cases: usize = distribution
+            .iter()
+            .map(|&(cases, count)| cases * count)
+            .sum();
+        // Every infection is counted once, and every infection except the seeds has an infector
+        assert_eq!(infections, get_cumulative_infections(&context));
+        assert_eq!(secondary_cases, infections - num_seeds);
+
+        // Most infections cause no secondary cases, while a few cause many
+        let mode = distribution
+            .iter()
+            .max_by_key(|&&(_, count)| count)
+            .unwrap()
+            .0;
+        assert_eq!(mode, 0);
+        let mut cumulative = 0;
+        let median = distribution
+            .iter()
+            .find(|&&(_, count)| {
+                cumulative += count;
+                2 * cumulative >= infections
+            })
+            .unwrap()
+            .0;
+        let mean = secondary_cases as f64 / infections as f64;
+        assert!(
+            mean > median as f64,
+            "Mean {mean} is not above median {median}"
+        );
+        let max = distribution.last().unwrap().0;
+        assert!(
+            max as f64 > 3.0 * mean,
+            "Max {max} is not far above mean {mean}"
+        );
+    }
+
+    /// Run an epidemic in a homogeneously mixing population with a constant infectious period of
+    /// 5.0, and return the realized infectious period of everyone who recovered.
+    fn run_realized_infectious_periods(time_scale: Option<f64>) -> Vec<f64> {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 100.0,
+                    initial_infection_seeding: Some(SeedingMode::ExactCount(5)),
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 0.5,
+                        duration: 5.0,
+                    },
+                    time_scale,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        context.set_start_time(-1000.0);
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 1.0,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 1.0,
+                    }),
+                },
+            )
+            .unwrap();
+        let people: Vec<PersonId> = (0..100).map(|_| context.add_person(()).unwrap()).collect();
+        for &person in &people {
+            set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+        }
+        init(&mut context).unwrap();
+        context.add_plan(100.0, ixa::Context::shutdown);
+        context.execute();
+
+        people
+            .into_iter()
+            .filter_map(
+                |person| match context.get_person_property(person, InfectionData) {
+                    InfectionDataValue::Recovered {
+                        infection_time,
+                        recovery_time,
+                    } => Some(recovery_time - infection_time),
+                    _ => None,
+                },
+            )
+            .collect()
+    }
+
+    #[test]
+    fn test_time_scale_shortens_realized_infectious_periods() {
+        let periods = run_realized_infectious_periods(None);
+        let compressed_periods = run_realized_infectious_periods(Some(10.0));
+        assert!(periods.len() > 5);
+        assert!(compressed_periods.len() > 5);
+        for period in periods {
+            assert_almost_eq!(period, 5.0, 1e-10);
+        }
+        for period in compressed_periods {
+            assert_almost_eq!(period, 0.5, 1e-10);
+        }
+    }
+
+    /// Infect two people at time 1.0, who recover at time 3.0, and reinfect one of them soon after
+    /// recovery and the other long after recovery. Returns the rapidly reinfected person, the
+    /// late reinfected person, and the number of symptom episodes each person started.
+    fn run_reinfections(
+        prevents_infection: bool,
+    ) -> (Context, PersonId, PersonId, HashMap<PersonId, usize>) {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 200.0,
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 1.0,
+                        duration: 2.0,
+                    },
+                    proportion_asymptomatic: 0.0,
+                    symptom_progression_library: Some(ProgressionLibraryType::EmpiricalFromFile {
+                        file: PathBuf::from("./input/library_symptom_parameters.csv"),
+                    }),
+                    transmission_enabled: Some(false),
+                    min_reinfection_interval: Some(10.0),
+                    min_reinfection_interval_prevents_infection: Some(prevents_infection),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        let rapid = context.add_person(()).unwrap();
+        let late = context.add_person(()).unwrap();
+        crate::symptom_progression::init(&mut context).unwrap();
+        init(&mut context).unwrap();
+
+        let symptom_episodes = Rc::new(RefCell::new(HashMap::default()));
+        let symptom_episodes_clone = Rc::clone(&symptom_episodes);
+        context.subscribe_to_event::<PersonPropertyChangeEvent<Symptoms>>(move |_, event| {
+            if event.current == Some(SymptomValue::Presymptomatic) {
+                *symptom_episodes_clone
+                    .borrow_mut()
+                    .entry(event.person_id)
+                    .or_insert(0) += 1;
+            }
+        });
+        context.add_plan(1.0, move |context| {
+            context.infect_person(rapid, None, None, None);
+            context.infect_person(late, None, None, None);
+        });
+        for (person, reinfection_time) in [(rapid, 5.0), (late, 150.0)] {
+            context.add_plan(reinfection_time, move |context| {
+                context.set_person_property(person, InfectionData, InfectionDataValue::Susceptible);
+                context.infect_person(person, None, None, None);
+            });
+        }
+        context.execute();
+        let symptom_episodes = symptom_episodes.borrow().clone();
+        (context, rapid, late, symptom_episodes)
+    }
+
+    #[test]
+    fn test_rapid_reinfection_starts_no_new_symptom_episode() {
+        let (context, rapid, late, symptom_episodes) = run_reinfections(false);
+        // Both people are reinfected, but only the late reinfection starts a new symptom episode
+        for person in [rapid, late] {
+            let InfectionDataValue::Recovered { infection_time, .. } =
+                context.get_person_property(person, InfectionData)
+            else {
+                panic!("Person {person} should have recovered from their reinfection");
+            };
+            assert!(infection_time > 1.0);
+        }
+        assert_eq!(symptom_episodes[&rapid], 1);
+        assert_eq!(symptom_episodes[&late], 2);
+    }
+
+    #[test]
+    fn test_min_reinfection_interval_prevents_rapid_reinfection() {
+        let (context, rapid, late, symptom_episodes) = run_reinfections(true);
+        assert_eq!(
+            context.get_person_property(rapid, InfectionStatus),
+            InfectionStatusValue::Susceptible
+        );
+        let InfectionDataValue::Recovered { infection_time, .. } =
+            context.get_person_property(late, InfectionData)
+        else {
+            panic!("Person {late} should have recovered from their reinfection");
+        };
+        assert_almost_eq!(infection_time, 150.0, 0.0);
+        assert_eq!(symptom_episodes[&rapid], 1);
+        assert_eq!(symptom_episodes[&late], 2);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn test_abortive_infections_neither_transmit_nor_develop_symptoms() {
+        let abortive_infection_probability = 0.4;
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 200.0,
+                    initial_infection_seeding: Some(SeedingMode::ExactCount(5)),
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 1.0,
+                        duration: 5.0,
+                    },
+                    proportion_asymptomatic: 0.0,
+                    symptom_progression_library: Some(ProgressionLibraryType::EmpiricalFromFile {
+                        file: PathBuf::from("./input/library_symptom_parameters.csv"),
+                    }),
+                    abortive_infection_probability: Some(abortive_infection_probability),
+                    abortive_infection_immunity: Some(true),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        context.set_start_time(-1000.0);
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 0.0,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 1.0,
+                    }),
+                },
+            )
+            .unwrap();
+        for _ in 0..1000 {
+            let person = context.add_person(()).unwrap();
+            set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+        }
+        crate::symptom_progression::init(&mut context).unwrap();
+        init(&mut context).unwrap();
+
+        // Abortive infections go straight from susceptible to recovered
+        let aborted = Rc::new(RefCell::new(HashSet::new()));
+        let infectors = Rc::new(RefCell::new(Vec::new()));
+        let num_infections = Rc::new(RefCell::new(0usize));
+        let aborted_clone = Rc::clone(&aborted);
+        let infectors_clone = Rc::clone(&infectors);
+        let num_infections_clone = Rc::clone(&num_infections);
+        context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionData>>(move |_, event| {
+            match (event.previous, event.current) {
+                (InfectionDataValue::Susceptible, InfectionDataValue::Recovered { .. }) => {
+                    aborted_clone.borrow_mut().insert(event.person_id);
+                }
+                (
+                    _,
+                    InfectionDataValue::Infectious {
+                        infected_by: Some(infector),
+                        ..
+                    },
+                ) => {
+                    *num_infections_clone.borrow_mut() += 1;
+                    infectors_clone.borrow_mut().push(infector);
+                }
+                _ => (),
+            }
+        });
+        context.execute();
+
+        let aborted = aborted.borrow();
+        let num_aborted = aborted.len();
+        let num_exposed = num_aborted + *num_infections.borrow();
+        assert!(num_exposed > 100);
+        assert_almost_eq!(
+            num_aborted as f64 / num_exposed as f64,
+            abortive_infection_probability,
+            0.05
+        );
+        for &person in aborted.iter() {
+            // Aborted people are immune, never develop symptoms, and never transmit
+            assert_eq!(
+                context.get_person_property(person, InfectionStatus),
+                InfectionStatusValue::Recovered
+            );
+            assert_eq!(context.get_person_property(person, Symptoms), None);
+            assert_eq!(context.get_person_property(person, SymptomRecord), None);
+        }
+        assert!(infectors
+            .borrow()
+            .iter()
+            .all(|infector| !aborted.contains(infector)));
+    }
+
+    #[test]
+    fn test_home_setting_rt_matches_analytic_expectation() {
+        let num_sims = 1000;
+        let mut home_rt = 0.0;
+        for seed in 0..num_sims {
+            let mut context = Context::new();
+            context.init_random(seed);
+            context
+                .set_global_property_value(
+                    GlobalParams,
+                    Params {
+                        max_time: 100.0,
+                        infectiousness_rate_fn: RateFnType::Constant {
+                            rate: 1.0,
+                            duration: 5.0,
+                        },
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            for category in [&Home as &dyn SettingCategory, &Workplace] {
+                context
+                    .register_setting_category(
+                        category,
+                        SettingProperties {
+                            alpha: 0.0,
+                            itinerary_specification: None,
+                        },
+                    )
+                    .unwrap();
+            }
+            load_rate_fns(&mut context).unwrap();
+            track_setting_transmission(&mut context);
+
+            // The infector splits their time between a home with one other person, who is reset
+            // to susceptible upon infection, and a workplace with nobody else in it
+            let infector = context.add_person(()).unwrap();
+            let housemate = context.add_person(()).unwrap();
+            context
+                .add_itinerary(
+                    infector,
+                    vec![
+                        ItineraryEntry::new(SettingId::new(Home, 0), 0.5),
+                        ItineraryEntry::new(SettingId::new(Workplace, 0), 0.5),
+                    ],
+                )
+                .unwrap();
+            context
+                .add_itinerary(
+                    housemate,
+                    vec![ItineraryEntry::new(SettingId::new(Home, 0), 1.0)],
+                )
+                .unwrap();
+            context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionStatus>>(
+                move |context, event| {
+                    if event.current == InfectionStatusValue::Infectious
+                        && event.person_id == housemate
+                    {
+                        context.set_person_property(
+                            housemate,
+                            InfectionData,
+                            InfectionDataValue::Susceptible,
+                        );
+                    }
+                },
+            );
+            context.infect_person(infector, None, None, None);
+            schedule_next_forecasted_infection(&mut context, infector);
+            schedule_recovery(&mut context, infector);
+            context.execute();
+
+            let setting_rt = get_setting_rt(&context);
+            // The infector spent half of their time in each setting type
+            assert_eq!(setting_rt.len(), 2);
+            assert_eq!(setting_rt[0].0, "Home");
+            assert_eq!(setting_rt[1], ("Workplace", 0.0));
+            home_rt += setting_rt[0].1;
+        }
+        // Attempts occur at a rate of 1.0 for 5.0 time units, half of them in the home, and all
+        // attempts in the home infect the housemate. So there are 2.5 home infections per 0.5
+        // units of infector time in the home.
+        #[allow(clippy::cast_precision_loss)]
+        let mean_home_rt = home_rt / num_sims as f64;
+        assert_almost_eq!(mean_home_rt, 5.0, 0.25);
+    }
+
+    #[test]
+    fn test_setting_attack_rates_home_only_transmission() {
+        let mut context = Context::new();
+        context.init_random(0);
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 100.0,
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 3.0,
+                        duration: 5.0,
+                    },
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        for category in [&Home as &dyn SettingCategory, &Workplace] {
+            context
+                .register_setting_category(
+                    category,
+                    SettingProperties {
+                        alpha: 0.0,
+                        itinerary_specification: None,
+                    },
+                )
+                .unwrap();
+        }
+        // Ten homes of four people, and a workplace whose members never go home
+        let mut index_cases = vec![];
+        for home in 0..10 {
+            for member in 0..4 {
+                let person = context.add_person(()).unwrap();
+                context
+                    .add_itinerary(
+                        person,
+                        vec![ItineraryEntry::new(SettingId::new(Home, home), 1.0)],
+                    )
+                    .unwrap();
+                if member == 0 {
+                    index_cases.push(person);
+                }
+            }
+        }
+        for _ in 0..20 {
+            let person = context.add_person(()).unwrap();
+            context
+                .add_itinerary(
+                    person,
+                    vec![ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0)],
+                )
+                .unwrap();
+        }
+        init(&mut context).unwrap();
+        context.add_plan(0.1, move |context| {
+            for &person in &index_cases {
+                context.infect_person(person, None, None, None);
+            }
+        });
+        context.execute();
+
+        let attack_rates = get_setting_attack_rates(&context);
+        assert_eq!(attack_rates.len(), 2);
+        assert_eq!(attack_rates[0].0, "Home");
+        assert!(attack_rates[0].1 > 0.9);
+        assert_eq!(attack_rates[1], ("Workplace".to_string(), 0.0));
+    }
+
+    #[test]
+    fn test_daily_activity_profile_limits_workplace_transmission_to_work_hours() {
+        let mut context = Context::new();
+        context.init_random(0);
+        // Workplaces are only active from 9 to 5
+        let hourly_multipliers = (0..24)
+            .map(|hour| if (9..17).contains(&hour) { 1.0 } else { 0.0 })
+            .collect();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 100.0,
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 1.0,
+                        duration: 5.0,
+                    },
+                    settings_properties: [(
+                        CoreSettingsTypes::Workplace,
+                        SettingProperties {
+                            alpha: 0.0,
+                            itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                                ratio: 1.0,
+                            }),
+                        },
+                    )]
+                    .into_iter()
+                    .collect(),
+                    daily_activity_profiles: Some(
+                        [(
+                            CoreSettingsTypes::Workplace,
+                            DailyActivityProfile { hourly_multipliers },
+                        )]
+                        .into_iter()
+                        .collect(),
+                    ),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        crate::settings::init(&mut context).unwrap();
+        let mut workers = vec![];
+        for _ in 0..200 {
+            let person = context.add_person(()).unwrap();
+            context
+                .add_itinerary(
+                    person,
+                    vec![ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0)],
+                )
+                .unwrap();
+            workers.push(person);
+        }
+        init(&mut context).unwrap();
+        // The index cases are infected at night, when the workplace is inactive
+        context.add_plan(0.1, move |context| {
+            for &person in &workers[..5] {
+                context.infect_person(person, None, None, None);
+            }
+        });
+        let transmission_hours = Rc::new(RefCell::new(Vec::new()));
+        let transmission_hours_clone = Rc::clone(&transmission_hours);
+        context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionData>>(
+            move |context, event| {
+                if let InfectionDataValue::Infectious {
+                    infected_by: Some(_),
+                    ..
+                } = event.current
+                {
+                    let hour = context.get_current_time().rem_euclid(1.0) * 24.0;
+                    transmission_hours_clone.borrow_mut().push(hour);
+                }
+            },
+        );
+        context.execute();
+
+        let transmission_hours = transmission_hours.borrow();
+        assert!(!transmission_hours.is_empty());
+        for &hour in transmission_hours.iter() {
+            assert!((9.0..17.0).contains(&hour));
+        }
+    }
+
+    #[test]
+    fn test_setting_contact_rate_determines_secondary_cases() {
+        let mut context = Context::new();
+        context.init_random(0);
+        let contacts_per_day = 2.0;
+        let transmission_probability = 0.25;
+        let duration = 4.0;
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 4.5,
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 1.0,
+                        duration,
+                    },
+                    settings_properties: [(
+                        CoreSettingsTypes::Workplace,
+                        SettingProperties {
+                            alpha: 1.0,
+                            itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                                ratio: 1.0,
+                            }),
+                        },
+                    )]
+                    .into_iter()
+                    .collect(),
+                    setting_contact_rates: Some(
+                        [(
+                            CoreSettingsTypes::Workplace,
+                            SettingContactRate {
+                                contacts_per_day,
+                                transmission_probability,
+                            },
+                        )]
+                        .into_iter()
+                        .collect(),
+                    ),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        crate::settings::init(&mut context).unwrap();
+        // The workplace is large enough that the index cases barely deplete its susceptibles
+        let mut workers = vec![];
+        for _ in 0..50_000 {
+            let person = context.add_person(()).unwrap();
+            context
+                .add_itinerary(
+                    person,
+                    vec![ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0)],
+                )
+                .unwrap();
+            workers.push(person);
+        }
+        init(&mut context).unwrap();
+        let index_cases: HashSet<PersonId> = workers[..400].iter().copied().collect();
+        let index_cases_clone = index_cases.clone();
+        context.add_plan(0.1, move |context| {
+            for &person in &index_cases_clone {
+                context.infect_person(person, None, None, None);
+            }
+        });
+        let secondary_cases = Rc::new(RefCell::new(0_u32));
+        let secondary_cases_clone = Rc::clone(&secondary_cases);
+        context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionData>>(
+            move |_context, event| {
+                if let InfectionDataValue::Infectious {
+                    infected_by: Some(infector),
+                    ..
+                } = event.current
+                {
+                    if index_cases.contains(&infector) {
+                        *secondary_cases_clone.borrow_mut() += 1;
+                    }
+                }
+            },
+        );
+        context.execute();
+
+        // The multiplier no longer depends on the size of the workplace, so each index case
+        // infects contacts_per_day * transmission_probability * duration people on average
+        let mean_secondary_cases = f64::from(*secondary_cases.borrow()) / 400.0;
+        assert_almost_eq!(
+            mean_secondary_cases,
+            contacts_per_day * transmission_probability * duration,
+            0.25
+        );
+    }
+
+    #[test]
+    fn test_asymptomatic_infectors_recover_earlier_with_fewer_secondary_cases() {
+        let duration = 4.0;
+        let (context, workers, secondary_cases) = run_workplace_index_cases(
+            duration,
+            Params {
+                asymptomatic_duration_factor: Some(0.5),
+                ..Default::default()
+            },
+            |context, workers| {
+                // Half of the index cases develop symptoms, and nobody else does
+                let symptomatic: HashSet<PersonId> = workers[..100].iter().copied().collect();
+                context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionStatus>>(
+                    move |context, event| {
+                        if event.current == InfectionStatusValue::Infectious
+                            && symptomatic.contains(&event.person_id)
+                        {
+                            context.set_person_property(
+                                event.person_id,
+                                Symptoms,
+                                Some(SymptomValue::Presymptomatic),
+                            );
+                        }
+                    },
+                );
+            },
+        );
+        let symptomatic: HashSet<PersonId> = workers[..100].iter().copied().collect();
+        let asymptomatic: HashSet<PersonId> = workers[100..200].iter().copied().collect();
+
+        let recovery_time =
+            |person: PersonId| match context.get_person_property(person, InfectionData) {
+                InfectionDataValue::Recovered { recovery_time, .. } => recovery_time,
+                _ => panic!("Person {person} should have recovered"),
+            };
+        for &person in &symptomatic {
+            assert_almost_eq!(recovery_time(person), 0.1 + duration, 1e-12);
+        }
+        for &person in &asymptomatic {
+            assert_almost_eq!(recovery_time(person), 0.1 + 0.5 * duration, 1e-12);
+        }
+        // Each index case makes one infection attempt per unit time while infectious
+        let symptomatic_mean = mean_secondary_cases(&secondary_cases, &symptomatic);
+        let asymptomatic_mean = mean_secondary_cases(&secondary_cases, &asymptomatic);
+        assert!(asymptomatic_mean < symptomatic_mean);
+        assert_almost_eq!(symptomatic_mean, duration, 0.6);
+        assert_almost_eq!(asymptomatic_mean, 0.5 * duration, 0.45);
+    }
+
+    #[test]
+    fn test_reinfected_infectors_cause_fewer_secondary_cases() {
+        let duration = 4.0;
+        let (context, workers, secondary_cases) = run_workplace_index_cases(
+            duration,
+            Params {
+                reinfection_infectiousness_factor: Some(0.5),
+                ..Default::default()
+            },
+            |context, workers| {
+                // Half of the index cases start the simulation recovered from a prior infection,
+                // and everyone else is susceptible, so the settings are identical for both halves
+                let previously_infected: Vec<PersonId> = workers[100..200].to_vec();
+                context.add_plan(0.0, move |context| {
+                    for &person in &previously_infected {
+                        seed_recovered(context, person);
+                    }
+                });
+            },
+        );
+        let first_infections: HashSet<PersonId> = workers[..100].iter().copied().collect();
+        let reinfections: HashSet<PersonId> = workers[100..200].iter().copied().collect();
+
+        for &person in &first_infections {
+            assert_eq!(context.get_person_property(person, InfectionCount), 1);
+        }
+        for &person in &reinfections {
+            assert_eq!(context.get_person_property(person, InfectionCount), 2);
+        }
+        // Each reinfection makes half as many infection attempts as a first infection. Onward
+        // transmission from the secondary cases depletes the susceptible pool, which lowers both
+        // means below the attempt rate by the same fraction, so we compare their ratio
+        let first_infection_mean = mean_secondary_cases(&secondary_cases, &first_infections);
+        let reinfection_mean = mean_secondary_cases(&secondary_cases, &reinfections);
+        assert!(reinfection_mean < first_infection_mean);
+        assert!(first_infection_mean <= duration + 0.6);
+        assert_almost_eq!(reinfection_mean / first_infection_mean, 0.5, 0.1);
+    }
+
+    #[test]
+    fn test_age_transmission_matrix_concentrates_on_diagonal_with_assortative_mixing() {
+        let mut context = Context::new();
+        context.init_random(0);
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 100.0,
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 1.0,
+                        duration: 5.0,
+                    },
+                    age_transmission_matrix: Some(true),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        for category in [&Workplace as &dyn SettingCategory, &CensusTract] {
+            context
+                .register_setting_category(
+                    category,
+                    SettingProperties {
+                        alpha: 0.0,
+                        itinerary_specification: None,
+                    },
+                )
+                .unwrap();
+        }
+        // Children and older adults each mostly mix in their own setting, with a little mixing
+        // in a shared census tract
+        let mut index_cases = vec![];
+        for (group, age) in [10u8, 70u8].into_iter().enumerate() {
+            for member in 0..100 {
+                let person = context.add_person((Age, age)).unwrap();
+                context
+                    .add_itinerary(
+                        person,
+                        vec![
+                            ItineraryEntry::new(SettingId::new(Workplace, group), 0.9),
+                            ItineraryEntry::new(SettingId::new(CensusTract, 0), 0.1),
+                        ],
+                    )
+                    .unwrap();
+                if member == 0 {
+                    index_cases.push(person);
+                }
+            }
+        }
+        init(&mut context).unwrap();
+        context.add_plan(0.1, move |context| {
+            for &person in &index_cases {
+                context.infect_person(person, None, None, None);
+            }
+        });
+        context.execute();
+
+        let matrix = get_age_transmission_matrix(&context);
+        let (diagonal, off_diagonal) = matrix.iter().fold(
+            (0, 0),
+            |(diagonal, off_diagonal), &(infectee_age, infector_age, count)| {
+                if infectee_age == infector_age {
+                    (diagonal + count, off_diagonal)
+                } else {
+                    (diagonal, off_diagonal + count)
+                }
+            },
+        );
+        assert!(matrix
+            .iter()
+            .all(
+                |&(infectee_age, infector_age, _)| [10, 70].contains(&infectee_age)
+                    && [10, 70].contains(&infector_age)
+            ));
+        assert!(diagonal > 0);
+        assert!(diagonal > 5 * off_diagonal);
+    }
+
+    /// Calibrate the transmission scale to a target R0 of 2.0 with the given contacts and cap on
+    /// infections per forecast, and check that the mean number of secondary cases of an index case
+    /// early in the epidemic matches the target
+    fn assert_calibrated_secondary_cases_match_target_r0(
+        contacts_per_forecast: Option<usize>,
+        max_infections_per_forecast: Option<usize>,
+    ) {
+        let num_sims = 300;
+        let target_r0 = 2.0;
+        let infections_per_forecast = contacts_per_forecast
+            .unwrap_or(1)
+            .min(max_infections_per_forecast.unwrap_or(usize::MAX));
+        let index_case_infections = Rc::new(RefCell::new(0usize));
+        for seed in 0..num_sims {
+            let mut context = Context::new();
+            context.init_random(seed);
+            context
+                .set_global_property_value(
+                    GlobalParams,
+                    Params {
+                        max_time: 100.0,
+                        infectiousness_rate_fn: RateFnType::Constant {
+                            rate: 1.0,
+                            duration: 5.0,
+                        },
+                        target_r0: Some(target_r0),
+                        contacts_per_forecast,
+                        max_infections_per_forecast,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            context
+                .register_setting_category(
+                    &HomogeneousMixing,
+                    SettingProperties {
+                        alpha: 0.0,
+                        itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                            ratio: 1.0,
+                        }),
+                    },
+                )
+                .unwrap();
+            let mut people = Vec::new();
+            for _ in 0..1000 {
+                let person = context.add_person(()).unwrap();
+                set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+                people.push(person);
+            }
+            init(&mut context).unwrap();
+            // Each person makes forecasts at a rate of 1.0 for 5.0 time units, each of which
+            // infects `infections_per_forecast` people when everyone is susceptible
+            assert_almost_eq!(estimate_r0(&context), target_r0, 1e-6);
+            #[allow(clippy::cast_precision_loss)]
+            let expected_multiplier = target_r0 / (5.0 * infections_per_forecast as f64);
+            assert_almost_eq!(
+                max_total_infectiousness_multiplier(&context, people[0]),
+                expected_multiplier,
+                1e-6
+            );
+
+            // Count the infections caused by a single index case early in the epidemic, when
+            // nearly everyone is susceptible
+            let index_case = people[0];
+            context.add_plan(1.0, move |context| {
+                context.infect_person(index_case, None, None, None);
+            });
+            let index_case_infections_clone = Rc::clone(&index_case_infections);
+            context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionData>>(
+                move |_, event| {
+                    if let InfectionDataValue::Infectious {
+                        infected_by: Some(infector),
+                        ..
+                    } = event.current
+                    {
+                        if infector == index_case {
+                            *index_case_infections_clone.borrow_mut() += 1;
+                        }
+                    }
+                },
+            );
+            // The index case recovers at 6.0
+            context.add_plan(6.5, ixa::Context::shutdown);
+            context.execute();
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let mean_secondary_cases = *index_case_infections.borrow() as f64 / num_sims as f64;
+        assert_almost_eq!(mean_secondary_cases, target_r0, 0.25);
+    }
+
+    #[test]
+    fn test_calibrated_transmission_scale_matches_target_r0() {
+        assert_calibrated_secondary_cases_match_target_r0(None, None);
+    }
+
+    #[test]
+    fn test_calibrated_transmission_scale_matches_target_r0_with_multi_contact_forecasts() {
+        // At most two of the three contacts of each forecast are infected
+        assert_calibrated_secondary_cases_match_target_r0(Some(3), Some(2));
+    }
+
+    #[test]
+    fn test_estimated_r0_excludes_shielded_people() {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 1.0,
+                        duration: 5.0,
+                    },
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        load_rate_fns(&mut context).unwrap();
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 0.0,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 1.0,
+                    }),
+                },
+            )
+            .unwrap();
+        // Half of the population is shielded and never transmits
+        for i in 0..10 {
+            let person = context.add_person((Shielded, i % 2 == 0)).unwrap();
+            set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+        }
+        assert_almost_eq!(estimate_r0(&context), 0.5 * 5.0, 1e-12);
+    }
+
+    #[test]
+    fn test_calibration_fails_without_contacts() {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(GlobalParams, Params::default())
+            .unwrap();
+        load_rate_fns(&mut context).unwrap();
+        // Nobody is in the population, so no transmission scale can produce any secondary cases
+        let e = calibrate_transmission_scale(&mut context, 2.0).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "Could not calibrate the transmission scale to a target R0 of 2: the estimated R0 is zero at every transmission scale."
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that calibration failed. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, calibration passed."),
+        }
+    }
+
+    #[test]
+    fn test_transmission_scale_doubles_secondary_cases() {
+        let num_sims = 10_000;
+        let baseline = mean_infections_with_transmission_scale(1.0, num_sims);
+        let doubled = mean_infections_with_transmission_scale(2.0, num_sims);
+        // With one contact and a rate of 1.0, we expect 1.0 infections in 1.0 time units at the
+        // baseline scale and 2.0 at double the scale.
+        assert_almost_eq!(baseline, 1.0, 0.05);
+        assert_almost_eq!(doubled / baseline, 2.0, 0.1);
+    }
+
+    #[test]
+    fn test_mean_generation_interval_constant_rate() {
+        // Under a constant rate of infection, infection attempts are uniform over the infectious
+        // period, so the mean generation interval is half of the infection duration. The one
+        // susceptible is reset to susceptible upon infection, so there is no susceptible depletion.
+        let num_sims: u64 = 1000;
+        let duration = 5.0;
+        let generation_intervals = Rc::new(RefCell::new(Vec::<f64>::new()));
+        for seed in 0..num_sims {
+            let generation_intervals_clone = Rc::clone(&generation_intervals);
+            let mut context = setup_context(seed, 2.0, 1.0, duration, 0.0);
+            load_rate_fns(&mut context).unwrap();
+            let susceptible = context.add_person(()).unwrap();
+            set_homogeneous_mixing_itinerary(&mut context, susceptible).unwrap();
+            let infectious_person = context.add_person(()).unwrap();
+            set_homogeneous_mixing_itinerary(&mut context, infectious_person).unwrap();
+            context.infect_person(infectious_person, None, None, None);
+            schedule_recovery(&mut context, infectious_person);
+            context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionData>>(
+                move |context, event| {
+                    if let InfectionDataValue::Infectious {
+                        generation_interval: Some(generation_interval),
+                        ..
+                    } = event.current
+                    {
+                        generation_intervals_clone
+                            .borrow_mut()
+                            .push(generation_interval);
+                        context.set_person_property(
+                            event.person_id,
+                            InfectionData,
+                            InfectionDataValue::Susceptible,
+                        );
+                    }
+                },
+            );
+            schedule_next_forecasted_infection(&mut context, infectious_person);
+            context.execute();
+        }
+        let generation_intervals = generation_intervals.borrow();
+        assert!(generation_intervals.len() > 1000);
+        assert!(generation_intervals
+            .iter()
+            .all(|&interval| (0.0..=duration).contains(&interval)));
+        #[allow(clippy::cast_precision_loss)]
+        let mean = generation_intervals.iter().sum::<f64>() / generation_intervals.len() as f64;
+        assert_almost_eq!(mean, duration / 2.0, 0.05);
+    }
+
+    #[test]
+    fn test_age_contact_multiplier_scales_secondary_cases() {
+        let num_sims = 10_000;
+        let parameters = Params {
+            max_time: 100.0,
+            infectiousness_rate_fn: RateFnType::Constant {
+                rate: 1.0,
+                duration: 5.0,
+            },
+            age_contact_multipliers: Some(vec![
+                AgeContactMultiplier {
+                    min: 0,
+                    multiplier: 0.5,
+                },
+                AgeContactMultiplier {
+                    min: 18,
+                    multiplier: 1.0,
+                },
+                AgeContactMultiplier {
+                    min: 65,
+                    multiplier: 0.25,
+                },
+            ]),
+            ..Default::default()
+        };
+        let child = mean_infections_in_one_time_unit(&parameters, 10, num_sims);
+        let adult = mean_infections_in_one_time_unit(&parameters, 30, num_sims);
+        let older_adult = mean_infections_in_one_time_unit(&parameters, 70, num_sims);
+        // With one contact and a rate of 1.0, the number of infections in 1.0 time units is the
+        // age band's contact multiplier.
+        assert_almost_eq!(adult, 1.0, 0.05);
+        assert_almost_eq!(child / adult, 0.5, 0.05);
+        assert_almost_eq!(older_adult / adult, 0.25, 0.05);
+    }
+
+    #[test]
+    fn test_schedule_recovery() {
+        // Create a simulation with an infected person and schedule their recovery.
+        let mut context = setup_context(0, 0.0, 1.0, 5.0, 0.0);
+        load_rate_fns(&mut context).unwrap();
+        let person = context.add_person(()).unwrap();
+        context.infect_person(person, None, None, None);
+        // For later, we need to get the recovery time from the rate function.
+        context.execute();
+        let recovery_time = context.get_person_rate_fn(person).infection_duration();
+        schedule_recovery(&mut context, person);
+        context.execute();
+        // Make sure person is recovered.
+        assert_eq!(
+            context.get_person_property(person, InfectionData),
+            InfectionDataValue::Recovered {
+                infection_time: 0.0,
+                recovery_time
+            }
+        );
+        // Make sure nothing has happened after person is recovered.
+        assert_almost_eq!(context.get_current_time(), recovery_time, 0.0);
+    }
+
+    #[test]
+    fn test_shielded_infector_no_secondary_cases() {
+        let mut context = setup_context(0, 10.0, 1.0, 5.0, 0.0);
+        init(&mut context).unwrap();
+        let mut contacts = vec![];
+        for _ in 0..10 {
+            let contact = context.add_person(()).unwrap();
+            set_homogeneous_mixing_itinerary(&mut context, contact).unwrap();
+            contacts.push(contact);
+        }
+        let shielded_person = context.add_person((Shielded, true)).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, shielded_person).unwrap();
+        context.add_plan(1.0, move |context| {
+            context.infect_person(shielded_person, None, None, None);
+        });
+        context.execute();
+
+        // The shielded person was infectious and recovered, but never infected anyone.
+        assert_eq!(
+            context.get_person_property(shielded_person, InfectionStatus),
+            InfectionStatusValue::Recovered
+        );
+        for contact in contacts {
+            assert_eq!(
+                context.get_person_property(contact, InfectionStatus),
+                InfectionStatusValue::Susceptible
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_infection_when_forecast_ties_with_recovery() {
+        // Force a forecast at exactly the recovery time that is scheduled after the recovery, so
+        // the recovery plan runs first. The pending forecast must not lead to an infection.
+        let rate = 1.0;
+        let duration = 5.0;
+        let mut context = setup_context(0, rate, 1.0, duration, 0.0);
+        load_rate_fns(&mut context).unwrap();
+        let contact = context.add_person(()).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, contact).unwrap();
+        let infectious_person = context.add_person(()).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, infectious_person).unwrap();
+        context.infect_person(infectious_person, None, None, None);
+
+        schedule_recovery(&mut context, infectious_person);
+        let recovery_time = context
+            .get_person_rate_fn(infectious_person)
+            .infection_duration();
+        schedule_forecast(
+            &mut context,
+            infectious_person,
+            Forecast {
+                next_time: recovery_time,
+                forecasted_total_infectiousness: rate,
+            },
+        );
+        context.execute();
+
+        assert_eq!(
+            context.get_person_property(infectious_person, InfectionStatus),
+            InfectionStatusValue::Recovered
+        );
+        assert_eq!(
+            context.get_person_property(contact, InfectionStatus),
+            InfectionStatusValue::Susceptible
+        );
+        assert!(context.get_data(ForecastPlanPlugin).plans.is_empty());
+    }
+
+    #[test]
+    fn test_location_infections() {
+        // Does one infectious person generate the number of infections as expected in different
+        // settings? We're going to run many simulations that each start with one infectious and three
+        // susceptible person. Each susceptible person belongs in one of three setting types
+        // and the infectious person is in all three settings. The simulation ends after the
+        // first person is infected. The location of this infection is records. We compare the number of
         // infected people in each setting to the expected proportion defined by the ratios. We examine
         // seven scenarios of ratios for the infectious individual.
         let num_sims: u64 = 1000;
@@ -687,7 +3166,7 @@ mod test {
                 let num_infected_cenustract_clone = Rc::clone(&num_infected_censustract);
                 let num_infected_workplace_clone = Rc::clone(&num_infected_workplace);
                 let mut context = setup_context(seed, rate, alpha, 5.0, 0.0);
-                crate::settings::init(&mut context);
+                crate::settings::init(&mut context).unwrap();
 
                 // Add a a person who will get infected.
                 let infectious_person = context.add_person(()).unwrap();
@@ -1105,7 +3584,13 @@ mod test {
             // Modify itinerary of infector to increase infectiousness due to isolating at home with alpha > 0
             context.add_plan(0.0, move |context| {
                 context
-                    .modify_itinerary(infector, ItineraryModifiers::RestrictTo { setting: &Home })
+                    .modify_itinerary(
+                        infector,
+                        ItineraryModifiers::RestrictTo {
+                            setting: &Home,
+                            setting_id: None,
+                        },
+                    )
                     .unwrap();
             });
 
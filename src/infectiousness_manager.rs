@@ -1,14 +1,18 @@
 use ixa::{
-    define_derived_property, define_person_property_with_default, define_rng, trace, Context,
-    ContextPeopleExt, ContextRandomExt, PersonId, PluginContext,
+    define_data_plugin, define_derived_property, define_person_property_with_default, define_rng,
+    trace, Context, ContextPeopleExt, ContextRandomExt, HashMap, IxaError, PersonId,
+    PersonPropertyChangeEvent, PluginContext,
 };
 use rand_distr::Exp;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    hospitalizations::Hospitalized,
     interventions::ContextTransmissionModifierExt,
+    parameters::{ContextParametersExt, Params},
+    population_loader::{Age, Alive, Shielded},
     rate_fns::{InfectiousnessRateExt, InfectiousnessRateFn, ScaledRateFn},
-    settings::ContextSettingExt,
+    settings::{AnySettingId, ContextSettingExt, SettingCategory, SettingId},
 };
 use ixa::profiling::{increment_named_count, open_span};
 
@@ -20,6 +24,9 @@ pub enum InfectionDataValue {
         infected_by: Option<PersonId>,
         infection_setting_type: Option<&'static str>,
         infection_setting_id: Option<usize>,
+        /// The time from the infector's infection to this infection, or `None` if there is no
+        /// infector (e.g., an initial infection)
+        generation_interval: Option<f64>,
     },
     Recovered {
         infection_time: f64,
@@ -40,6 +47,38 @@ define_person_property_with_default!(
     InfectionDataValue::Susceptible
 );
 
+/// Who infected a person, when, and in which setting. Unlike `InfectionData`, this is kept after
+/// the person recovers, so it can be used to construct who-infected-whom over the whole simulation.
+#[derive(Serialize, PartialEq, Debug, Clone, Copy)]
+pub struct Provenance {
+    /// The infector, or `None` if there is no infector (e.g., an initial infection)
+    pub infected_by: Option<PersonId>,
+    pub infection_time: f64,
+    pub infection_setting_type: Option<&'static str>,
+    pub infection_setting_id: Option<usize>,
+    /// The number of transmissions in the chain leading to this infection, which is zero for
+    /// infections without an infector and one more than the infector's generation otherwise
+    pub generation: usize,
+}
+
+// The provenance of a person's most recent infection, or `None` if they have never been infected
+define_person_property_with_default!(InfectionProvenance, Option<Provenance>, None);
+// The largest generation of any infection, i.e., the length of the longest transmission chain
+define_data_plugin!(LongestTransmissionChainPlugin, usize, 0);
+
+// Whether a person has ever been infectious, which is not reset if they lose their immunity
+define_person_property_with_default!(EverInfected, bool, false);
+// The number of infections a person has had, counting a prior infection for people who start the
+// simulation recovered
+define_person_property_with_default!(InfectionCount, u32, 0);
+// The number of people who have ever been infectious
+define_data_plugin!(CumulativeInfectionPlugin, usize, 0);
+// The time of a person's most recent recovery from an infection in the simulation
+define_person_property_with_default!(LastRecoveryTime, Option<f64>, None);
+// Whether a person's current infection started within `min_reinfection_interval` of their previous
+// recovery, in which case it does not start a new symptom episode
+define_person_property_with_default!(WithinMinReinfectionInterval, bool, false);
+
 define_derived_property!(
     InfectionStatus,
     InfectionStatusValue,
@@ -61,7 +100,10 @@ define_derived_property!(
 /// the setting type and are linear
 pub fn calc_total_infectiousness_multiplier(context: &Context, person_id: PersonId) -> f64 {
     let relative_transmission_potential = context.get_relative_total_transmission(person_id);
-    relative_transmission_potential
+    get_transmission_scale(context)
+        * get_age_contact_multiplier(context, person_id)
+        * get_reinfection_multiplier(context, person_id)
+        * relative_transmission_potential
         * context.calculate_current_infectiousness_multiplier_for_person(person_id)
 }
 
@@ -69,49 +111,298 @@ pub fn calc_total_infectiousness_multiplier(context: &Context, person_id: Person
 /// for a person, given information we know at the time of a forecast.
 /// The modifier used for intrinsic infectiousness is ignored because all modifiers must
 /// be less than or equal to one.
+/// The global transmission scale and the age contact multiplier are included so that forecasts
+/// are always an upper bound on the scaled current infectiousness.
 pub fn max_total_infectiousness_multiplier(context: &Context, person_id: PersonId) -> f64 {
-    context.calculate_max_infectiousness_multiplier_for_person(person_id)
+    get_transmission_scale(context)
+        * get_age_contact_multiplier(context, person_id)
+        * get_reinfection_multiplier(context, person_id)
+        * context.calculate_max_infectiousness_multiplier_for_person(person_id)
+}
+
+// A transmission scale set by calibrating to a target R0, which takes precedence over the
+// `transmission_scale` parameter
+define_data_plugin!(CalibratedTransmissionScalePlugin, Option<f64>, None);
+
+/// The global multiplier on all transmission, defaulting to 1.0 if not specified
+fn get_transmission_scale(context: &Context) -> f64 {
+    context
+        .get_data(CalibratedTransmissionScalePlugin)
+        .or(context.get_params().transmission_scale)
+        .unwrap_or(1.0)
+}
+
+/// Estimate the basic reproduction number of the loaded population: the expected number of
+/// secondary infections caused by a person chosen uniformly from the living population when
+/// everyone else is susceptible. This is the product of the transmission scale, the mean over
+/// people of their setting and age contact multipliers (zero for shielded people), the mean total infectiousness of the rate
+/// function library, the mean relative infectiousness of symptomatic and asymptomatic people, and
+/// the number of infections per accepted forecast (the contacts per forecast, up to the cap on
+/// infections per forecast). Transmission modifiers from interventions are not included.
+#[allow(dead_code)]
+pub fn estimate_r0(context: &Context) -> f64 {
+    estimate_r0_at_scale(context, get_transmission_scale(context))
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn estimate_r0_at_scale(context: &Context, transmission_scale: f64) -> f64 {
+    let mut num_people = 0;
+    let mut total_contact_multiplier = 0.0;
+    context.with_query_results((Alive, true), &mut |people| {
+        num_people = people.len();
+        // Shielded people never transmit, so they cause no secondary infections
+        total_contact_multiplier = people
+            .iter()
+            .filter(|&&person_id| !context.get_person_property(person_id, Shielded))
+            .map(|&person_id| {
+                get_age_contact_multiplier(context, person_id)
+                    * context.calculate_current_infectiousness_multiplier_for_person(person_id)
+            })
+            .sum::<f64>();
+    });
+    if num_people == 0 {
+        return 0.0;
+    }
+    let mean_contact_multiplier = total_contact_multiplier / num_people as f64;
+    let params = context.get_params();
+    let mean_relative_infectiousness = 1.0 - params.proportion_asymptomatic
+        + params.proportion_asymptomatic * params.relative_infectiousness_asymptomatics;
+    // When everyone else is susceptible, every contact of an accepted forecast is infected up to
+    // the cap on infections per forecast
+    let infections_per_forecast = params
+        .contacts_per_forecast
+        .unwrap_or(1)
+        .min(params.max_infections_per_forecast.unwrap_or(usize::MAX));
+    transmission_scale
+        * mean_contact_multiplier
+        * context.get_mean_total_infectiousness()
+        * mean_relative_infectiousness
+        * infections_per_forecast as f64
+}
+
+/// Solve for the transmission scale at which the estimated R0 (see `estimate_r0`) equals
+/// `target_r0`, and apply it to all transmission for the rest of the simulation. Because the
+/// estimated R0 is proportional to the transmission scale, the scale is the ratio of the target to
+/// the estimated R0 at a scale of one. Settings, people, and rate functions must be loaded before
+/// calibrating. Returns the calibrated transmission scale.
+/// # Errors
+/// - If the target R0 is not positive and finite
+/// - If the estimated R0 is zero at every transmission scale (e.g., because nobody has any
+///   contacts)
+pub fn calibrate_transmission_scale(
+    context: &mut Context,
+    target_r0: f64,
+) -> Result<f64, IxaError> {
+    if !(target_r0 > 0.0 && target_r0.is_finite()) {
+        return Err(IxaError::IxaError(
+            "The target R0 must be positive and finite.".to_string(),
+        ));
+    }
+    let unscaled_r0 = estimate_r0_at_scale(context, 1.0);
+    if unscaled_r0 <= 0.0 {
+        return Err(IxaError::IxaError(format!(
+            "Could not calibrate the transmission scale to a target R0 of {target_r0}: the estimated R0 is zero at every transmission scale."
+        )));
+    }
+    let scale = target_r0 / unscaled_r0;
+    trace!("Calibrated the transmission scale to {scale} for a target R0 of {target_r0}");
+    *context.get_data_mut(CalibratedTransmissionScalePlugin) = Some(scale);
+    Ok(scale)
+}
+
+/// The contact-rate multiplier of a person's age band, defaulting to 1.0 if no age contact
+/// multipliers are specified or the person is younger than the first band
+fn get_age_contact_multiplier(context: &Context, person_id: PersonId) -> f64 {
+    let Some(bands) = &context.get_params().age_contact_multipliers else {
+        return 1.0;
+    };
+    let age = context.get_person_property(person_id, Age);
+    bands
+        .iter()
+        .rev()
+        .find(|band| band.min <= age)
+        .map_or(1.0, |band| band.multiplier)
+}
+
+/// The infectiousness multiplier of a person's current infection, which is the reinfection
+/// infectiousness factor for each of their previous infections, defaulting to 1.0 if no factor is
+/// specified
+fn get_reinfection_multiplier(context: &Context, person_id: PersonId) -> f64 {
+    let Some(factor) = context.get_params().reinfection_infectiousness_factor else {
+        return 1.0;
+    };
+    let previous_infections = context
+        .get_person_property(person_id, InfectionCount)
+        .saturating_sub(1);
+    factor.powi(i32::try_from(previous_infections).unwrap_or(i32::MAX))
 }
 
 define_rng!(ForecastRng);
+define_rng!(AbortiveInfectionRng);
 
 // Infection attempt function for a context and given `PersonId`
 pub fn infection_attempt(context: &mut Context, person_id: PersonId) -> Option<PersonId> {
+    infection_attempt_in_setting(context, person_id, None)
+}
+
+/// Make the infection attempts of an accepted forecast. The infector visits a setting sampled from
+/// their current itinerary and attempts to infect `contacts_per_forecast` contacts there, stopping
+/// once `max_infections_per_forecast` of them have been infected. With one contact per forecast,
+/// this is a single infection attempt and the cap has no effect. Returns the people infected.
+pub fn forecast_infection_attempts(context: &mut Context, person_id: PersonId) -> Vec<PersonId> {
+    let &Params {
+        contacts_per_forecast,
+        max_infections_per_forecast,
+        ..
+    } = context.get_params();
+    let contacts_per_forecast = contacts_per_forecast.unwrap_or(1);
+    if contacts_per_forecast == 1 {
+        return infection_attempt(context, person_id).into_iter().collect();
+    }
+    let max_infections = max_infections_per_forecast.unwrap_or(usize::MAX);
+    // All contacts are made in the same setting, so clone it rather than borrowing it from the
+    // context while the contacts are infected
+    let Some(setting) = context
+        .sample_current_setting(person_id)
+        .map(dyn_clone::clone_box)
+    else {
+        return vec![];
+    };
+    let mut infected = vec![];
+    for _ in 0..contacts_per_forecast {
+        if infected.len() >= max_infections {
+            trace!("Person {person_id}: Reached the maximum number of infections for a forecast");
+            break;
+        }
+        if let Some(contact) = infection_attempt_in_setting(context, person_id, Some(&*setting)) {
+            infected.push(contact);
+        }
+    }
+    infected
+}
+
+// The number of transmissions by the number of distinct settings the infector was active in at
+// the time of transmission, or `None` if these counts are not being tracked
+define_data_plugin!(
+    InfectorSettingCountPlugin,
+    Option<HashMap<usize, usize>>,
+    None
+);
+
+/// Start counting the number of distinct settings each infector is active in at the time of each
+/// transmission, for the distribution returned by `get_infector_setting_counts`.
+pub fn track_infector_setting_counts(context: &mut Context) {
+    *context.get_data_mut(InfectorSettingCountPlugin) = Some(HashMap::default());
+}
+
+/// The distribution of the number of distinct settings infectors were active in at the time of
+/// each transmission, as pairs of the number of settings and the number of transmissions, sorted by
+/// the number of settings
+pub fn get_infector_setting_counts(context: &Context) -> Vec<(usize, usize)> {
+    let mut counts: Vec<(usize, usize)> = context
+        .get_data(InfectorSettingCountPlugin)
+        .iter()
+        .flatten()
+        .map(|(&settings, &transmissions)| (settings, transmissions))
+        .collect();
+    counts.sort_unstable();
+    counts
+}
+
+/// The number of people who have ever been infectious, counting people who were reinfected once
+pub fn get_cumulative_infections(context: &Context) -> usize {
+    *context.get_data(CumulativeInfectionPlugin)
+}
+
+/// The length of the longest transmission chain, the largest number of successive transmissions
+/// leading to any infection. Infections without an infector start chains of length zero.
+pub fn get_longest_transmission_chain(context: &Context) -> usize {
+    *context.get_data(LongestTransmissionChainPlugin)
+}
+
+/// The realized attack rate of each setting type: the proportion of its members who have ever been
+/// infected, sorted by setting type. People who belong to several setting types are counted in
+/// each of them.
+pub fn get_setting_attack_rates(context: &Context) -> Vec<(String, f64)> {
+    context.get_setting_category_member_proportions(|person_id| {
+        context.get_person_property(person_id, EverInfected)
+    })
+}
+
+/// Infection attempt in a given setting, or in a setting sampled from the person's current
+/// itinerary if none is provided (e.g., for experiments that target a specific setting).
+/// Memberships may have changed since the setting was chosen (e.g., someone isolated), so the
+/// contact is drawn from the setting's currently active members, and no infection occurs if the
+/// person is no longer active in a provided setting. Recovered contacts can only be reinfected when
+/// `recovered_susceptibility_factor` is specified.
+pub fn infection_attempt_in_setting(
+    context: &mut Context,
+    person_id: PersonId,
+    setting: Option<&dyn AnySettingId>,
+) -> Option<PersonId> {
     let _span = open_span("infection_attempt");
-    if let Some(setting) = context.sample_current_setting(person_id) {
-        let next_contact = context
-            .sample_from_setting_with_exclusion(person_id, setting)
-            .unwrap()?;
-        match context.get_person_property(next_contact, InfectionStatus) {
-            InfectionStatusValue::Susceptible => {
-                if context.sample_bool(
-                    ForecastRng,
-                    context.get_relative_total_transmission(next_contact),
-                ) {
-                    increment_named_count("infection_success");
-                    trace!(
-                        "Infection attempt successful. Person {}, setting type {} {}, infecting {}",
-                        person_id,
-                        setting.get_category_id(),
-                        setting.id(),
-                        next_contact
-                    );
-                    context.infect_person(
-                        next_contact,
-                        Some(person_id),
-                        Some(setting.get_category_id()),
-                        Some(setting.id()),
-                    );
-                    Some(next_contact)
-                } else {
-                    None
-                }
+    let setting = match setting {
+        Some(setting) => {
+            if !context.is_setting_active(person_id, setting) {
+                trace!("Person {person_id} is no longer active in setting {setting:?}");
+                return None;
             }
-            _ => None,
+            setting
         }
+        None => context.sample_current_setting(person_id)?,
+    };
+    let next_contact = context
+        .sample_from_setting_with_exclusion(person_id, setting)
+        .unwrap()?;
+    // Take what we need from the setting before mutating the context, since a sampled setting
+    // is borrowed from the context
+    let setting_type = setting.get_category_id();
+    let setting_id = setting.id();
+    let infectable = match context.get_person_property(next_contact, InfectionStatus) {
+        InfectionStatusValue::Susceptible => true,
+        // With partial immunity, recovered people can be reinfected, and their reduced
+        // susceptibility is a transmission modifier on the recovered status
+        InfectionStatusValue::Recovered => context
+            .get_params()
+            .recovered_susceptibility_factor
+            .is_some(),
+        InfectionStatusValue::Infectious => false,
+    };
+    if !infectable {
+        return None;
+    }
+    let accepted = if context.get_params().dose_response.is_some() {
+        accumulate_dose_and_sample_infection(context, next_contact, setting_type)
     } else {
-        None
+        context.sample_bool(
+            ForecastRng,
+            context.get_relative_total_transmission(next_contact),
+        )
+    };
+    if !accepted {
+        return None;
+    }
+    // The infection can still fail to take hold (e.g., an abortive infection), in which case the
+    // attempt is not counted as a success
+    if !context.infect_person(
+        next_contact,
+        Some(person_id),
+        Some(setting_type),
+        Some(setting_id),
+    ) {
+        return None;
+    }
+    increment_named_count("infection_success");
+    if context.get_data(InfectorSettingCountPlugin).is_some() {
+        let active_setting_count = context.get_active_setting_count(person_id);
+        if let Some(counts) = context.get_data_mut(InfectorSettingCountPlugin) {
+            *counts.entry(active_setting_count).or_insert(0) += 1;
+        }
     }
+    trace!(
+        "Infection attempt successful. Person {person_id}, setting type {setting_type} {setting_id}, infected {next_contact}"
+    );
+    Some(next_contact)
 }
 
 pub struct Forecast {
@@ -119,6 +410,88 @@ pub struct Forecast {
     pub forecasted_total_infectiousness: f64,
 }
 
+// Each person's accumulated dose under the dose-response infection model
+define_data_plugin!(
+    AccumulatedDosePlugin,
+    HashMap<PersonId, f64>,
+    HashMap::default()
+);
+
+/// Returns the dose a person has accumulated from contacts with infectious people under the
+/// dose-response infection model.
+pub fn get_accumulated_dose(context: &Context, person_id: PersonId) -> f64 {
+    context
+        .get_data(AccumulatedDosePlugin)
+        .get(&person_id)
+        .copied()
+        .unwrap_or(0.0)
+}
+
+/// Adds the dose from a contact in a setting of type `setting_type` to the contacted person's
+/// accumulated dose and samples whether the person is infected. The infector's infectiousness is
+/// not part of the dose because forecasts have already been thinned by it. The cumulative hazard of
+/// infection is `(dose / threshold_dose)^shape`, so the probability of infection from this
+/// contact is the probability that the hazard fires as the dose rises from its old to new value.
+fn accumulate_dose_and_sample_infection(
+    context: &mut Context,
+    person_id: PersonId,
+    setting_type: &str,
+) -> bool {
+    let Some(dose_response) = context.get_params().dose_response.clone() else {
+        unreachable!("The dose-response model should only be used if it is parameterized");
+    };
+    let setting_multiplier = dose_response
+        .setting_dose_multipliers
+        .as_ref()
+        .and_then(|multipliers| multipliers.get(setting_type).copied())
+        .unwrap_or(1.0);
+    let dose = dose_response.dose_per_contact
+        * setting_multiplier
+        * context.get_relative_total_transmission(person_id);
+    let previous_dose = get_accumulated_dose(context, person_id);
+    let accumulated_dose = previous_dose + dose;
+    context
+        .get_data_mut(AccumulatedDosePlugin)
+        .insert(person_id, accumulated_dose);
+
+    let cum_hazard = |dose: f64| (dose / dose_response.threshold_dose).powf(dose_response.shape);
+    let hazard_increment = cum_hazard(accumulated_dose) - cum_hazard(previous_dose);
+    context.sample_bool(ForecastRng, 1.0 - (-hazard_increment).exp())
+}
+
+// Next forecasted infection times injected by tests in place of sampling, each used once
+#[cfg(test)]
+define_data_plugin!(
+    ForecastOverridePlugin,
+    std::cell::RefCell<HashMap<PersonId, f64>>,
+    std::cell::RefCell::new(HashMap::default())
+);
+
+/// Use `next_time` as the time of a person's next forecasted infection attempt instead of sampling
+/// it, for deterministic tests of the infection propagation loop. The override is used by the
+/// person's next forecast only, and the forecasted infectiousness is their maximum infectiousness
+/// at `next_time`.
+#[cfg(test)]
+pub fn set_next_forecast_override(context: &mut Context, person_id: PersonId, next_time: f64) {
+    context
+        .get_data_mut(ForecastOverridePlugin)
+        .borrow_mut()
+        .insert(person_id, next_time);
+}
+
+#[cfg(test)]
+fn take_next_forecast_override(context: &Context, person_id: PersonId) -> Option<f64> {
+    context
+        .get_data(ForecastOverridePlugin)
+        .borrow_mut()
+        .remove(&person_id)
+}
+
+#[cfg(not(test))]
+fn take_next_forecast_override(_context: &Context, _person_id: PersonId) -> Option<f64> {
+    None
+}
+
 /// Forecast of the next expected infection time, and the expected rate of
 /// infection at that time.
 pub fn get_forecast(context: &Context, person_id: PersonId) -> Option<Forecast> {
@@ -127,8 +500,27 @@ pub fn get_forecast(context: &Context, person_id: PersonId) -> Option<Forecast>
     // This scales infectiousness by the maximum possible infectiousness across all settings
     let scale = max_total_infectiousness_multiplier(context, person_id);
     let elapsed = context.get_elapsed_infection_time(person_id);
+    // Rejection sampling is only valid if the forecast is an upper bound on the person's actual
+    // infectiousness. A multiplier above its ceiling (e.g., a custom transmission modifier that
+    // returns a value greater than one) would otherwise only be caught if the forecast happens to
+    // be evaluated, so check the ceiling whenever a forecast is made in debug builds.
+    debug_assert!(
+        calc_total_infectiousness_multiplier(context, person_id) <= scale + 1e-10,
+        "Person {person_id}: The rejection sampling ceiling is below the current infectiousness at time {}. Current multiplier: {}, Ceiling multiplier: {scale}, Rate at elapsed infection time {elapsed}: {}",
+        context.get_current_time(),
+        calc_total_infectiousness_multiplier(context, person_id),
+        rate_fn.rate(elapsed),
+    );
     let total_rate_fn = ScaledRateFn::new(rate_fn, scale, elapsed);
 
+    if let Some(next_time) = take_next_forecast_override(context, person_id) {
+        let t = next_time - context.get_current_time();
+        return Some(Forecast {
+            next_time,
+            forecasted_total_infectiousness: total_rate_fn.rate(t),
+        });
+    }
+
     // Draw an exponential and use that to determine the next time
     let exp = Exp::new(1.0).unwrap();
     let e = context.sample_distr(ForecastRng, exp);
@@ -151,6 +543,12 @@ pub fn evaluate_forecast(
     person_id: PersonId,
     forecasted_total_infectiousness: f64,
 ) -> bool {
+    // A person who is no longer infectious (e.g., who recovered at the same time as their
+    // forecast) cannot infect anyone.
+    if context.get_person_property(person_id, InfectionStatus) != InfectionStatusValue::Infectious {
+        trace!("Person {person_id}: Forecast rejected because they are no longer infectious");
+        return false;
+    }
     let rate_fn = context.get_person_rate_fn(person_id);
 
     let total_multiplier = calc_total_infectiousness_multiplier(context, person_id);
@@ -162,7 +560,7 @@ pub fn evaluate_forecast(
     assert!(
         // 1e-10 is a small enough tolerance for floating point comparison.
         current_infectiousness <= forecasted_total_infectiousness + 1e-10,
-        "Person {person_id}: Forecasted infectiousness must always be greater than or equal to current infectiousness. Current: {current_infectiousness}, Forecasted: {forecasted_total_infectiousness}"
+        "Person {person_id}: Forecasted infectiousness must always be greater than or equal to current infectiousness. Current: {current_infectiousness}, Forecasted: {forecasted_total_infectiousness}, Multiplier: {total_multiplier}, Elapsed infection time: {elapsed_t}"
     );
 
     // If they are less infectious as we expected...
@@ -181,19 +579,422 @@ pub fn evaluate_forecast(
     true
 }
 
-pub trait InfectionContextExt: PluginContext + ContextPeopleExt + InfectiousnessRateExt {
+// Secondary cases and infector time by setting type, used to calculate the within-setting Rt
+#[derive(Default)]
+struct SettingTransmissionContainer {
+    // The secondary cases of each currently infectious person by setting type
+    current_secondary_cases: HashMap<PersonId, HashMap<&'static str, usize>>,
+    // Totals over people whose infections have ended
+    secondary_cases: HashMap<&'static str, usize>,
+    infector_time: HashMap<&'static str, f64>,
+}
+
+define_data_plugin!(
+    SettingTransmissionPlugin,
+    SettingTransmissionContainer,
+    SettingTransmissionContainer::default()
+);
+
+/// Attribute each transmission to the setting type in which it occurred, and when an infector
+/// recovers, add their secondary cases and the proportion of time they spend in each setting type
+/// (according to their default itinerary) to the totals used by `get_setting_rt`. Infections
+/// that started before time 0 are excluded because those infectors only transmit for part of
+/// their infectious period.
+pub fn track_setting_transmission(context: &mut Context) {
+    context.subscribe_to_event(|context, event: PersonPropertyChangeEvent<InfectionData>| {
+        match event.current {
+            InfectionDataValue::Infectious {
+                infected_by: Some(infector),
+                infection_setting_type: Some(setting_type),
+                ..
+            } => {
+                *context
+                    .get_data_mut(SettingTransmissionPlugin)
+                    .current_secondary_cases
+                    .entry(infector)
+                    .or_default()
+                    .entry(setting_type)
+                    .or_insert(0) += 1;
+            }
+            // People who recover without having been infectious (e.g., after an abortive
+            // infection) are not infectors
+            InfectionDataValue::Recovered { infection_time, .. }
+                if matches!(event.previous, InfectionDataValue::Infectious { .. }) =>
+            {
+                let proportions =
+                    context.get_default_itinerary_category_proportions(event.person_id);
+                let container = context.get_data_mut(SettingTransmissionPlugin);
+                let secondary_cases = container
+                    .current_secondary_cases
+                    .remove(&event.person_id)
+                    .unwrap_or_default();
+                if infection_time < 0.0 {
+                    return;
+                }
+                for (setting_type, cases) in secondary_cases {
+                    *container.secondary_cases.entry(setting_type).or_insert(0) += cases;
+                }
+                for (setting_type, proportion) in proportions {
+                    *container.infector_time.entry(setting_type).or_insert(0.0) += proportion;
+                }
+            }
+            _ => (),
+        }
+    });
+}
+
+// The number of transmissions by the ages of the infectee and the infector
+define_data_plugin!(
+    AgeTransmissionPlugin,
+    HashMap<(u8, u8), usize>,
+    HashMap::default()
+);
+
+/// Count each transmission by the age of the infectee and the age of their infector, for the
+/// "who infects whom" matrix returned by `get_age_transmission_matrix`. Everyone must have an
+/// `Age`.
+pub fn track_age_transmission(context: &mut Context) {
+    context.subscribe_to_event(|context, event: PersonPropertyChangeEvent<InfectionData>| {
+        if let InfectionDataValue::Infectious {
+            infected_by: Some(infector),
+            ..
+        } = event.current
+        {
+            let infectee_age = context.get_person_property(event.person_id, Age);
+            let infector_age = context.get_person_property(infector, Age);
+            *context
+                .get_data_mut(AgeTransmissionPlugin)
+                .entry((infectee_age, infector_age))
+                .or_insert(0) += 1;
+        }
+    });
+}
+
+/// The number of transmissions by the ages of the infectee and the infector, as triples of the
+/// infectee age, the infector age, and the number of transmissions, sorted by infectee age and
+/// then infector age. Only transmissions that happened are included.
+pub fn get_age_transmission_matrix(context: &Context) -> Vec<(u8, u8, usize)> {
+    let mut matrix: Vec<(u8, u8, usize)> = context
+        .get_data(AgeTransmissionPlugin)
+        .iter()
+        .map(|(&(infectee_age, infector_age), &count)| (infectee_age, infector_age, count))
+        .collect();
+    matrix.sort_unstable();
+    matrix
+}
+
+// The number of members of each setting who are currently infectious, and the peak number and
+// the first time it was reached, keyed by the setting category name and setting id
+#[derive(Default)]
+struct SettingPeakInfectiousContainer {
+    current: HashMap<(&'static str, usize), usize>,
+    peaks: HashMap<(&'static str, usize), (usize, f64)>,
+}
+
+define_data_plugin!(
+    SettingPeakInfectiousPlugin,
+    SettingPeakInfectiousContainer,
+    SettingPeakInfectiousContainer::default()
+);
+
+/// Track the number of simultaneously infectious members of each setting in people's default
+/// itineraries, for the peaks returned by `get_setting_peak_infectious`. The counts are updated
+/// whenever someone becomes infectious or stops being infectious.
+pub fn track_setting_peak_infectious(context: &mut Context) {
+    context.subscribe_to_event(|context, event: PersonPropertyChangeEvent<InfectionData>| {
+        let was_infectious = matches!(event.previous, InfectionDataValue::Infectious { .. });
+        let is_infectious = matches!(event.current, InfectionDataValue::Infectious { .. });
+        if was_infectious == is_infectious {
+            return;
+        }
+        let current_time = context.get_current_time();
+        let settings = context.get_default_itinerary_settings(event.person_id);
+        let container = context.get_data_mut(SettingPeakInfectiousPlugin);
+        for setting in settings {
+            let current = container.current.entry(setting).or_insert(0);
+            if is_infectious {
+                *current += 1;
+                let peak = container.peaks.entry(setting).or_insert((0, current_time));
+                if *current > peak.0 {
+                    *peak = (*current, current_time);
+                }
+            } else {
+                *current -= 1;
+            }
+        }
+    });
+}
+
+/// The peak number of simultaneously infectious members of each setting and the first time the
+/// peak was reached, as tuples of the setting category name, the setting id, the peak, and the
+/// time, sorted from the largest peak to the smallest and then by setting. Only settings that had
+/// an infectious member are included.
+pub fn get_setting_peak_infectious(context: &Context) -> Vec<(&'static str, usize, usize, f64)> {
+    let mut peaks: Vec<(&'static str, usize, usize, f64)> = context
+        .get_data(SettingPeakInfectiousPlugin)
+        .peaks
+        .iter()
+        .map(|(&(setting_type, setting_id), &(peak, peak_time))| {
+            (setting_type, setting_id, peak, peak_time)
+        })
+        .collect();
+    peaks.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(b.0)).then(a.1.cmp(&b.1)));
+    peaks
+}
+
+// The number of secondary cases of each infection that is still ongoing, and the number of
+// infections that have ended with each number of secondary cases
+#[derive(Default)]
+struct OffspringContainer {
+    current: HashMap<PersonId, usize>,
+    ended: HashMap<usize, usize>,
+}
+
+define_data_plugin!(
+    OffspringPlugin,
+    OffspringContainer,
+    OffspringContainer::default()
+);
+
+/// Count the secondary cases of each infection, for the offspring distribution returned by
+/// `get_offspring_distribution`. An infection's count is final once the infector stops being
+/// infectious, and a person who is reinfected starts a new count.
+pub fn track_offspring_distribution(context: &mut Context) {
+    context.subscribe_to_event(|context, event: PersonPropertyChangeEvent<InfectionData>| {
+        let container = context.get_data_mut(OffspringPlugin);
+        if let InfectionDataValue::Infectious { infected_by, .. } = event.current {
+            container.current.insert(event.person_id, 0);
+            if let Some(infector) = infected_by {
+                *container.current.entry(infector).or_insert(0) += 1;
+            }
+        } else if let Some(secondary_cases) = container.current.remove(&event.person_id) {
+            *container.ended.entry(secondary_cases).or_insert(0) += 1;
+        }
+    });
+}
+
+// The time of every infection, including reinfections
+define_data_plugin!(InfectionTimesPlugin, Vec<f64>, Vec::new());
+
+/// Record the time of every infection, for the infection doubling time.
+pub fn track_infection_times(context: &mut Context) {
+    context.subscribe_to_event(|context, event: PersonPropertyChangeEvent<InfectionData>| {
+        if let InfectionDataValue::Infectious { infection_time, .. } = event.current {
+            context
+                .get_data_mut(InfectionTimesPlugin)
+                .push(infection_time);
+        }
+    });
+}
+
+/// Get the time of every infection in the order they occurred, if infection times were tracked
+/// (see the `doubling_time_summary` parameter).
+pub fn get_infection_times(context: &Context) -> &[f64] {
+    context.get_data(InfectionTimesPlugin)
+}
+
+/// The offspring distribution, as pairs of a number of secondary cases and the number of
+/// infections that caused that many, sorted by the number of secondary cases. Infections that are
+/// still ongoing are included with their secondary cases to date. Numbers of secondary cases that
+/// no infection caused are omitted.
+pub fn get_offspring_distribution(context: &Context) -> Vec<(usize, usize)> {
+    let container = context.get_data(OffspringPlugin);
+    let mut histogram = container.ended.clone();
+    for &secondary_cases in container.current.values() {
+        *histogram.entry(secondary_cases).or_insert(0) += 1;
+    }
+    let mut distribution: Vec<(usize, usize)> = histogram.into_iter().collect();
+    distribution.sort_unstable();
+    distribution
+}
+
+/// The within-setting Rt of each setting type, sorted by setting type: the secondary cases
+/// attributed to the setting type per unit of infector time spent in it, where an infector who
+/// spends all their time in a setting type contributes one unit. A setting type with no infector
+/// time has an Rt of zero.
+#[allow(clippy::cast_precision_loss)]
+pub fn get_setting_rt(context: &Context) -> Vec<(&'static str, f64)> {
+    let container = context.get_data(SettingTransmissionPlugin);
+    let mut setting_types: Vec<&'static str> = container
+        .infector_time
+        .keys()
+        .chain(container.secondary_cases.keys())
+        .copied()
+        .collect();
+    setting_types.sort_unstable();
+    setting_types.dedup();
+    setting_types
+        .into_iter()
+        .map(|setting_type| {
+            let infector_time = container
+                .infector_time
+                .get(setting_type)
+                .copied()
+                .unwrap_or(0.0);
+            let secondary_cases = container
+                .secondary_cases
+                .get(setting_type)
+                .copied()
+                .unwrap_or(0);
+            if infector_time > 0.0 {
+                (setting_type, secondary_cases as f64 / infector_time)
+            } else {
+                (setting_type, 0.0)
+            }
+        })
+        .collect()
+}
+
+/// A snapshot of the number of people in each infection status and in the hospital
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompartmentCounts {
+    pub susceptible: usize,
+    pub infectious: usize,
+    pub recovered: usize,
+    pub hospitalized: usize,
+}
+
+/// The change in each compartment count between two snapshots
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompartmentCountsDiff {
+    pub susceptible: i64,
+    pub infectious: i64,
+    pub recovered: i64,
+    pub hospitalized: i64,
+}
+
+impl CompartmentCounts {
+    /// Returns the change in counts from an `earlier` snapshot to this one.
+    #[allow(dead_code)]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn diff(&self, earlier: &CompartmentCounts) -> CompartmentCountsDiff {
+        CompartmentCountsDiff {
+            susceptible: self.susceptible as i64 - earlier.susceptible as i64,
+            infectious: self.infectious as i64 - earlier.infectious as i64,
+            recovered: self.recovered as i64 - earlier.recovered as i64,
+            hospitalized: self.hospitalized as i64 - earlier.hospitalized as i64,
+        }
+    }
+}
+
+/// Get all living people with the given infection status
+fn living_people_with_status<C: ContextPeopleExt + ?Sized>(
+    context: &C,
+    status: InfectionStatusValue,
+) -> Vec<PersonId> {
+    let mut people = Vec::new();
+    context.with_query_results(((InfectionStatus, status), (Alive, true)), &mut |results| {
+        people.extend(results.iter().copied());
+    });
+    people
+}
+
+pub trait InfectionContextExt:
+    PluginContext + ContextPeopleExt + ContextRandomExt + ContextParametersExt + InfectiousnessRateExt
+{
     // This function should be called from the main loop whenever
     // someone is first infected. It assigns all their properties needed to
-    // calculate intrinsic infectiousness
+    // calculate intrinsic infectiousness. Returns whether the person was infected, which they are
+    // not if the source is no longer infectious, the infection is abortive, or they are within the
+    // minimum reinfection interval.
     fn infect_person(
         &mut self,
         target_id: PersonId,
         source_id: Option<PersonId>,
         setting_type: Option<&'static str>,
         setting_id: Option<usize>,
-    ) {
+    ) -> bool {
         let infection_time = self.get_current_time();
+        // No transmission can happen after the source has recovered, even if an infection attempt
+        // was scheduled right at the boundary of their infectious period
+        let generation_interval = match source_id {
+            Some(source_id) => {
+                let InfectionDataValue::Infectious {
+                    infection_time: source_infection_time,
+                    ..
+                } = self.get_person_property(source_id, InfectionData)
+                else {
+                    trace!(
+                        "Person {target_id}: Not infected because source {source_id} is no longer infectious"
+                    );
+                    return false;
+                };
+                Some(infection_time - source_infection_time)
+            }
+            None => None,
+        };
+        let &Params {
+            min_reinfection_interval,
+            min_reinfection_interval_prevents_infection,
+            ..
+        } = self.get_params();
+        if let Some(min_reinfection_interval) = min_reinfection_interval {
+            let within_interval = self
+                .get_person_property(target_id, LastRecoveryTime)
+                .is_some_and(|recovery_time| {
+                    infection_time - recovery_time < min_reinfection_interval
+                });
+            if within_interval && min_reinfection_interval_prevents_infection.unwrap_or(false) {
+                trace!(
+                    "Person {target_id}: Not reinfected within the minimum reinfection interval"
+                );
+                return false;
+            }
+            self.set_person_property(target_id, WithinMinReinfectionInterval, within_interval);
+        }
+        // Only infections caused by transmission can be abortive, so initial infections always
+        // become infectious
+        if source_id.is_some() {
+            let &Params {
+                abortive_infection_probability,
+                abortive_infection_immunity,
+                ..
+            } = self.get_params();
+            if let Some(abortive_infection_probability) = abortive_infection_probability {
+                if self.sample_bool(AbortiveInfectionRng, abortive_infection_probability) {
+                    trace!("Person {target_id}: Abortive infection at {infection_time}");
+                    if abortive_infection_immunity.unwrap_or(false) {
+                        self.set_person_property(
+                            target_id,
+                            InfectionData,
+                            InfectionDataValue::Recovered {
+                                infection_time,
+                                recovery_time: infection_time,
+                            },
+                        );
+                    }
+                    return false;
+                }
+            }
+        }
         trace!("Person {target_id}: Infected at {infection_time}");
+        if !self.get_person_property(target_id, EverInfected) {
+            *self.get_data_mut(CumulativeInfectionPlugin) += 1;
+            self.set_person_property(target_id, EverInfected, true);
+        }
+        let infection_count = self.get_person_property(target_id, InfectionCount);
+        self.set_person_property(target_id, InfectionCount, infection_count + 1);
+        let generation = source_id.map_or(0, |source_id| {
+            self.get_person_property(source_id, InfectionProvenance)
+                .map_or(0, |provenance| provenance.generation)
+                + 1
+        });
+        let longest_chain = self.get_data_mut(LongestTransmissionChainPlugin);
+        *longest_chain = (*longest_chain).max(generation);
+        // Set before the infection data so that subscribers to infections can read the provenance
+        self.set_person_property(
+            target_id,
+            InfectionProvenance,
+            Some(Provenance {
+                infected_by: source_id,
+                infection_time,
+                infection_setting_type: setting_type,
+                infection_setting_id: setting_id,
+                generation,
+            }),
+        );
         self.set_person_property(
             target_id,
             InfectionData,
@@ -202,8 +1003,15 @@ pub trait InfectionContextExt: PluginContext + ContextPeopleExt + Infectiousness
                 infected_by: source_id,
                 infection_setting_type: setting_type,
                 infection_setting_id: setting_id,
+                generation_interval,
             },
         );
+        true
+    }
+    /// Get who infected a person, when, and in which setting for their most recent infection, or
+    /// `None` if they have never been infected.
+    fn get_infection_provenance(&self, person_id: PersonId) -> Option<Provenance> {
+        self.get_person_property(person_id, InfectionProvenance)
     }
     fn recover_person(&mut self, person_id: PersonId) {
         let recovery_time = self.get_current_time();
@@ -212,6 +1020,7 @@ pub trait InfectionContextExt: PluginContext + ContextPeopleExt + Infectiousness
         else {
             panic!("Person {person_id} is not infectious")
         };
+        self.set_person_property(person_id, LastRecoveryTime, Some(recovery_time));
         self.set_person_property(
             person_id,
             InfectionData,
@@ -229,31 +1038,99 @@ pub trait InfectionContextExt: PluginContext + ContextPeopleExt + Infectiousness
         };
         self.get_current_time() - infection_time
     }
+    /// Get all living people who are currently susceptible. Like the other accessors by infection
+    /// status, this queries `InfectionStatus`, which is indexed in `main`, so the query does not
+    /// scan the whole population.
+    #[allow(dead_code)]
+    fn susceptible_people(&self) -> Vec<PersonId> {
+        living_people_with_status(self, InfectionStatusValue::Susceptible)
+    }
+    /// Get all living people who are currently infectious.
+    #[allow(dead_code)]
+    fn infectious_people(&self) -> Vec<PersonId> {
+        living_people_with_status(self, InfectionStatusValue::Infectious)
+    }
+    /// Get all living people who have recovered.
+    #[allow(dead_code)]
+    fn recovered_people(&self) -> Vec<PersonId> {
+        living_people_with_status(self, InfectionStatusValue::Recovered)
+    }
+    /// Take a snapshot of the number of people in each compartment at the current time.
+    #[allow(dead_code)]
+    fn compartment_counts(&self) -> CompartmentCounts {
+        CompartmentCounts {
+            susceptible: self
+                .query_people_count((InfectionStatus, InfectionStatusValue::Susceptible)),
+            infectious: self
+                .query_people_count((InfectionStatus, InfectionStatusValue::Infectious)),
+            recovered: self.query_people_count((InfectionStatus, InfectionStatusValue::Recovered)),
+            hospitalized: self.query_people_count((Hospitalized, true)),
+        }
+    }
+    /// Call `callback` with each newly infected person and the provenance of their infection
+    /// whenever a transmission is attributed to a setting of the given category, e.g., to update a
+    /// live count of hospital transmissions. Infections without an infector never trigger it.
+    #[allow(dead_code)]
+    fn on_transmission_in<S: SettingCategory + Clone>(
+        &mut self,
+        setting_category: S,
+        callback: impl Fn(&mut Context, PersonId, Provenance) + 'static,
+    ) {
+        let category_id = SettingId::new(setting_category, 0).get_category_id();
+        self.subscribe_to_event(
+            move |context, event: PersonPropertyChangeEvent<InfectionData>| {
+                let InfectionDataValue::Infectious {
+                    infected_by: Some(_),
+                    infection_setting_type: Some(setting_type),
+                    ..
+                } = event.current
+                else {
+                    return;
+                };
+                if setting_type != category_id {
+                    return;
+                }
+                // The provenance is set before the infection data, so it describes this infection
+                let provenance = context
+                    .get_infection_provenance(event.person_id)
+                    .expect("An infected person has a provenance");
+                callback(context, event.person_id, provenance);
+            },
+        );
+    }
 }
 impl InfectionContextExt for Context {}
 
 #[cfg(test)]
 mod test {
+    use std::{cell::RefCell, rc::Rc};
+
     use ixa::assert_almost_eq;
     use serde::{Deserialize, Serialize};
 
     use super::{
-        evaluate_forecast, get_forecast, infection_attempt, max_total_infectiousness_multiplier,
-        InfectionContextExt,
+        evaluate_forecast, forecast_infection_attempts, get_accumulated_dose, get_forecast,
+        get_infector_setting_counts, get_longest_transmission_chain, get_setting_peak_infectious,
+        infection_attempt, infection_attempt_in_setting, max_total_infectiousness_multiplier,
+        track_infector_setting_counts, track_setting_peak_infectious, CompartmentCountsDiff,
+        InfectionContextExt, Provenance,
     };
     use crate::{
         define_setting_category,
         infectiousness_manager::{
             InfectionData, InfectionDataValue, InfectionStatus, InfectionStatusValue,
         },
-        interventions::ContextTransmissionModifierExt,
-        parameters::{GlobalParams, ItinerarySpecificationType, Params},
+        interventions::{ContextTransmissionModifierExt, TransmissionModifier},
+        parameters::{DoseResponseParameters, GlobalParams, ItinerarySpecificationType, Params},
+        population_loader::Alive,
         rate_fns::{load_rate_fns, InfectiousnessRateExt},
-        settings::{ContextSettingExt, ItineraryEntry, SettingId, SettingProperties},
+        settings::{
+            ContextSettingExt, ItineraryEntry, ItineraryModifiers, SettingId, SettingProperties,
+        },
     };
     use ixa::{
         define_person_property, Context, ContextGlobalPropertiesExt, ContextPeopleExt,
-        ContextRandomExt, IxaError, PersonId,
+        ContextRandomExt, HashSet, IxaError, PersonId,
     };
 
     define_setting_category!(HomogeneousMixing);
@@ -298,6 +1175,69 @@ mod test {
         context
     }
 
+    fn setup_context_with_forecast_contacts(
+        contacts_per_forecast: usize,
+        max_infections_per_forecast: Option<usize>,
+    ) -> (Context, PersonId) {
+        let mut context = Context::new();
+        context.init_random(0);
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 10.0,
+                    contacts_per_forecast: Some(contacts_per_forecast),
+                    max_infections_per_forecast,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        load_rate_fns(&mut context).unwrap();
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 0.0,
+                    itinerary_specification: None,
+                },
+            )
+            .unwrap();
+        let mut people = vec![];
+        for _ in 0..1000 {
+            let person = context.add_person(()).unwrap();
+            set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+            people.push(person);
+        }
+        context.infect_person(people[0], None, None, None);
+        (context, people[0])
+    }
+
+    #[test]
+    fn test_forecast_infections_capped_with_multiple_contacts() {
+        // Without a cap, nearly every one of the contacts is infected
+        let (mut context, infector) = setup_context_with_forecast_contacts(20, None);
+        assert!(forecast_infection_attempts(&mut context, infector).len() > 10);
+
+        let (mut context, infector) = setup_context_with_forecast_contacts(20, Some(3));
+        for _ in 0..10 {
+            let infected = forecast_infection_attempts(&mut context, infector);
+            assert_eq!(infected.len(), 3);
+            for person in infected {
+                assert_eq!(
+                    context.get_person_property(person, InfectionStatus),
+                    InfectionStatusValue::Infectious
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_forecast_infections_single_contact_ignores_cap() {
+        let (mut context, infector) = setup_context_with_forecast_contacts(1, Some(1));
+        let infected = forecast_infection_attempts(&mut context, infector);
+        assert_eq!(infected.len(), 1);
+    }
+
     #[test]
     fn test_infect_person() {
         let mut context = setup_context();
@@ -315,6 +1255,26 @@ mod test {
         context.get_person_rate_fn(p1);
     }
 
+    #[test]
+    fn test_compartment_counts_diff_after_infection() {
+        let mut context = setup_context();
+        let p1 = context.add_person(()).unwrap();
+        context.add_person(()).unwrap();
+        let before = context.compartment_counts();
+        assert_eq!(before.susceptible, 2);
+        context.infect_person(p1, None, None, None);
+        let after = context.compartment_counts();
+        assert_eq!(
+            after.diff(&before),
+            CompartmentCountsDiff {
+                susceptible: -1,
+                infectious: 1,
+                recovered: 0,
+                hospitalized: 0,
+            }
+        );
+    }
+
     #[test]
     fn test_recover_person() {
         let mut context = setup_context();
@@ -406,6 +1366,206 @@ mod test {
         evaluate_forecast(&mut context, p1, invalid_forecast);
     }
 
+    // A deliberately wrong transmission modifier that makes infectious people more infectious,
+    // which is not accounted for in the forecast ceiling
+    #[derive(Debug)]
+    struct UnboundedModifier;
+
+    impl TransmissionModifier for UnboundedModifier {
+        fn get_relative_transmission(&self, _context: &Context, _person_id: PersonId) -> f64 {
+            2.0
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic = "Person 0: The rejection sampling ceiling is below the current infectiousness at time 0. Current multiplier: 2, Ceiling multiplier: 1"]
+    fn test_assert_forecast_fails_when_ceiling_below_infectiousness() {
+        let mut context = setup_context();
+        let p1 = context.add_person(()).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, p1).unwrap();
+        let p2 = context.add_person(()).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, p2).unwrap();
+        context.infect_person(p1, None, None, None);
+        context
+            .register_transmission_modifier_fn(InfectionStatusValue::Infectious, UnboundedModifier);
+
+        let _ = get_forecast(&context, p1);
+    }
+
+    #[test]
+    fn test_people_by_infection_status_partition_living_population() {
+        let mut context = setup_context();
+        let mut people = Vec::new();
+        for _ in 0..10 {
+            people.push(context.add_person(()).unwrap());
+        }
+        for &person in &people[..6] {
+            context.infect_person(person, None, None, None);
+        }
+        for &person in &people[..3] {
+            context.recover_person(person);
+        }
+        // People who have died are not part of any compartment
+        context.set_person_property(people[0], Alive, false);
+        context.set_person_property(people[9], Alive, false);
+
+        let susceptible = context.susceptible_people();
+        let infectious = context.infectious_people();
+        let recovered = context.recovered_people();
+        assert_eq!(
+            HashSet::from_iter(susceptible.iter().copied()),
+            HashSet::from_iter(people[6..9].iter().copied())
+        );
+        assert_eq!(
+            HashSet::from_iter(infectious.iter().copied()),
+            HashSet::from_iter(people[3..6].iter().copied())
+        );
+        assert_eq!(
+            HashSet::from_iter(recovered.iter().copied()),
+            HashSet::from_iter(people[1..3].iter().copied())
+        );
+
+        // Each living person is in exactly one compartment
+        let all = [susceptible, infectious, recovered].concat();
+        let mut living: HashSet<PersonId> = HashSet::default();
+        context.with_query_results((Alive, true), &mut |people| {
+            living.extend(people.iter().copied());
+        });
+        assert_eq!(all.len(), living.len());
+        assert_eq!(HashSet::from_iter(all), living);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_setting_peak_infectious_in_single_household() {
+        let mut context = setup_context();
+        track_setting_peak_infectious(&mut context);
+        let members: Vec<PersonId> = (0..5).map(|_| context.add_person(()).unwrap()).collect();
+        for &person in &members {
+            set_homogeneous_mixing_itinerary(&mut context, person).unwrap();
+        }
+
+        // Three members are infectious at the same time, then one recovers and another is
+        // infected, which returns to but does not exceed the peak
+        for &person in &members[..3] {
+            context.add_plan(1.0, move |context| {
+                context.infect_person(person, None, None, None);
+            });
+        }
+        let (first_recovered, last_infected) = (members[0], members[3]);
+        context.add_plan(2.0, move |context| {
+            context.recover_person(first_recovered);
+        });
+        context.add_plan(3.0, move |context| {
+            context.infect_person(last_infected, None, None, None);
+        });
+        context.add_plan(4.0, Context::shutdown);
+        context.execute();
+
+        assert_eq!(
+            get_setting_peak_infectious(&context),
+            vec![("HomogeneousMixing", 0, 3, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_abortive_infection_is_not_a_successful_attempt() {
+        let mut context = Context::new();
+        context.init_random(0);
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 10.0,
+                    abortive_infection_probability: Some(1.0),
+                    contacts_per_forecast: Some(5),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        load_rate_fns(&mut context).unwrap();
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 1.0,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 1.0,
+                    }),
+                },
+            )
+            .unwrap();
+        let source = context.add_person(()).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, source).unwrap();
+        let contact = context.add_person(()).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, contact).unwrap();
+        track_infector_setting_counts(&mut context);
+        context.infect_person(source, None, None, None);
+
+        // Every attempt is accepted, but every infection is abortive, so nobody is infected
+        assert_eq!(infection_attempt(&mut context, source), None);
+        assert!(forecast_infection_attempts(&mut context, source).is_empty());
+        assert_eq!(
+            context.get_person_property(contact, InfectionStatus),
+            InfectionStatusValue::Susceptible
+        );
+        // No transmissions are recorded in the infector setting counts either
+        assert!(get_infector_setting_counts(&context).is_empty());
+    }
+
+    #[test]
+    fn test_infector_setting_counts() {
+        let mut context = setup_context();
+        track_infector_setting_counts(&mut context);
+        let single = context.add_person(()).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, single).unwrap();
+        let multi = context.add_person(()).unwrap();
+        context
+            .add_itinerary(
+                multi,
+                vec![
+                    ItineraryEntry::new(SettingId::new(HomogeneousMixing, 0), 1.0),
+                    ItineraryEntry::new(SettingId::new(HomogeneousMixing, 1), 1.0),
+                ],
+            )
+            .unwrap();
+        for setting_id in [0, 1] {
+            for _ in 0..50 {
+                let person = context.add_person(()).unwrap();
+                context
+                    .add_itinerary(
+                        person,
+                        vec![ItineraryEntry::new(
+                            SettingId::new(HomogeneousMixing, setting_id),
+                            1.0,
+                        )],
+                    )
+                    .unwrap();
+            }
+        }
+        context.infect_person(single, None, None, None);
+        context.infect_person(multi, None, None, None);
+
+        let mut single_transmissions = 0;
+        let mut multi_transmissions = 0;
+        for _ in 0..10 {
+            if infection_attempt(&mut context, single).is_some() {
+                single_transmissions += 1;
+            }
+            if infection_attempt(&mut context, multi).is_some() {
+                multi_transmissions += 1;
+            }
+        }
+
+        assert!(single_transmissions > 0);
+        assert!(multi_transmissions > 0);
+        assert_eq!(
+            get_infector_setting_counts(&context),
+            vec![(1, single_transmissions), (2, multi_transmissions)]
+        );
+    }
+
     #[test]
     fn test_evaluate_still_succeeds_when_forecast_slightly_bigger() {
         let mut context = setup_context();
@@ -419,12 +1579,451 @@ mod test {
         assert!(evaluate_forecast(&mut context, p1, still_valid_forecast));
     }
 
+    #[test]
+    fn test_evaluate_forecast_rejected_after_recovery() {
+        let mut context = setup_context();
+        let p1 = context.add_person(()).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, p1).unwrap();
+        context.infect_person(p1, None, None, None);
+        let p2 = context.add_person(()).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, p2).unwrap();
+
+        let f = get_forecast(&context, p1).expect("Forecast should be returned");
+        context.recover_person(p1);
+        assert!(!evaluate_forecast(
+            &mut context,
+            p1,
+            f.forecasted_total_infectiousness
+        ));
+    }
+
+    #[test]
+    fn test_on_transmission_in_fires_only_for_setting_type() {
+        let mut context = setup_context();
+        let people: Vec<PersonId> = (0..4).map(|_| context.add_person(()).unwrap()).collect();
+        let transmissions = Rc::new(RefCell::new(Vec::new()));
+        let recorded = Rc::clone(&transmissions);
+        context.on_transmission_in(HomogeneousMixing, move |_, person_id, provenance| {
+            recorded.borrow_mut().push((
+                person_id,
+                provenance.infected_by,
+                provenance.infection_setting_id,
+            ));
+        });
+        let infected = people.clone();
+        context.add_plan(1.0, move |context| {
+            let people = &infected;
+            context.infect_person(people[0], None, None, None);
+            context.infect_person(
+                people[1],
+                Some(people[0]),
+                Some("HomogeneousMixing"),
+                Some(0),
+            );
+            context.infect_person(people[2], Some(people[0]), Some("Home"), Some(0));
+            context.infect_person(
+                people[3],
+                Some(people[1]),
+                Some("HomogeneousMixing"),
+                Some(0),
+            );
+        });
+        context.execute();
+
+        assert_eq!(
+            *transmissions.borrow(),
+            vec![
+                (people[1], Some(people[0]), Some(0)),
+                (people[3], Some(people[1]), Some(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_transmission_after_source_recovery() {
+        let mut context = setup_context();
+        let source = context.add_person(()).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, source).unwrap();
+        let contact = context.add_person(()).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, contact).unwrap();
+        context.infect_person(source, None, None, None);
+
+        // A forecast made while the source was infectious that resolves after they recover
+        let f = get_forecast(&context, source).expect("Forecast should be returned");
+        context.recover_person(source);
+        context.infect_person(contact, Some(source), None, None);
+        assert_eq!(
+            context.get_person_property(contact, InfectionData),
+            InfectionDataValue::Susceptible
+        );
+        assert!(!evaluate_forecast(
+            &mut context,
+            source,
+            f.forecasted_total_infectiousness
+        ));
+    }
+
+    #[test]
+    fn test_dose_response_low_doses_accumulate_before_infection() {
+        let mut context = Context::new();
+        context.init_random(0);
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 10.0,
+                    // A steep hazard around a threshold of four contacts' worth of dose
+                    dose_response: Some(DoseResponseParameters {
+                        dose_per_contact: 0.25,
+                        threshold_dose: 1.0,
+                        shape: 40.0,
+                        setting_dose_multipliers: None,
+                    }),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        load_rate_fns(&mut context).unwrap();
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 1.0,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 1.0,
+                    }),
+                },
+            )
+            .unwrap();
+        let source = context.add_person(()).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, source).unwrap();
+        let contact = context.add_person(()).unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, contact).unwrap();
+        context.infect_person(source, None, None, None);
+
+        // The first contacts deliver too little dose to plausibly infect
+        for i in 1..=3 {
+            assert_eq!(infection_attempt(&mut context, source), None);
+            assert_almost_eq!(
+                get_accumulated_dose(&context, contact),
+                0.25 * f64::from(i),
+                0.0
+            );
+        }
+        // Once the accumulated dose passes the threshold, infection is all but certain
+        let mut attempts = 3;
+        while context.get_person_property(contact, InfectionStatus)
+            == InfectionStatusValue::Susceptible
+        {
+            infection_attempt(&mut context, source);
+            attempts += 1;
+            assert!(attempts <= 5);
+        }
+        assert!(attempts >= 4);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn test_dose_response_masked_infector_reduces_dose_once() {
+        let n = 10_000;
+        let relative_effect = 0.8;
+        let dose_per_contact = 0.001;
+        let mut context = Context::new();
+        context.init_random(0);
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 10.0,
+                    // A threshold far above the total dose, so no one is infected
+                    dose_response: Some(DoseResponseParameters {
+                        dose_per_contact,
+                        threshold_dose: 100.0,
+                        shape: 40.0,
+                        setting_dose_multipliers: None,
+                    }),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        load_rate_fns(&mut context).unwrap();
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 1.0,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 1.0,
+                    }),
+                },
+            )
+            .unwrap();
+        context
+            .store_transmission_modifier_values(
+                InfectionStatusValue::Infectious,
+                MandatoryInterventionStatus,
+                &[
+                    (MandatoryIntervention::NoEffect, 1.0),
+                    (MandatoryIntervention::Partial, relative_effect),
+                    (MandatoryIntervention::Full, 0.0),
+                ],
+            )
+            .unwrap();
+        let source = context
+            .add_person((MandatoryInterventionStatus, MandatoryIntervention::Partial))
+            .unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, source).unwrap();
+        let contact = context
+            .add_person((MandatoryInterventionStatus, MandatoryIntervention::NoEffect))
+            .unwrap();
+        set_homogeneous_mixing_itinerary(&mut context, contact).unwrap();
+        context.infect_person(source, None, None, None);
+
+        // The masked infector's forecasts are thinned by their relative infectiousness, and every
+        // accepted forecast delivers the full dose to the only contact
+        let mut forecasts = 0;
+        let mut accepted = 0;
+        for _ in 0..n {
+            // Forecasts past the end of the infectious period are not returned
+            let Some(f) = get_forecast(&context, source) else {
+                continue;
+            };
+            forecasts += 1;
+            if evaluate_forecast(&mut context, source, f.forecasted_total_infectiousness) {
+                accepted += 1;
+                assert_eq!(infection_attempt(&mut context, source), None);
+            }
+        }
+        assert_almost_eq!(
+            get_accumulated_dose(&context, contact),
+            f64::from(accepted) * dose_per_contact,
+            1e-9
+        );
+        assert_almost_eq!(
+            get_accumulated_dose(&context, contact) / (f64::from(forecasts) * dose_per_contact),
+            relative_effect,
+            0.02
+        );
+    }
+
+    #[test]
+    fn test_infection_attempt_in_provided_setting() {
+        let mut context = setup_context();
+        let source = context.add_person(()).unwrap();
+        context
+            .add_itinerary(
+                source,
+                vec![
+                    ItineraryEntry::new(SettingId::new(HomogeneousMixing, 0), 0.5),
+                    ItineraryEntry::new(SettingId::new(HomogeneousMixing, 1), 0.5),
+                ],
+            )
+            .unwrap();
+        let contact_0 = context.add_person(()).unwrap();
+        context
+            .add_itinerary(
+                contact_0,
+                vec![ItineraryEntry::new(
+                    SettingId::new(HomogeneousMixing, 0),
+                    1.0,
+                )],
+            )
+            .unwrap();
+        let contact_1 = context.add_person(()).unwrap();
+        context
+            .add_itinerary(
+                contact_1,
+                vec![ItineraryEntry::new(
+                    SettingId::new(HomogeneousMixing, 1),
+                    1.0,
+                )],
+            )
+            .unwrap();
+        context.infect_person(source, None, None, None);
+
+        for _ in 0..100 {
+            let infected = infection_attempt_in_setting(
+                &mut context,
+                source,
+                Some(&SettingId::new(HomogeneousMixing, 1)),
+            );
+            assert_eq!(infected, Some(contact_1));
+            let InfectionDataValue::Infectious {
+                infection_setting_id,
+                ..
+            } = context.get_person_property(contact_1, InfectionData)
+            else {
+                panic!("Person {contact_1} should be infectious");
+            };
+            assert_eq!(infection_setting_id, Some(1));
+            context.set_person_property(contact_1, InfectionData, InfectionDataValue::Susceptible);
+        }
+        assert_eq!(
+            context.get_person_property(contact_0, InfectionStatus),
+            InfectionStatusValue::Susceptible
+        );
+    }
+
+    #[test]
+    fn test_infection_provenance_matches_controlled_transmission() {
+        let mut context = setup_context();
+        let source = context.add_person(()).unwrap();
+        let contact = context.add_person(()).unwrap();
+        for person in [source, contact] {
+            context
+                .add_itinerary(
+                    person,
+                    vec![ItineraryEntry::new(
+                        SettingId::new(HomogeneousMixing, 3),
+                        1.0,
+                    )],
+                )
+                .unwrap();
+        }
+        assert_eq!(context.get_infection_provenance(contact), None);
+        context.infect_person(source, None, None, None);
+        context.add_plan(2.0, move |context| {
+            // The contact is the only other member of the setting, and people without modifiers
+            // are always infected
+            let infected = infection_attempt_in_setting(
+                context,
+                source,
+                Some(&SettingId::new(HomogeneousMixing, 3)),
+            );
+            assert_eq!(infected, Some(contact));
+        });
+        context.add_plan(3.0, move |context| {
+            context.recover_person(contact);
+        });
+        context.execute();
+
+        // The provenance is kept after the contact recovers
+        assert_eq!(
+            context.get_infection_provenance(contact),
+            Some(Provenance {
+                infected_by: Some(source),
+                infection_time: 2.0,
+                infection_setting_type: Some("HomogeneousMixing"),
+                infection_setting_id: Some(3),
+                generation: 1,
+            })
+        );
+        assert_eq!(
+            context.get_infection_provenance(source),
+            Some(Provenance {
+                infected_by: None,
+                infection_time: 0.0,
+                infection_setting_type: None,
+                infection_setting_id: None,
+                generation: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_longest_transmission_chain_equals_chain_depth() {
+        let mut context = setup_context();
+        let depth = 4;
+        let people: Vec<PersonId> = (0..=depth)
+            .map(|_| context.add_person(()).unwrap())
+            .collect();
+        // A side branch off the start of the chain does not lengthen it
+        let branch = context.add_person(()).unwrap();
+        assert_eq!(get_longest_transmission_chain(&context), 0);
+        context.infect_person(people[0], None, None, None);
+        context.infect_person(branch, Some(people[0]), None, None);
+        for pair in people.windows(2) {
+            context.infect_person(pair[1], Some(pair[0]), None, None);
+        }
+
+        assert_eq!(get_longest_transmission_chain(&context), depth);
+        for (generation, &person) in people.iter().enumerate() {
+            assert_eq!(
+                context.get_infection_provenance(person).unwrap().generation,
+                generation
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_infection_after_itinerary_changes_between_forecast_and_attempt() {
+        let mut context = setup_context();
+        let source = context.add_person(()).unwrap();
+        let contact = context.add_person(()).unwrap();
+        for person in [source, contact] {
+            context
+                .add_itinerary(
+                    person,
+                    vec![
+                        ItineraryEntry::new(SettingId::new(HomogeneousMixing, 0), 0.5),
+                        ItineraryEntry::new(SettingId::new(HomogeneousMixing, 1), 0.5),
+                    ],
+                )
+                .unwrap();
+        }
+        context.infect_person(source, None, None, None);
+        assert!(get_forecast(&context, source).is_some());
+
+        // After the forecast, the contact isolates in setting 1, so they can no longer be
+        // contacted in setting 0
+        context
+            .modify_itinerary(
+                contact,
+                ItineraryModifiers::ReplaceWith {
+                    itinerary: vec![
+                        ItineraryEntry::new(SettingId::new(HomogeneousMixing, 0), 0.0),
+                        ItineraryEntry::new(SettingId::new(HomogeneousMixing, 1), 1.0),
+                    ],
+                },
+            )
+            .unwrap();
+        for _ in 0..100 {
+            let infected = infection_attempt_in_setting(
+                &mut context,
+                source,
+                Some(&SettingId::new(HomogeneousMixing, 0)),
+            );
+            assert_eq!(infected, None);
+        }
+        assert_eq!(
+            context.get_person_property(contact, InfectionStatus),
+            InfectionStatusValue::Susceptible
+        );
+
+        // Once the source is no longer active in setting 1, they can't infect the contact there
+        context
+            .modify_itinerary(
+                source,
+                ItineraryModifiers::ReplaceWith {
+                    itinerary: vec![
+                        ItineraryEntry::new(SettingId::new(HomogeneousMixing, 0), 1.0),
+                        ItineraryEntry::new(SettingId::new(HomogeneousMixing, 1), 0.0),
+                    ],
+                },
+            )
+            .unwrap();
+        for _ in 0..100 {
+            let infected = infection_attempt_in_setting(
+                &mut context,
+                source,
+                Some(&SettingId::new(HomogeneousMixing, 1)),
+            );
+            assert_eq!(infected, None);
+        }
+        assert_eq!(
+            context.get_person_property(contact, InfectionStatus),
+            InfectionStatusValue::Susceptible
+        );
+    }
+
     #[test]
     fn test_infected_options() {
         let mut context = setup_context();
         let index = context.add_person(()).unwrap();
         let contact = context.add_person(()).unwrap();
 
+        // The source must be infectious to transmit
+        context.infect_person(index, None, None, None);
         context.infect_person(contact, Some(index), Some("Home"), Some(0));
         context.execute();
 
@@ -486,6 +2085,8 @@ mod test {
             .add_person((MandatoryInterventionStatus, MandatoryIntervention::NoEffect))
             .unwrap();
         set_homogeneous_mixing_itinerary(&mut context, source).unwrap();
+        // The source must be infectious to transmit
+        context.infect_person(source, None, None, None);
 
         for _ in 0..n {
             if infection_attempt(&mut context, source).is_some() {
@@ -62,7 +62,11 @@ define_data_plugin!(
 );
 
 pub trait ContextTransmissionModifierExt: PluginContext {
-    /// Register a generic transmission modifier for a specific infection status.
+    /// Register a generic transmission modifier for a specific infection status. Modifiers are
+    /// stored by type, so modifiers of different types (e.g., a personal facemask modifier and a
+    /// setting ventilation modifier) compose multiplicatively in
+    /// `get_relative_total_transmission`, while registering a modifier of a type that is already
+    /// registered for the infection status replaces the existing modifier.
     fn register_transmission_modifier_fn<T: TransmissionModifier>(
         &mut self,
         infection_status: InfectionStatusValue,
@@ -179,6 +183,7 @@ mod test {
         infectiousness_manager::{InfectionContextExt, InfectionStatusValue},
         parameters::{ContextParametersExt, GlobalParams, Params},
         rate_fns::load_rate_fns,
+        settings::{ContextSettingExt, ItineraryEntry, SettingId, SettingProperties, Workplace},
     };
     use std::any::TypeId;
 
@@ -526,6 +531,75 @@ mod test {
         );
     }
 
+    define_person_property_with_default!(Masked, bool, false);
+
+    // Reduces the infectiousness of people who are active in a ventilated workplace
+    #[derive(Debug)]
+    struct VentilationModifier {
+        ventilated_workplace: usize,
+        relative_transmission: f64,
+    }
+
+    impl TransmissionModifier for VentilationModifier {
+        fn get_relative_transmission(&self, context: &Context, person_id: PersonId) -> f64 {
+            if context.is_setting_active(
+                person_id,
+                &SettingId::new(Workplace, self.ventilated_workplace),
+            ) {
+                self.relative_transmission
+            } else {
+                1.0
+            }
+        }
+    }
+
+    #[test]
+    fn test_mask_and_ventilation_modifiers_compose_multiplicatively() {
+        let mut context = setup();
+        context
+            .register_setting_category(
+                &Workplace,
+                SettingProperties {
+                    alpha: 0.0,
+                    itinerary_specification: None,
+                },
+            )
+            .unwrap();
+        context
+            .store_transmission_modifier_values(
+                InfectionStatusValue::Infectious,
+                Masked,
+                &[(true, 0.5)],
+            )
+            .unwrap();
+        context.register_transmission_modifier_fn(
+            InfectionStatusValue::Infectious,
+            VentilationModifier {
+                ventilated_workplace: 0,
+                relative_transmission: 0.5,
+            },
+        );
+        let person_id = context
+            .add_person((MandatoryInterventionStatus, MandatoryIntervention::NoEffect))
+            .unwrap();
+        context
+            .add_itinerary(
+                person_id,
+                vec![ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0)],
+            )
+            .unwrap();
+        context.infect_person(person_id, None, None, None);
+
+        // Only the ventilation modifier applies until the person masks
+        assert_almost_eq!(context.get_relative_total_transmission(person_id), 0.5, 0.0);
+        context.set_person_property(person_id, Masked, true);
+        assert_almost_eq!(
+            context.get_relative_total_transmission(person_id),
+            0.25,
+            0.0
+        );
+    }
+
     #[test]
     // Test that the default aggregator works correctly when person properties change
     fn test_get_relative_total_transmission() {
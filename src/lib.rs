@@ -9,6 +9,7 @@ pub mod infection_propagation_loop;
 pub mod infectiousness_manager;
 pub mod interventions;
 pub mod natural_history_parameter_manager;
+pub mod observers;
 pub mod parameters;
 pub mod policies;
 pub mod population_loader;
@@ -4,6 +4,7 @@ mod infection_propagation_loop;
 mod infectiousness_manager;
 mod interventions;
 mod natural_history_parameter_manager;
+mod observers;
 mod parameters;
 mod policies;
 mod population_loader;
@@ -14,6 +15,7 @@ mod settings;
 mod symptom_progression;
 pub mod utils;
 
+use infectiousness_manager::InfectionStatus;
 use ixa::profiling::ProfilingContextExt;
 use ixa::runner::run_with_args;
 use ixa::{ContextPeopleExt, ContextRandomExt};
@@ -39,12 +41,13 @@ fn main() {
         });
 
         context.set_start_time(-1000.);
-        settings::init(context);
+        settings::init(context)?;
 
         // Load the synthetic population from the `synthetic_population_file`
         // specified in input.json.
         population_loader::init(context)?;
         context.index_property(Age);
+        context.index_property(InfectionStatus);
 
         infection_propagation_loop::init(context)?;
         reports::init(context)?;
@@ -63,4 +66,15 @@ fn main() {
     // Write the profiling data and context's execution statistics to a JSON file.
     context.write_profiling_data();
     ixa::profiling::print_profiling_data();
+    reports::finalize(&mut context).unwrap();
+    computed_statistics::print_time_to_isolation_summary(&context);
+    computed_statistics::print_peak_hospital_census_summary(&context);
+    computed_statistics::print_doubling_time_summary(&context);
+    computed_statistics::print_setting_rt_summary(&context);
+    computed_statistics::print_infector_setting_count_summary(&context);
+    computed_statistics::print_setting_attack_rate_summary(&context);
+    computed_statistics::print_age_transmission_matrix_summary(&context);
+    computed_statistics::print_setting_peak_infectious_summary(&context);
+    computed_statistics::print_offspring_distribution_summary(&context);
+    computed_statistics::print_final_size_summary(&context);
 }
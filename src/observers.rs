@@ -0,0 +1,179 @@
+use ixa::{Context, ExecutionPhase, IxaError};
+
+use crate::infectiousness_manager::{CompartmentCounts, InfectionContextExt};
+
+/// Register an observer that is called every `period` time units, starting at time 0, with the
+/// compartment counts at the end of that time step. Observers get mutable access to the context
+/// so that they can apply interventions (e.g., setting the person properties that registered
+/// transmission modifiers depend on) in response to the state of the simulation, which allows
+/// coupling the model with an external controller. Observers stop being called once there are no
+/// other plans left in the simulation.
+/// # Errors
+/// - If the period is not positive and finite
+#[allow(dead_code)]
+pub fn add_observer(
+    context: &mut Context,
+    period: f64,
+    observer: impl Fn(&mut Context, &CompartmentCounts) + 'static,
+) -> Result<(), IxaError> {
+    if !(period > 0.0 && period.is_finite()) {
+        return Err(IxaError::IxaError(
+            "The observer period must be positive and finite.".to_string(),
+        ));
+    }
+    context.add_periodic_plan_with_phase(
+        period,
+        move |context: &mut Context| {
+            let counts = context.compartment_counts();
+            observer(context, &counts);
+        },
+        ExecutionPhase::Last,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use ixa::{
+        define_person_property_with_default, Context, ContextGlobalPropertiesExt, ContextPeopleExt,
+        ContextRandomExt, IxaError, PersonPropertyChangeEvent,
+    };
+    use serde::{Deserialize, Serialize};
+
+    use super::add_observer;
+    use crate::{
+        define_setting_category,
+        infectiousness_manager::{InfectionStatus, InfectionStatusValue},
+        interventions::ContextTransmissionModifierExt,
+        parameters::{GlobalParams, ItinerarySpecificationType, Params, RateFnType},
+        settings::{ContextSettingExt, ItineraryEntry, SettingId, SettingProperties},
+    };
+
+    define_setting_category!(HomogeneousMixing);
+
+    #[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Debug, Hash)]
+    pub enum MaskStatus {
+        None,
+        Masked,
+    }
+
+    define_person_property_with_default!(MaskStatusType, MaskStatus, MaskStatus::None);
+
+    #[test]
+    fn test_observer_rejects_nonpositive_period() {
+        let mut context = Context::new();
+        let e = add_observer(&mut context, 0.0, |_, _| {}).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(msg, "The observer period must be positive and finite.");
+            }
+            Some(ue) => panic!(
+                "Expected an error that the period is invalid. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, adding the observer passed."),
+        }
+    }
+
+    #[test]
+    fn test_observer_masks_everyone_above_prevalence_threshold() {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 50.0,
+                    initial_incidence: 0.05,
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 2.0,
+                        duration: 5.0,
+                    },
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        context.set_start_time(-1000.0);
+        context
+            .register_setting_category(
+                &HomogeneousMixing,
+                SettingProperties {
+                    alpha: 0.0,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 1.0,
+                    }),
+                },
+            )
+            .unwrap();
+        for _ in 0..200 {
+            let person = context.add_person(()).unwrap();
+            context
+                .add_itinerary(
+                    person,
+                    vec![ItineraryEntry::new(
+                        SettingId::new(HomogeneousMixing, 0),
+                        1.0,
+                    )],
+                )
+                .unwrap();
+        }
+        // Masks fully prevent an infectious person from transmitting
+        context
+            .store_transmission_modifier_values(
+                InfectionStatusValue::Infectious,
+                MaskStatusType,
+                &[(MaskStatus::Masked, 0.0)],
+            )
+            .unwrap();
+        crate::infection_propagation_loop::init(&mut context).unwrap();
+        context.add_plan(50.0, Context::shutdown);
+
+        // The controller masks everyone once more than 20% of the population is infectious
+        let masking_time = Rc::new(RefCell::new(None));
+        let masking_time_clone = Rc::clone(&masking_time);
+        add_observer(&mut context, 1.0, move |context, counts| {
+            let total = counts.susceptible + counts.infectious + counts.recovered;
+            #[allow(clippy::cast_precision_loss)]
+            let prevalence = counts.infectious as f64 / total as f64;
+            if masking_time_clone.borrow().is_none() && prevalence > 0.2 {
+                *masking_time_clone.borrow_mut() = Some(context.get_current_time());
+                let mut unmasked = Vec::new();
+                context.with_query_results((MaskStatusType, MaskStatus::None), &mut |people| {
+                    unmasked.extend(people.iter().copied());
+                });
+                for person in unmasked {
+                    context.set_person_property(person, MaskStatusType, MaskStatus::Masked);
+                }
+            }
+        })
+        .unwrap();
+
+        let infections_before = Rc::new(RefCell::new(0usize));
+        let infections_after = Rc::new(RefCell::new(0usize));
+        let infections_before_clone = Rc::clone(&infections_before);
+        let infections_after_clone = Rc::clone(&infections_after);
+        let masking_time_clone = Rc::clone(&masking_time);
+        context.subscribe_to_event(
+            move |context, event: PersonPropertyChangeEvent<InfectionStatus>| {
+                if event.current != InfectionStatusValue::Infectious
+                    || context.get_current_time() <= 0.0
+                {
+                    return;
+                }
+                match *masking_time_clone.borrow() {
+                    Some(time) if context.get_current_time() > time => {
+                        *infections_after_clone.borrow_mut() += 1;
+                    }
+                    _ => *infections_before_clone.borrow_mut() += 1,
+                }
+            },
+        );
+        context.execute();
+
+        assert!(masking_time.borrow().is_some());
+        assert!(*infections_before.borrow() > 0);
+        assert_eq!(*infections_after.borrow(), 0);
+    }
+}
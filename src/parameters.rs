@@ -6,9 +6,21 @@ use ixa::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::policies::{validate_guidance_policy, Policies};
-use crate::reports::ReportParams;
-use crate::{hospitalizations::HospitalAgeGroups, settings::SettingProperties};
+use crate::policies::{
+    reopening::{validate_reopening_schedule, ReopeningStage},
+    symptomatic_avoidance::{validate_symptomatic_avoidance, SymptomaticAvoidanceParameters},
+    validate_guidance_policy, AdherenceMode, Policies,
+};
+use crate::reports::{resolve_output_directory, ReportParams};
+use crate::{
+    hospitalizations::HospitalAgeGroups,
+    rate_fns::DoseResponse,
+    settings::{
+        ActivityCalendar, CensusTractKernel, DailyActivityProfile, DistanceKernel,
+        SettingContactRate, SettingProperties,
+    },
+    utils::calendar::DAYS_PER_WEEK,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum RateFnType {
@@ -25,6 +37,72 @@ pub enum RateFnType {
         /// calibrated) to convert the relative hazard rates to absolute rates of infection.
         scale: f64,
     },
+    /// A library of rate functions read in from a JSON file. The file is an array of entries, each
+    /// with a `rate_fn` (`Constant` or `Empirical`), optional `groups` tags, and an optional
+    /// sampling `weight` (defaults to 1.0).
+    LibraryFromJson { file: PathBuf },
+    /// A single rate function from a within-host viral load trajectory, mapped through a
+    /// dose-response function to an infectiousness hazard.
+    ViralLoad {
+        /// The path to the viral load trajectory with columns `time` and `viral_load`.
+        trajectory_file: PathBuf,
+        /// The function from viral load to the absolute hazard rate of infection.
+        dose_response: DoseResponse,
+    },
+}
+
+/// One or more synthetic population files. Multiple files (e.g., one per region) are loaded in
+/// order and merged into a single population.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum SynthPopulationFiles {
+    Single(PathBuf),
+    Multiple(Vec<PathBuf>),
+}
+
+impl SynthPopulationFiles {
+    /// The paths of the synthetic population files in the order they are loaded
+    #[must_use]
+    pub fn paths(&self) -> &[PathBuf] {
+        match self {
+            SynthPopulationFiles::Single(path) => std::slice::from_ref(path),
+            SynthPopulationFiles::Multiple(paths) => paths,
+        }
+    }
+}
+
+/// A contact-rate multiplier for an age band, which starts at `min` and ends at the next band's
+/// `min` (exclusive)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct AgeContactMultiplier {
+    pub min: u8,
+    pub multiplier: f64,
+}
+
+/// How to choose the number of initial infections
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum SeedingMode {
+    /// Draw the number of initial infections from a binomial distribution with the population size
+    /// and `initial_incidence`.
+    Binomial,
+    /// Seed exactly this many initial infections (e.g., a single index case).
+    ExactCount(usize),
+    /// Seed the susceptible members of `count` homes chosen at random. If `member_fraction` is
+    /// specified, only that fraction of the susceptible members of each home (rounded up) are
+    /// seeded, and otherwise all of them are.
+    Households {
+        count: usize,
+        member_fraction: Option<f64>,
+    },
+}
+
+/// How to assign prior infection and recovery times to people who are recovered at the start of
+/// the simulation.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum PriorInfectionHistory {
+    /// Recovery times are drawn uniformly over the `max_time_since_recovery` time units before the
+    /// simulation starts, and infection times precede recovery by the person's infection duration.
+    Uniform { max_time_since_recovery: f64 },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +116,7 @@ pub enum CoreSettingsTypes {
     School,
     Workplace,
     CensusTract,
+    Hospital,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
@@ -50,11 +129,59 @@ pub struct FacemaskParameters {
     pub facemask_efficacy: f64,
 }
 
+/// Parameters for the dose-response infection model, where each contact adds a dose to the
+/// contacted person's accumulated dose and infection occurs with a hazard that grows with the
+/// accumulated dose.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DoseResponseParameters {
+    /// The dose a susceptible person receives from a single contact with an infectious person.
+    pub dose_per_contact: f64,
+    /// The accumulated dose at which the cumulative hazard of infection is 1.
+    pub threshold_dose: f64,
+    /// The shape of the cumulative hazard, `(dose / threshold_dose)^shape`. Larger values make
+    /// infection more threshold-like, so that small doses must accumulate before infection.
+    pub shape: f64,
+    /// Multipliers on the dose per contact by setting category name (e.g., `Home`). Settings
+    /// that are not listed have a multiplier of 1.0.
+    pub setting_dose_multipliers: Option<HashMap<String, f64>>,
+}
+
+/// A distribution of non-negative waiting times, such as the delay to or duration of
+/// hospitalization
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum DelayDistribution {
+    /// An exponential distribution with the given mean
+    Exponential { mean: f64 },
+    /// A gamma distribution with the given shape and scale, so with mean `shape * scale`
+    Gamma { shape: f64, scale: f64 },
+    /// A log-normal distribution whose logarithm has mean `mu` and standard deviation `sigma`
+    LogNormal { mu: f64, sigma: f64 },
+}
+
+impl DelayDistribution {
+    fn validate(&self) -> Result<(), IxaError> {
+        let valid = match *self {
+            DelayDistribution::Exponential { mean } => mean >= 0.0,
+            DelayDistribution::Gamma { shape, scale } => shape > 0.0 && scale > 0.0,
+            DelayDistribution::LogNormal { mu, sigma } => mu.is_finite() && sigma >= 0.0,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(IxaError::IxaError(format!(
+                "The hospitalization distribution {self:?} has invalid parameters."
+            )))
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HospitalizationParameters {
-    /// The mean of the delay distribution to hospitalization.
+    /// The mean of the delay distribution to hospitalization. Used for age groups that do not
+    /// specify their own delay distribution.
     pub mean_delay_to_hospitalization: f64,
-    /// The mean of the duration of hospitalization.
+    /// The mean of the duration of hospitalization. Used for age groups that do not specify their
+    /// own duration distribution.
     pub mean_duration_of_hospitalization: f64,
     /// Age groups for hospitalization probabilities.
     pub age_groups: Vec<HospitalAgeGroups>,
@@ -76,29 +203,171 @@ pub struct Params {
     /// The maximum run time of the simulation; even if there are still infections
     /// scheduled to occur, the simulation will stop at this time.
     pub max_time: f64,
-    /// The path to the synthetic population file loaded in `population_loader`
-    pub synth_population_file: PathBuf,
+    /// The path to the synthetic population file loaded in `population_loader`, or a list of
+    /// paths to files that are merged into a single population
+    pub synth_population_file: SynthPopulationFiles,
+    /// A CSV file with a `recovered` column and one row per person in the same order as the
+    /// synthetic population file, marking the people who start the simulation recovered. If not
+    /// specified, only `initial_recovered` determines who starts recovered.
+    pub initial_immunity_file: Option<PathBuf>,
     /// The proportion of initial people who are infectious when we seed the population.
     pub initial_incidence: f64,
+    /// How to choose the number of initial infections. Defaults to `Binomial` if not specified.
+    pub initial_infection_seeding: Option<SeedingMode>,
     /// The proportion of people that are initially recovered (fully immune to disease).
     pub initial_recovered: f64,
+    /// How to choose the initially recovered, for example whole households to cluster immunity
+    /// as a prior wave would. Defaults to `Binomial` if not specified.
+    pub initial_recovered_seeding: Option<SeedingMode>,
+    /// How to draw prior infection and recovery times for the initially recovered. If not
+    /// specified, their infection and recovery times are unknown (NaN).
+    pub initial_recovered_history: Option<PriorInfectionHistory>,
+    /// The time after recovery at which immunity wanes and a person becomes susceptible again.
+    /// If not specified, immunity never wanes.
+    pub immunity_duration: Option<f64>,
+    /// Whether people are removed from all of their settings when they recover (e.g., because they
+    /// permanently leave their workplace), so they are no longer counted in setting sizes or
+    /// sampled as contacts. Defaults to false (recovered people stay in their settings) if not
+    /// specified.
+    pub remove_recovered_from_settings: Option<bool>,
+    /// The relative susceptibility of recovered people to reinfection, from 0.0 (fully immune) to
+    /// 1.0 (as susceptible as someone who was never infected). If not specified, recovered people
+    /// cannot be reinfected until their immunity wanes.
+    pub recovered_susceptibility_factor: Option<f64>,
+    /// The factor by which each reinfection scales a person's infectiousness, from 0.0 (reinfections
+    /// are not infectious) to 1.0 (as infectious as a first infection). A person's `n`th infection
+    /// has its infectiousness scaled by the factor to the power `n - 1`. If not specified,
+    /// reinfections are as infectious as first infections.
+    pub reinfection_infectiousness_factor: Option<f64>,
+    /// The minimum time after a person's recovery before a reinfection starts a new symptom
+    /// episode. If not specified, every infection can start a symptom episode.
+    pub min_reinfection_interval: Option<f64>,
+    /// Whether people cannot be reinfected at all within `min_reinfection_interval` of their
+    /// recovery, rather than only not starting a new symptom episode. Defaults to false if not
+    /// specified.
+    pub min_reinfection_interval_prevents_infection: Option<bool>,
+    /// The proportion of people who are shielded when the population is loaded. Shielded people
+    /// can be infected but never transmit. Defaults to 0.0 if not specified.
+    pub shielded_proportion: Option<f64>,
+    /// The age at which students graduate: when a person's age reaches this value, their school
+    /// is replaced by a workplace in their itinerary. If not specified, people never graduate.
+    /// The model does not age people itself, so graduation requires something else to update
+    /// their age over the simulation.
+    pub graduation_age: Option<u8>,
     /// A library of infection rates to assign to infected people.
     pub infectiousness_rate_fn: RateFnType,
     /// Proportion of infected individuals who do not develop symptoms
     pub proportion_asymptomatic: f64,
     /// Asymptomatic individuals are less infectious than symptomatic individuals
     pub relative_infectiousness_asymptomatics: f64,
+    /// Presymptomatic individuals (those who have been infected but have not yet developed
+    /// symptoms) transmit at this fraction of their infectiousness. Defaults to 1.0 (no reduction)
+    /// if not specified.
+    pub presymptomatic_infectiousness_factor: Option<f64>,
+    /// The hazard per unit time with which people whose symptoms start mild escalate to moderate
+    /// symptoms while they are symptomatic. If not specified, people keep the severity of their
+    /// symptom category for their whole symptom episode.
+    pub symptom_escalation_rate: Option<f64>,
+    /// The fraction of their rate function's infection duration after which asymptomatic people
+    /// recover, truncating their infectiousness. If not specified, asymptomatic people are
+    /// infectious for the full duration of their rate function.
+    pub asymptomatic_duration_factor: Option<f64>,
+    /// A global multiplier on all transmission, used as a single knob when calibrating R0.
+    /// Defaults to 1.0 (no scaling) if not specified.
+    pub transmission_scale: Option<f64>,
+    /// Whether infectious people make infection attempts. When false, seeded infections progress
+    /// through their natural history (symptoms, hospitalization, and recovery) without causing
+    /// secondary cases. Defaults to true if not specified.
+    pub transmission_enabled: Option<bool>,
+    /// Whether the simulation stops once nobody is infectious, rather than running until
+    /// `max_time`, which remains an upper bound. Defaults to false if not specified.
+    pub stop_on_extinction: Option<bool>,
+    /// The fraction of the population that has ever been infected at which the simulation stops,
+    /// between 0 and 1. If not specified, the simulation does not stop early based on incidence.
+    pub stop_at_cumulative_incidence: Option<f64>,
+    /// The wall clock time budget of the simulation in seconds. The budget is checked every
+    /// simulated day, and the simulation stops once it is exceeded. If not specified, the
+    /// simulation runs until `max_time`.
+    pub max_wall_clock_seconds: Option<f64>,
+    /// A target basic reproduction number. When specified, the transmission scale is calibrated
+    /// at startup so that the estimated R0 of the loaded population matches the target. Cannot be
+    /// combined with `transmission_scale`, the dose-response infection model,
+    /// `presymptomatic_infectiousness_factor`, or `asymptomatic_duration_factor`, which the R0
+    /// estimate does not account for.
+    pub target_r0: Option<f64>,
+    /// The probability that an infection caused by transmission is abortive: the person is exposed
+    /// but clears the infection without becoming infectious or developing symptoms. Defaults to 0.0
+    /// (no abortive infections) if not specified.
+    pub abortive_infection_probability: Option<f64>,
+    /// Whether people who clear an abortive infection gain immunity (are recovered) rather than
+    /// remaining susceptible. Defaults to false if not specified.
+    pub abortive_infection_immunity: Option<bool>,
+    /// The number of contacts an infector attempts to infect in a setting each time one of their
+    /// forecasts is accepted. Defaults to 1 if not specified, in which case each accepted forecast
+    /// is a single infection attempt.
+    pub contacts_per_forecast: Option<usize>,
+    /// The most people a single accepted forecast can infect, modeling saturation within a setting
+    /// visit. Has no effect when there is one contact per forecast. Not capped if not specified.
+    pub max_infections_per_forecast: Option<usize>,
+    /// Contact-rate multipliers by age band, applied to an infectious person's total
+    /// infectiousness multiplier. People younger than the first band have a multiplier of 1.0.
+    /// If not specified, contact rates do not depend on age.
+    pub age_contact_multipliers: Option<Vec<AgeContactMultiplier>>,
+    /// Opt-in dose-response infection model. When specified, contacts accumulate dose in the
+    /// contacted person instead of each contact being accepted independently.
+    pub dose_response: Option<DoseResponseParameters>,
     /// A library of symptom progressions
     pub symptom_progression_library: Option<ProgressionLibraryType>,
+    /// Correlation between symptom severity and infectiousness. At 0 (or if not specified), rate
+    /// functions are assigned independently of severity. At 1, people whose symptom progression
+    /// is severe always draw from the more infectious half of the rate function library, and all
+    /// others from the less infectious half.
+    pub infectiousness_severity_correlation: Option<f64>,
     /// Hospitalization parameters contain the probability of hospitalization by age group
     /// The mean of the delay distribution to hospitalization, and the mean of the duration of hospitalization.
     pub hospitalization_parameters: HospitalizationParameters,
+    /// The id of the hospital setting (ward) that hospitalized people move into for their stay, in
+    /// which they can infect and be infected by other patients. Requires `Hospital` setting
+    /// properties. If not specified, hospitalization does not change people's itineraries.
+    pub hospital_ward_id: Option<usize>,
     /// Setting properties by setting type
     pub settings_properties: HashMap<CoreSettingsTypes, SettingProperties>,
+    /// A global calendar of weekends and holidays on which workplaces and schools are closed. If
+    /// not specified, workplaces and schools are open every day.
+    pub activity_calendar: Option<ActivityCalendar>,
+    /// The day of the week of day 0 of the simulation, from 0 (Monday) to 6 (Sunday), used by
+    /// calendar-aware features such as weekend closures. Defaults to 0 (Monday) if not specified.
+    pub start_day_of_week: Option<u8>,
+    /// Activity profiles over each day by setting type, which scale the infectiousness multiplier
+    /// of a setting type by the multiplier of the current hour (e.g., workplaces that are only
+    /// active from 9 to 5). Setting types without a profile are equally active at all hours.
+    pub daily_activity_profiles: Option<HashMap<CoreSettingsTypes, DailyActivityProfile>>,
+    /// Contacts per unit time and per-contact transmission probabilities by setting type, which
+    /// replace the `(N - 1)^alpha` infectiousness multiplier of a setting type with the product of
+    /// the two. Setting types without a contact rate use their `alpha`.
+    pub setting_contact_rates: Option<HashMap<CoreSettingsTypes, SettingContactRate>>,
+    /// Census tract coordinates and a distance kernel with which contacts made in a census tract
+    /// are drawn from neighboring tracts. If not specified, contacts made in a census tract are
+    /// always drawn from that tract.
+    pub census_tract_kernel: Option<CensusTractKernel>,
     /// Guidance Policy
     /// Specifies the policy guidance to use for interventions, defaulting to None
     /// Enum variants should contain structs with policy-relevant data values
     pub guidance_policy: Option<Policies>,
+    /// Whether a person's adherence to the guidance policy is decided anew each time they develop
+    /// symptoms or fixed per person, defaulting to per event if not specified
+    pub adherence_mode: Option<AdherenceMode>,
+    /// Staged reopening of workplaces, where each stage scales the time workers spend at work
+    /// starting at a given time. Cannot be combined with a guidance policy.
+    pub reopening_schedule: Option<Vec<ReopeningStage>>,
+    /// Background behavior where symptomatic people reduce their time in non-home settings,
+    /// independent of any guidance policy. Cannot be combined with a guidance policy or a
+    /// reopening schedule.
+    pub symptomatic_avoidance: Option<SymptomaticAvoidanceParameters>,
+    /// A CSV file of `time` and `action` columns that turns mask mandates, school closures, and
+    /// the guidance policy on and off at the given times. Mask mandates require facemask
+    /// parameters and isolation guidance actions require a guidance policy.
+    pub intervention_timeline_file: Option<PathBuf>,
     /// Facemask parameters
     /// The reduction in transmission associated with wearing a facemask.
     pub facemask_parameters: Option<FacemaskParameters>,
@@ -108,6 +377,78 @@ pub struct Params {
     pub incidence_report: ReportParams,
     /// Transmission report with a name required
     pub transmission_report: ReportParams,
+    /// Report of the fraction of the population presenting with symptoms, with a period and name
+    /// required. Not written if not specified.
+    pub symptomatic_report: Option<ReportParams>,
+    /// Report of the id of the infectiousness rate function each infected person is assigned
+    /// from the natural history library, with a name required. Not written if not specified.
+    pub natural_history_report: Option<ReportParams>,
+    /// Report of the mean number of active members per occupied setting of each setting type,
+    /// with a period and name required. Not written if not specified.
+    pub setting_size_report: Option<ReportParams>,
+    /// Report of the number of infections detected by surveillance each period, with a period and
+    /// name required. Not written if not specified.
+    pub observed_incidence_report: Option<ReportParams>,
+    /// Report of the mean number of secondary cases caused by the infections that began in each
+    /// period, with a period and name required. Not written if not specified.
+    pub reproduction_report: Option<ReportParams>,
+    /// Report of the number of people in the hospital and the cumulative admissions and
+    /// discharges, with a period and name required. Not written if not specified.
+    pub hospitalization_report: Option<ReportParams>,
+    /// Report of the time from each infector's symptom onset to their infectee's symptom onset,
+    /// with a name required. Not written if not specified.
+    pub serial_interval_report: Option<ReportParams>,
+    /// The probability that an infection is detected by surveillance and counted in the observed
+    /// incidence report. Defaults to 1.0 (every infection is detected) if not specified.
+    pub case_ascertainment_probability: Option<f64>,
+    /// The time from a detected infection becoming infectious to its being reported in the
+    /// observed incidence report. Defaults to 0.0 (no delay) if not specified.
+    pub reporting_delay: Option<f64>,
+    /// A template for the directory reports and profiling data are written to, where `{name}`
+    /// placeholders are replaced by the values of parameters (e.g., `out/seed_{seed}`). If not
+    /// specified, the output directory from the command line is used.
+    pub output_directory: Option<String>,
+    /// Whether to append the seed to the filename of each report (e.g., `incidence_seed42.csv`) so
+    /// that replicates written to the same directory do not overwrite each other. Defaults to
+    /// false if not specified.
+    pub seed_in_report_filenames: Option<bool>,
+    /// The fraction of the loaded population sampled into a cohort to which the per-person
+    /// reports (transmission and natural history) are restricted, to reduce their size for large
+    /// populations. Aggregate reports remain population-wide. If not specified, everyone is
+    /// included in the per-person reports.
+    pub report_sample_fraction: Option<f64>,
+    /// Whether to track transmission by setting type and print the within-setting Rt of each
+    /// setting type at the end of the simulation. Defaults to false if not specified.
+    pub setting_rt_summary: Option<bool>,
+    /// Whether to track transmissions by the ages of the infectee and the infector and print the
+    /// age-by-age transmission matrix at the end of the simulation. Defaults to false if not
+    /// specified.
+    pub age_transmission_matrix: Option<bool>,
+    /// Whether to track the number of simultaneously infectious members of each setting and print
+    /// the settings with the highest peaks at the end of the simulation. Defaults to false if not
+    /// specified.
+    pub setting_peak_infectious_summary: Option<bool>,
+    /// Whether to track the number of secondary cases of each infection and print their
+    /// distribution (the offspring distribution) at the end of the simulation. Defaults to false if
+    /// not specified.
+    pub offspring_distribution_summary: Option<bool>,
+    /// Whether to track the number of distinct settings each infector is active in at the time of
+    /// each transmission and print their distribution at the end of the simulation. Defaults to
+    /// false if not specified.
+    pub infector_setting_count_summary: Option<bool>,
+    /// Whether to record the time of every infection and hospital admission and print the
+    /// doubling times of infections and hospitalizations fit over their growth phases at the end
+    /// of the simulation. Defaults to false if not specified.
+    pub doubling_time_summary: Option<bool>,
+    /// Whether to print the resolved properties of each registered setting type (its alpha,
+    /// itinerary ratio, and any contact rate, daily activity profile, or activity calendar that
+    /// applies to it) once the settings are initialized. Defaults to false if not specified.
+    pub setting_properties_summary: Option<bool>,
+    /// A factor by which all durations (infectious periods, symptom progressions, policy
+    /// durations and delays, hospitalization delays and stays, and immunity) are divided, so that
+    /// the same dynamics play out over a shorter `max_time`. Intended for fast smoke tests.
+    /// Defaults to 1.0 (no compression) if not specified.
+    pub time_scale: Option<f64>,
 }
 
 // Any default parameters must be specified here
@@ -119,7 +460,18 @@ impl Default for Params {
             seed: 0,
             max_time: 0.0,
             initial_incidence: 0.0,
+            initial_infection_seeding: None,
             initial_recovered: 0.0,
+            initial_recovered_seeding: None,
+            initial_recovered_history: None,
+            immunity_duration: None,
+            remove_recovered_from_settings: None,
+            recovered_susceptibility_factor: None,
+            reinfection_infectiousness_factor: None,
+            min_reinfection_interval: None,
+            min_reinfection_interval_prevents_infection: None,
+            shielded_proportion: None,
+            graduation_age: None,
             infectiousness_rate_fn: RateFnType::Constant {
                 rate: 1.0,
                 duration: 5.0,
@@ -127,40 +479,148 @@ impl Default for Params {
             proportion_asymptomatic: 0.0,
             // Asymptomatics, if included, should act as symptomatics unless otherwise specified
             relative_infectiousness_asymptomatics: 1.0,
+            presymptomatic_infectiousness_factor: None,
+            symptom_escalation_rate: None,
+            asymptomatic_duration_factor: None,
+            transmission_scale: None,
+            transmission_enabled: None,
+            stop_on_extinction: None,
+            stop_at_cumulative_incidence: None,
+            max_wall_clock_seconds: None,
+            target_r0: None,
+            abortive_infection_probability: None,
+            abortive_infection_immunity: None,
+            contacts_per_forecast: None,
+            max_infections_per_forecast: None,
+            age_contact_multipliers: None,
+            dose_response: None,
             symptom_progression_library: None,
+            infectiousness_severity_correlation: None,
             hospitalization_parameters: HospitalizationParameters {
                 mean_delay_to_hospitalization: 0.0,
                 mean_duration_of_hospitalization: 0.0,
                 age_groups: vec![HospitalAgeGroups {
                     min: 0,
                     probability: 0.0,
+                    delay_to_hospitalization: None,
+                    duration_of_hospitalization: None,
                 }],
             },
+            hospital_ward_id: None,
             settings_properties: HashMap::new(),
+            activity_calendar: None,
+            start_day_of_week: None,
+            daily_activity_profiles: None,
+            setting_contact_rates: None,
+            census_tract_kernel: None,
             guidance_policy: None,
+            adherence_mode: None,
+            reopening_schedule: None,
+            intervention_timeline_file: None,
+            symptomatic_avoidance: None,
             facemask_parameters: None,
-            synth_population_file: PathBuf::new(),
+            synth_population_file: SynthPopulationFiles::Single(PathBuf::new()),
+            initial_immunity_file: None,
             prevalence_report: ReportParams {
                 write: false,
                 filename: None,
                 period: None,
+                cumulative: false,
+                by_setting_type: false,
+                age_groups: None,
+                compress: None,
+                include_cumulative: false,
             },
             incidence_report: ReportParams {
                 write: false,
                 filename: None,
                 period: None,
+                cumulative: false,
+                by_setting_type: false,
+                age_groups: None,
+                compress: None,
+                include_cumulative: false,
             },
             transmission_report: ReportParams {
                 write: false,
                 filename: None,
                 period: None,
+                cumulative: false,
+                by_setting_type: false,
+                age_groups: None,
+                compress: None,
+                include_cumulative: false,
             },
+            symptomatic_report: None,
+            natural_history_report: None,
+            setting_size_report: None,
+            observed_incidence_report: None,
+            reproduction_report: None,
+            hospitalization_report: None,
+            serial_interval_report: None,
+            case_ascertainment_probability: None,
+            reporting_delay: None,
+            output_directory: None,
+            seed_in_report_filenames: None,
+            report_sample_fraction: None,
+            setting_rt_summary: None,
+            age_transmission_matrix: None,
+            setting_peak_infectious_summary: None,
+            offspring_distribution_summary: None,
+            infector_setting_count_summary: None,
+            doubling_time_summary: None,
+            setting_properties_summary: None,
+            time_scale: None,
         }
     }
 }
 
 #[allow(clippy::too_many_lines)]
+/// Checks that a seeding mode other than `Binomial` is not combined with a seeding proportion,
+/// which only the `Binomial` mode uses.
+fn validate_seeding_mode(
+    seeding: Option<SeedingMode>,
+    proportion: f64,
+    proportion_name: &str,
+    seeded: &str,
+) -> Result<(), IxaError> {
+    match seeding {
+        Some(SeedingMode::ExactCount(_)) if proportion > 0.0 => Err(IxaError::IxaError(format!(
+            "The {proportion_name} must be 0 when seeding an exact count of {seeded}."
+        ))),
+        Some(SeedingMode::Households {
+            member_fraction, ..
+        }) => {
+            if proportion > 0.0 {
+                return Err(IxaError::IxaError(format!(
+                    "The {proportion_name} must be 0 when seeding households."
+                )));
+            }
+            if member_fraction.is_some_and(|fraction| !(fraction > 0.0 && fraction <= 1.0)) {
+                return Err(IxaError::IxaError(
+                    "The fraction of household members to seed must be greater than 0 and at most 1."
+                        .to_string(),
+                ));
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
 fn validate_inputs(parameters: &Params) -> Result<(), IxaError> {
+    if let Some(time_scale) = parameters.time_scale {
+        if !(time_scale > 0.0 && time_scale.is_finite()) {
+            return Err(IxaError::IxaError(
+                "The time scale must be positive and finite.".to_string(),
+            ));
+        }
+    }
+    if parameters.synth_population_file.paths().is_empty() {
+        return Err(IxaError::IxaError(
+            "At least one synthetic population file must be specified.".to_string(),
+        ));
+    }
     if parameters.max_time < 0.0 {
         return Err(IxaError::IxaError(
             "The max simulation running time must be non-negative.".to_string(),
@@ -184,6 +644,82 @@ fn validate_inputs(parameters: &Params) -> Result<(), IxaError> {
                 .to_string(),
         ));
     }
+    if parameters
+        .start_day_of_week
+        .is_some_and(|start_day_of_week| start_day_of_week >= DAYS_PER_WEEK)
+    {
+        return Err(IxaError::IxaError(
+            "The start day of the week must be between 0 (Monday) and 6 (Sunday).".to_string(),
+        ));
+    }
+    validate_seeding_mode(
+        parameters.initial_infection_seeding,
+        parameters.initial_incidence,
+        "initial incidence",
+        "initial infections",
+    )?;
+    validate_seeding_mode(
+        parameters.initial_recovered_seeding,
+        parameters.initial_recovered,
+        "initial recovered proportion",
+        "initial recovered",
+    )?;
+    if let Some(PriorInfectionHistory::Uniform {
+        max_time_since_recovery,
+    }) = parameters.initial_recovered_history
+    {
+        if max_time_since_recovery < 0.0 {
+            return Err(IxaError::IxaError(
+                "The maximum time since recovery of the initially recovered must be non-negative."
+                    .to_string(),
+            ));
+        }
+    }
+    if let Some(immunity_duration) = parameters.immunity_duration {
+        if immunity_duration < 0.0 {
+            return Err(IxaError::IxaError(
+                "The immunity duration must be non-negative.".to_string(),
+            ));
+        }
+    }
+    if let Some(reinfection_infectiousness_factor) = parameters.reinfection_infectiousness_factor {
+        if !(0.0..=1.0).contains(&reinfection_infectiousness_factor) {
+            return Err(IxaError::IxaError(
+                "The reinfection infectiousness factor must be between 0 and 1, inclusive."
+                    .to_string(),
+            ));
+        }
+    }
+    if let Some(recovered_susceptibility_factor) = parameters.recovered_susceptibility_factor {
+        if !(0.0..=1.0).contains(&recovered_susceptibility_factor) {
+            return Err(IxaError::IxaError(
+                "The recovered susceptibility factor must be between 0 and 1, inclusive."
+                    .to_string(),
+            ));
+        }
+    }
+    if parameters.remove_recovered_from_settings.unwrap_or(false)
+        && (parameters.immunity_duration.is_some()
+            || parameters.recovered_susceptibility_factor.is_some())
+    {
+        return Err(IxaError::IxaError(
+            "Removing recovered people from their settings cannot be combined with waning immunity or reinfection of recovered people.".to_string(),
+        ));
+    }
+    if let Some(min_reinfection_interval) = parameters.min_reinfection_interval {
+        if min_reinfection_interval < 0.0 {
+            return Err(IxaError::IxaError(
+                "The minimum reinfection interval must be non-negative.".to_string(),
+            ));
+        }
+    }
+    if let Some(shielded_proportion) = parameters.shielded_proportion {
+        if !(0.0..=1.0).contains(&shielded_proportion) {
+            return Err(IxaError::IxaError(
+                "The shielded proportion must be between 0 and 1, inclusive.".to_string(),
+            ));
+        }
+    }
 
     // Check the infectiousness rate function
     match parameters.infectiousness_rate_fn {
@@ -207,10 +743,31 @@ fn validate_inputs(parameters: &Params) -> Result<(), IxaError> {
                 ));
             }
         }
+        RateFnType::LibraryFromJson { .. } => {}
+        RateFnType::ViralLoad { dose_response, .. } => dose_response.validate()?,
     }
 
     // The policies module contains it's own validation function based on a match statement for the enum variant
     validate_guidance_policy(parameters.guidance_policy)?;
+    resolve_output_directory(parameters)?;
+    if let Some(reopening_schedule) = &parameters.reopening_schedule {
+        // Both apply modified itineraries, and a person can only have one at a time
+        if parameters.guidance_policy.is_some() {
+            return Err(IxaError::IxaError(
+                "A reopening schedule cannot be combined with a guidance policy.".to_string(),
+            ));
+        }
+        validate_reopening_schedule(reopening_schedule)?;
+    }
+    if let Some(symptomatic_avoidance) = parameters.symptomatic_avoidance {
+        // Like the policies, symptomatic avoidance applies a modified itinerary
+        if parameters.guidance_policy.is_some() || parameters.reopening_schedule.is_some() {
+            return Err(IxaError::IxaError(
+                "Symptomatic avoidance cannot be combined with a guidance policy or a reopening schedule.".to_string(),
+            ));
+        }
+        validate_symptomatic_avoidance(symptomatic_avoidance)?;
+    }
 
     // If all the itinerary ratios are None, we can't validate them.
     // If some of them are zero and the rest are none, we still shouldn't fail.
@@ -256,6 +813,90 @@ fn validate_inputs(parameters: &Params) -> Result<(), IxaError> {
             ));
         }
     }
+    if let Some(activity_calendar) = &parameters.activity_calendar {
+        if activity_calendar
+            .holidays
+            .windows(2)
+            .any(|days| days[0] >= days[1])
+        {
+            return Err(IxaError::IxaError(
+                "The holidays in the activity calendar must be strictly increasing.".to_string(),
+            ));
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let late_holiday = activity_calendar
+            .holidays
+            .iter()
+            .find(|&&day| day as f64 >= parameters.max_time);
+        if let Some(day) = late_holiday {
+            return Err(IxaError::IxaError(format!(
+                "Holiday {day} in the activity calendar is not before the max simulation time."
+            )));
+        }
+    }
+    if let Some(stop_at_cumulative_incidence) = parameters.stop_at_cumulative_incidence {
+        if !(stop_at_cumulative_incidence > 0.0 && stop_at_cumulative_incidence <= 1.0) {
+            return Err(IxaError::IxaError(
+                "The cumulative incidence at which to stop must be greater than 0 and at most 1."
+                    .to_string(),
+            ));
+        }
+    }
+    if let Some(max_wall_clock_seconds) = parameters.max_wall_clock_seconds {
+        if !(max_wall_clock_seconds > 0.0 && max_wall_clock_seconds.is_finite()) {
+            return Err(IxaError::IxaError(
+                "The maximum wall clock time must be positive and finite.".to_string(),
+            ));
+        }
+    }
+    if let Some(daily_activity_profiles) = &parameters.daily_activity_profiles {
+        for (setting_category, profile) in daily_activity_profiles {
+            if profile.hourly_multipliers.len() != 24 {
+                return Err(IxaError::IxaError(format!(
+                    "The daily activity profile of {setting_category:?} must have 24 hourly multipliers."
+                )));
+            }
+            if profile
+                .hourly_multipliers
+                .iter()
+                .any(|multiplier| !(0.0..=1.0).contains(multiplier))
+            {
+                return Err(IxaError::IxaError(format!(
+                    "The hourly multipliers of the daily activity profile of {setting_category:?} must be between 0 and 1, inclusive."
+                )));
+            }
+        }
+    }
+    if let Some(setting_contact_rates) = &parameters.setting_contact_rates {
+        for (setting_category, contact_rate) in setting_contact_rates {
+            if !(contact_rate.contacts_per_day >= 0.0 && contact_rate.contacts_per_day.is_finite())
+            {
+                return Err(IxaError::IxaError(format!(
+                    "The contacts per day of {setting_category:?} must be non-negative and finite."
+                )));
+            }
+            if !(0.0..=1.0).contains(&contact_rate.transmission_probability) {
+                return Err(IxaError::IxaError(format!(
+                    "The per-contact transmission probability of {setting_category:?} must be between 0 and 1, inclusive."
+                )));
+            }
+        }
+    }
+    if let Some(census_tract_kernel) = &parameters.census_tract_kernel {
+        let valid = match census_tract_kernel.kernel {
+            DistanceKernel::Exponential { length_scale } => length_scale > 0.0,
+            DistanceKernel::PowerLaw {
+                length_scale,
+                exponent,
+            } => length_scale > 0.0 && exponent > 0.0,
+        };
+        if !valid {
+            return Err(IxaError::IxaError(
+                "The length scale and exponent of the census tract distance kernel must be positive."
+                    .to_string(),
+            ));
+        }
+    }
 
     // Check asymptomatic parameters
     if !(0.0..=1.0).contains(&parameters.proportion_asymptomatic) {
@@ -264,6 +905,172 @@ fn validate_inputs(parameters: &Params) -> Result<(), IxaError> {
     if !(0.0..=1.0).contains(&parameters.relative_infectiousness_asymptomatics) {
         return Err(IxaError::IxaError("The relative infectiousness of asymptomatic individuals must be between 0 and 1, inclusive.".to_string()));
     }
+    if let Some(presymptomatic_infectiousness_factor) =
+        parameters.presymptomatic_infectiousness_factor
+    {
+        if !(0.0..=1.0).contains(&presymptomatic_infectiousness_factor) {
+            return Err(IxaError::IxaError("The relative infectiousness of presymptomatic individuals must be between 0 and 1, inclusive.".to_string()));
+        }
+    }
+    if let Some(asymptomatic_duration_factor) = parameters.asymptomatic_duration_factor {
+        if !(asymptomatic_duration_factor > 0.0 && asymptomatic_duration_factor <= 1.0) {
+            return Err(IxaError::IxaError(
+                "The asymptomatic duration factor must be greater than 0 and at most 1."
+                    .to_string(),
+            ));
+        }
+    }
+    if let Some(symptom_escalation_rate) = parameters.symptom_escalation_rate {
+        if !(symptom_escalation_rate > 0.0 && symptom_escalation_rate.is_finite()) {
+            return Err(IxaError::IxaError(
+                "The symptom escalation rate must be positive and finite.".to_string(),
+            ));
+        }
+    }
+    if let Some(transmission_scale) = parameters.transmission_scale {
+        if transmission_scale.is_nan() || transmission_scale < 0.0 {
+            return Err(IxaError::IxaError(
+                "The transmission scale must be a non-negative number.".to_string(),
+            ));
+        }
+    }
+    if let Some(target_r0) = parameters.target_r0 {
+        if !(target_r0 > 0.0 && target_r0.is_finite()) {
+            return Err(IxaError::IxaError(
+                "The target R0 must be positive and finite.".to_string(),
+            ));
+        }
+        if parameters.transmission_scale.is_some() {
+            return Err(IxaError::IxaError(
+                "Only one of the transmission scale and the target R0 can be specified."
+                    .to_string(),
+            ));
+        }
+        if parameters.dose_response.is_some() {
+            return Err(IxaError::IxaError(
+                "The transmission scale cannot be calibrated to a target R0 under the dose-response infection model."
+                    .to_string(),
+            ));
+        }
+        if parameters.presymptomatic_infectiousness_factor.is_some()
+            || parameters.asymptomatic_duration_factor.is_some()
+        {
+            return Err(IxaError::IxaError(
+                "The transmission scale cannot be calibrated to a target R0 with a presymptomatic infectiousness factor or an asymptomatic duration factor."
+                    .to_string(),
+            ));
+        }
+    }
+    if let Some(abortive_infection_probability) = parameters.abortive_infection_probability {
+        if !(0.0..=1.0).contains(&abortive_infection_probability) {
+            return Err(IxaError::IxaError(
+                "The abortive infection probability must be between 0 and 1, inclusive."
+                    .to_string(),
+            ));
+        }
+    }
+    if let Some(case_ascertainment_probability) = parameters.case_ascertainment_probability {
+        if !(0.0..=1.0).contains(&case_ascertainment_probability) {
+            return Err(IxaError::IxaError(
+                "The case ascertainment probability must be between 0 and 1, inclusive."
+                    .to_string(),
+            ));
+        }
+    }
+    if let Some(report_sample_fraction) = parameters.report_sample_fraction {
+        if !(0.0..=1.0).contains(&report_sample_fraction) {
+            return Err(IxaError::IxaError(
+                "The report sample fraction must be between 0 and 1, inclusive.".to_string(),
+            ));
+        }
+    }
+    if let Some(reporting_delay) = parameters.reporting_delay {
+        if reporting_delay < 0.0 {
+            return Err(IxaError::IxaError(
+                "The reporting delay must be non-negative.".to_string(),
+            ));
+        }
+    }
+    if parameters.contacts_per_forecast == Some(0) {
+        return Err(IxaError::IxaError(
+            "The number of contacts per forecast must be at least one.".to_string(),
+        ));
+    }
+    if parameters.max_infections_per_forecast == Some(0) {
+        return Err(IxaError::IxaError(
+            "The maximum number of infections per forecast must be at least one.".to_string(),
+        ));
+    }
+    if let Some(age_contact_multipliers) = &parameters.age_contact_multipliers {
+        if age_contact_multipliers
+            .windows(2)
+            .any(|bands| bands[0].min >= bands[1].min)
+        {
+            return Err(IxaError::IxaError(
+                "The minimum ages of the age contact multiplier bands must be strictly increasing."
+                    .to_string(),
+            ));
+        }
+        if age_contact_multipliers
+            .iter()
+            .any(|band| !(band.multiplier >= 0.0 && band.multiplier.is_finite()))
+        {
+            return Err(IxaError::IxaError(
+                "Age contact multipliers must be non-negative and finite.".to_string(),
+            ));
+        }
+    }
+    if let Some(dose_response) = &parameters.dose_response {
+        if dose_response.dose_per_contact < 0.0 {
+            return Err(IxaError::IxaError(
+                "The dose per contact must be non-negative.".to_string(),
+            ));
+        }
+        if dose_response.threshold_dose <= 0.0 || dose_response.shape <= 0.0 {
+            return Err(IxaError::IxaError(
+                "The threshold dose and shape of the dose-response hazard must be positive."
+                    .to_string(),
+            ));
+        }
+        if let Some(multipliers) = &dose_response.setting_dose_multipliers {
+            if multipliers
+                .values()
+                .any(|&multiplier| !(multiplier >= 0.0 && multiplier.is_finite()))
+            {
+                return Err(IxaError::IxaError(
+                    "Setting dose multipliers must be non-negative and finite.".to_string(),
+                ));
+            }
+        }
+    }
+    if let Some(correlation) = parameters.infectiousness_severity_correlation {
+        if !(0.0..=1.0).contains(&correlation) {
+            return Err(IxaError::IxaError(
+                "The infectiousness-severity correlation must be between 0 and 1, inclusive."
+                    .to_string(),
+            ));
+        }
+        if correlation > 0.0 {
+            if let RateFnType::EmpiricalFromFile { .. } = parameters.infectiousness_rate_fn {
+                return Err(IxaError::IxaError(
+                    "The infectiousness-severity correlation cannot be used with empirical rate functions because they are already paired one-to-one with symptom progressions."
+                        .to_string(),
+                ));
+            }
+            if let RateFnType::LibraryFromJson { .. } = parameters.infectiousness_rate_fn {
+                return Err(IxaError::IxaError(
+                    "The infectiousness-severity correlation cannot be used with a JSON rate function library because those rate functions are assigned by weight."
+                        .to_string(),
+                ));
+            }
+            if parameters.symptom_progression_library.is_none() {
+                return Err(IxaError::IxaError(
+                    "The infectiousness-severity correlation requires a symptom progression library."
+                        .to_string(),
+                ));
+            }
+        }
+    }
     if let Some(facemask_parameters) = parameters.facemask_parameters {
         if !(0.0..=1.0).contains(&facemask_parameters.facemask_efficacy) {
             return Err(IxaError::IxaError(
@@ -310,6 +1117,27 @@ fn validate_inputs(parameters: &Params) -> Result<(), IxaError> {
             "The first age group for hospitalization probabilities must start at 0.".to_string(),
         ));
     }
+    if parameters.hospital_ward_id.is_some()
+        && !parameters
+            .settings_properties
+            .contains_key(&CoreSettingsTypes::Hospital)
+    {
+        return Err(IxaError::IxaError(
+            "A hospital ward id requires setting properties for the Hospital setting type."
+                .to_string(),
+        ));
+    }
+    for group in &hospitalization_parameters.age_groups {
+        for distribution in [
+            group.delay_to_hospitalization,
+            group.duration_of_hospitalization,
+        ]
+        .iter()
+        .flatten()
+        {
+            distribution.validate()?;
+        }
+    }
 
     Ok(())
 }
@@ -321,6 +1149,18 @@ pub trait ContextParametersExt: PluginContext + ContextGlobalPropertiesExt {
         self.get_global_property_value(GlobalParams)
             .expect("Expected GlobalParams to be set")
     }
+    /// The factor by which all durations are divided (see `time_scale`), or 1.0 if it is not
+    /// specified or the parameters have not been set
+    fn get_time_scale(&self) -> f64 {
+        self.get_global_property_value(GlobalParams)
+            .and_then(|params| params.time_scale)
+            .unwrap_or(1.0)
+    }
+    /// Compress a duration drawn from the model's natural history or policy parameters by the
+    /// `time_scale`
+    fn scale_duration(&self, duration: f64) -> f64 {
+        duration / self.get_time_scale()
+    }
 }
 impl ContextParametersExt for Context {}
 
@@ -328,10 +1168,13 @@ impl ContextParametersExt for Context {}
 mod test {
     use ixa::{assert_almost_eq, Context, ContextGlobalPropertiesExt, HashMap, IxaError};
 
-    use super::{validate_inputs, CoreSettingsTypes, ItinerarySpecificationType};
+    use super::{
+        validate_inputs, AgeContactMultiplier, CoreSettingsTypes, DoseResponseParameters,
+        ItinerarySpecificationType,
+    };
     use crate::{
         parameters::{ContextParametersExt, GlobalParams, Params, RateFnType},
-        settings::SettingProperties,
+        settings::{ActivityCalendar, SettingProperties},
     };
 
     #[test]
@@ -406,6 +1249,55 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_validate_activity_calendar_holidays() {
+        let parameters = Params {
+            max_time: 10.0,
+            activity_calendar: Some(ActivityCalendar {
+                weekends: true,
+                holidays: vec![3, 2],
+            }),
+            ..Default::default()
+        };
+        let e = validate_inputs(&parameters).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "The holidays in the activity calendar must be strictly increasing."
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that the holidays are not increasing. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, validation passed with no errors."),
+        }
+
+        let parameters = Params {
+            max_time: 10.0,
+            activity_calendar: Some(ActivityCalendar {
+                weekends: false,
+                holidays: vec![2, 10],
+            }),
+            ..Default::default()
+        };
+        let e = validate_inputs(&parameters).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "Holiday 10 in the activity calendar is not before the max simulation time."
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that the holiday is too late. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, validation passed with no errors."),
+        }
+    }
+
     #[test]
     fn test_validate_split_zeros() {
         let parameters = Params {
@@ -553,6 +1445,180 @@ mod test {
         assert!(e.is_none(), "Expected no error, but got: {e:?}");
     }
 
+    #[test]
+    fn test_validation_nan_multipliers() {
+        let parameters = Params {
+            age_contact_multipliers: Some(vec![AgeContactMultiplier {
+                min: 0,
+                multiplier: f64::NAN,
+            }]),
+            ..Default::default()
+        };
+        let e = validate_inputs(&parameters).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "Age contact multipliers must be non-negative and finite."
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that the age contact multiplier is invalid. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, validation passed with no errors."),
+        }
+
+        let parameters = Params {
+            dose_response: Some(DoseResponseParameters {
+                dose_per_contact: 1.0,
+                threshold_dose: 1.0,
+                shape: 1.0,
+                setting_dose_multipliers: Some(HashMap::from_iter([(
+                    "Home".to_string(),
+                    f64::NAN,
+                )])),
+            }),
+            ..Default::default()
+        };
+        let e = validate_inputs(&parameters).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "Setting dose multipliers must be non-negative and finite."
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that the setting dose multiplier is invalid. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, validation passed with no errors."),
+        }
+    }
+
+    #[test]
+    fn test_validation_time_scale() {
+        for time_scale in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let parameters = Params {
+                time_scale: Some(time_scale),
+                ..Default::default()
+            };
+            let e = validate_inputs(&parameters).err();
+            match e {
+                Some(IxaError::IxaError(msg)) => {
+                    assert_eq!(msg, "The time scale must be positive and finite.");
+                }
+                Some(ue) => panic!(
+                    "Expected an error that the time scale is invalid. Instead got {:?}",
+                    ue.to_string()
+                ),
+                None => panic!("Expected an error. Instead, validation passed with no errors."),
+            }
+        }
+        let parameters = Params {
+            time_scale: Some(10.0),
+            ..Default::default()
+        };
+        let e = validate_inputs(&parameters).err();
+        assert!(e.is_none(), "Expected no error, but got: {e:?}");
+    }
+
+    #[test]
+    fn test_validation_transmission_scale() {
+        for transmission_scale in [-1.0, f64::NAN] {
+            let parameters = Params {
+                transmission_scale: Some(transmission_scale),
+                ..Default::default()
+            };
+            let e = validate_inputs(&parameters).err();
+            match e {
+                Some(IxaError::IxaError(msg)) => {
+                    assert_eq!(msg, "The transmission scale must be a non-negative number.");
+                }
+                Some(ue) => panic!(
+                    "Expected an error that the transmission scale is invalid. Instead got {:?}",
+                    ue.to_string()
+                ),
+                None => panic!("Expected an error. Instead, validation passed with no errors."),
+            }
+        }
+        let parameters = Params {
+            transmission_scale: Some(0.0),
+            ..Default::default()
+        };
+        let e = validate_inputs(&parameters).err();
+        assert!(e.is_none(), "Expected no error, but got: {e:?}");
+    }
+
+    #[test]
+    fn test_validation_target_r0_with_unestimated_factors() {
+        for (presymptomatic_infectiousness_factor, asymptomatic_duration_factor) in
+            [(Some(0.5), None), (None, Some(0.5))]
+        {
+            let parameters = Params {
+                target_r0: Some(2.0),
+                presymptomatic_infectiousness_factor,
+                asymptomatic_duration_factor,
+                ..Default::default()
+            };
+            let e = validate_inputs(&parameters).err();
+            match e {
+                Some(IxaError::IxaError(msg)) => {
+                    assert_eq!(msg, "The transmission scale cannot be calibrated to a target R0 with a presymptomatic infectiousness factor or an asymptomatic duration factor.");
+                }
+                Some(ue) => panic!(
+                    "Expected an error that the target R0 cannot be combined with these factors. Instead got {:?}",
+                    ue.to_string()
+                ),
+                None => panic!("Expected an error. Instead, validation passed with no errors."),
+            }
+        }
+        let parameters = Params {
+            target_r0: Some(2.0),
+            ..Default::default()
+        };
+        let e = validate_inputs(&parameters).err();
+        assert!(e.is_none(), "Expected no error, but got: {e:?}");
+    }
+
+    #[test]
+    fn test_validation_remove_recovered_from_settings_with_reinfection() {
+        for parameters in [
+            Params {
+                remove_recovered_from_settings: Some(true),
+                immunity_duration: Some(10.0),
+                ..Default::default()
+            },
+            Params {
+                remove_recovered_from_settings: Some(true),
+                recovered_susceptibility_factor: Some(0.5),
+                ..Default::default()
+            },
+        ] {
+            let e = validate_inputs(&parameters).err();
+            match e {
+                Some(IxaError::IxaError(msg)) => {
+                    assert_eq!(
+                        msg,
+                        "Removing recovered people from their settings cannot be combined with waning immunity or reinfection of recovered people."
+                    );
+                }
+                Some(ue) => panic!(
+                    "Expected an error that removing recovered people from their settings cannot be combined with reinfection. Instead got {:?}",
+                    ue.to_string()
+                ),
+                None => panic!("Expected an error. Instead, validation passed with no errors."),
+            }
+        }
+        let parameters = Params {
+            remove_recovered_from_settings: Some(false),
+            immunity_duration: Some(10.0),
+            ..Default::default()
+        };
+        assert!(validate_inputs(&parameters).is_ok());
+    }
+
     #[test]
     fn test_deserialization_rates() {
         let deserialized = serde_json::from_str::<RateFnType>(
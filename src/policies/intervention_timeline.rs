@@ -0,0 +1,485 @@
+use std::path::Path;
+
+use ixa::{define_data_plugin, trace, Context, ContextPeopleExt, IxaError, PersonId};
+use serde::Deserialize;
+
+use crate::{
+    infectiousness_manager::InfectionStatusValue,
+    interventions::{ContextTransmissionModifierExt, TransmissionModifier},
+    parameters::{ContextParametersExt, Params},
+    population_loader::Alive,
+    settings::{Classroom, ContextSettingExt, School},
+};
+
+/// An action in an intervention timeline, which turns one of the interventions on or off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineAction {
+    /// Everyone who is infectious wears a facemask
+    StartMaskMandate,
+    EndMaskMandate,
+    /// Every school is closed, so students spend their school time in their other settings
+    CloseSchools,
+    ReopenSchools,
+    /// People who develop symptoms follow the guidance policy
+    StartIsolationGuidance,
+    EndIsolationGuidance,
+}
+
+const TIMELINE_ACTIONS: [(&str, TimelineAction); 6] = [
+    ("StartMaskMandate", TimelineAction::StartMaskMandate),
+    ("EndMaskMandate", TimelineAction::EndMaskMandate),
+    ("CloseSchools", TimelineAction::CloseSchools),
+    ("ReopenSchools", TimelineAction::ReopenSchools),
+    (
+        "StartIsolationGuidance",
+        TimelineAction::StartIsolationGuidance,
+    ),
+    ("EndIsolationGuidance", TimelineAction::EndIsolationGuidance),
+];
+
+impl TimelineAction {
+    fn from_name(name: &str) -> Option<TimelineAction> {
+        TIMELINE_ACTIONS
+            .iter()
+            .find(|(action_name, _)| *action_name == name)
+            .map(|&(_, action)| action)
+    }
+
+    /// The intervention the action toggles and whether it turns it on
+    fn toggle(self) -> (&'static str, bool) {
+        match self {
+            TimelineAction::StartMaskMandate => ("mask mandate", true),
+            TimelineAction::EndMaskMandate => ("mask mandate", false),
+            TimelineAction::CloseSchools => ("school closure", true),
+            TimelineAction::ReopenSchools => ("school closure", false),
+            TimelineAction::StartIsolationGuidance => ("isolation guidance", true),
+            TimelineAction::EndIsolationGuidance => ("isolation guidance", false),
+        }
+    }
+}
+
+/// A row of an intervention timeline: the action taken at `time`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimelineEntry {
+    pub time: f64,
+    pub action: TimelineAction,
+}
+
+#[derive(Deserialize)]
+struct TimelineRecord {
+    time: f64,
+    action: String,
+}
+
+/// Reads an intervention timeline with `time` and `action` columns.
+/// # Errors
+/// - If the file cannot be read
+/// - If an action is not one of the timeline actions
+pub fn read_intervention_timeline(path: &Path) -> Result<Vec<TimelineEntry>, IxaError> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let mut timeline = Vec::new();
+    for record in reader.deserialize() {
+        let TimelineRecord { time, action } = record?;
+        let Some(action) = TimelineAction::from_name(action.trim()) else {
+            let valid_actions: Vec<&str> = TIMELINE_ACTIONS.iter().map(|(name, _)| *name).collect();
+            return Err(IxaError::IxaError(format!(
+                "Unknown intervention timeline action `{action}`. Valid actions are {}.",
+                valid_actions.join(", ")
+            )));
+        };
+        timeline.push(TimelineEntry { time, action });
+    }
+    Ok(timeline)
+}
+
+/// Checks that an intervention timeline is in chronological order and that each intervention is
+/// turned on before it is turned off, and is not turned on or off twice in a row.
+/// # Errors
+/// - If any time is negative or not finite
+/// - If the times decrease
+/// - If an intervention is turned off while it is off or turned on while it is on
+pub fn validate_intervention_timeline(timeline: &[TimelineEntry]) -> Result<(), IxaError> {
+    let mut active = Vec::new();
+    let mut previous_time = 0.0;
+    for &TimelineEntry { time, action } in timeline {
+        if !(time >= 0.0 && time.is_finite()) {
+            return Err(IxaError::IxaError(
+                "The intervention timeline times must be non-negative and finite.".to_string(),
+            ));
+        }
+        if time < previous_time {
+            return Err(IxaError::IxaError(
+                "The intervention timeline times must be non-decreasing.".to_string(),
+            ));
+        }
+        previous_time = time;
+        let (intervention, on) = action.toggle();
+        let was_on = active.contains(&intervention);
+        if on == was_on {
+            let state = if on { "on" } else { "off" };
+            return Err(IxaError::IxaError(format!(
+                "The intervention timeline turns the {intervention} {state} at time {time}, but it is already {state}."
+            )));
+        }
+        if on {
+            active.push(intervention);
+        } else {
+            active.retain(|&other| other != intervention);
+        }
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct InterventionTimelineContainer {
+    mask_mandate_active: bool,
+    // Isolation guidance only applies while active, so a timeline that toggles it starts with it
+    // off. Without a timeline, the guidance policy applies for the whole simulation.
+    isolation_guidance_inactive: bool,
+}
+
+define_data_plugin!(
+    InterventionTimelinePlugin,
+    InterventionTimelineContainer,
+    InterventionTimelineContainer::default()
+);
+
+/// Whether people who develop symptoms now follow the guidance policy. The guidance always
+/// applies unless an intervention timeline turns it off.
+pub fn is_isolation_guidance_active(context: &Context) -> bool {
+    !context
+        .get_data(InterventionTimelinePlugin)
+        .isolation_guidance_inactive
+}
+
+/// A mask mandate reduces the infectiousness of everyone who is infectious by the facemask
+/// efficacy while the mandate is in effect
+#[derive(Debug)]
+struct MaskMandate {
+    facemask_efficacy: f64,
+}
+
+impl TransmissionModifier for MaskMandate {
+    fn get_relative_transmission(&self, context: &Context, _person_id: PersonId) -> f64 {
+        if context
+            .get_data(InterventionTimelinePlugin)
+            .mask_mandate_active
+        {
+            1.0 - self.facemask_efficacy
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Open or close every school, along with the classrooms within it. People who can't be kept out
+/// of school (e.g., because it is their only setting) keep attending, and people whose school
+/// settings can't be reopened stay out of school.
+fn set_schools_open(context: &mut Context, open: bool) {
+    let mut people = Vec::new();
+    context.with_query_results((Alive, true), &mut |current_people| {
+        people.extend(current_people.iter().copied());
+    });
+    for person in people {
+        if let Err(e) = context.set_setting_categories_active(person, &[&School, &Classroom], open)
+        {
+            trace!("Person {person}: Could not set their schools open to {open}: {e}");
+        }
+    }
+}
+
+fn apply_timeline_action(context: &mut Context, action: TimelineAction) {
+    trace!("Intervention timeline: {action:?}");
+    match action {
+        TimelineAction::StartMaskMandate | TimelineAction::EndMaskMandate => {
+            context
+                .get_data_mut(InterventionTimelinePlugin)
+                .mask_mandate_active = action == TimelineAction::StartMaskMandate;
+        }
+        TimelineAction::CloseSchools => set_schools_open(context, false),
+        TimelineAction::ReopenSchools => set_schools_open(context, true),
+        TimelineAction::StartIsolationGuidance | TimelineAction::EndIsolationGuidance => {
+            context
+                .get_data_mut(InterventionTimelinePlugin)
+                .isolation_guidance_inactive = action == TimelineAction::EndIsolationGuidance;
+        }
+    }
+}
+
+/// Read the intervention timeline, if one is specified, and schedule each of its actions.
+/// # Errors
+/// - If the timeline cannot be read or is invalid
+/// - If the timeline has mask mandate actions but no facemask parameters, or isolation guidance
+///   actions but no guidance policy
+pub fn init(context: &mut Context) -> Result<(), IxaError> {
+    let Params {
+        intervention_timeline_file,
+        facemask_parameters,
+        guidance_policy,
+        ..
+    } = context.get_params().clone();
+    let Some(intervention_timeline_file) = intervention_timeline_file else {
+        return Ok(());
+    };
+    let timeline = read_intervention_timeline(&intervention_timeline_file)?;
+    validate_intervention_timeline(&timeline)?;
+
+    let has_action =
+        |actions: [TimelineAction; 2]| timeline.iter().any(|entry| actions.contains(&entry.action));
+    if has_action([
+        TimelineAction::StartMaskMandate,
+        TimelineAction::EndMaskMandate,
+    ]) {
+        let Some(facemask_parameters) = facemask_parameters else {
+            return Err(IxaError::IxaError(
+                "No facemask parameters provided. They are required for a mask mandate in the intervention timeline.".to_string(),
+            ));
+        };
+        context.register_transmission_modifier_fn(
+            InfectionStatusValue::Infectious,
+            MaskMandate {
+                facemask_efficacy: facemask_parameters.facemask_efficacy,
+            },
+        );
+    }
+    if has_action([
+        TimelineAction::StartIsolationGuidance,
+        TimelineAction::EndIsolationGuidance,
+    ]) {
+        if guidance_policy.is_none() {
+            return Err(IxaError::IxaError(
+                "No guidance policy provided. It is required for isolation guidance in the intervention timeline.".to_string(),
+            ));
+        }
+        context
+            .get_data_mut(InterventionTimelinePlugin)
+            .isolation_guidance_inactive = true;
+    }
+
+    for TimelineEntry { time, action } in timeline {
+        context.add_plan(time, move |context| {
+            apply_timeline_action(context, action);
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, io::Write, path::PathBuf, rc::Rc};
+
+    use ixa::{
+        assert_almost_eq, Context, ContextGlobalPropertiesExt, ContextPeopleExt, ContextRandomExt,
+        HashMap, IxaError,
+    };
+    use tempfile::NamedTempFile;
+
+    use super::{
+        init, read_intervention_timeline, validate_intervention_timeline, TimelineAction,
+        TimelineEntry,
+    };
+    use crate::{
+        infectiousness_manager::InfectionContextExt,
+        interventions::ContextTransmissionModifierExt,
+        parameters::{
+            CoreSettingsTypes, FacemaskParameters, GlobalParams, ItinerarySpecificationType, Params,
+        },
+        rate_fns::load_rate_fns,
+        settings::{
+            Classroom, ContextSettingExt, Home, ItineraryEntry, School, SettingId,
+            SettingProperties,
+        },
+    };
+
+    fn persist_tmp_csv(content: &str) -> PathBuf {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        let (_file, path) = file.keep().unwrap();
+        path
+    }
+
+    fn setup_context(timeline: &str) -> Context {
+        let mut context = Context::new();
+        let setting_properties = SettingProperties {
+            alpha: 0.5,
+            itinerary_specification: Some(ItinerarySpecificationType::Constant { ratio: 0.5 }),
+        };
+        let parameters = Params {
+            max_time: 10.0,
+            settings_properties: HashMap::from_iter([
+                (CoreSettingsTypes::Home, setting_properties),
+                (CoreSettingsTypes::School, setting_properties),
+            ]),
+            facemask_parameters: Some(FacemaskParameters {
+                facemask_efficacy: 0.8,
+            }),
+            intervention_timeline_file: Some(persist_tmp_csv(timeline)),
+            ..Default::default()
+        };
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        context.init_random(0);
+        crate::settings::init(&mut context).unwrap();
+        load_rate_fns(&mut context).unwrap();
+        context
+    }
+
+    #[test]
+    fn test_mask_mandate_activates_and_deactivates() {
+        let mut context = setup_context("time,action\n1.0,StartMaskMandate\n3.0,EndMaskMandate\n");
+        let person = context.add_person(()).unwrap();
+        context.infect_person(person, None, None, None);
+        init(&mut context).unwrap();
+
+        let relative_transmission = Rc::new(RefCell::new(Vec::new()));
+        for time in [0.5, 2.0, 4.0] {
+            let relative_transmission = Rc::clone(&relative_transmission);
+            context.add_plan(time, move |context| {
+                relative_transmission
+                    .borrow_mut()
+                    .push(context.get_relative_total_transmission(person));
+            });
+        }
+        context.execute();
+
+        let relative_transmission = relative_transmission.borrow();
+        assert_eq!(relative_transmission.len(), 3);
+        assert_almost_eq!(relative_transmission[0], 1.0, 0.0);
+        assert_almost_eq!(relative_transmission[1], 0.2, 1e-12);
+        assert_almost_eq!(relative_transmission[2], 1.0, 0.0);
+    }
+
+    #[test]
+    fn test_school_closure_activates_and_deactivates() {
+        let mut context = setup_context("time,action\n1.0,CloseSchools\n3.0,ReopenSchools\n");
+        let school = SettingId::new(School, 0);
+        let student = context.add_person(()).unwrap();
+        context
+            .add_itinerary(
+                student,
+                vec![
+                    ItineraryEntry::new(SettingId::new(Home, 0), 0.5),
+                    ItineraryEntry::new(school, 0.5),
+                ],
+            )
+            .unwrap();
+        init(&mut context).unwrap();
+
+        let attending = Rc::new(RefCell::new(Vec::new()));
+        for time in [0.5, 2.0, 4.0] {
+            let attending = Rc::clone(&attending);
+            context.add_plan(time, move |context| {
+                attending
+                    .borrow_mut()
+                    .push(context.is_setting_active(student, &school));
+            });
+        }
+        context.execute();
+
+        assert_eq!(*attending.borrow(), vec![true, false, true]);
+    }
+
+    #[test]
+    fn test_school_closure_closes_classrooms() {
+        let mut context = setup_context("time,action\n1.0,CloseSchools\n3.0,ReopenSchools\n");
+        context
+            .register_setting_category(
+                &Classroom,
+                SettingProperties {
+                    alpha: 0.5,
+                    itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                        ratio: 0.5,
+                    }),
+                },
+            )
+            .unwrap();
+        context.link_classroom_to_school(0, 0).unwrap();
+        let classroom = SettingId::new(Classroom, 0);
+        let student = context.add_person(()).unwrap();
+        context
+            .add_itinerary(
+                student,
+                vec![
+                    ItineraryEntry::new(SettingId::new(Home, 0), 0.5),
+                    ItineraryEntry::new(SettingId::new(School, 0), 0.25),
+                    ItineraryEntry::new(classroom, 0.25),
+                ],
+            )
+            .unwrap();
+        // A person whose only setting is a school can't be kept out of it, so they keep attending
+        let boarder = context.add_person(()).unwrap();
+        context
+            .add_itinerary(
+                boarder,
+                vec![ItineraryEntry::new(SettingId::new(School, 0), 1.0)],
+            )
+            .unwrap();
+        init(&mut context).unwrap();
+
+        let attending = Rc::new(RefCell::new(Vec::new()));
+        for time in [0.5, 2.0, 4.0] {
+            let attending = Rc::clone(&attending);
+            context.add_plan(time, move |context| {
+                attending.borrow_mut().push((
+                    context.is_setting_active(student, &classroom),
+                    context.is_setting_active(boarder, &SettingId::new(School, 0)),
+                ));
+            });
+        }
+        context.execute();
+
+        assert_eq!(
+            *attending.borrow(),
+            vec![(true, true), (false, true), (true, true)]
+        );
+    }
+
+    #[test]
+    fn test_validate_intervention_timeline() {
+        let path = persist_tmp_csv("time,action\n1.0,CloseSchools\n2.0,OpenSchools\n");
+        let e = read_intervention_timeline(&path).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => assert_eq!(
+                msg,
+                "Unknown intervention timeline action `OpenSchools`. Valid actions are StartMaskMandate, EndMaskMandate, CloseSchools, ReopenSchools, StartIsolationGuidance, EndIsolationGuidance."
+            ),
+            Some(ue) => panic!("Expected an error about the action name. Instead got {ue:?}"),
+            None => panic!("Expected an error. Instead, the timeline was read."),
+        }
+
+        let e = validate_intervention_timeline(&[
+            TimelineEntry {
+                time: 1.0,
+                action: TimelineAction::StartMaskMandate,
+            },
+            TimelineEntry {
+                time: 0.5,
+                action: TimelineAction::EndMaskMandate,
+            },
+        ])
+        .err();
+        match e {
+            Some(IxaError::IxaError(msg)) => assert_eq!(
+                msg,
+                "The intervention timeline times must be non-decreasing."
+            ),
+            Some(ue) => panic!("Expected an error about the times. Instead got {ue:?}"),
+            None => panic!("Expected an error. Instead, validation passed with no errors."),
+        }
+
+        let e = validate_intervention_timeline(&[TimelineEntry {
+            time: 1.0,
+            action: TimelineAction::ReopenSchools,
+        }])
+        .err();
+        match e {
+            Some(IxaError::IxaError(msg)) => assert_eq!(
+                msg,
+                "The intervention timeline turns the school closure off at time 1, but it is already off."
+            ),
+            Some(ue) => panic!("Expected an error about the school closure. Instead got {ue:?}"),
+            None => panic!("Expected an error. Instead, validation passed with no errors."),
+        }
+    }
+}
@@ -1,9 +1,19 @@
-use ixa::{Context, IxaError};
+use ixa::{
+    define_person_property_with_default, define_rng, Context, ContextPeopleExt, ContextRandomExt,
+    IxaError, PersonId, PluginContext,
+};
 use serde::{Deserialize, Serialize};
 
-use crate::parameters::{ContextParametersExt, Params};
+use crate::{
+    parameters::{ContextParametersExt, Params},
+    population_loader::Alive,
+    settings::{ContextSettingExt, Home, ItineraryModifiers},
+};
 
+pub mod intervention_timeline;
 pub mod previous_guidance;
+pub mod reopening;
+pub mod symptomatic_avoidance;
 pub mod updated_guidance;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
@@ -19,6 +29,9 @@ pub enum Policies {
         policy_adherence: f64,
         // delay from symptom onset to when an individual starts following the isolation guidance policy
         isolation_delay_period: f64,
+        // the fraction of their transmission outside their primary home that isolating individuals
+        // keep, defaulting to fully restricting isolating individuals to their home
+        isolation_leakage: Option<f64>,
     },
     PreviousIsolationGuidance {
         // the minimum duration from symptom onset of the policy if the individuals has
@@ -37,9 +50,149 @@ pub enum Policies {
         isolation_delay_period: f64,
         // sensitivity of the test used to determine if an individual is infected
         test_sensitivity: f64,
+        // whether isolating individuals are tested periodically and end isolation early upon a
+        // negative test, defaulting to false
+        test_to_exit: Option<bool>,
+        // the time between tests to exit isolation, defaulting to `delay_to_retest`
+        test_to_exit_retest_interval: Option<f64>,
+        // the fraction of their transmission outside their primary home that isolating individuals
+        // keep, defaulting to fully restricting isolating individuals to their home
+        isolation_leakage: Option<f64>,
     },
 }
 
+impl Policies {
+    /// The fraction of their transmission outside their primary home that isolating people keep,
+    /// or `None` if isolation fully restricts people to their home
+    pub fn isolation_leakage(&self) -> Option<f64> {
+        match self {
+            Policies::UpdatedIsolationGuidance {
+                isolation_leakage, ..
+            }
+            | Policies::PreviousIsolationGuidance {
+                isolation_leakage, ..
+            } => *isolation_leakage,
+        }
+    }
+}
+
+/// How people decide whether to adhere to the guidance policy
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AdherenceMode {
+    /// Each time a person develops symptoms, they adhere with probability `policy_adherence`, so
+    /// the same person may adhere in one symptom episode and not in another.
+    PerEvent,
+    /// Each person has a fixed adherence propensity drawn uniformly from [0, 1) and adheres
+    /// whenever their propensity is below `policy_adherence`, modeling persistent behavioral
+    /// types that are consistently compliant or not.
+    PerPerson,
+}
+
+// A person's fixed adherence propensity under per-person adherence, drawn at load time
+define_person_property_with_default!(AdherencePropensity, Option<f64>, None);
+
+define_rng!(AdherenceRng);
+
+pub trait ContextAdherenceExt:
+    PluginContext + ContextRandomExt + ContextPeopleExt + ContextParametersExt
+{
+    /// Get a person's fixed adherence propensity, drawing it if it has not been drawn yet (e.g.,
+    /// for people added after the policies were initialized).
+    fn get_adherence_propensity(&mut self, person_id: PersonId) -> f64 {
+        if let Some(propensity) = self.get_person_property(person_id, AdherencePropensity) {
+            return propensity;
+        }
+        let propensity = self.sample_range(AdherenceRng, 0.0..1.0);
+        self.set_person_property(person_id, AdherencePropensity, Some(propensity));
+        propensity
+    }
+
+    /// Whether a person adheres to a policy with adherence `policy_adherence` under per-person
+    /// adherence, or `None` if adherence is decided per event and the policy must sample it.
+    fn get_persistent_adherence(
+        &mut self,
+        person_id: PersonId,
+        policy_adherence: f64,
+    ) -> Option<bool> {
+        match self.get_params().adherence_mode {
+            Some(AdherenceMode::PerPerson) => {
+                Some(self.get_adherence_propensity(person_id) < policy_adherence)
+            }
+            Some(AdherenceMode::PerEvent) | None => None,
+        }
+    }
+}
+impl ContextAdherenceExt for Context {}
+
+pub trait ContextIsolationExt: PluginContext + ContextSettingExt + ContextParametersExt {
+    /// Scale the ratio of each non-home setting in a person's itinerary by `factor` relative to
+    /// their home. Scaling every non-home setting by the factor is the same as scaling the home by
+    /// its inverse once the itinerary is renormalized, so this applies `ScaleSetting` to the home,
+    /// or restricts the itinerary to the home when the factor is zero. People without a home keep
+    /// their itinerary, since scaling every setting by the same factor leaves it unchanged.
+    /// # Errors
+    /// - If the person's itinerary cannot be modified
+    fn scale_non_home_settings(
+        &mut self,
+        person_id: PersonId,
+        factor: f64,
+    ) -> Result<(), IxaError> {
+        if self.get_setting_ids(person_id, &Home).is_empty() {
+            return Ok(());
+        }
+        let itinerary_modifier = if factor > 0.0 {
+            ItineraryModifiers::ScaleSetting {
+                setting: &Home,
+                factor: 1.0 / factor,
+            }
+        } else {
+            ItineraryModifiers::RestrictTo {
+                setting: &Home,
+                setting_id: None,
+            }
+        };
+        self.modify_itinerary(person_id, itinerary_modifier)
+    }
+
+    /// Isolate a person at their primary home if they belong to more than one. If the guidance
+    /// policy has a nonzero `isolation_leakage`, isolation is leaky: the ratio of every setting
+    /// other than the primary home is scaled by the leakage, and the time freed up is spent at the
+    /// primary home. The ratios still sum to one, so the person's transmission in each of those
+    /// settings is exactly the leakage times its value without isolation. People without a home
+    /// are restricted to their (empty) home as with non-leaky isolation.
+    /// # Errors
+    /// - If the person's itinerary cannot be modified
+    fn isolate_at_home(&mut self, person_id: PersonId) -> Result<(), IxaError> {
+        let isolation_leakage = self
+            .get_params()
+            .guidance_policy
+            .and_then(|policy| policy.isolation_leakage());
+        let primary_home = self.get_primary_setting_id(person_id, &Home);
+        let itinerary_modifier = match (isolation_leakage, primary_home) {
+            (Some(leakage), Some(_)) if leakage > 0.0 => ItineraryModifiers::LeakyRestrictTo {
+                setting: &Home,
+                setting_id: primary_home,
+                leakage,
+            },
+            _ => ItineraryModifiers::RestrictTo {
+                setting: &Home,
+                setting_id: primary_home,
+            },
+        };
+        self.modify_itinerary(person_id, itinerary_modifier)
+    }
+}
+impl ContextIsolationExt for Context {}
+
+fn validate_isolation_leakage(isolation_leakage: Option<f64>) -> Result<(), IxaError> {
+    if isolation_leakage.is_some_and(|leakage| !(0.0..=1.0).contains(&leakage)) {
+        return Err(IxaError::IxaError(
+            "The isolation leakage must be between 0 and 1, inclusive.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
 pub fn validate_guidance_policy(guidance_policy: Option<Policies>) -> Result<(), IxaError> {
     match guidance_policy {
         None => (),
@@ -47,7 +200,9 @@ pub fn validate_guidance_policy(guidance_policy: Option<Policies>) -> Result<(),
             post_isolation_duration,
             policy_adherence,
             isolation_delay_period,
+            isolation_leakage,
         }) => {
+            validate_isolation_leakage(isolation_leakage)?;
             if post_isolation_duration < 0.0 {
                 return Err(IxaError::IxaError(
                     "The post-isolation duration must be non-negative.".to_string(),
@@ -72,7 +227,11 @@ pub fn validate_guidance_policy(guidance_policy: Option<Policies>) -> Result<(),
             policy_adherence,
             isolation_delay_period,
             test_sensitivity,
+            test_to_exit_retest_interval,
+            isolation_leakage,
+            ..
         }) => {
+            validate_isolation_leakage(isolation_leakage)?;
             if overall_policy_duration < 0.0 {
                 return Err(IxaError::IxaError(
                     "The overall duration of the policy must be non-negative.".to_string(),
@@ -116,6 +275,11 @@ pub fn validate_guidance_policy(guidance_policy: Option<Policies>) -> Result<(),
                     "The test sensitivity must be between 0 and 1, inclusive.".to_string(),
                 ));
             }
+            if test_to_exit_retest_interval.is_some_and(|interval| interval <= 0.0) {
+                return Err(IxaError::IxaError(
+                    "The test-to-exit retest interval must be positive.".to_string(),
+                ));
+            }
         }
     }
     Ok(())
@@ -123,9 +287,20 @@ pub fn validate_guidance_policy(guidance_policy: Option<Policies>) -> Result<(),
 
 pub fn init(context: &mut Context) -> Result<(), IxaError> {
     let &Params {
-        guidance_policy, ..
+        guidance_policy,
+        adherence_mode,
+        ..
     } = context.get_params();
 
+    if guidance_policy.is_some() && adherence_mode == Some(AdherenceMode::PerPerson) {
+        let mut people = Vec::new();
+        context.with_query_results((Alive, true), &mut |current_people| {
+            people.extend(current_people.iter().copied());
+        });
+        for person_id in people {
+            context.get_adherence_propensity(person_id);
+        }
+    }
     match guidance_policy {
         None => (),
         Some(Policies::UpdatedIsolationGuidance { .. }) => {
@@ -135,5 +310,8 @@ pub fn init(context: &mut Context) -> Result<(), IxaError> {
             previous_guidance::init(context)?;
         }
     }
+    reopening::init(context);
+    symptomatic_avoidance::init(context);
+    intervention_timeline::init(context)?;
     Ok(())
 }
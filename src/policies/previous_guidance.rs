@@ -1,16 +1,20 @@
 use std::f64;
 
 use ixa::{
-    define_person_property_with_default, define_rng, trace, Context, ContextPeopleExt,
-    ContextRandomExt, IxaError, PersonId, PersonPropertyChangeEvent, PluginContext,
+    define_data_plugin, define_person_property_with_default, define_rng, trace, Context,
+    ContextPeopleExt, ContextRandomExt, ExecutionPhase, IxaError, PersonId,
+    PersonPropertyChangeEvent, PluginContext,
 };
 
 use crate::{
-    infectiousness_manager::{InfectionStatus, InfectionStatusValue},
+    infectiousness_manager::{InfectionContextExt, InfectionStatus, InfectionStatusValue},
     interventions::ContextTransmissionModifierExt,
     parameters::{ContextParametersExt, Params},
-    policies::Policies,
-    settings::{ContextSettingExt, Home, ItineraryModifiers},
+    policies::{
+        intervention_timeline::is_isolation_guidance_active, ContextAdherenceExt,
+        ContextIsolationExt, Policies,
+    },
+    settings::ContextSettingExt,
     symptom_progression::{PresentingWithSymptoms, SymptomRecord},
 };
 
@@ -20,6 +24,33 @@ define_person_property_with_default!(LastTestResult, bool, false);
 
 define_rng!(PreviousPolicyRng);
 
+// The realized delays from symptom onset and from infection to the start of isolation for each
+// person who isolates
+#[derive(Default)]
+struct TimeToIsolationContainer {
+    delays: Vec<f64>,
+    infection_delays: Vec<f64>,
+}
+
+define_data_plugin!(
+    TimeToIsolationPlugin,
+    TimeToIsolationContainer,
+    TimeToIsolationContainer::default()
+);
+
+/// Get the delays from symptom onset to the start of isolation for everyone who has begun
+/// isolating under the previous guidance policy
+pub fn get_time_to_isolation_delays(context: &Context) -> &[f64] {
+    &context.get_data(TimeToIsolationPlugin).delays
+}
+
+/// Get the delays from infection to the start of isolation for everyone who has begun isolating
+/// under the previous guidance policy. These capture the full detection lag: the incubation
+/// period followed by the delay from symptom onset to isolation.
+pub fn get_time_from_infection_to_isolation_delays(context: &Context) -> &[f64] {
+    &context.get_data(TimeToIsolationPlugin).infection_delays
+}
+
 #[derive(Debug, Clone, Copy)]
 struct InterventionPolicyParameters {
     overall_policy_duration: f64,
@@ -29,14 +60,16 @@ struct InterventionPolicyParameters {
     policy_adherence: f64,
     isolation_delay_period: f64,
     test_sensitivity: f64,
+    // The time between tests to exit isolation, if isolating people can test to exit
+    test_to_exit_retest_interval: Option<f64>,
 }
 
 trait ContextIsolationGuidanceInternalExt:
-    PluginContext + ContextRandomExt + ContextPeopleExt + ContextSettingExt
+    PluginContext + ContextRandomExt + ContextPeopleExt + ContextSettingExt + ContextIsolationExt
 {
     fn begin_isolation(&mut self, person: PersonId) -> Result<(), IxaError> {
         self.set_person_property(person, IsolatingStatus, true);
-        self.modify_itinerary(person, ItineraryModifiers::RestrictTo { setting: &Home })?;
+        self.isolate_at_home(person)?;
         Ok(())
     }
 
@@ -53,7 +86,9 @@ trait ContextIsolationGuidanceInternalExt:
     ) {
         // Schedule person to start following the policy after the isolation delay period
         // The person will test and begin to isolate at that time if they are symptomatic
-        self.add_plan(
+        // The decision runs in the last phase so that it sees any symptom or infection status
+        // changes (e.g., recovery) that happen at the same time
+        self.add_plan_with_phase(
             self.get_current_time() + intervention_policy_parameters.isolation_delay_period,
             move |context| {
                 if context.get_person_property(person_id, PresentingWithSymptoms) {
@@ -63,8 +98,10 @@ trait ContextIsolationGuidanceInternalExt:
                     );
                     context.begin_isolation(person_id).unwrap();
                     trace!("Person {person_id} is now isolating");
+                    context.schedule_test_to_exit(person_id, intervention_policy_parameters);
                 }
             },
+            ExecutionPhase::Last,
         );
     }
 
@@ -122,7 +159,8 @@ trait ContextIsolationGuidanceInternalExt:
         person_id: PersonId,
         intervention_policy_parameters: InterventionPolicyParameters,
     ) {
-        self.add_plan(
+        // Like the initial test, the retest sees same-time infection status changes
+        self.add_plan_with_phase(
             self.get_current_time() + intervention_policy_parameters.delay_to_retest,
             move |context| {
                 // checking for symptoms during the retest; individuals are not tested
@@ -131,6 +169,43 @@ trait ContextIsolationGuidanceInternalExt:
                     context.test_for_infection(person_id, intervention_policy_parameters);
                 }
             },
+            ExecutionPhase::Last,
+        );
+    }
+
+    fn schedule_test_to_exit(
+        &mut self,
+        person_id: PersonId,
+        intervention_policy_parameters: InterventionPolicyParameters,
+    ) {
+        let Some(retest_interval) = intervention_policy_parameters.test_to_exit_retest_interval
+        else {
+            return;
+        };
+        // Like the other tests, the test to exit sees same-time infection status changes
+        self.add_plan_with_phase(
+            self.get_current_time() + retest_interval,
+            move |context| {
+                // People who have already ended isolation are no longer tested
+                if !context.get_person_property(person_id, IsolatingStatus) {
+                    return;
+                }
+                // As with other tests, only infectious people can test positive
+                let positive = context.get_person_property(person_id, InfectionStatus)
+                    == InfectionStatusValue::Infectious
+                    && context.sample_bool(
+                        PreviousPolicyRng,
+                        intervention_policy_parameters.test_sensitivity,
+                    );
+                context.set_person_property(person_id, LastTestResult, positive);
+                if positive {
+                    context.schedule_test_to_exit(person_id, intervention_policy_parameters);
+                } else {
+                    context.end_isolation(person_id).unwrap();
+                    trace!("Person {person_id} tested negative and is no longer isolating");
+                }
+            },
+            ExecutionPhase::Last,
         );
     }
 
@@ -155,6 +230,10 @@ trait ContextIsolationGuidanceInternalExt:
             // otherwise end isolation immediately
             let isolation_end = f64::max(minimum_isolation_time, self.get_current_time());
             self.add_plan(isolation_end, move |context| {
+                // People who tested negative to exit isolation early have nothing left to end
+                if !context.get_person_property(person_id, IsolatingStatus) {
+                    return;
+                }
                 context.end_isolation(person_id).unwrap();
                 trace!("Person {person_id} is now no longer isolating");
 
@@ -178,13 +257,42 @@ trait ContextIsolationGuidanceInternalExt:
         &mut self,
         intervention_policy_parameters: InterventionPolicyParameters,
     ) {
+        self.subscribe_to_event(
+            |context, event: PersonPropertyChangeEvent<IsolatingStatus>| {
+                if event.current {
+                    // isolation is always preceded by symptom onset, so there is a symptom record
+                    if let Some(symptom_record) =
+                        context.get_person_property(event.person_id, SymptomRecord)
+                    {
+                        let delay = context.get_current_time() - symptom_record.symptom_start;
+                        context
+                            .get_data_mut(TimeToIsolationPlugin)
+                            .delays
+                            .push(delay);
+                    }
+                    if let Some(provenance) = context.get_infection_provenance(event.person_id) {
+                        let delay = context.get_current_time() - provenance.infection_time;
+                        context
+                            .get_data_mut(TimeToIsolationPlugin)
+                            .infection_delays
+                            .push(delay);
+                    }
+                }
+            },
+        );
         self.subscribe_to_event(
             move |context, event: PersonPropertyChangeEvent<PresentingWithSymptoms>| {
                 if event.current {
-                    if context.sample_bool(
-                        PreviousPolicyRng,
-                        intervention_policy_parameters.policy_adherence,
-                    ) {
+                    if !is_isolation_guidance_active(context) {
+                        return;
+                    }
+                    let policy_adherence = intervention_policy_parameters.policy_adherence;
+                    let adheres = context
+                        .get_persistent_adherence(event.person_id, policy_adherence)
+                        .unwrap_or_else(|| {
+                            context.sample_bool(PreviousPolicyRng, policy_adherence)
+                        });
+                    if adheres {
                         // the policy begins when an individual's symptoms begin and they have been selected
                         // to adhere to the policy
                         context
@@ -234,15 +342,24 @@ pub fn init(context: &mut Context) -> Result<(), IxaError> {
             policy_adherence,
             isolation_delay_period,
             test_sensitivity,
+            test_to_exit,
+            test_to_exit_retest_interval,
+            ..
         }) => {
+            let test_to_exit_retest_interval = test_to_exit.unwrap_or(false).then(|| {
+                context.scale_duration(test_to_exit_retest_interval.unwrap_or(delay_to_retest))
+            });
             let intervention_policy_parameters = InterventionPolicyParameters {
-                overall_policy_duration,
-                mild_symptom_isolation_duration,
-                moderate_symptom_isolation_duration,
-                delay_to_retest,
+                overall_policy_duration: context.scale_duration(overall_policy_duration),
+                mild_symptom_isolation_duration: context
+                    .scale_duration(mild_symptom_isolation_duration),
+                moderate_symptom_isolation_duration: context
+                    .scale_duration(moderate_symptom_isolation_duration),
+                delay_to_retest: context.scale_duration(delay_to_retest),
                 policy_adherence,
-                isolation_delay_period,
+                isolation_delay_period: context.scale_duration(isolation_delay_period),
                 test_sensitivity,
+                test_to_exit_retest_interval,
             };
             context.setup_isolation_guidance_event_sequence(intervention_policy_parameters);
         }
@@ -259,6 +376,7 @@ pub fn init(context: &mut Context) -> Result<(), IxaError> {
 #[cfg(test)]
 mod test {
     use crate::{
+        computed_statistics::time_to_isolation_quantiles,
         infectiousness_manager::InfectionContextExt,
         parameters::{
             CoreSettingsTypes, FacemaskParameters, GlobalParams, ItinerarySpecificationType,
@@ -271,7 +389,7 @@ mod test {
             CensusTract, ContextSettingExt, Home, ItineraryEntry, SettingId, SettingProperties,
             Workplace,
         },
-        symptom_progression::{PresentingWithSymptoms, SymptomRecord},
+        symptom_progression::{PresentingWithSymptoms, SymptomRecord, SymptomValue, Symptoms},
         Params,
     };
     use std::{cell::RefCell, path::PathBuf, rc::Rc};
@@ -281,7 +399,10 @@ mod test {
         ContextRandomExt, HashMap, HashSetExt, IxaError, PersonPropertyChangeEvent,
     };
 
-    use super::{IsolatingStatus, MaskingStatus};
+    use super::{
+        get_time_from_infection_to_isolation_delays, get_time_to_isolation_delays, IsolatingStatus,
+        MaskingStatus,
+    };
 
     use ixa::assert_almost_eq;
     #[allow(clippy::too_many_arguments)]
@@ -297,8 +418,35 @@ mod test {
         proportion_asymptomatic: f64,
         seed: u64,
     ) -> Context {
-        let mut context = Context::new();
-        let parameters = Params {
+        setup_context_with_parameters(
+            guidance_parameters(
+                overall_policy_duration,
+                mild_symptom_isolation_duration,
+                moderate_symptom_isolation_duration,
+                delay_to_retest,
+                policy_adherence,
+                isolation_delay_period,
+                test_sensitivity,
+                facemask_efficacy,
+                proportion_asymptomatic,
+            ),
+            seed,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn guidance_parameters(
+        overall_policy_duration: f64,
+        mild_symptom_isolation_duration: f64,
+        moderate_symptom_isolation_duration: f64,
+        delay_to_retest: f64,
+        policy_adherence: f64,
+        isolation_delay_period: f64,
+        test_sensitivity: f64,
+        facemask_efficacy: f64,
+        proportion_asymptomatic: f64,
+    ) -> Params {
+        Params {
             max_time: 100.0,
             initial_incidence: 0.1,
             initial_recovered: 0.35,
@@ -351,16 +499,23 @@ mod test {
                 policy_adherence,
                 isolation_delay_period,
                 test_sensitivity,
+                test_to_exit: None,
+                test_to_exit_retest_interval: None,
+                isolation_leakage: None,
             }),
             facemask_parameters: Some(FacemaskParameters { facemask_efficacy }),
             ..Default::default()
-        };
+        }
+    }
+
+    fn setup_context_with_parameters(parameters: Params, seed: u64) -> Context {
+        let mut context = Context::new();
         context.init_random(seed);
         context
             .set_global_property_value(GlobalParams, parameters)
             .unwrap();
         load_rate_fns(&mut context).unwrap();
-        crate::settings::init(&mut context);
+        crate::settings::init(&mut context).unwrap();
         context
     }
 
@@ -544,6 +699,231 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_escalated_mild_cases_follow_moderate_isolation_duration() {
+        let mild_symptom_isolation_duration = 5.0;
+        let moderate_symptom_isolation_duration = 10.0;
+        let isolation_delay_period = 1.0;
+        let num_isolation_ends = Rc::new(RefCell::new(0));
+        for seed in 0..50 {
+            // Mild symptoms escalate almost immediately, so everyone is treated as severe
+            let parameters = Params {
+                symptom_escalation_rate: Some(1000.0),
+                ..guidance_parameters(
+                    10.0,
+                    mild_symptom_isolation_duration,
+                    moderate_symptom_isolation_duration,
+                    2.0,
+                    1.0,
+                    isolation_delay_period,
+                    1.0,
+                    0.5,
+                    0.0,
+                )
+            };
+            let mut context = setup_context_with_parameters(parameters, seed);
+            let p1 = context.add_person(()).unwrap();
+            let itinerary = vec![
+                ItineraryEntry::new(SettingId::new(Home, 0), 1.0),
+                ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0),
+            ];
+            context.add_itinerary(p1, itinerary).unwrap();
+            crate::symptom_progression::init(&mut context).unwrap();
+            super::init(&mut context).unwrap();
+
+            let num_isolation_ends_clone = Rc::clone(&num_isolation_ends);
+            context.subscribe_to_event::<PersonPropertyChangeEvent<IsolatingStatus>>(
+                move |context, event| {
+                    if !event.current {
+                        let symptom_record = context
+                            .get_person_property(event.person_id, SymptomRecord)
+                            .unwrap();
+                        assert!(symptom_record.severe);
+                        assert_eq!(symptom_record.category, SymptomValue::Category1);
+                        assert_almost_eq!(
+                            f64::max(
+                                symptom_record.symptom_end.unwrap(),
+                                symptom_record.symptom_start + moderate_symptom_isolation_duration
+                            ),
+                            context.get_current_time(),
+                            0.000_001
+                        );
+                        *num_isolation_ends_clone.borrow_mut() += 1;
+                    }
+                },
+            );
+            context.infect_person(p1, None, None, None);
+            context.execute();
+        }
+        // Make sure the test is not vacuous
+        assert!(*num_isolation_ends.borrow() > 0);
+    }
+
+    #[test]
+    fn test_time_to_isolation_equals_isolation_delay() {
+        let isolation_delay_period = 1.5;
+        let mut num_isolating = 0;
+        for seed in 0..50 {
+            let mut context = setup_context(
+                10.0,
+                5.0,
+                10.0,
+                2.0,
+                1.0,
+                isolation_delay_period,
+                1.0,
+                0.5,
+                0.0,
+                seed,
+            );
+            let p1 = context.add_person(()).unwrap();
+            let itinerary = vec![
+                ItineraryEntry::new(SettingId::new(Home, 0), 1.0),
+                ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0),
+            ];
+            context.add_itinerary(p1, itinerary).unwrap();
+            crate::symptom_progression::init(&mut context).unwrap();
+            super::init(&mut context).unwrap();
+            context.infect_person(p1, None, None, None);
+            context.execute();
+
+            let delays = get_time_to_isolation_delays(&context);
+            num_isolating += delays.len();
+            for delay in delays {
+                assert_almost_eq!(*delay, isolation_delay_period, 0.000_001);
+            }
+            if let Some(quantiles) = time_to_isolation_quantiles(delays) {
+                for (_, value) in quantiles {
+                    assert_almost_eq!(value, isolation_delay_period, 0.000_001);
+                }
+            }
+        }
+        // Make sure the test is not vacuous
+        assert!(num_isolating > 0);
+    }
+
+    #[test]
+    fn test_time_from_infection_to_isolation_sums_incubation_and_isolation_delay() {
+        let isolation_delay_period = 1.5;
+        // Infected at 0.5 with symptom onset after an incubation period of 1.25
+        let infection_time = 0.5;
+        let incubation_period = 1.25;
+        let mut num_isolating = 0;
+        for seed in 0..50 {
+            // Everyone is asymptomatic so that symptom onset can be set manually
+            let mut context = setup_context(
+                10.0,
+                5.0,
+                10.0,
+                2.0,
+                1.0,
+                isolation_delay_period,
+                1.0,
+                0.5,
+                1.0,
+                seed,
+            );
+            let p1 = context.add_person(()).unwrap();
+            let itinerary = vec![
+                ItineraryEntry::new(SettingId::new(Home, 0), 1.0),
+                ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0),
+            ];
+            context.add_itinerary(p1, itinerary).unwrap();
+            crate::symptom_progression::init(&mut context).unwrap();
+            super::init(&mut context).unwrap();
+            context.add_plan(infection_time, move |context| {
+                context.infect_person(p1, None, None, None);
+            });
+            context.add_plan(infection_time + incubation_period, move |context| {
+                context.set_person_property(p1, Symptoms, Some(SymptomValue::Category2));
+            });
+            context.execute();
+
+            // Symptoms can improve before the isolation delay has passed, in which case the
+            // person never isolates
+            let delays = get_time_to_isolation_delays(&context);
+            let infection_delays = get_time_from_infection_to_isolation_delays(&context);
+            assert_eq!(delays.len(), infection_delays.len());
+            num_isolating += infection_delays.len();
+            for delay in delays {
+                assert_almost_eq!(*delay, isolation_delay_period, 1e-12);
+            }
+            for delay in infection_delays {
+                assert_almost_eq!(*delay, incubation_period + isolation_delay_period, 1e-12);
+            }
+            if let Some(quantiles) = time_to_isolation_quantiles(infection_delays) {
+                for (_, value) in quantiles {
+                    assert_almost_eq!(value, incubation_period + isolation_delay_period, 1e-12);
+                }
+            }
+        }
+        // Make sure the test is not vacuous
+        assert!(num_isolating > 0);
+    }
+
+    /// Returns the number of positive tests and whether the person isolated when their isolation
+    /// decision and recovery both happen at time 2.0, with the recovery plan scheduled either
+    /// before or after the isolation decision plan.
+    fn isolation_decision_with_coincident_recovery(recovery_scheduled_first: bool) -> (u32, bool) {
+        // Everyone is asymptomatic so that symptom onset can be set manually at time 1.0, and
+        // the isolation decision happens after a delay of 1.0
+        let mut context = setup_context(10.0, 5.0, 10.0, 2.0, 1.0, 1.0, 1.0, 0.5, 1.0, 0);
+        let p1 = context.add_person(()).unwrap();
+        let itinerary = vec![
+            ItineraryEntry::new(SettingId::new(Home, 0), 1.0),
+            ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0),
+        ];
+        context.add_itinerary(p1, itinerary).unwrap();
+        crate::symptom_progression::init(&mut context).unwrap();
+        super::init(&mut context).unwrap();
+        context.infect_person(p1, None, None, None);
+
+        let positive_tests = Rc::new(RefCell::new(0));
+        let positive_tests_clone = Rc::clone(&positive_tests);
+        context.subscribe_to_event::<PersonPropertyChangeEvent<LastTestResult>>(move |_, event| {
+            if event.current {
+                *positive_tests_clone.borrow_mut() += 1;
+            }
+        });
+        let isolated = Rc::new(RefCell::new(false));
+        let isolated_clone = Rc::clone(&isolated);
+        context.subscribe_to_event::<PersonPropertyChangeEvent<IsolatingStatus>>(
+            move |_, event| {
+                if event.current {
+                    *isolated_clone.borrow_mut() = true;
+                }
+            },
+        );
+
+        if recovery_scheduled_first {
+            context.add_plan(2.0, move |context| context.recover_person(p1));
+        }
+        context.add_plan(1.0, move |context| {
+            context.set_person_property(p1, Symptoms, Some(SymptomValue::Category1));
+        });
+        if !recovery_scheduled_first {
+            // Scheduled after symptom onset, so after the isolation decision plan was created
+            context.add_plan(1.5, move |context| {
+                context.add_plan(2.0, move |context| context.recover_person(p1));
+            });
+        }
+        context.execute();
+
+        let positive_tests = *positive_tests.borrow();
+        let isolated = *isolated.borrow();
+        (positive_tests, isolated)
+    }
+
+    #[test]
+    fn test_isolation_decision_sees_coincident_recovery() {
+        let recovery_first = isolation_decision_with_coincident_recovery(true);
+        let decision_first = isolation_decision_with_coincident_recovery(false);
+        assert_eq!(recovery_first, decision_first);
+        // The person has recovered when the decision is made, so they cannot test positive, but
+        // they still isolate because they are presenting with symptoms
+        assert_eq!(recovery_first, (0, true));
+    }
+
     #[allow(clippy::too_many_lines)]
     #[test]
     fn test_isolation_guidance_event_sequence_negative_tests() {
@@ -990,6 +1370,89 @@ mod test {
         );
     }
 
+    /// Returns the times a person starts and ends isolation when they test to exit every 1.0 time
+    /// units with a perfectly sensitive test. Symptoms start at time 1.0 and never resolve, so
+    /// isolation can only end on a test to exit. If `infected`, the person is infectious until
+    /// time 20.0.
+    fn isolation_with_test_to_exit(infected: bool) -> (f64, f64) {
+        let mut context = Context::new();
+        let setting_properties = SettingProperties {
+            alpha: 0.5,
+            itinerary_specification: Some(ItinerarySpecificationType::Constant { ratio: 1.0 }),
+        };
+        let parameters = Params {
+            max_time: 100.0,
+            infectiousness_rate_fn: RateFnType::Constant {
+                rate: 1.0,
+                duration: 20.0,
+            },
+            settings_properties: HashMap::from_iter([
+                (CoreSettingsTypes::Home, setting_properties),
+                (CoreSettingsTypes::Workplace, setting_properties),
+            ]),
+            guidance_policy: Some(Policies::PreviousIsolationGuidance {
+                overall_policy_duration: 10.0,
+                mild_symptom_isolation_duration: 5.0,
+                moderate_symptom_isolation_duration: 10.0,
+                delay_to_retest: 2.0,
+                policy_adherence: 1.0,
+                isolation_delay_period: 1.0,
+                test_sensitivity: 1.0,
+                test_to_exit: Some(true),
+                test_to_exit_retest_interval: Some(1.0),
+                isolation_leakage: None,
+            }),
+            facemask_parameters: Some(FacemaskParameters {
+                facemask_efficacy: 0.5,
+            }),
+            ..Default::default()
+        };
+        context.init_random(0);
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        load_rate_fns(&mut context).unwrap();
+        crate::settings::init(&mut context).unwrap();
+        let p1 = context.add_person(()).unwrap();
+        let itinerary = vec![
+            ItineraryEntry::new(SettingId::new(Home, 0), 1.0),
+            ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0),
+        ];
+        context.add_itinerary(p1, itinerary).unwrap();
+        super::init(&mut context).unwrap();
+        if infected {
+            context.infect_person(p1, None, None, None);
+            context.add_plan(20.0, move |context| context.recover_person(p1));
+        }
+
+        let isolation_times = Rc::new(RefCell::new(Vec::new()));
+        let isolation_times_clone = Rc::clone(&isolation_times);
+        context.subscribe_to_event::<PersonPropertyChangeEvent<IsolatingStatus>>(
+            move |context, _| {
+                isolation_times_clone
+                    .borrow_mut()
+                    .push(context.get_current_time());
+            },
+        );
+        context.add_plan(1.0, move |context| {
+            context.set_person_property(p1, Symptoms, Some(SymptomValue::Category1));
+        });
+        context.execute();
+
+        let isolation_times = isolation_times.borrow();
+        assert_eq!(isolation_times.len(), 2);
+        (isolation_times[0], isolation_times[1])
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_test_to_exit_isolation() {
+        // An infectious person always tests positive, so they isolate until they recover
+        assert_eq!(isolation_with_test_to_exit(true), (2.0, 20.0));
+        // Someone isolating without being infected exits on their first test to exit
+        assert_eq!(isolation_with_test_to_exit(false), (2.0, 3.0));
+    }
+
     #[test]
     fn test_isolation_guidance_input_validation() {
         // this test checks that the correct errors are raised when the input parameters
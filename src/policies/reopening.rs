@@ -0,0 +1,253 @@
+use ixa::{trace, Context, ContextPeopleExt, IxaError};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    parameters::ContextParametersExt,
+    population_loader::Alive,
+    settings::{ContextSettingExt, ItineraryModifiers, Workplace},
+};
+
+/// A stage of a staged reopening policy. Starting at `time`, the ratio of every worker's
+/// workplace in their itinerary is scaled by `workplace_factor`, where 0.0 is fully closed and
+/// 1.0 is fully reopened.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ReopeningStage {
+    pub time: f64,
+    pub workplace_factor: f64,
+}
+
+/// Checks that the stages of a reopening schedule are in chronological order and that workplace
+/// activity only ramps up over time.
+/// # Errors
+/// - If the schedule is empty
+/// - If any stage time is negative or any workplace factor is not between 0 and 1
+/// - If the stage times are not strictly increasing or the workplace factors decrease
+pub fn validate_reopening_schedule(schedule: &[ReopeningStage]) -> Result<(), IxaError> {
+    if schedule.is_empty() {
+        return Err(IxaError::IxaError(
+            "The reopening schedule must have at least one stage.".to_string(),
+        ));
+    }
+    for stage in schedule {
+        if stage.time < 0.0 {
+            return Err(IxaError::IxaError(
+                "The reopening stage times must be non-negative.".to_string(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&stage.workplace_factor) {
+            return Err(IxaError::IxaError(
+                "The reopening workplace factors must be between 0 and 1, inclusive.".to_string(),
+            ));
+        }
+    }
+    for stages in schedule.windows(2) {
+        if stages[1].time <= stages[0].time {
+            return Err(IxaError::IxaError(
+                "The reopening stage times must be strictly increasing.".to_string(),
+            ));
+        }
+        if stages[1].workplace_factor < stages[0].workplace_factor {
+            return Err(IxaError::IxaError(
+                "The reopening workplace factors must be non-decreasing.".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn apply_reopening_stage(context: &mut Context, workplace_factor: f64) {
+    let mut people = Vec::new();
+    context.with_query_results((Alive, true), &mut |current_people| {
+        people.extend(current_people.iter().copied());
+    });
+    for person in people {
+        if context.get_setting_ids(person, &Workplace).is_empty() {
+            continue;
+        }
+        // Replace the previous stage's scaling. People return to their default itinerary once
+        // workplaces are fully reopened.
+        context.remove_modified_itinerary(person).unwrap();
+        if workplace_factor < 1.0 {
+            context
+                .modify_itinerary(
+                    person,
+                    ItineraryModifiers::ScaleSetting {
+                        setting: &Workplace,
+                        factor: workplace_factor,
+                    },
+                )
+                .unwrap();
+        }
+    }
+}
+
+/// Schedule each stage of the reopening schedule, if one is specified. Workplaces are fully
+/// open until the first stage.
+pub fn init(context: &mut Context) {
+    let Some(reopening_schedule) = context.get_params().reopening_schedule.clone() else {
+        return;
+    };
+    for ReopeningStage {
+        time,
+        workplace_factor,
+    } in reopening_schedule
+    {
+        context.add_plan(time, move |context| {
+            trace!("Reopening workplaces with factor {workplace_factor}");
+            apply_reopening_stage(context, workplace_factor);
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use ixa::{
+        assert_almost_eq, Context, ContextGlobalPropertiesExt, ContextPeopleExt, HashMap, IxaError,
+    };
+
+    use super::{init, validate_reopening_schedule, ReopeningStage};
+    use crate::{
+        parameters::{CoreSettingsTypes, GlobalParams, ItinerarySpecificationType, Params},
+        settings::{
+            ContextSettingExt, Home, ItineraryEntry, SettingId, SettingProperties, Workplace,
+        },
+    };
+
+    fn setup_context(reopening_schedule: Vec<ReopeningStage>) -> Context {
+        let mut context = Context::new();
+        let setting_properties = SettingProperties {
+            alpha: 0.5,
+            itinerary_specification: Some(ItinerarySpecificationType::Constant { ratio: 0.5 }),
+        };
+        let parameters = Params {
+            max_time: 30.0,
+            settings_properties: HashMap::from_iter([
+                (CoreSettingsTypes::Home, setting_properties),
+                (CoreSettingsTypes::Workplace, setting_properties),
+            ]),
+            reopening_schedule: Some(reopening_schedule),
+            ..Default::default()
+        };
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        crate::settings::init(&mut context).unwrap();
+        context
+    }
+
+    #[test]
+    fn test_workplace_activity_increases_stepwise() {
+        let mut context = setup_context(vec![
+            ReopeningStage {
+                time: 0.0,
+                workplace_factor: 0.0,
+            },
+            ReopeningStage {
+                time: 10.0,
+                workplace_factor: 0.5,
+            },
+            ReopeningStage {
+                time: 20.0,
+                workplace_factor: 1.0,
+            },
+        ]);
+        let workplace = SettingId::new(Workplace, 0);
+        let mut workers = vec![];
+        for home_id in 0..2 {
+            let person = context.add_person(()).unwrap();
+            context
+                .add_itinerary(
+                    person,
+                    vec![
+                        ItineraryEntry::new(SettingId::new(Home, home_id), 0.5),
+                        ItineraryEntry::new(workplace, 0.5),
+                    ],
+                )
+                .unwrap();
+            workers.push(person);
+        }
+        let worker = workers[0];
+        init(&mut context);
+
+        // Each worker is the only member of their home, so all of their infectiousness comes from
+        // the workplace. Record it in the middle of each stage.
+        let workplace_transmission = Rc::new(RefCell::new(Vec::new()));
+        for time in [5.0, 15.0, 25.0] {
+            let workplace_transmission = Rc::clone(&workplace_transmission);
+            context.add_plan(time, move |context| {
+                workplace_transmission.borrow_mut().push((
+                    context.calculate_current_infectiousness_multiplier_for_person(worker),
+                    context.active_member_count(&workplace),
+                ));
+            });
+        }
+        context.execute();
+
+        // Closed: no one is at work. Half open: a third of the worker's time is spent at work.
+        // Fully open: the worker is back to their default itinerary.
+        let workplace_transmission = workplace_transmission.borrow();
+        assert_eq!(workplace_transmission.len(), 3);
+        assert_eq!(workplace_transmission[0].1, 0);
+        assert_eq!(workplace_transmission[1].1, 2);
+        assert_eq!(workplace_transmission[2].1, 2);
+        assert!(workplace_transmission[0].0 < workplace_transmission[1].0);
+        assert!(workplace_transmission[1].0 < workplace_transmission[2].0);
+        assert_almost_eq!(workplace_transmission[1].0, 1.0 / 3.0, 1e-12);
+        assert_almost_eq!(workplace_transmission[2].0, 0.5, 0.0);
+    }
+
+    #[test]
+    fn test_validate_reopening_schedule() {
+        let e = validate_reopening_schedule(&[
+            ReopeningStage {
+                time: 10.0,
+                workplace_factor: 0.5,
+            },
+            ReopeningStage {
+                time: 5.0,
+                workplace_factor: 1.0,
+            },
+        ])
+        .err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "The reopening stage times must be strictly increasing."
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that the stage times are out of order. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, validation passed with no errors."),
+        }
+
+        let e = validate_reopening_schedule(&[
+            ReopeningStage {
+                time: 0.0,
+                workplace_factor: 1.0,
+            },
+            ReopeningStage {
+                time: 5.0,
+                workplace_factor: 0.5,
+            },
+        ])
+        .err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "The reopening workplace factors must be non-decreasing."
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that the workplace factors decrease. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, validation passed with no errors."),
+        }
+    }
+}
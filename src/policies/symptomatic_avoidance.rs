@@ -0,0 +1,212 @@
+use ixa::{
+    define_person_property_with_default, define_rng, trace, Context, ContextPeopleExt,
+    ContextRandomExt, IxaError, PersonId, PersonPropertyChangeEvent,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    parameters::ContextParametersExt,
+    policies::{ContextAdherenceExt, ContextIsolationExt},
+    settings::ContextSettingExt,
+    symptom_progression::PresentingWithSymptoms,
+};
+
+/// Background behavior where people cut back on their activity outside the home while they are
+/// symptomatic, independent of any formal guidance policy. On symptom onset, a person reduces
+/// their activity with probability `adherence`, and the ratio of each non-home setting in their
+/// itinerary is scaled by `non_home_factor` until their symptoms resolve.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct SymptomaticAvoidanceParameters {
+    pub adherence: f64,
+    pub non_home_factor: f64,
+}
+
+/// Checks that the adherence is a probability and that the non-home factor only reduces activity.
+/// # Errors
+/// - If the adherence or the non-home factor is not between 0 and 1
+pub fn validate_symptomatic_avoidance(
+    parameters: SymptomaticAvoidanceParameters,
+) -> Result<(), IxaError> {
+    if !(0.0..=1.0).contains(&parameters.adherence) {
+        return Err(IxaError::IxaError(
+            "The symptomatic avoidance adherence must be between 0 and 1, inclusive.".to_string(),
+        ));
+    }
+    if !(0.0..=1.0).contains(&parameters.non_home_factor) {
+        return Err(IxaError::IxaError(
+            "The symptomatic avoidance non-home factor must be between 0 and 1, inclusive."
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Whether a person is currently reducing their non-home activity because they are symptomatic
+define_person_property_with_default!(AvoidingSettings, bool, false);
+
+define_rng!(SymptomaticAvoidanceRng);
+
+fn begin_avoidance(
+    context: &mut Context,
+    person_id: PersonId,
+    non_home_factor: f64,
+) -> Result<(), IxaError> {
+    trace!("Person {person_id} is reducing their non-home activity");
+    context.set_person_property(person_id, AvoidingSettings, true);
+    context.scale_non_home_settings(person_id, non_home_factor)
+}
+
+fn end_avoidance(context: &mut Context, person_id: PersonId) -> Result<(), IxaError> {
+    trace!("Person {person_id} is returning to their usual activity");
+    context.set_person_property(person_id, AvoidingSettings, false);
+    context.remove_modified_itinerary(person_id)
+}
+
+/// Subscribe to symptom onset and resolution if symptomatic avoidance is specified.
+pub fn init(context: &mut Context) {
+    let Some(SymptomaticAvoidanceParameters {
+        adherence,
+        non_home_factor,
+    }) = context.get_params().symptomatic_avoidance
+    else {
+        return;
+    };
+    context.subscribe_to_event(
+        move |context, event: PersonPropertyChangeEvent<PresentingWithSymptoms>| {
+            if event.current {
+                let adheres = context
+                    .get_persistent_adherence(event.person_id, adherence)
+                    .unwrap_or_else(|| context.sample_bool(SymptomaticAvoidanceRng, adherence));
+                if adheres {
+                    begin_avoidance(context, event.person_id, non_home_factor).unwrap();
+                }
+            } else if event.previous
+                && context.get_person_property(event.person_id, AvoidingSettings)
+            {
+                end_avoidance(context, event.person_id).unwrap();
+            }
+        },
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use ixa::{
+        assert_almost_eq, Context, ContextGlobalPropertiesExt, ContextPeopleExt, ContextRandomExt,
+        HashMap, IxaError,
+    };
+
+    use super::{
+        init, validate_symptomatic_avoidance, AvoidingSettings, SymptomaticAvoidanceParameters,
+    };
+    use crate::{
+        parameters::{CoreSettingsTypes, GlobalParams, ItinerarySpecificationType, Params},
+        settings::{
+            ContextSettingExt, Home, ItineraryEntry, SettingId, SettingProperties, Workplace,
+        },
+        symptom_progression::{SymptomValue, Symptoms},
+    };
+
+    #[test]
+    fn test_symptomatic_workplace_transmission_drops_without_guidance_policy() {
+        let mut context = Context::new();
+        let setting_properties = SettingProperties {
+            alpha: 0.5,
+            itinerary_specification: Some(ItinerarySpecificationType::Constant { ratio: 0.5 }),
+        };
+        let parameters = Params {
+            max_time: 10.0,
+            settings_properties: HashMap::from_iter([
+                (CoreSettingsTypes::Home, setting_properties),
+                (CoreSettingsTypes::Workplace, setting_properties),
+            ]),
+            symptomatic_avoidance: Some(SymptomaticAvoidanceParameters {
+                adherence: 1.0,
+                non_home_factor: 0.5,
+            }),
+            ..Default::default()
+        };
+        assert!(parameters.guidance_policy.is_none());
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        context.init_random(0);
+        crate::settings::init(&mut context).unwrap();
+
+        let workplace = SettingId::new(Workplace, 0);
+        let mut workers = vec![];
+        for home_id in 0..2 {
+            let person = context.add_person(()).unwrap();
+            context
+                .add_itinerary(
+                    person,
+                    vec![
+                        ItineraryEntry::new(SettingId::new(Home, home_id), 0.5),
+                        ItineraryEntry::new(workplace, 0.5),
+                    ],
+                )
+                .unwrap();
+            workers.push(person);
+        }
+        let worker = workers[0];
+        init(&mut context);
+
+        context.add_plan(1.0, move |context| {
+            context.set_person_property(worker, Symptoms, Some(SymptomValue::Category1));
+        });
+        context.add_plan(3.0, move |context| {
+            context.set_person_property(worker, Symptoms, None);
+        });
+
+        // Each worker is the only member of their home, so all of their infectiousness comes from
+        // the workplace. Record it before, during, and after the symptomatic period.
+        let workplace_transmission = Rc::new(RefCell::new(Vec::new()));
+        for time in [0.5, 2.0, 4.0] {
+            let workplace_transmission = Rc::clone(&workplace_transmission);
+            context.add_plan(time, move |context| {
+                workplace_transmission.borrow_mut().push((
+                    context.calculate_current_infectiousness_multiplier_for_person(worker),
+                    context.get_person_property(worker, AvoidingSettings),
+                ));
+            });
+        }
+        context.execute();
+
+        // While symptomatic, the worker spends half as much time at work relative to home, so a
+        // third of their time is spent at work
+        let workplace_transmission = workplace_transmission.borrow();
+        assert_eq!(workplace_transmission.len(), 3);
+        assert_almost_eq!(workplace_transmission[0].0, 0.5, 0.0);
+        assert!(!workplace_transmission[0].1);
+        assert_almost_eq!(workplace_transmission[1].0, 1.0 / 3.0, 1e-12);
+        assert!(workplace_transmission[1].1);
+        assert_almost_eq!(workplace_transmission[2].0, 0.5, 0.0);
+        assert!(!workplace_transmission[2].1);
+    }
+
+    #[test]
+    fn test_validate_symptomatic_avoidance() {
+        assert!(
+            validate_symptomatic_avoidance(SymptomaticAvoidanceParameters {
+                adherence: 0.5,
+                non_home_factor: 0.0,
+            })
+            .is_ok()
+        );
+        let e = validate_symptomatic_avoidance(SymptomaticAvoidanceParameters {
+            adherence: 0.5,
+            non_home_factor: 1.5,
+        })
+        .err();
+        match e {
+            Some(IxaError::IxaError(msg)) => assert_eq!(
+                msg,
+                "The symptomatic avoidance non-home factor must be between 0 and 1, inclusive."
+            ),
+            Some(ue) => panic!("Expected an error about the non-home factor. Instead got {ue:?}"),
+            None => panic!("Expected an error. Instead, validation passed with no errors."),
+        }
+    }
+}
@@ -1,14 +1,17 @@
 use ixa::{
     define_person_property_with_default, define_rng, trace, Context, ContextPeopleExt,
-    ContextRandomExt, IxaError, PersonId, PersonPropertyChangeEvent, PluginContext,
+    ContextRandomExt, ExecutionPhase, IxaError, PersonId, PersonPropertyChangeEvent, PluginContext,
 };
 
 use crate::{
     infectiousness_manager::InfectionStatusValue,
     interventions::ContextTransmissionModifierExt,
     parameters::{ContextParametersExt, Params},
-    policies::Policies,
-    settings::{ContextSettingExt, Home, ItineraryModifiers},
+    policies::{
+        intervention_timeline::is_isolation_guidance_active, ContextAdherenceExt,
+        ContextIsolationExt, Policies,
+    },
+    settings::ContextSettingExt,
     symptom_progression::PresentingWithSymptoms,
 };
 
@@ -25,7 +28,12 @@ struct InterventionPolicyParameters {
 }
 
 trait ContextIsolationGuidanceInternalExt:
-    PluginContext + ContextRandomExt + ContextPeopleExt + ContextSettingExt
+    PluginContext
+    + ContextRandomExt
+    + ContextPeopleExt
+    + ContextSettingExt
+    + ContextAdherenceExt
+    + ContextIsolationExt
 {
     fn modify_isolation_status(
         &mut self,
@@ -35,7 +43,7 @@ trait ContextIsolationGuidanceInternalExt:
         if self.get_person_property(person, IsolatingStatus) != isolation_status {
             self.set_person_property(person, IsolatingStatus, isolation_status);
             if isolation_status {
-                self.modify_itinerary(person, ItineraryModifiers::RestrictTo { setting: &Home })?;
+                self.isolate_at_home(person)?;
             } else {
                 self.remove_modified_itinerary(person)?;
             }
@@ -48,11 +56,14 @@ trait ContextIsolationGuidanceInternalExt:
         person_id: PersonId,
         intervention_policy_parameters: InterventionPolicyParameters,
     ) {
-        if self.sample_bool(
-            UpdatedPolicyRng,
-            intervention_policy_parameters.policy_adherence,
-        ) {
-            self.add_plan(
+        let policy_adherence = intervention_policy_parameters.policy_adherence;
+        let adheres = self
+            .get_persistent_adherence(person_id, policy_adherence)
+            .unwrap_or_else(|| self.sample_bool(UpdatedPolicyRng, policy_adherence));
+        if adheres {
+            // The decision runs in the last phase so that it sees any symptom changes that
+            // happen at the same time
+            self.add_plan_with_phase(
                 self.get_current_time() + intervention_policy_parameters.isolation_delay_period,
                 move |context| {
                     if context.get_person_property(person_id, PresentingWithSymptoms) {
@@ -60,6 +71,7 @@ trait ContextIsolationGuidanceInternalExt:
                         trace!("Person {person_id} is now isolating");
                     }
                 },
+                ExecutionPhase::Last,
             );
         }
     }
@@ -90,7 +102,10 @@ trait ContextIsolationGuidanceInternalExt:
         self.subscribe_to_event(
             move |context, event: PersonPropertyChangeEvent<PresentingWithSymptoms>| {
                 if event.current {
-                    context.make_isolation_plan(event.person_id, intervention_policy_parameters);
+                    if is_isolation_guidance_active(context) {
+                        context
+                            .make_isolation_plan(event.person_id, intervention_policy_parameters);
+                    }
                 } else if event.previous {
                     //individuals transition from presenting with symptoms to not presenting with symptoms
                     context.make_post_isolation_masking_plan(
@@ -131,11 +146,12 @@ pub fn init(context: &mut Context) -> Result<(), IxaError> {
             post_isolation_duration,
             policy_adherence,
             isolation_delay_period,
+            ..
         }) => {
             let intervention_policy_parameters = InterventionPolicyParameters {
-                post_isolation_duration,
+                post_isolation_duration: context.scale_duration(post_isolation_duration),
                 policy_adherence,
-                isolation_delay_period,
+                isolation_delay_period: context.scale_duration(isolation_delay_period),
             };
             context.setup_isolation_guidance_event_sequence(intervention_policy_parameters);
         }
@@ -157,14 +173,14 @@ mod test {
             CoreSettingsTypes, FacemaskParameters, GlobalParams, ItinerarySpecificationType,
             ProgressionLibraryType,
         },
-        policies::Policies,
+        policies::{AdherenceMode, Policies},
         population_loader::Alive,
         rate_fns::load_rate_fns,
         settings::{
             CensusTract, ContextSettingExt, Home, ItineraryEntry, SettingId, SettingProperties,
             Workplace,
         },
-        symptom_progression::{PresentingWithSymptoms, SymptomRecord},
+        symptom_progression::{PresentingWithSymptoms, SymptomRecord, SymptomValue, Symptoms},
         Params,
     };
     use std::{cell::RefCell, path::PathBuf, rc::Rc};
@@ -174,7 +190,7 @@ mod test {
         HashSetExt, IxaError, PersonPropertyChangeEvent,
     };
 
-    use super::{IsolatingStatus, MaskingStatus};
+    use super::{ContextIsolationGuidanceInternalExt, IsolatingStatus, MaskingStatus};
 
     use ixa::assert_almost_eq;
 
@@ -184,6 +200,24 @@ mod test {
         isolation_delay_period: f64,
         facemask_efficacy: f64,
         proportion_asymptomatic: f64,
+    ) -> Context {
+        setup_context_with_adherence_mode(
+            post_isolation_duration,
+            policy_adherence,
+            isolation_delay_period,
+            facemask_efficacy,
+            proportion_asymptomatic,
+            None,
+        )
+    }
+
+    fn setup_context_with_adherence_mode(
+        post_isolation_duration: f64,
+        policy_adherence: f64,
+        isolation_delay_period: f64,
+        facemask_efficacy: f64,
+        proportion_asymptomatic: f64,
+        adherence_mode: Option<AdherenceMode>,
     ) -> Context {
         let mut context = Context::new();
         let parameters = Params {
@@ -231,7 +265,9 @@ mod test {
                 post_isolation_duration,
                 policy_adherence,
                 isolation_delay_period,
+                isolation_leakage: None,
             }),
+            adherence_mode,
             facemask_parameters: Some(FacemaskParameters { facemask_efficacy }),
             ..Default::default()
         };
@@ -240,7 +276,7 @@ mod test {
             .set_global_property_value(GlobalParams, parameters)
             .unwrap();
         load_rate_fns(&mut context).unwrap();
-        crate::settings::init(&mut context);
+        crate::settings::init(&mut context).unwrap();
         context
     }
 
@@ -433,6 +469,166 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_per_person_adherence_is_consistent_across_symptom_episodes() {
+        let num_people = 200;
+        let num_episodes: u32 = 3;
+        let mut context = setup_context_with_adherence_mode(
+            5.0,
+            0.5,
+            0.0,
+            0.5,
+            0.0,
+            Some(AdherenceMode::PerPerson),
+        );
+        super::init(&mut context).unwrap();
+        let mut people = Vec::new();
+        for _ in 0..num_people {
+            let person_id = context.add_person(()).unwrap();
+            context
+                .add_itinerary(
+                    person_id,
+                    vec![ItineraryEntry::new(SettingId::new(Home, 0), 1.0)],
+                )
+                .unwrap();
+            people.push(person_id);
+        }
+
+        // Each person has several symptom episodes, each of which ends before the next begins
+        for episode in 0..num_episodes {
+            let onset = 20.0 * f64::from(episode);
+            for &person_id in &people {
+                context.add_plan(onset + 1.0, move |context| {
+                    context.set_person_property(person_id, Symptoms, Some(SymptomValue::Category1));
+                });
+                context.add_plan(onset + 5.0, move |context| {
+                    context.set_person_property(person_id, Symptoms, None);
+                });
+            }
+        }
+
+        let isolation_counts = Rc::new(RefCell::new(HashMap::default()));
+        let isolation_counts_clone = Rc::clone(&isolation_counts);
+        context.subscribe_to_event::<PersonPropertyChangeEvent<IsolatingStatus>>(
+            move |_context, event| {
+                if event.current {
+                    *isolation_counts_clone
+                        .borrow_mut()
+                        .entry(event.person_id)
+                        .or_insert(0) += 1;
+                }
+            },
+        );
+        context.execute();
+
+        // Everyone who isolates does so in every episode, and everyone else never isolates
+        let isolation_counts = isolation_counts.borrow();
+        assert!(isolation_counts
+            .values()
+            .all(|&count| count == num_episodes));
+        assert!(!isolation_counts.is_empty());
+        assert!(isolation_counts.len() < num_people);
+    }
+
+    fn isolated_workplace_transmission(isolation_leakage: Option<f64>) -> (f64, f64) {
+        let mut context = Context::new();
+        let setting_properties = SettingProperties {
+            alpha: 0.5,
+            itinerary_specification: Some(ItinerarySpecificationType::Constant { ratio: 0.5 }),
+        };
+        let parameters = Params {
+            max_time: 10.0,
+            settings_properties: HashMap::from_iter([
+                (CoreSettingsTypes::Home, setting_properties),
+                (CoreSettingsTypes::Workplace, setting_properties),
+            ]),
+            guidance_policy: Some(Policies::UpdatedIsolationGuidance {
+                post_isolation_duration: 0.0,
+                policy_adherence: 1.0,
+                isolation_delay_period: 0.0,
+                isolation_leakage,
+            }),
+            ..Default::default()
+        };
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        crate::settings::init(&mut context).unwrap();
+        let mut workers = vec![];
+        for home_id in 0..2 {
+            let person = context.add_person(()).unwrap();
+            context
+                .add_itinerary(
+                    person,
+                    vec![
+                        ItineraryEntry::new(SettingId::new(Home, home_id), 0.5),
+                        ItineraryEntry::new(SettingId::new(Workplace, 0), 0.5),
+                    ],
+                )
+                .unwrap();
+            workers.push(person);
+        }
+        // Each worker is the only member of their home, so all of their infectiousness comes from
+        // the workplace
+        let baseline = context.calculate_current_infectiousness_multiplier_for_person(workers[0]);
+        context.modify_isolation_status(workers[0], true).unwrap();
+        let isolated = context.calculate_current_infectiousness_multiplier_for_person(workers[0]);
+        (baseline, isolated)
+    }
+
+    #[test]
+    fn test_leaky_isolation_keeps_fraction_of_workplace_transmission() {
+        let (baseline, isolated) = isolated_workplace_transmission(None);
+        assert_almost_eq!(baseline, 0.5, 0.0);
+        assert_almost_eq!(isolated, 0.0, 0.0);
+
+        // The isolating worker keeps exactly the leakage's fraction of their workplace transmission
+        for isolation_leakage in [0.1, 0.2, 1.0] {
+            let (baseline, isolated) = isolated_workplace_transmission(Some(isolation_leakage));
+            assert_almost_eq!(isolated / baseline, isolation_leakage, 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_leaky_isolation_restricts_people_without_a_home() {
+        let mut context = Context::new();
+        let setting_properties = SettingProperties {
+            alpha: 0.5,
+            itinerary_specification: Some(ItinerarySpecificationType::Constant { ratio: 0.5 }),
+        };
+        let parameters = Params {
+            max_time: 10.0,
+            settings_properties: HashMap::from_iter([
+                (CoreSettingsTypes::Home, setting_properties),
+                (CoreSettingsTypes::Workplace, setting_properties),
+            ]),
+            guidance_policy: Some(Policies::UpdatedIsolationGuidance {
+                post_isolation_duration: 0.0,
+                policy_adherence: 1.0,
+                isolation_delay_period: 0.0,
+                isolation_leakage: Some(0.1),
+            }),
+            ..Default::default()
+        };
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        crate::settings::init(&mut context).unwrap();
+        let mut workers = vec![];
+        for _ in 0..2 {
+            let person = context.add_person(()).unwrap();
+            context
+                .add_itinerary(
+                    person,
+                    vec![ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0)],
+                )
+                .unwrap();
+            workers.push(person);
+        }
+        context.modify_isolation_status(workers[0], true).unwrap();
+        assert!(!context.is_setting_active(workers[0], &SettingId::new(Workplace, 0)));
+    }
+
     #[test]
     fn test_isolation_guidance_input_validation() {
         // this test checks that the correct errors are raised when the input parameters
@@ -1,6 +1,6 @@
 use ixa::{
-    define_person_property, define_person_property_with_default, Context, ContextPeopleExt,
-    IxaError,
+    define_person_property, define_person_property_with_default, define_rng, trace, Context,
+    ContextPeopleExt, ContextRandomExt, IxaError, PersonId, PersonPropertyChangeEvent,
 };
 
 use serde::Deserialize;
@@ -8,7 +8,7 @@ use std::path::PathBuf;
 
 use crate::parameters::{ContextParametersExt, Params};
 use crate::settings::{
-    append_itinerary_entry, CensusTract, ContextSettingExt, Home, School, SettingId, Workplace,
+    build_default_itinerary, ContextSettingExt, ItineraryEntry, School, SettingId, Workplace,
 };
 use ixa::profiling::open_span;
 
@@ -21,15 +21,36 @@ pub struct PeopleRecord<'a> {
     workplaceId: &'a [u8],
 }
 
+/// The columns every synthetic population file must have
+const SYNTH_POPULATION_COLUMNS: [&str; 4] = ["age", "homeId", "schoolId", "workplaceId"];
+
 define_person_property!(Age, u8);
 define_person_property_with_default!(Alive, bool, true);
+// Shielded people can still be infected, but they never transmit
+define_person_property_with_default!(Shielded, bool, false);
+
+// Whether a person starts the simulation recovered according to the initial immunity file
+define_person_property_with_default!(InitiallyImmune, bool, false);
+
+define_rng!(PopulationLoaderRng);
+
+#[derive(Deserialize, Debug)]
+struct ImmunityRecord {
+    recovered: bool,
+}
 
+/// Add the person described by a record and return their itinerary, which is added along with
+/// the itineraries of everyone else in the population once they are all loaded.
 fn create_person_from_record(
     context: &mut Context,
     person_record: &PeopleRecord,
-) -> Result<(), IxaError> {
+    shielded_proportion: f64,
+) -> Result<(PersonId, Vec<ItineraryEntry>), IxaError> {
     // Add person to context
     let person_id = context.add_person((Age, person_record.age))?;
+    if shielded_proportion > 0.0 && context.sample_bool(PopulationLoaderRng, shielded_proportion) {
+        context.set_person_property(person_id, Shielded, true);
+    }
 
     // Create itinerary entries for all setting memberships in input file
     let tract: String = String::from_utf8(person_record.homeId[..11].to_owned())?;
@@ -37,73 +58,199 @@ fn create_person_from_record(
     let school_string: String = String::from_utf8(person_record.schoolId.to_owned())?;
     let workplace_string: String = String::from_utf8(person_record.workplaceId.to_owned())?;
 
-    // Initialize a vector of home and census tract since everyone has these settings
-    let mut itinerary = vec![];
-    append_itinerary_entry(
-        &mut itinerary,
+    // Everyone has a home and census tract, and only some people have a school or workplace
+    let school_id = if school_string.is_empty() {
+        None
+    } else {
+        Some(school_string.parse()?)
+    };
+    let workplace_id = if workplace_string.is_empty() {
+        None
+    } else {
+        Some(workplace_string.parse()?)
+    };
+    let itinerary = build_default_itinerary(
         context,
-        SettingId::new(Home, home_id.parse()?),
-        None,
-    )?;
-    append_itinerary_entry(
-        &mut itinerary,
-        context,
-        SettingId::new(CensusTract, tract.parse()?),
-        None,
+        home_id.parse()?,
+        workplace_id,
+        tract.parse()?,
+        school_id,
     )?;
 
-    // Check for school and work memberships
-    if !school_string.is_empty() {
-        append_itinerary_entry(
-            &mut itinerary,
-            context,
-            SettingId::new(School, school_string.parse()?),
-            None,
-        )?;
-    }
-    if !workplace_string.is_empty() {
-        append_itinerary_entry(
-            &mut itinerary,
-            context,
-            SettingId::new(Workplace, workplace_string.parse()?),
-            None,
-        )?;
-    }
+    Ok((person_id, itinerary))
+}
 
-    // Create the itinerary using write rules stored in Context
-    context.add_itinerary(person_id, itinerary)?;
+/// Load the people in the synthetic population files and return them in the order of the files.
+/// People are numbered consecutively across files, so the people in each file follow those of the
+/// files before it. Every file must have the same columns as the first.
+/// # Errors
+/// - If a file cannot be opened or read
+/// - If a file is missing one of the required columns
+/// - If a row cannot be parsed (e.g., an age that is not a whole number)
+fn load_synth_population(
+    context: &mut Context,
+    synth_input_files: &[PathBuf],
+) -> Result<Vec<PersonId>, IxaError> {
+    let shielded_proportion = context.get_params().shielded_proportion.unwrap_or(0.0);
+    let mut expected_headers: Option<(&PathBuf, csv::ByteRecord)> = None;
 
-    Ok(())
-}
+    let mut itineraries = vec![];
+    for synth_input_file in synth_input_files {
+        let mut reader = csv::Reader::from_path(synth_input_file).map_err(|e| {
+            IxaError::IxaError(format!(
+                "Could not open the synthetic population file {}: {e}",
+                synth_input_file.display()
+            ))
+        })?;
+        let mut raw_record = csv::ByteRecord::new();
+        let headers = reader
+            .byte_headers()
+            .map_err(|e| {
+                IxaError::IxaError(format!(
+                    "Could not read the header of the synthetic population file {}: {e}",
+                    synth_input_file.display()
+                ))
+            })?
+            .clone();
+        if let Some(column) = SYNTH_POPULATION_COLUMNS
+            .iter()
+            .find(|column| !headers.iter().any(|header| header == column.as_bytes()))
+        {
+            return Err(IxaError::IxaError(format!(
+                "The synthetic population file {} is missing the `{column}` column.",
+                synth_input_file.display()
+            )));
+        }
+        if let Some((first_file, first_headers)) = &expected_headers {
+            if headers != *first_headers {
+                return Err(IxaError::IxaError(format!(
+                    "The synthetic population file {} has columns {:?}, but {} has columns {:?}.",
+                    synth_input_file.display(),
+                    headers,
+                    first_file.display(),
+                    first_headers
+                )));
+            }
+        } else {
+            expected_headers = Some((synth_input_file, headers.clone()));
+        }
 
-fn load_synth_population(context: &mut Context, synth_input_file: PathBuf) -> Result<(), IxaError> {
-    let mut reader = csv::Reader::from_path(synth_input_file)?;
-    let mut raw_record = csv::ByteRecord::new();
-    let headers = reader.byte_headers()?.clone();
+        let parse_error = |e: csv::Error| {
+            IxaError::IxaError(format!(
+                "Could not parse the synthetic population file {}: {e}",
+                synth_input_file.display()
+            ))
+        };
+        while reader
+            .read_byte_record(&mut raw_record)
+            .map_err(parse_error)?
+        {
+            let record: PeopleRecord = raw_record
+                .deserialize(Some(&headers))
+                .map_err(parse_error)?;
+            itineraries.push(create_person_from_record(
+                context,
+                &record,
+                shielded_proportion,
+            )?);
+        }
+    }
+    let people = itineraries
+        .iter()
+        .map(|(person_id, _)| *person_id)
+        .collect();
+    // Adding the itineraries together avoids updating the setting memberships once per person
+    context.add_itineraries_bulk(itineraries)?;
+    Ok(people)
+}
 
-    while reader.read_byte_record(&mut raw_record)? {
-        let record: PeopleRecord = raw_record.deserialize(Some(&headers))?;
-        create_person_from_record(context, &record)?;
+/// Mark the people who start the simulation recovered according to the initial immunity file,
+/// which has a `recovered` column with one row per person in the same order as the synthetic
+/// population file.
+/// # Errors
+/// - If the file cannot be read or a row cannot be parsed
+/// - If the number of rows does not match the number of people in the synthetic population
+fn load_initial_immunity(
+    context: &mut Context,
+    people: &[PersonId],
+    initial_immunity_file: PathBuf,
+) -> Result<(), IxaError> {
+    let mut reader = csv::Reader::from_path(initial_immunity_file)?;
+    let mut recovered = vec![];
+    for record in reader.deserialize() {
+        let record: ImmunityRecord = record?;
+        recovered.push(record.recovered);
+    }
+    if recovered.len() != people.len() {
+        return Err(IxaError::IxaError(format!(
+            "The initial immunity file has {} rows, but the synthetic population has {} people.",
+            recovered.len(),
+            people.len()
+        )));
+    }
+    for (&person_id, recovered) in people.iter().zip(recovered) {
+        if recovered {
+            context.set_person_property(person_id, InitiallyImmune, true);
+        }
     }
     Ok(())
 }
 
+/// Move a graduating student from their school to a workplace drawn uniformly from all
+/// workplaces. If there are no workplaces, the student only leaves school.
+fn graduate_to_workplace(context: &mut Context, person_id: PersonId) -> Result<(), IxaError> {
+    if context.get_primary_setting_id(person_id, &School).is_none() {
+        return Ok(());
+    }
+    let workplaces = context.get_category_setting_ids(&Workplace);
+    let workplace = if workplaces.is_empty() {
+        None
+    } else {
+        let index = context.sample_range(PopulationLoaderRng, 0..workplaces.len());
+        Some(SettingId::new(Workplace, workplaces[index]))
+    };
+    trace!("Person {person_id} graduated from school to workplace {workplace:?}");
+    context.replace_setting_category_in_itinerary(person_id, &School, workplace)
+}
+
+/// Graduate students once their age reaches `graduation_age`. Ages are set when the population
+/// is loaded and the model does not update them, so students only graduate when their `Age` is
+/// changed elsewhere (e.g., by an observer).
+fn subscribe_to_graduation(context: &mut Context, graduation_age: u8) {
+    context.subscribe_to_event(move |context, event: PersonPropertyChangeEvent<Age>| {
+        if event.previous < graduation_age && event.current >= graduation_age {
+            graduate_to_workplace(context, event.person_id).unwrap();
+        }
+    });
+}
+
 pub fn init(context: &mut Context) -> Result<(), IxaError> {
     let _span = open_span("load_synth_population");
     let Params {
         synth_population_file,
+        initial_immunity_file,
+        graduation_age,
         ..
-    } = context.get_params();
-    load_synth_population(context, synth_population_file.clone())?;
+    } = context.get_params().clone();
+    let people = load_synth_population(context, synth_population_file.paths())?;
+    if let Some(initial_immunity_file) = initial_immunity_file {
+        load_initial_immunity(context, &people, initial_immunity_file)?;
+    }
+    if let Some(graduation_age) = graduation_age {
+        subscribe_to_graduation(context, graduation_age);
+    }
     Ok(())
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::infectiousness_manager::{InfectionStatus, InfectionStatusValue};
     use crate::parameters::{CoreSettingsTypes, GlobalParams, ItinerarySpecificationType};
-    use crate::settings::{CensusTract, Home, School, SettingId, SettingProperties, Workplace};
-    use ixa::{ContextGlobalPropertiesExt, ContextPeopleExt, HashMap};
+    use crate::settings::{
+        AnySettingId, CensusTract, Home, School, SettingId, SettingProperties, Workplace,
+    };
+    use ixa::{ContextGlobalPropertiesExt, ContextPeopleExt, ContextRandomExt, HashMap};
     use std::io::Write;
     use std::path::PathBuf;
     use tempfile::NamedTempFile;
@@ -116,10 +263,16 @@ mod test {
     }
 
     fn setup() -> Context {
+        setup_with_shielded_proportion(None)
+    }
+
+    fn setup_with_shielded_proportion(shielded_proportion: Option<f64>) -> Context {
         let mut context = Context::new();
+        context.init_random(0);
         let parameters = Params {
+            shielded_proportion,
             // We need to specify an itinerary split here even though we don't draw people from
-            // itineraries because `load_synth_population` calls `create_itinerary` for each person,
+            // itineraries because `load_synth_population` builds an itinerary for each person,
             // and that function requires an itinerary write function to be set.
             settings_properties: HashMap::from_iter(
                 [
@@ -168,7 +321,7 @@ mod test {
         context
             .set_global_property_value(GlobalParams, parameters)
             .unwrap();
-        crate::settings::init(&mut context);
+        crate::settings::init(&mut context).unwrap();
         context
     }
 
@@ -179,7 +332,7 @@ mod test {
             "age,homeId,schoolId,workplaceId\n43,360930331020001,,\n42,360930331020002,,",
         );
         let synth_file = persist_tmp_csv(&input);
-        load_synth_population(&mut context, synth_file).unwrap();
+        load_synth_population(&mut context, &[synth_file]).unwrap();
         let age = [43, 42];
         let home_id = [360_930_331_020_001, 360_930_331_020_002];
         let census_tract_id = 36_093_033_102;
@@ -205,6 +358,151 @@ mod test {
         );
     }
 
+    #[test]
+    fn check_initial_immunity_file_recovers_listed_people() {
+        let mut context = setup();
+        let input = String::from(
+            "age,homeId,schoolId,workplaceId\n43,360930331020001,,\n42,360930331020002,,\n41,360930331020003,,",
+        );
+        let people = load_synth_population(&mut context, &[persist_tmp_csv(&input)]).unwrap();
+        let immunity = String::from("recovered\nfalse\ntrue\nfalse");
+        load_initial_immunity(&mut context, &people, persist_tmp_csv(&immunity)).unwrap();
+        crate::infection_propagation_loop::init(&mut context).unwrap();
+
+        for (i, &person) in people.iter().enumerate() {
+            let expected = if i == 1 {
+                InfectionStatusValue::Recovered
+            } else {
+                InfectionStatusValue::Susceptible
+            };
+            assert_eq!(
+                context.get_person_property(person, InfectionStatus),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn check_initial_immunity_file_must_match_population() {
+        let mut context = setup();
+        let input = String::from(
+            "age,homeId,schoolId,workplaceId\n43,360930331020001,,\n42,360930331020002,,",
+        );
+        let people = load_synth_population(&mut context, &[persist_tmp_csv(&input)]).unwrap();
+        let immunity = String::from("recovered\ntrue");
+        let e = load_initial_immunity(&mut context, &people, persist_tmp_csv(&immunity)).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "The initial immunity file has 1 rows, but the synthetic population has 2 people."
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that the immunity file does not match the population. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, loading the immunity file passed."),
+        }
+    }
+
+    #[test]
+    fn check_missing_synth_population_file() {
+        let mut context = setup();
+        let synth_file = PathBuf::from("./tests/data/no_such_population.csv");
+        let e = load_synth_population(&mut context, &[synth_file]).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert!(
+                    msg.starts_with(
+                        "Could not open the synthetic population file ./tests/data/no_such_population.csv: "
+                    ),
+                    "Unexpected error message: {msg}"
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that the population file is missing. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, loading the population passed."),
+        }
+    }
+
+    #[test]
+    fn check_synth_population_file_missing_age_column() {
+        let mut context = setup();
+        let input = String::from(
+            "homeId,schoolId,workplaceId
+360930331020001,,",
+        );
+        let synth_file = persist_tmp_csv(&input);
+        let e = load_synth_population(&mut context, std::slice::from_ref(&synth_file)).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    format!(
+                        "The synthetic population file {} is missing the `age` column.",
+                        synth_file.display()
+                    )
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that the age column is missing. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, loading the population passed."),
+        }
+    }
+
+    #[test]
+    fn check_synth_population_file_bad_age() {
+        let mut context = setup();
+        let input = String::from(
+            "age,homeId,schoolId,workplaceId
+forty,360930331020001,,",
+        );
+        let synth_file = persist_tmp_csv(&input);
+        let e = load_synth_population(&mut context, std::slice::from_ref(&synth_file)).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert!(
+                    msg.starts_with(&format!(
+                        "Could not parse the synthetic population file {}: ",
+                        synth_file.display()
+                    )),
+                    "Unexpected error message: {msg}"
+                );
+                assert!(
+                    msg.contains("invalid digit"),
+                    "Unexpected error message: {msg}"
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that the age could not be parsed. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, loading the population passed."),
+        }
+    }
+
+    #[test]
+    fn check_shielded_proportion() {
+        let input = String::from(
+            "age,homeId,schoolId,workplaceId\n43,360930331020001,,\n42,360930331020002,,",
+        );
+        for (shielded_proportion, expected_shielded) in [(None, 0), (Some(0.0), 0), (Some(1.0), 2)]
+        {
+            let mut context = setup_with_shielded_proportion(shielded_proportion);
+            let synth_file = persist_tmp_csv(&input);
+            load_synth_population(&mut context, &[synth_file]).unwrap();
+            assert_eq!(
+                expected_shielded,
+                context.query_people_count((Shielded, true))
+            );
+        }
+    }
+
     #[test]
     #[should_panic(expected = "range end index 11 out of range for slice of length 9")]
     fn check_invalid_census_tract() {
@@ -212,7 +510,7 @@ mod test {
         let input =
             String::from("age,homeId,schoolId,workplaceId\n43,360930331,,\n42,360930331020002,,");
         let synth_file = persist_tmp_csv(&input);
-        load_synth_population(&mut context, synth_file).unwrap();
+        load_synth_population(&mut context, &[synth_file]).unwrap();
     }
 
     #[test]
@@ -222,7 +520,7 @@ mod test {
             "age,homeId,schoolId,workplaceId\n43,360930331020001,1,\n42,360930331020002,2,",
         );
         let synth_file = persist_tmp_csv(&input);
-        load_synth_population(&mut context, synth_file).unwrap();
+        load_synth_population(&mut context, &[synth_file]).unwrap();
         let age = [43, 42];
         let school_id = [1, 2];
         let home_id = [360_930_331_020_001, 360_930_331_020_002];
@@ -263,7 +561,7 @@ mod test {
             "age,homeId,schoolId,workplaceId\n43,360930331020001,,1\n42,360930331020002,,2",
         );
         let synth_file = persist_tmp_csv(&input);
-        load_synth_population(&mut context, synth_file).unwrap();
+        load_synth_population(&mut context, &[synth_file]).unwrap();
         let age = [43, 42];
         let workplace_id = [1, 2];
         let home_id = [360_930_331_020_001, 360_930_331_020_002];
@@ -296,4 +594,124 @@ mod test {
                 .len()
         );
     }
+
+    #[test]
+    fn check_graduation_moves_school_to_workplace() {
+        let mut context = setup();
+        let input = String::from(
+            "age,homeId,schoolId,workplaceId\n17,360930331020001,1,\n43,360930331020002,,7",
+        );
+        let synth_file = persist_tmp_csv(&input);
+        load_synth_population(&mut context, &[synth_file]).unwrap();
+        subscribe_to_graduation(&mut context, 18);
+        let mut students = Vec::new();
+        context.with_query_results((Age, 17), &mut |people| {
+            students.extend(people.iter().copied());
+        });
+        let student = students[0];
+
+        context.add_plan(1.0, move |context| {
+            context.set_person_property(student, Age, 18);
+        });
+        context.execute();
+
+        assert!(!context.is_setting_active(student, &SettingId::new(School, 1)));
+        assert_eq!(
+            0,
+            context
+                .get_setting_members(&SettingId::new(School, 1))
+                .unwrap()
+                .len()
+        );
+        // The only workplace is the one the other person works at
+        assert!(context.is_setting_active(student, &SettingId::new(Workplace, 7)));
+        assert_eq!(
+            2,
+            context
+                .get_setting_members(&SettingId::new(Workplace, 7))
+                .unwrap()
+                .len()
+        );
+        assert_eq!(context.get_primary_setting_id(student, &Workplace), Some(7));
+        assert_eq!(context.get_primary_setting_id(student, &School), None);
+    }
+
+    #[test]
+    fn check_multiple_synth_files_are_merged() {
+        let mut context = setup();
+        let first_region = String::from(
+            "age,homeId,schoolId,workplaceId\n43,360930331020001,,7\n42,360930331020001,,",
+        );
+        let second_region = String::from(
+            "age,homeId,schoolId,workplaceId\n10,560000003000001,1,\n35,560000003000001,,7\n70,560000003000002,,",
+        );
+        let people = load_synth_population(
+            &mut context,
+            &[
+                persist_tmp_csv(&first_region),
+                persist_tmp_csv(&second_region),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(context.get_current_population(), 5);
+        // People are numbered consecutively across files in the order of the files
+        let ages: Vec<u8> = people
+            .iter()
+            .map(|&person| context.get_person_property(person, Age))
+            .collect();
+        assert_eq!(ages, vec![43, 42, 10, 35, 70]);
+
+        for (setting_id, expected_members) in [
+            (
+                &SettingId::new(Home, 360_930_331_020_001) as &dyn AnySettingId,
+                vec![people[0], people[1]],
+            ),
+            (
+                &SettingId::new(Home, 560_000_003_000_001),
+                vec![people[2], people[3]],
+            ),
+            (&SettingId::new(Home, 560_000_003_000_002), vec![people[4]]),
+            (
+                &SettingId::new(CensusTract, 36_093_033_102),
+                vec![people[0], people[1]],
+            ),
+            (
+                &SettingId::new(CensusTract, 56_000_000_300),
+                vec![people[2], people[3], people[4]],
+            ),
+            (&SettingId::new(School, 1), vec![people[2]]),
+            // Setting ids shared between files refer to the same setting
+            (&SettingId::new(Workplace, 7), vec![people[0], people[3]]),
+        ] {
+            let members = context.get_setting_members(setting_id).unwrap();
+            assert_eq!(members.len(), expected_members.len());
+            for person in expected_members {
+                assert!(members.contains(&person));
+            }
+        }
+    }
+
+    #[test]
+    fn check_multiple_synth_files_must_have_same_columns() {
+        let mut context = setup();
+        let first_region = String::from("age,homeId,schoolId,workplaceId\n43,360930331020001,,");
+        let second_region = String::from("age,homeId,workplaceId,schoolId\n42,560000003000001,,");
+        let second_path = persist_tmp_csv(&second_region);
+        let e = load_synth_population(
+            &mut context,
+            &[persist_tmp_csv(&first_region), second_path.clone()],
+        )
+        .err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert!(msg.starts_with(&format!(
+                    "The synthetic population file {} has columns",
+                    second_path.display()
+                )));
+            }
+            Some(ue) => panic!("Expected an error that the columns differ. Instead, got {ue:?}"),
+            None => panic!("Expected an error. Instead, the files were loaded."),
+        }
+    }
 }
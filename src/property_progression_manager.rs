@@ -10,7 +10,10 @@ use ixa::{
 };
 use serde::Deserialize;
 
-use crate::{parameters::ProgressionLibraryType, symptom_progression::SymptomData};
+use crate::{
+    parameters::{ContextParametersExt, ProgressionLibraryType},
+    symptom_progression::SymptomData,
+};
 
 use crate::natural_history_parameter_manager::{
     ContextNaturalHistoryParameterExt, NaturalHistoryParameterLibrary,
@@ -67,8 +70,9 @@ pub trait ContextPropertyProgressionExt: PluginContext {
                     .unwrap()
                     .as_ref();
                 if let Some((next_value, time_to_next)) = tcr.next(context, event) {
-                    let current_time = context.get_current_time();
-                    context.add_plan(current_time + time_to_next, move |ctx| {
+                    let next_time =
+                        context.get_current_time() + context.scale_duration(time_to_next);
+                    context.add_plan(next_time, move |ctx| {
                         increment_named_count("property progression");
                         ctx.set_person_property(event.person_id, property, next_value);
                     });
@@ -1,5 +1,5 @@
 pub mod rate_fn;
-pub use rate_fn::{InfectiousnessRateFn, ScaledRateFn};
+pub use rate_fn::{InfectiousnessRateFn, ScaledRateFn, TimeScaledRateFn};
 pub mod rate_fn_storage;
 pub use rate_fn_storage::{load_rate_fns, InfectiousnessRateExt, RateFn};
 
@@ -8,3 +8,6 @@ pub use constant_rate::ConstantRate;
 
 pub mod empirical_rate;
 pub use empirical_rate::EmpiricalRate;
+
+pub mod viral_load;
+pub use viral_load::DoseResponse;
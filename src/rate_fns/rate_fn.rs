@@ -80,12 +80,34 @@ impl<T: ?Sized + InfectiousnessRateFn> InfectiousnessRateFn for ScaledRateFn<'_,
     }
 }
 
+/// An infectiousness rate function compressed in time by a factor of `time_scale`, so that the
+/// same total infectiousness is delivered over an infectious period `time_scale` times shorter
+pub struct TimeScaledRateFn {
+    pub base: Box<dyn InfectiousnessRateFn>,
+    pub time_scale: f64,
+}
+
+impl InfectiousnessRateFn for TimeScaledRateFn {
+    fn rate(&self, t: f64) -> f64 {
+        self.base.rate(t * self.time_scale) * self.time_scale
+    }
+    fn cum_rate(&self, t: f64) -> f64 {
+        self.base.cum_rate(t * self.time_scale)
+    }
+    fn inverse_cum_rate(&self, events: f64) -> Option<f64> {
+        Some(self.base.inverse_cum_rate(events)? / self.time_scale)
+    }
+    fn infection_duration(&self) -> f64 {
+        self.base.infection_duration() / self.time_scale
+    }
+}
+
 #[cfg(test)]
 mod test {
     use ixa::assert_almost_eq;
 
     use crate::rate_fns::{
-        rate_fn::{InfectiousnessRateFn, ScaledRateFn},
+        rate_fn::{InfectiousnessRateFn, ScaledRateFn, TimeScaledRateFn},
         ConstantRate,
     };
 
@@ -158,4 +180,19 @@ mod test {
         };
         assert_almost_eq!(scaled_rate_fn.infection_duration(), 3.0, 0.0);
     }
+
+    #[test]
+    fn test_time_scaled_rate_fn_keeps_total_infectiousness() {
+        let time_scaled_rate_fn = TimeScaledRateFn {
+            base: Box::new(ConstantRate::new(2.0, 5.0).unwrap()),
+            time_scale: 10.0,
+        };
+        assert_almost_eq!(time_scaled_rate_fn.infection_duration(), 0.5, 0.0);
+        assert_almost_eq!(time_scaled_rate_fn.rate(0.25), 20.0, 0.0);
+        assert_almost_eq!(time_scaled_rate_fn.cum_rate(0.25), 5.0, 0.0);
+        // The total infectiousness over the shorter infectious period is unchanged
+        assert_almost_eq!(time_scaled_rate_fn.cum_rate(0.5), 10.0, 0.0);
+        assert_eq!(time_scaled_rate_fn.inverse_cum_rate(5.0), Some(0.25));
+        assert_eq!(time_scaled_rate_fn.inverse_cum_rate(11.0), None);
+    }
 }
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use ixa::{
     define_data_plugin, define_rng, Context, ContextRandomExt, IxaError, PersonId, PluginContext,
@@ -10,14 +10,22 @@ use crate::{
         ContextNaturalHistoryParameterExt, NaturalHistoryParameterLibrary,
     },
     parameters::{ContextParametersExt, Params, RateFnType},
+    symptom_progression::{get_assigned_symptom_category, is_severe},
 };
 
-use super::{rate_fn::InfectiousnessRateFn, ConstantRate, EmpiricalRate};
+use super::{
+    rate_fn::{InfectiousnessRateFn, TimeScaledRateFn},
+    viral_load::load_viral_load_rate_fn,
+    ConstantRate, EmpiricalRate,
+};
 
 define_rng!(InfectiousnessRng);
 
 struct RateFnContainer {
     rates: Vec<Box<dyn InfectiousnessRateFn>>,
+    // The group tags and sampling weight of each rate function, indexed like `rates`.
+    groups: Vec<Vec<String>>,
+    weights: Vec<f64>,
 }
 
 pub struct RateFn;
@@ -31,13 +39,64 @@ impl NaturalHistoryParameterLibrary for RateFn {
 define_data_plugin!(
     RateFnPlugin,
     RateFnContainer,
-    RateFnContainer { rates: Vec::new() }
+    RateFnContainer {
+        rates: Vec::new(),
+        groups: Vec::new(),
+        weights: Vec::new(),
+    }
 );
 
 pub trait InfectiousnessRateExt: PluginContext + ContextNaturalHistoryParameterExt {
     fn add_rate_fn(&mut self, dist: impl InfectiousnessRateFn + 'static) {
+        self.add_rate_fn_with_groups(dist, Vec::new(), 1.0);
+    }
+
+    /// Add a rate function to the library tagged with the given groups. The weight is the
+    /// relative probability of assigning this rate function when the library is sampled by weight.
+    fn add_rate_fn_with_groups(
+        &mut self,
+        dist: impl InfectiousnessRateFn + 'static,
+        groups: Vec<String>,
+        weight: f64,
+    ) {
         let container = self.get_data_mut(RateFnPlugin);
         container.rates.push(Box::new(dist));
+        container.groups.push(groups);
+        container.weights.push(weight);
+    }
+
+    /// Returns the group tags of the rate function with the given library id.
+    fn get_rate_fn_groups(&self, id: usize) -> &[String] {
+        &self.get_data(RateFnPlugin).groups[id]
+    }
+
+    /// Returns the library ids of all rate functions tagged with `group`.
+    fn get_rate_fn_ids_in_group(&self, group: &str) -> Vec<usize> {
+        self.get_data(RateFnPlugin)
+            .groups
+            .iter()
+            .enumerate()
+            .filter(|(_, groups)| groups.iter().any(|g| g == group))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Returns the expected total infectiousness (the expected number of infection attempts over
+    /// the whole infectious period) of a rate function drawn from the library, weighting each
+    /// rate function by its sampling weight. Returns 0.0 if the library is empty.
+    fn get_mean_total_infectiousness(&self) -> f64 {
+        let container = self.get_data(RateFnPlugin);
+        let total_weight: f64 = container.weights.iter().sum();
+        if total_weight <= 0.0 {
+            return 0.0;
+        }
+        container
+            .rates
+            .iter()
+            .zip(&container.weights)
+            .map(|(rate_fn, weight)| weight * rate_fn.cum_rate(rate_fn.infection_duration()))
+            .sum::<f64>()
+            / total_weight
     }
 
     fn get_person_rate_fn(&self, person_id: PersonId) -> &dyn InfectiousnessRateFn {
@@ -53,6 +112,7 @@ impl InfectiousnessRateExt for Context {}
 /// # Errors
 /// - If the parameters used to specify the rate functions are invalid
 /// - If the file specified in the parameters cannot be read and turned into `EmpiricalRate` objects
+/// - If the JSON library specified in the parameters is malformed or has invalid entries
 pub fn load_rate_fns(context: &mut Context) -> Result<(), IxaError> {
     let rate_of_infection = context.get_params().infectiousness_rate_fn.clone();
 
@@ -63,15 +123,85 @@ pub fn load_rate_fns(context: &mut Context) -> Result<(), IxaError> {
         RateFnType::EmpiricalFromFile { file, .. } => {
             add_rate_fns_from_file(context, file)?;
         }
+        RateFnType::ViralLoad {
+            trajectory_file,
+            dose_response,
+        } => {
+            context.add_rate_fn(load_viral_load_rate_fn(&trajectory_file, dose_response)?);
+        }
+        RateFnType::LibraryFromJson { file } => {
+            add_rate_fns_from_json(context, &file)?;
+            compress_rate_fns(context);
+            // Rate functions from a JSON library are assigned in proportion to their weights.
+            context.register_parameter_id_assigner(RateFn, |context, _person_id| {
+                let weights = &context.get_data(RateFnPlugin).weights;
+                context.sample_weighted(InfectiousnessRng, weights)
+            })?;
+            return Ok(());
+        }
     }
+    compress_rate_fns(context);
 
-    context.register_parameter_id_assigner(RateFn, |context, _person_id| {
-        let library_size = RateFn.library_size(context);
-        context.sample_range(InfectiousnessRng, 0..library_size)
-    })?;
+    let correlation = context
+        .get_params()
+        .infectiousness_severity_correlation
+        .unwrap_or(0.0);
+    if correlation > 0.0 {
+        let (low_pool, high_pool) = split_rate_fns_by_infectiousness(context);
+        context.register_parameter_id_assigner(RateFn, move |context, person_id| {
+            // With probability `correlation`, people draw from the pool that matches their symptom
+            // severity. Otherwise, they draw from the whole library, as when uncorrelated.
+            if context.sample_bool(InfectiousnessRng, correlation) {
+                let severe =
+                    get_assigned_symptom_category(context, person_id).is_some_and(is_severe);
+                let pool = if severe { &high_pool } else { &low_pool };
+                pool[context.sample_range(InfectiousnessRng, 0..pool.len())]
+            } else {
+                let library_size = RateFn.library_size(context);
+                context.sample_range(InfectiousnessRng, 0..library_size)
+            }
+        })?;
+    } else {
+        context.register_parameter_id_assigner(RateFn, |context, _person_id| {
+            let library_size = RateFn.library_size(context);
+            context.sample_range(InfectiousnessRng, 0..library_size)
+        })?;
+    }
     Ok(())
 }
 
+/// Compresses every rate function in the library in time by the `time_scale`, if specified
+fn compress_rate_fns(context: &mut Context) {
+    let Some(time_scale) = context.get_params().time_scale else {
+        return;
+    };
+    let container = context.get_data_mut(RateFnPlugin);
+    container.rates = std::mem::take(&mut container.rates)
+        .into_iter()
+        .map(|base| {
+            Box::new(TimeScaledRateFn { base, time_scale }) as Box<dyn InfectiousnessRateFn>
+        })
+        .collect();
+}
+
+/// Splits the ids of the rate function library into a less infectious and a more infectious pool
+/// based on each rate function's total infectiousness (the expected number of infections over the
+/// whole infectious period). Each pool contains half of the library, rounded up, so the pools share
+/// the median rate function when the library size is odd.
+fn split_rate_fns_by_infectiousness(context: &Context) -> (Vec<usize>, Vec<usize>) {
+    let rates = &context.get_data(RateFnPlugin).rates;
+    let mut ids = (0..rates.len()).collect::<Vec<usize>>();
+    ids.sort_by(|&a, &b| {
+        let total_a = rates[a].cum_rate(rates[a].infection_duration());
+        let total_b = rates[b].cum_rate(rates[b].infection_duration());
+        total_a.total_cmp(&total_b)
+    });
+    let half = ids.len() / 2;
+    let low_pool = ids[..ids.len() - half].to_vec();
+    let high_pool = ids[half..].to_vec();
+    (low_pool, high_pool)
+}
+
 #[derive(Deserialize)]
 pub struct EmpiricalRateFnRecord {
     id: u32,
@@ -79,6 +209,82 @@ pub struct EmpiricalRateFnRecord {
     value: f64,
 }
 
+/// A single rate function in a JSON rate function library.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RateFnSpec {
+    rate_fn: RateFnSpecType,
+    /// Tags used to look up subsets of the library, e.g., by strain or by population.
+    #[serde(default)]
+    groups: Vec<String>,
+    /// The relative probability of assigning this rate function to a newly infectious person.
+    #[serde(default = "default_rate_fn_weight")]
+    weight: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+enum RateFnSpecType {
+    Constant { rate: f64, duration: f64 },
+    Empirical { times: Vec<f64>, values: Vec<f64> },
+}
+
+fn default_rate_fn_weight() -> f64 {
+    1.0
+}
+
+fn add_rate_fns_from_json(context: &mut Context, file: &Path) -> Result<(), IxaError> {
+    let contents = std::fs::read_to_string(file).map_err(|e| {
+        IxaError::IxaError(format!(
+            "Could not read rate function library {}: {e}",
+            file.display()
+        ))
+    })?;
+    let specs: Vec<RateFnSpec> = serde_json::from_str(&contents).map_err(|e| {
+        IxaError::IxaError(format!(
+            "Could not parse rate function library {}: {e}",
+            file.display()
+        ))
+    })?;
+    if specs.is_empty() {
+        return Err(IxaError::IxaError(
+            "The rate function library must contain at least one rate function.".to_string(),
+        ));
+    }
+    if !specs.iter().any(|spec| spec.weight > 0.0) {
+        return Err(IxaError::IxaError(
+            "At least one rate function in the library must have a positive weight.".to_string(),
+        ));
+    }
+    // Check every weight and build every rate function before adding any of them, so an invalid
+    // library is not partially registered
+    let mut rate_fns: Vec<(Box<dyn InfectiousnessRateFn>, Vec<String>, f64)> = Vec::new();
+    for (i, spec) in specs.into_iter().enumerate() {
+        if !(spec.weight.is_finite() && spec.weight >= 0.0) {
+            return Err(IxaError::IxaError(format!(
+                "Rate function {i} in the library has weight {}, but weights must be finite and non-negative.",
+                spec.weight
+            )));
+        }
+        let rate_fn: Box<dyn InfectiousnessRateFn> = match spec.rate_fn {
+            RateFnSpecType::Constant { rate, duration } => {
+                Box::new(ConstantRate::new(rate, duration)?)
+            }
+            RateFnSpecType::Empirical { times, values } => {
+                Box::new(EmpiricalRate::new(times, values)?)
+            }
+        };
+        rate_fns.push((rate_fn, spec.groups, spec.weight));
+    }
+    let container = context.get_data_mut(RateFnPlugin);
+    for (rate_fn, groups, weight) in rate_fns {
+        container.rates.push(rate_fn);
+        container.groups.push(groups);
+        container.weights.push(weight);
+    }
+    Ok(())
+}
+
 fn add_rate_fns_from_file(context: &mut Context, file: PathBuf) -> Result<(), IxaError> {
     let Params {
         infectiousness_rate_fn,
@@ -134,7 +340,9 @@ fn add_rate_fns_from_file(context: &mut Context, file: PathBuf) -> Result<(), Ix
 
 #[cfg(test)]
 mod test {
-    use crate::parameters::{GlobalParams, Params};
+    use crate::parameters::{GlobalParams, Params, ProgressionLibraryType};
+    use crate::property_progression_manager::load_progressions;
+    use crate::symptom_progression::Symptoms;
 
     use super::*;
     use ixa::assert_almost_eq;
@@ -293,4 +501,226 @@ mod test {
             ),
         }
     }
+
+    #[test]
+    fn test_load_rate_functions_from_json_library() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(
+            &mut file,
+            br#"[
+                {"rate_fn": {"Constant": {"rate": 1.0, "duration": 5.0}}, "groups": ["mild"]},
+                {
+                    "rate_fn": {"Empirical": {"times": [0.0, 1.0, 2.0], "values": [1.0, 2.0, 3.0]}},
+                    "groups": ["severe", "strain_b"],
+                    "weight": 3.0
+                }
+            ]"#,
+        )
+        .unwrap();
+        let mut context = Context::new();
+        context.init_random(0);
+        let parameters = Params {
+            infectiousness_rate_fn: RateFnType::LibraryFromJson {
+                file: file.path().to_path_buf(),
+            },
+            ..Default::default()
+        };
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        load_rate_fns(&mut context).unwrap();
+        let rate_fns = context.get_data(RateFnPlugin);
+        assert_eq!(rate_fns.rates.len(), 2);
+        assert_eq!(rate_fns.weights, vec![1.0, 3.0]);
+        assert_eq!(context.get_rate_fn_groups(0), ["mild".to_string()]);
+        assert_eq!(
+            context.get_rate_fn_groups(1),
+            ["severe".to_string(), "strain_b".to_string()]
+        );
+        assert_eq!(context.get_rate_fn_ids_in_group("severe"), vec![1]);
+        assert_eq!(context.get_rate_fn_ids_in_group("mild"), vec![0]);
+        assert!(context.get_rate_fn_ids_in_group("strain_a").is_empty());
+        // Check that the entries were turned into the right rate functions
+        assert_almost_eq!(
+            context.get_data(RateFnPlugin).rates[0].infection_duration(),
+            5.0,
+            0.0
+        );
+        assert_almost_eq!(
+            context.get_data(RateFnPlugin).rates[1].cum_rate(2.0),
+            4.0,
+            0.0
+        );
+        // The assigner samples rate functions in proportion to their weights
+        let mut num_second = 0;
+        for _ in 0..1000 {
+            let person = context.add_person(()).unwrap();
+            if context.get_parameter_id(RateFn, person) == 1 {
+                num_second += 1;
+            }
+        }
+        assert!((700..800).contains(&num_second));
+    }
+
+    #[test]
+    fn test_load_rate_functions_from_json_library_invalid_weight() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(
+            &mut file,
+            br#"[
+                {"rate_fn": {"Constant": {"rate": 1.0, "duration": 5.0}}, "groups": ["mild"]},
+                {"rate_fn": {"Constant": {"rate": 2.0, "duration": 5.0}}, "groups": [], "weight": -1.0}
+            ]"#,
+        )
+        .unwrap();
+        let mut context = Context::new();
+        let parameters = Params {
+            infectiousness_rate_fn: RateFnType::LibraryFromJson {
+                file: file.path().to_path_buf(),
+            },
+            ..Default::default()
+        };
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        let e = load_rate_fns(&mut context).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "Rate function 1 in the library has weight -1, but weights must be finite and non-negative."
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that the weight is negative. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!(
+                "Expected an error. Instead, reading the rate functions passed with no errors."
+            ),
+        }
+        // The valid rate function before the invalid one is not registered either
+        assert!(context.get_data(RateFnPlugin).rates.is_empty());
+        assert!(context.get_rate_fn_ids_in_group("mild").is_empty());
+    }
+
+    #[test]
+    fn test_load_rate_functions_from_json_library_invalid_rate_fn() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(
+            &mut file,
+            br#"[
+                {"rate_fn": {"Constant": {"rate": 1.0, "duration": 5.0}}, "groups": ["mild"]},
+                {"rate_fn": {"Constant": {"rate": -1.0, "duration": 5.0}}, "groups": ["severe"]}
+            ]"#,
+        )
+        .unwrap();
+        let mut context = Context::new();
+        let parameters = Params {
+            infectiousness_rate_fn: RateFnType::LibraryFromJson {
+                file: file.path().to_path_buf(),
+            },
+            ..Default::default()
+        };
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        let e = load_rate_fns(&mut context).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(msg, "The rate of infection must be non-negative.");
+            }
+            Some(ue) => panic!(
+                "Expected an error that the rate is negative. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!(
+                "Expected an error. Instead, reading the rate functions passed with no errors."
+            ),
+        }
+        // The valid rate function before the invalid one is not registered either
+        assert!(context.get_data(RateFnPlugin).rates.is_empty());
+        assert!(context.get_rate_fn_ids_in_group("mild").is_empty());
+    }
+
+    #[test]
+    fn test_load_rate_functions_from_json_library_unknown_field() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(
+            &mut file,
+            br#"[{"rate_fn": {"Constant": {"rate": 1.0, "duration": 5.0}}, "group": ["mild"]}]"#,
+        )
+        .unwrap();
+        let mut context = Context::new();
+        let parameters = Params {
+            infectiousness_rate_fn: RateFnType::LibraryFromJson {
+                file: file.path().to_path_buf(),
+            },
+            ..Default::default()
+        };
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        let e = load_rate_fns(&mut context).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert!(msg.starts_with("Could not parse rate function library"));
+                assert!(msg.contains("unknown field `group`"));
+            }
+            Some(ue) => panic!(
+                "Expected an error that the library could not be parsed. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!(
+                "Expected an error. Instead, reading the rate functions passed with no errors."
+            ),
+        }
+    }
+
+    #[test]
+    fn test_infectiousness_severity_correlation_one() {
+        let mut context = Context::new();
+        context.init_random(0);
+        let parameters = Params {
+            infectiousness_rate_fn: RateFnType::Constant {
+                rate: 4.0,
+                duration: 5.0,
+            },
+            symptom_progression_library: Some(ProgressionLibraryType::EmpiricalFromFile {
+                file: PathBuf::from("./input/library_symptom_parameters.csv"),
+            }),
+            infectiousness_severity_correlation: Some(1.0),
+            ..Default::default()
+        };
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        // Add less infectious rate functions to the library ahead of the one in the parameters
+        for rate in [1.0, 2.0, 3.0] {
+            context.add_rate_fn(ConstantRate::new(rate, 5.0).unwrap());
+        }
+        load_rate_fns(&mut context).unwrap();
+        let library = context.get_params().symptom_progression_library.clone();
+        load_progressions(&mut context, library).unwrap();
+        context
+            .register_parameter_id_assigner(Symptoms, |context, _person_id| {
+                let library_size = Symptoms.library_size(context);
+                context.sample_range(InfectiousnessRng, 0..library_size)
+            })
+            .unwrap();
+
+        let mut num_severe = 0;
+        for _ in 0..1000 {
+            let person = context.add_person(()).unwrap();
+            let rate = context.get_person_rate_fn(person).rate(0.0);
+            if get_assigned_symptom_category(&context, person).is_some_and(is_severe) {
+                num_severe += 1;
+                // Severe people always draw from the two most infectious rate functions
+                assert!(rate >= 3.0);
+            } else {
+                assert!(rate <= 2.0);
+            }
+        }
+        assert!(num_severe > 0);
+    }
 }
@@ -0,0 +1,206 @@
+use std::path::Path;
+
+use ixa::IxaError;
+use serde::{Deserialize, Serialize};
+
+use super::EmpiricalRate;
+
+/// A dose-response function that maps a within-host viral load to an infectiousness hazard
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DoseResponse {
+    /// A saturating Hill function, `max_rate * V^n / (V^n + K^n)` for viral load `V`, where `K` is
+    /// the `half_maximal_load` and `n` is the `hill_coefficient`
+    Hill {
+        max_rate: f64,
+        half_maximal_load: f64,
+        hill_coefficient: f64,
+    },
+    /// A hazard that increases linearly with the log10 viral load above a threshold,
+    /// `slope * (log10(V) - log10_threshold)`, and is zero below it
+    LogLinear { slope: f64, log10_threshold: f64 },
+}
+
+impl DoseResponse {
+    /// Checks that the parameters of the dose-response function are valid.
+    /// # Errors
+    /// - If any of the Hill parameters are not positive and finite
+    /// - If the log-linear slope is not positive and finite or the threshold is not finite
+    pub fn validate(&self) -> Result<(), IxaError> {
+        let valid = match *self {
+            DoseResponse::Hill {
+                max_rate,
+                half_maximal_load,
+                hill_coefficient,
+            } => [max_rate, half_maximal_load, hill_coefficient]
+                .iter()
+                .all(|&parameter| parameter > 0.0 && parameter.is_finite()),
+            DoseResponse::LogLinear {
+                slope,
+                log10_threshold,
+            } => slope > 0.0 && slope.is_finite() && log10_threshold.is_finite(),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(IxaError::IxaError(format!(
+                "The dose-response function parameters are invalid: {self:?}."
+            )))
+        }
+    }
+
+    /// The infectiousness hazard at the given viral load
+    #[must_use]
+    pub fn hazard(&self, viral_load: f64) -> f64 {
+        match *self {
+            DoseResponse::Hill {
+                max_rate,
+                half_maximal_load,
+                hill_coefficient,
+            } => {
+                let scaled_load = viral_load.powf(hill_coefficient);
+                max_rate * scaled_load / (scaled_load + half_maximal_load.powf(hill_coefficient))
+            }
+            DoseResponse::LogLinear {
+                slope,
+                log10_threshold,
+            } => {
+                if viral_load <= 0.0 {
+                    return 0.0;
+                }
+                (slope * (viral_load.log10() - log10_threshold)).max(0.0)
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ViralLoadRecord {
+    time: f64,
+    viral_load: f64,
+}
+
+/// Maps a within-host viral load trajectory through a dose-response function to an empirical
+/// rate function whose hazard at each trajectory time is the mapped viral load.
+/// # Errors
+/// - If any viral load is negative or not finite
+/// - If the times do not make a valid `EmpiricalRate`
+pub fn viral_load_rate_fn(
+    times: Vec<f64>,
+    viral_loads: &[f64],
+    dose_response: DoseResponse,
+) -> Result<EmpiricalRate, IxaError> {
+    if viral_loads
+        .iter()
+        .any(|&viral_load| !(viral_load >= 0.0 && viral_load.is_finite()))
+    {
+        return Err(IxaError::IxaError(
+            "Viral loads must be non-negative and finite.".to_string(),
+        ));
+    }
+    let hazards = viral_loads
+        .iter()
+        .map(|&viral_load| dose_response.hazard(viral_load))
+        .collect();
+    EmpiricalRate::new(times, hazards)
+}
+
+/// Reads a viral load trajectory with `time` and `viral_load` columns and maps it through the
+/// dose-response function to an empirical rate function.
+/// # Errors
+/// - If the file cannot be read or has no rows
+/// - If the trajectory cannot be mapped to a valid rate function
+pub fn load_viral_load_rate_fn(
+    trajectory_file: &Path,
+    dose_response: DoseResponse,
+) -> Result<EmpiricalRate, IxaError> {
+    let mut reader = csv::Reader::from_path(trajectory_file)?;
+    let mut times = Vec::new();
+    let mut viral_loads = Vec::new();
+    for record in reader.deserialize() {
+        let record: ViralLoadRecord = record?;
+        times.push(record.time);
+        viral_loads.push(record.viral_load);
+    }
+    if times.is_empty() {
+        return Err(IxaError::IxaError(format!(
+            "The viral load trajectory {} has no rows.",
+            trajectory_file.display()
+        )));
+    }
+    viral_load_rate_fn(times, &viral_loads, dose_response)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use ixa::{assert_almost_eq, IxaError};
+
+    use super::{load_viral_load_rate_fn, viral_load_rate_fn, DoseResponse};
+    use crate::rate_fns::InfectiousnessRateFn;
+
+    #[test]
+    fn test_viral_load_inverse_cum_rate_integrates_mapped_hazard() {
+        let dose_response = DoseResponse::Hill {
+            max_rate: 2.0,
+            half_maximal_load: 1e4,
+            hill_coefficient: 1.0,
+        };
+        let rate_fn = load_viral_load_rate_fn(
+            &PathBuf::from("./tests/data/viral_load_trajectory.csv"),
+            dose_response,
+        )
+        .unwrap();
+        // The trajectory is 0, 1e2, 1e6, 1e4, and 0 at times 0 to 4
+        let hazards: Vec<f64> = [0.0, 1e2, 1e6, 1e4, 0.0]
+            .iter()
+            .map(|&viral_load| dose_response.hazard(viral_load))
+            .collect();
+        assert_almost_eq!(hazards[3], 1.0, 1e-12);
+        let mut cum_hazard = 0.0;
+        for (t, pair) in hazards.windows(2).enumerate() {
+            cum_hazard += (pair[0] + pair[1]) / 2.0;
+            let end = f64::from(u8::try_from(t).unwrap() + 1);
+            assert_almost_eq!(rate_fn.rate(end), pair[1], 1e-12);
+            assert_almost_eq!(rate_fn.cum_rate(end), cum_hazard, 1e-12);
+            assert_almost_eq!(rate_fn.inverse_cum_rate(cum_hazard).unwrap(), end, 1e-9);
+        }
+        assert_almost_eq!(rate_fn.infection_duration(), 4.0, 0.0);
+        assert!(rate_fn.inverse_cum_rate(cum_hazard + 0.1).is_none());
+    }
+
+    #[test]
+    fn test_log_linear_hazard_is_zero_below_threshold() {
+        let dose_response = DoseResponse::LogLinear {
+            slope: 0.5,
+            log10_threshold: 3.0,
+        };
+        assert_almost_eq!(dose_response.hazard(0.0), 0.0, 0.0);
+        assert_almost_eq!(dose_response.hazard(1e2), 0.0, 0.0);
+        assert_almost_eq!(dose_response.hazard(1e5), 1.0, 1e-12);
+    }
+
+    #[test]
+    fn test_viral_load_rate_fn_errors() {
+        let dose_response = DoseResponse::LogLinear {
+            slope: 0.5,
+            log10_threshold: 3.0,
+        };
+        let e = viral_load_rate_fn(vec![0.0, 1.0], &[1.0, -1.0], dose_response).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(msg, "Viral loads must be non-negative and finite.");
+            }
+            Some(ue) => panic!("Expected an error about the viral loads. Instead got {ue:?}"),
+            None => panic!("Expected an error. Instead, the rate function was created."),
+        }
+        let e = DoseResponse::Hill {
+            max_rate: 1.0,
+            half_maximal_load: 0.0,
+            hill_coefficient: 1.0,
+        }
+        .validate()
+        .err();
+        assert!(matches!(e, Some(IxaError::IxaError(_))));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{
+    hospitalizations::Hospitalized,
+    reports::{add_periodic_report_plan, report_sink::ContextReportSinkExt},
+};
+use ixa::{define_data_plugin, define_report, Context, IxaError, PersonPropertyChangeEvent};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct HospitalizationReport {
+    t: f64,
+    hospitalized: u32,
+    cumulative_admissions: u32,
+    cumulative_discharges: u32,
+}
+
+define_report!(HospitalizationReport);
+
+#[derive(Default)]
+struct HospitalizationReportContainer {
+    cumulative_admissions: u32,
+    cumulative_discharges: u32,
+}
+
+define_data_plugin!(
+    HospitalizationReportPlugin,
+    HospitalizationReportContainer,
+    HospitalizationReportContainer::default()
+);
+
+fn update_hospitalization_counts(
+    context: &mut Context,
+    event: PersonPropertyChangeEvent<Hospitalized>,
+) {
+    let container = context.get_data_mut(HospitalizationReportPlugin);
+    if event.current {
+        container.cumulative_admissions += 1;
+    } else if event.previous {
+        container.cumulative_discharges += 1;
+    }
+}
+
+fn send_hospitalization_counts(context: &mut Context) {
+    let container = context.get_data(HospitalizationReportPlugin);
+    let cumulative_admissions = container.cumulative_admissions;
+    let cumulative_discharges = container.cumulative_discharges;
+    context.send_report_to_sink(HospitalizationReport {
+        t: context.get_current_time(),
+        hospitalized: cumulative_admissions - cumulative_discharges,
+        cumulative_admissions,
+        cumulative_discharges,
+    });
+}
+
+/// Record the number of people currently in the hospital every `period`, alongside the number of
+/// admissions and discharges since the start of the simulation.
+/// # Errors
+///
+/// Will return `IxaError` if the report cannot be added
+pub fn init(context: &mut Context, file_name: &str, period: f64) -> Result<(), IxaError> {
+    context.add_report_with_sink::<HospitalizationReport>(file_name)?;
+
+    context.subscribe_to_event::<PersonPropertyChangeEvent<Hospitalized>>(|context, event| {
+        update_hospitalization_counts(context, event);
+    });
+    add_periodic_report_plan(context, period, move |context: &mut Context| {
+        send_hospitalization_counts(context);
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        hospitalizations::Hospitalized,
+        parameters::{GlobalParams, Params},
+        reports::{
+            report_sink::{ContextReportSinkExt, ReportSink},
+            ReportParams,
+        },
+    };
+    use ixa::{assert_almost_eq, Context, ContextGlobalPropertiesExt, ContextPeopleExt};
+
+    use super::HospitalizationReport;
+
+    #[test]
+    fn test_hospital_census_tracks_admissions_and_discharges() {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 4.0,
+                    hospitalization_report: Some(ReportParams {
+                        write: true,
+                        filename: Some("hospitalization.csv".to_string()),
+                        period: Some(1.0),
+                        cumulative: false,
+                        by_setting_type: false,
+                        age_groups: None,
+                        compress: None,
+                        include_cumulative: false,
+                    }),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink(
+            "hospitalization.csv",
+            ReportSink::Memory(Rc::clone(&buffer)),
+        );
+        crate::reports::init(&mut context).unwrap();
+
+        // Three people are admitted in the first day, and two of them are discharged in the third
+        let people: Vec<_> = (0..3).map(|_| context.add_person(()).unwrap()).collect();
+        for &person in &people {
+            context.add_plan(0.5, move |context| {
+                context.set_person_property(person, Hospitalized, true);
+            });
+        }
+        for &person in &people[..2] {
+            context.add_plan(2.5, move |context| {
+                context.set_person_property(person, Hospitalized, false);
+            });
+        }
+        context.execute();
+        std::mem::drop(context);
+
+        let buffer = buffer.borrow();
+        let mut reader = csv::Reader::from_reader(buffer.as_slice());
+        let records: Vec<HospitalizationReport> =
+            reader.deserialize().map(Result::unwrap).collect();
+        // Each row is the time, census, cumulative admissions, and cumulative discharges
+        let expected = [
+            (0.0, 0, 0, 0),
+            (1.0, 3, 3, 0),
+            (2.0, 3, 3, 0),
+            (3.0, 1, 3, 2),
+            (4.0, 1, 3, 2),
+        ];
+        assert_eq!(records.len(), expected.len());
+        for (record, (t, hospitalized, admissions, discharges)) in records.iter().zip(expected) {
+            assert_almost_eq!(record.t, t, 0.0);
+            assert_eq!(record.hospitalized, hospitalized);
+            assert_eq!(record.cumulative_admissions, admissions);
+            assert_eq!(record.cumulative_discharges, discharges);
+        }
+    }
+}
@@ -1,14 +1,18 @@
 use crate::{
     hospitalizations::Hospitalized,
-    infectiousness_manager::{InfectionStatus, InfectionStatusValue},
+    infectiousness_manager::{
+        InfectionData, InfectionDataValue, InfectionStatus, InfectionStatusValue,
+    },
     population_loader::Age,
+    reports::{add_periodic_report_plan, append_to_filename, report_sink::ContextReportSinkExt},
+    settings::ContextSettingExt,
     symptom_progression::{SymptomValue, Symptoms},
 };
 use ixa::{
-    define_data_plugin, define_report, report::ContextReportExt, Context, ContextPeopleExt,
-    ExecutionPhase, HashMap, HashSet, HashSetExt, IxaError, PersonPropertyChangeEvent,
+    define_data_plugin, define_report, Context, ContextPeopleExt, HashMap, HashSet, HashSetExt,
+    IxaError, PersonPropertyChangeEvent,
 };
-use serde::{Deserialize, Serialize};
+use serde::{ser::SerializeSeq, Deserialize, Serialize, Serializer};
 use std::cell::RefCell;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
@@ -21,10 +25,91 @@ struct PersonPropertyIncidenceReport {
 
 define_report!(PersonPropertyIncidenceReport);
 
+/// An age group of the incidence report, which starts at `min` and ends at the next group's `min`
+/// (exclusive)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct AgeGroup {
+    pub min: u8,
+}
+
+/// Checks that the age groups start at 0 and that their minimum ages are strictly increasing, so
+/// every age belongs to exactly one age group.
+/// # Errors
+/// - If there are no age groups
+/// - If the first age group does not start at 0
+/// - If the minimum ages are not strictly increasing
+pub fn validate_age_groups(age_groups: &[AgeGroup]) -> Result<(), IxaError> {
+    if age_groups.is_empty() {
+        return Err(IxaError::IxaError(
+            "The incidence report age groups must not be empty.".to_string(),
+        ));
+    }
+    if age_groups[0].min != 0 {
+        return Err(IxaError::IxaError(
+            "The first incidence report age group must start at 0.".to_string(),
+        ));
+    }
+    if age_groups
+        .windows(2)
+        .any(|groups| groups[0].min >= groups[1].min)
+    {
+        return Err(IxaError::IxaError(
+            "The minimum ages of the incidence report age groups must be strictly increasing."
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// The column name of each age group: `age_<min>_<max>` with the inclusive maximum age, or
+// `age_<min>_plus` for the last age group
+fn age_group_columns(age_groups: &[AgeGroup]) -> Vec<String> {
+    age_groups
+        .iter()
+        .enumerate()
+        .map(|(i, group)| match age_groups.get(i + 1) {
+            Some(next_group) => format!("age_{}_{}", group.min, next_group.min - 1),
+            None => format!("age_{}_plus", group.min),
+        })
+        .collect()
+}
+
+// A row of the report of new infections by the setting type in which they were acquired and by
+// age group, which is written alongside the incidence report. The columns are only known once the
+// setting types are registered and the age groups are read from the parameters, so rows are
+// written as plain records, with the header sent as the first row.
+#[derive(Clone, Debug)]
+enum InfectionIncidenceReport {
+    Header(Vec<String>),
+    // The number of new infections in each column after `t`
+    Infections { t: f64, counts: Vec<u32> },
+}
+
+impl Serialize for InfectionIncidenceReport {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            InfectionIncidenceReport::Header(columns) => columns.serialize(serializer),
+            InfectionIncidenceReport::Infections { t, counts } => {
+                let mut row = serializer.serialize_seq(Some(counts.len() + 1))?;
+                row.serialize_element(t)?;
+                for count in counts {
+                    row.serialize_element(count)?;
+                }
+                row.end()
+            }
+        }
+    }
+}
+
+define_report!(InfectionIncidenceReport);
+
 struct PropertyReportDataContainer {
     infection_status_change: HashMap<(u8, InfectionStatusValue), u32>,
     symptom_onset: HashMap<(u8, SymptomValue), u32>,
     hospitalization: HashMap<u8, u32>,
+    // New infections by the setting type in which they were acquired, or "seed" for infections
+    // without an infector. Only tracked when the report is broken down by setting type.
+    setting_infections: HashMap<String, u32>,
 }
 
 define_data_plugin!(
@@ -34,9 +119,13 @@ define_data_plugin!(
         infection_status_change: HashMap::default(),
         symptom_onset: HashMap::default(),
         hospitalization: HashMap::default(),
+        setting_infections: HashMap::default(),
     }
 );
 
+// The setting type recorded for infections that were not acquired in a setting
+const SEED_SETTING_TYPE: &str = "seed";
+
 fn update_infection_incidence(
     context: &mut Context,
     event: PersonPropertyChangeEvent<InfectionStatus>,
@@ -54,6 +143,28 @@ fn update_infection_incidence(
     }
 }
 
+fn update_setting_infection_incidence(
+    context: &mut Context,
+    event: PersonPropertyChangeEvent<InfectionStatus>,
+) {
+    if event.current != InfectionStatusValue::Infectious {
+        return;
+    }
+    let InfectionDataValue::Infectious {
+        infection_setting_type,
+        ..
+    } = context.get_person_property(event.person_id, InfectionData)
+    else {
+        return;
+    };
+    let setting_type = infection_setting_type.unwrap_or(SEED_SETTING_TYPE);
+    *context
+        .get_data_mut(PropertyReportDataPlugin)
+        .setting_infections
+        .entry(setting_type.to_string())
+        .or_insert(0) += 1;
+}
+
 fn update_symptoms_incidence(context: &mut Context, event: PersonPropertyChangeEvent<Symptoms>) {
     let age = context.get_person_property(event.person_id, Age);
     let report_container_mut = context.get_data_mut(PropertyReportDataPlugin);
@@ -101,15 +212,19 @@ fn reset_incidence_map(context: &mut Context) {
         .hospitalization
         .values_mut()
         .for_each(|v| *v = 0);
+    report_container
+        .setting_infections
+        .values_mut()
+        .for_each(|v| *v = 0);
 }
 
-fn send_incidence_counts(context: &mut Context) {
+fn send_incidence_counts(context: &mut Context, cumulative: bool) {
     let report_container = context.get_data(PropertyReportDataPlugin);
     let t_upper = context.get_current_time();
 
     // Infection status
     for ((age, infection_status), count) in &report_container.infection_status_change {
-        context.send_report(PersonPropertyIncidenceReport {
+        context.send_report_to_sink(PersonPropertyIncidenceReport {
             t_upper,
             age: *age,
             event: format!("{infection_status:?}"),
@@ -118,7 +233,7 @@ fn send_incidence_counts(context: &mut Context) {
     }
     // Symptoms
     for ((age, symptoms), count) in &report_container.symptom_onset {
-        context.send_report(PersonPropertyIncidenceReport {
+        context.send_report_to_sink(PersonPropertyIncidenceReport {
             t_upper,
             age: *age,
             event: format!("{symptoms:?}"),
@@ -128,25 +243,98 @@ fn send_incidence_counts(context: &mut Context) {
     // Hospitalization
     for (age, count) in &report_container.hospitalization {
         // We only ever record entering the hospital, we print a string to avoid an ambiguous boolean value
-        context.send_report(PersonPropertyIncidenceReport {
+        context.send_report_to_sink(PersonPropertyIncidenceReport {
             t_upper,
             age: *age,
             event: "Hospitalized".to_string(),
             count: *count,
         });
     }
-    reset_incidence_map(context);
+    // Cumulative counts keep accumulating across periods
+    if !cumulative {
+        reset_incidence_map(context);
+    }
 }
 
+fn send_infection_counts(
+    context: &Context,
+    setting_types: Option<&[String]>,
+    age_groups: Option<&[AgeGroup]>,
+) {
+    let report_container = context.get_data(PropertyReportDataPlugin);
+    let mut counts = Vec::new();
+    if let Some(setting_types) = setting_types {
+        counts.extend(
+            setting_types
+                .iter()
+                .map(|setting_type| report_container.setting_infections[setting_type]),
+        );
+    }
+    if let Some(age_groups) = age_groups {
+        let mut age_group_counts = vec![0; age_groups.len()];
+        for ((age, infection_status), count) in &report_container.infection_status_change {
+            if *infection_status == InfectionStatusValue::Infectious {
+                // The first age group starts at 0, so every age is in an age group
+                let index = age_groups
+                    .iter()
+                    .rposition(|group| group.min <= *age)
+                    .unwrap();
+                age_group_counts[index] += count;
+            }
+        }
+        counts.extend(age_group_counts);
+    }
+    context.send_report_to_sink(InfectionIncidenceReport::Infections {
+        t: context.get_current_time(),
+        counts,
+    });
+}
+
+/// If `cumulative` is true, the report records the running total of each event since the start
+/// of the simulation instead of the count over each period. If `by_setting_type` is true, new
+/// infections are also counted by the setting type in which they were acquired, with one column
+/// per setting type and infections without an infector counted in a `seed` column. If
+/// `age_groups` are given, new infections are also counted in each age group, with one column per
+/// age group. These counts are written alongside the report to `file_name` with `_infections`
+/// appended to its stem.
+///
 /// # Errors
 ///
-/// Will return `IxaError` if the report cannot be added
+/// Will return `IxaError` if the report cannot be added or the age groups are invalid
 ///
 /// # Panics
 ///
 /// Will panic if an age group cannot be parsed from the tabulated string
-pub fn init(context: &mut Context, file_name: &str, period: f64) -> Result<(), IxaError> {
-    context.add_report::<PersonPropertyIncidenceReport>(file_name)?;
+pub fn init(
+    context: &mut Context,
+    file_name: &str,
+    period: f64,
+    cumulative: bool,
+    by_setting_type: bool,
+    age_groups: Option<&[AgeGroup]>,
+) -> Result<(), IxaError> {
+    if let Some(age_groups) = age_groups {
+        validate_age_groups(age_groups)?;
+    }
+    context.add_report_with_sink::<PersonPropertyIncidenceReport>(file_name)?;
+
+    let mut setting_types = context.get_setting_category_names();
+    setting_types.push(SEED_SETTING_TYPE.to_string());
+    if by_setting_type || age_groups.is_some() {
+        context.add_report_with_sink::<InfectionIncidenceReport>(&append_to_filename(
+            file_name,
+            "_infections",
+        ))?;
+        let mut header = vec!["t".to_string()];
+        if by_setting_type {
+            header.extend(setting_types.iter().cloned());
+        }
+        if let Some(age_groups) = age_groups {
+            header.extend(age_group_columns(age_groups));
+        }
+        context.send_report_to_sink(InfectionIncidenceReport::Header(header));
+    }
+    let age_groups = age_groups.map(<[AgeGroup]>::to_vec);
 
     let tabulator = (Age,);
     let ages: RefCell<HashSet<u8>> = RefCell::new(HashSet::new());
@@ -155,6 +343,13 @@ pub fn init(context: &mut Context, file_name: &str, period: f64) -> Result<(), I
     });
 
     let report_container = context.get_data_mut(PropertyReportDataPlugin);
+    if by_setting_type {
+        for setting_type in &setting_types {
+            report_container
+                .setting_infections
+                .insert(setting_type.clone(), 0);
+        }
+    }
 
     for age in ages.take() {
         let inf_vec = [
@@ -184,6 +379,13 @@ pub fn init(context: &mut Context, file_name: &str, period: f64) -> Result<(), I
     context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionStatus>>(|context, event| {
         update_infection_incidence(context, event);
     });
+    if by_setting_type {
+        context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionStatus>>(
+            |context, event| {
+                update_setting_infection_incidence(context, event);
+            },
+        );
+    }
     context.subscribe_to_event::<PersonPropertyChangeEvent<Symptoms>>(|context, event| {
         update_symptoms_incidence(context, event);
     });
@@ -191,30 +393,43 @@ pub fn init(context: &mut Context, file_name: &str, period: f64) -> Result<(), I
         update_hospitalization_incidence(context, event);
     });
 
-    context.add_periodic_plan_with_phase(
-        period,
-        move |context: &mut Context| {
-            send_incidence_counts(context);
-        },
-        ExecutionPhase::Last,
-    );
+    add_periodic_report_plan(context, period, move |context: &mut Context| {
+        // The counts are reset when the incidence report is sent, so they are sent first
+        if by_setting_type || age_groups.is_some() {
+            send_infection_counts(
+                context,
+                by_setting_type.then_some(setting_types.as_slice()),
+                age_groups.as_deref(),
+            );
+        }
+        send_incidence_counts(context, cumulative);
+    });
 
     Ok(())
 }
 
 #[cfg(test)]
 mod test {
+    use super::{validate_age_groups, AgeGroup};
     use crate::{
         infectiousness_manager::InfectionContextExt,
-        parameters::{ContextParametersExt, GlobalParams, Params},
+        parameters::{ContextParametersExt, GlobalParams, Params, RateFnType},
         rate_fns::load_rate_fns,
-        reports::ReportParams,
+        reports::{
+            report_sink::{ContextReportSinkExt, ReportSink},
+            ReportParams,
+        },
+        settings::{
+            ContextSettingExt, Home, ItineraryEntry, SettingCategory, SettingId, SettingProperties,
+            Workplace,
+        },
         Age,
     };
     use ixa::{
         Context, ContextGlobalPropertiesExt, ContextPeopleExt, ContextRandomExt, ContextReportExt,
+        HashMap, IxaError,
     };
-    use std::path::PathBuf;
+    use std::{cell::RefCell, path::PathBuf, rc::Rc};
     use tempfile::tempdir;
 
     fn setup_context_with_report(incidence_report: ReportParams) -> Context {
@@ -241,6 +456,11 @@ mod test {
             write: true,
             filename: Some("output.csv".to_string()),
             period: Some(2.0),
+            cumulative: false,
+            by_setting_type: false,
+            age_groups: None,
+            compress: None,
+            include_cumulative: false,
         });
 
         let temp_dir = tempdir().unwrap();
@@ -300,6 +520,11 @@ mod test {
             write: true,
             filename: Some("output.csv".to_string()),
             period: Some(2.0),
+            cumulative: false,
+            by_setting_type: false,
+            age_groups: None,
+            compress: None,
+            include_cumulative: false,
         });
 
         let temp_dir = tempdir().unwrap();
@@ -354,4 +579,319 @@ mod test {
         // 2 ages at first timepoint, 3 ages at second timepoint for only one event (7x2x2 + 1 = 29)
         assert_eq!(line_count, 29);
     }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_incidence_report_memory_sink() {
+        let mut context = setup_context_with_report(ReportParams {
+            write: true,
+            filename: Some("output.csv".to_string()),
+            period: Some(2.0),
+            cumulative: false,
+            by_setting_type: false,
+            age_groups: None,
+            compress: None,
+            include_cumulative: false,
+        });
+
+        // Direct the report to an in-memory buffer instead of a file
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink("output.csv", ReportSink::Memory(Rc::clone(&buffer)));
+
+        let source = context.add_person((Age, 42)).unwrap();
+        let target = context.add_person((Age, 43)).unwrap();
+        let infection_time = 1.0;
+
+        context.infect_person(source, None, None, None);
+        crate::reports::init(&mut context).unwrap();
+
+        context.add_plan(infection_time, move |context| {
+            context.infect_person(target, Some(source), Some("test_setting"), Some(1));
+        });
+        context.execute();
+        std::mem::drop(context);
+
+        let output = buffer.borrow();
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        let mut line_count = 0;
+        for result in reader.deserialize() {
+            let record: crate::reports::incidence_report::PersonPropertyIncidenceReport =
+                result.unwrap();
+            line_count += 1;
+            if record.t_upper == 2.0 && record.event == *"Infectious" && record.age == 43 {
+                assert_eq!(record.count, 1);
+            } else {
+                assert_eq!(record.count, 0);
+            }
+        }
+
+        // Same output as when writing to a file: 7 event types x 2 time points x 2 ages
+        assert_eq!(line_count, 28);
+    }
+
+    fn run_incidence_report_in_memory(
+        cumulative: bool,
+    ) -> Vec<crate::reports::incidence_report::PersonPropertyIncidenceReport> {
+        let mut context = setup_context_with_report(ReportParams {
+            write: true,
+            filename: Some("output.csv".to_string()),
+            period: Some(2.0),
+            cumulative,
+            by_setting_type: false,
+            age_groups: None,
+            compress: None,
+            include_cumulative: false,
+        });
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink("output.csv", ReportSink::Memory(Rc::clone(&buffer)));
+
+        let source = context.add_person((Age, 42)).unwrap();
+        let first_target = context.add_person((Age, 43)).unwrap();
+        let second_target = context.add_person((Age, 43)).unwrap();
+
+        context.infect_person(source, None, None, None);
+        crate::reports::init(&mut context).unwrap();
+
+        // One infection in each of the periods ending at 2.0 and 4.0
+        context.add_plan(1.0, move |context| {
+            context.infect_person(first_target, Some(source), Some("test_setting"), Some(1));
+        });
+        context.add_plan(3.0, move |context| {
+            context.infect_person(second_target, Some(source), Some("test_setting"), Some(1));
+        });
+        context.execute();
+        std::mem::drop(context);
+
+        let output = buffer.borrow();
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        reader.deserialize().map(Result::unwrap).collect()
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_cumulative_incidence_is_running_sum_of_interval_incidence() {
+        let interval = run_incidence_report_in_memory(false);
+        let cumulative = run_incidence_report_in_memory(true);
+        assert_eq!(interval.len(), cumulative.len());
+
+        // Accumulate the interval series by age and event in time order
+        let mut running_totals: HashMap<(u8, String), u32> = HashMap::default();
+        let mut expected: HashMap<(String, u8, String), u32> = HashMap::default();
+        let mut interval = interval;
+        interval.sort_by(|a, b| a.t_upper.partial_cmp(&b.t_upper).unwrap());
+        for record in interval {
+            let total = running_totals
+                .entry((record.age, record.event.clone()))
+                .or_insert(0);
+            *total += record.count;
+            expected.insert(
+                (record.t_upper.to_string(), record.age, record.event),
+                *total,
+            );
+        }
+
+        for record in cumulative {
+            assert_eq!(
+                expected[&(record.t_upper.to_string(), record.age, record.event.clone())],
+                record.count
+            );
+            if record.t_upper == 4.0 && record.event == *"Infectious" && record.age == 43 {
+                assert_eq!(record.count, 2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_setting_type_incidence_sums_to_total_incidence() {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 20.0,
+                    initial_incidence: 0.05,
+                    infectiousness_rate_fn: RateFnType::Constant {
+                        rate: 1.0,
+                        duration: 5.0,
+                    },
+                    incidence_report: ReportParams {
+                        write: true,
+                        filename: Some("output.csv".to_string()),
+                        period: Some(1.0),
+                        cumulative: false,
+                        by_setting_type: true,
+                        age_groups: None,
+                        compress: None,
+                        include_cumulative: false,
+                    },
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        context.set_start_time(-1000.0);
+        for category in [&Home as &dyn SettingCategory, &Workplace] {
+            context
+                .register_setting_category(
+                    category,
+                    SettingProperties {
+                        alpha: 0.0,
+                        itinerary_specification: None,
+                    },
+                )
+                .unwrap();
+        }
+        for i in 0..200 {
+            let person = context
+                .add_person((Age, if i % 2 == 0 { 30 } else { 60 }))
+                .unwrap();
+            context
+                .add_itinerary(
+                    person,
+                    vec![
+                        ItineraryEntry::new(SettingId::new(Home, i / 4), 0.5),
+                        ItineraryEntry::new(SettingId::new(Workplace, i % 5), 0.5),
+                    ],
+                )
+                .unwrap();
+        }
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink("output.csv", ReportSink::Memory(Rc::clone(&buffer)));
+        let infections_buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink(
+            "output_infections.csv",
+            ReportSink::Memory(Rc::clone(&infections_buffer)),
+        );
+        crate::infection_propagation_loop::init(&mut context).unwrap();
+        crate::reports::init(&mut context).unwrap();
+        context.add_plan(20.0, Context::shutdown);
+        context.execute();
+        std::mem::drop(context);
+
+        // The incidence report itself is unchanged by the breakdown
+        let output = buffer.borrow();
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        let records: Vec<crate::reports::incidence_report::PersonPropertyIncidenceReport> =
+            reader.deserialize().map(Result::unwrap).collect();
+        let mut total: HashMap<String, u32> = HashMap::default();
+        for record in records {
+            assert!(!record.event.contains(':'));
+            if record.event == "Infectious" {
+                *total.entry(record.t_upper.to_string()).or_insert(0) += record.count;
+            }
+        }
+
+        let output = infections_buffer.borrow();
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        // Every setting type has a column
+        assert_eq!(
+            reader.headers().unwrap().iter().collect::<Vec<_>>(),
+            vec!["t", "Home", "Workplace", "seed"]
+        );
+        let records: Vec<(f64, u32, u32, u32)> = reader.deserialize().map(Result::unwrap).collect();
+        assert_eq!(records.len(), total.len());
+        let mut setting_type_totals = [0; 3];
+        for (t, home, workplace, seed) in records {
+            assert_eq!(home + workplace + seed, total[&t.to_string()]);
+            for (setting_type_total, count) in
+                setting_type_totals.iter_mut().zip([home, workplace, seed])
+            {
+                *setting_type_total += count;
+            }
+        }
+        // There are seeds and infections in both settings
+        assert!(setting_type_totals.iter().all(|&count| count > 0));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_age_group_columns() {
+        let mut context = setup_context_with_report(ReportParams {
+            write: true,
+            filename: Some("output.csv".to_string()),
+            period: Some(2.0),
+            cumulative: false,
+            by_setting_type: false,
+            age_groups: Some(vec![
+                AgeGroup { min: 0 },
+                AgeGroup { min: 19 },
+                AgeGroup { min: 65 },
+            ]),
+            compress: None,
+            include_cumulative: false,
+        });
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink("output.csv", ReportSink::Memory(Rc::clone(&buffer)));
+        let infections_buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink(
+            "output_infections.csv",
+            ReportSink::Memory(Rc::clone(&infections_buffer)),
+        );
+
+        let child = context.add_person((Age, 10)).unwrap();
+        let adult = context.add_person((Age, 64)).unwrap();
+        let older_adult = context.add_person((Age, 80)).unwrap();
+        crate::reports::init(&mut context).unwrap();
+        context.add_plan(1.0, move |context| {
+            for person in [child, adult, older_adult] {
+                context.infect_person(person, None, None, None);
+            }
+        });
+        context.execute();
+        std::mem::drop(context);
+
+        // The incidence report is still written by age and event
+        let output = buffer.borrow();
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        let records: Vec<crate::reports::incidence_report::PersonPropertyIncidenceReport> =
+            reader.deserialize().map(Result::unwrap).collect();
+        for age in [10, 64, 80] {
+            assert!(records.iter().any(|record| record.t_upper == 2.0
+                && record.age == age
+                && record.event == "Infectious"
+                && record.count == 1));
+        }
+        assert!(records.iter().any(|record| record.event == "Hospitalized"));
+
+        let output = infections_buffer.borrow();
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        assert_eq!(
+            reader.headers().unwrap().iter().collect::<Vec<_>>(),
+            vec!["t", "age_0_18", "age_19_64", "age_65_plus"]
+        );
+        let records: Vec<(f64, u32, u32, u32)> = reader.deserialize().map(Result::unwrap).collect();
+        // Each person is infected once, in the period ending at 2.0
+        assert_eq!(records[0], (0.0, 0, 0, 0));
+        assert_eq!(records[1], (2.0, 1, 1, 1));
+        for record in &records[2..] {
+            assert_eq!((record.1, record.2, record.3), (0, 0, 0));
+        }
+    }
+
+    #[test]
+    fn test_validate_age_groups() {
+        let age_groups = [AgeGroup { min: 0 }, AgeGroup { min: 19 }];
+        assert!(validate_age_groups(&age_groups).is_ok());
+
+        let unordered = [
+            AgeGroup { min: 0 },
+            AgeGroup { min: 19 },
+            AgeGroup { min: 19 },
+        ];
+        let e = validate_age_groups(&unordered).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => assert_eq!(
+                msg,
+                "The minimum ages of the incidence report age groups must be strictly increasing."
+            ),
+            Some(ue) => {
+                panic!("Expected an error about unordered age groups. Instead got {ue:?}")
+            }
+            None => panic!("Expected an error. Instead, validation passed with no errors."),
+        }
+
+        let not_from_zero = [AgeGroup { min: 5 }, AgeGroup { min: 19 }];
+        assert!(validate_age_groups(&not_from_zero).is_err());
+        assert!(validate_age_groups(&[]).is_err());
+    }
 }
@@ -1,9 +1,28 @@
-use crate::parameters::{ContextParametersExt, Params};
-use ixa::{info, Context, IxaError};
+use crate::{
+    parameters::{ContextParametersExt, Params},
+    population_loader::Alive,
+    reports::report_sink::ContextReportSinkExt,
+};
+use ixa::{
+    define_person_property_with_default, define_rng, info, Context, ContextPeopleExt,
+    ContextRandomExt, ContextReportExt, ExecutionPhase, IxaError, PersonId,
+};
 use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
+pub mod hospitalization_report;
 pub mod incidence_report;
+pub mod natural_history_report;
+pub mod observed_incidence_report;
 pub mod prevalence_report;
+pub mod report_sink;
+pub mod reproduction_report;
+pub mod serial_interval_report;
+pub mod setting_size_report;
+pub mod symptomatic_report;
 pub mod transmission_report;
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -11,6 +30,26 @@ pub struct ReportParams {
     pub write: bool,
     pub filename: Option<String>,
     pub period: Option<f64>,
+    /// Whether a periodic count report records running totals since the start of the simulation
+    /// instead of the counts in each period. Only used by the incidence report.
+    #[serde(default)]
+    pub cumulative: bool,
+    /// Whether a count report also breaks new infections down by the setting type in which they
+    /// were acquired. Only used by the incidence report.
+    #[serde(default)]
+    pub by_setting_type: bool,
+    /// Age groups by which new infections are counted, with one count column per age group. Only
+    /// used by the incidence report.
+    #[serde(default)]
+    pub age_groups: Option<Vec<incidence_report::AgeGroup>>,
+    /// Whether the report is written gzip-compressed, with `.gz` appended to its filename.
+    /// Defaults to false if not specified.
+    #[serde(default)]
+    pub compress: Option<bool>,
+    /// Whether a count report also records the cumulative number of infections, counting each
+    /// reinfection. Only used by the prevalence report.
+    #[serde(default)]
+    pub include_cumulative: bool,
 }
 
 fn get_report_name(params: &ReportParams) -> Result<Option<&str>, IxaError> {
@@ -38,6 +77,11 @@ fn get_period_report_name(params: &ReportParams) -> Result<Option<(&str, f64)>,
                     format!("The report period must be greater than zero, found period {period} for {name} instead.")
                 ));
             }
+            if !period.is_finite() {
+                return Err(IxaError::IxaError(format!(
+                    "The report period must be finite, found period {period} for {name} instead."
+                )));
+            }
             return Ok(Some((name, period)));
         }
 
@@ -48,54 +92,328 @@ fn get_period_report_name(params: &ReportParams) -> Result<Option<(&str, f64)>,
     Ok(None)
 }
 
+/// Call `callback` at the end of every `period`, starting at time zero, or at the start time of a
+/// simulation that starts later. The n-th report is scheduled at exactly `start + n * period`
+/// rather than by repeatedly adding the period to the previous report time, so fractional
+/// periods do not accumulate floating point drift: with a period of 0.1, the tenth report is at
+/// exactly 1.0. Reports stop at `max_time`, when the simulation's shutdown plan runs and no other
+/// plans remain.
+pub fn add_periodic_report_plan(
+    context: &mut Context,
+    period: f64,
+    callback: impl Fn(&mut Context) + 'static,
+) {
+    let start = context.get_start_time().unwrap_or(0.0).max(0.0);
+    let max_time = context.get_params().max_time;
+    schedule_periodic_report(context, start, period, max_time, 0, Rc::new(callback));
+}
+
+fn schedule_periodic_report(
+    context: &mut Context,
+    start: f64,
+    period: f64,
+    max_time: f64,
+    n: u32,
+    callback: Rc<dyn Fn(&mut Context)>,
+) {
+    let report_time = start + f64::from(n) * period;
+    context.add_plan_with_phase(
+        report_time,
+        move |context| {
+            callback(context);
+            // No other plans remain after the simulation reaches `max_time`
+            if start + f64::from(n + 1) * period <= max_time {
+                schedule_periodic_report(context, start, period, max_time, n + 1, callback);
+            }
+        },
+        ExecutionPhase::Last,
+    );
+}
+
+define_rng!(ReportCohortRng);
+
+// Whether a person is in the sampled cohort whose events are written to per-person reports
+define_person_property_with_default!(InReportCohort, bool, false);
+
+/// Flag each person in the population as a member of the report cohort with probability
+/// `fraction`.
+fn sample_report_cohort(context: &mut Context, fraction: f64) {
+    let mut people = Vec::new();
+    context.with_query_results((Alive, true), &mut |current_people| {
+        people.extend(current_people.iter().copied());
+    });
+    for person_id in people {
+        if context.sample_bool(ReportCohortRng, fraction) {
+            context.set_person_property(person_id, InReportCohort, true);
+        }
+    }
+}
+
+/// Whether events involving the person are written to per-person reports. Everyone is reported
+/// unless `report_sample_fraction` is specified, in which case only the sampled cohort is.
+pub fn is_in_report_cohort(context: &Context, person_id: PersonId) -> bool {
+    context.get_params().report_sample_fraction.is_none()
+        || context.get_person_property(person_id, InReportCohort)
+}
+
+/// Append the seed to a report filename before its extension, so `incidence.csv` becomes
+/// `incidence_seed42.csv` for seed 42. Filenames without an extension have the seed appended to
+/// the end.
+fn append_seed_to_filename(name: &str, seed: u64) -> String {
+    append_to_filename(name, &format!("_seed{seed}"))
+}
+
+/// Append `suffix` to a report filename before its extension, so `incidence.csv` becomes
+/// `incidence_infections.csv` for the suffix `_infections`. A `.gz` ending of a compressed report
+/// is kept after the extension, and filenames without an extension have the suffix appended to
+/// the end.
+pub fn append_to_filename(name: &str, suffix: &str) -> String {
+    if let Some(name) = name.strip_suffix(".gz") {
+        return format!("{}.gz", append_to_filename(name, suffix));
+    }
+    let path = Path::new(name);
+    let stem = path.file_stem().map_or_else(
+        || name.to_string(),
+        |stem| stem.to_string_lossy().to_string(),
+    );
+    let file_name = match path.extension() {
+        Some(extension) => format!("{stem}{suffix}.{}", extension.to_string_lossy()),
+        None => format!("{stem}{suffix}"),
+    };
+    path.with_file_name(file_name).to_string_lossy().to_string()
+}
+
+/// Resolve the `output_directory` template against the parameter values. Each `{name}`
+/// placeholder is replaced by the value of the parameter `name`, and nested parameters are
+/// referenced with dots (e.g., `{facemask_parameters.facemask_efficacy}`). Returns `None` if no
+/// template is specified.
+/// # Errors
+/// - If a placeholder is not closed or a closing brace has no placeholder
+/// - If a placeholder does not name a parameter with a number, string, or boolean value
+pub fn resolve_output_directory(params: &Params) -> Result<Option<PathBuf>, IxaError> {
+    let Some(template) = &params.output_directory else {
+        return Ok(None);
+    };
+    let values = serde_json::to_value(params).map_err(|e| {
+        IxaError::IxaError(format!(
+            "Could not serialize parameters to resolve the output directory: {e}"
+        ))
+    })?;
+    let mut resolved = String::new();
+    let mut rest = template.as_str();
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start..].find('}') else {
+            return Err(IxaError::IxaError(format!(
+                "Unclosed placeholder in output directory template {template}"
+            )));
+        };
+        let literal = &rest[..start];
+        let key = &rest[start + 1..start + length];
+        if literal.contains('}') {
+            return Err(IxaError::IxaError(format!(
+                "Unmatched closing brace in output directory template {template}"
+            )));
+        }
+        let value = key
+            .split('.')
+            .try_fold(&values, |value, field| value.get(field))
+            .ok_or_else(|| {
+                IxaError::IxaError(format!(
+                    "Placeholder {{{key}}} in output directory template does not match a parameter"
+                ))
+            })?;
+        let value = match value {
+            serde_json::Value::Number(number) => number.to_string(),
+            serde_json::Value::String(string) => string.clone(),
+            serde_json::Value::Bool(boolean) => boolean.to_string(),
+            _ => {
+                return Err(IxaError::IxaError(format!(
+                    "Placeholder {{{key}}} in output directory template must be a number, string, or boolean"
+                )))
+            }
+        };
+        resolved.push_str(literal);
+        resolved.push_str(&value);
+        rest = &rest[start + length + 1..];
+    }
+    if rest.contains('}') {
+        return Err(IxaError::IxaError(format!(
+            "Unmatched closing brace in output directory template {template}"
+        )));
+    }
+    resolved.push_str(rest);
+    Ok(Some(PathBuf::from(resolved)))
+}
+
 /// # Errors
 ///
 /// Will return `IxaError` if any report within the reports list cannot be added
 /// or if the period for any periodic report is less than 0.0
 pub fn init(context: &mut Context) -> Result<(), IxaError> {
     let Params {
+        seed,
         prevalence_report,
         incidence_report,
         transmission_report,
+        symptomatic_report,
+        natural_history_report,
+        setting_size_report,
+        observed_incidence_report,
+        reproduction_report,
+        hospitalization_report,
+        serial_interval_report,
+        case_ascertainment_probability,
+        reporting_delay,
+        seed_in_report_filenames,
+        report_sample_fraction,
         ..
     } = context.get_params().clone();
+    let file_name = |report_params: &ReportParams, name: &str| {
+        let mut name = if seed_in_report_filenames.unwrap_or(false) {
+            append_seed_to_filename(name, seed)
+        } else {
+            name.to_string()
+        };
+        // The seed goes before the `.gz` so the report keeps its original extension
+        if report_params.compress.unwrap_or(false) {
+            name.push_str(".gz");
+        }
+        name
+    };
+    // The report directory is also used by the profiling writer
+    if let Some(directory) = resolve_output_directory(context.get_params())? {
+        info!("Writing output to {}.", directory.display());
+        std::fs::create_dir_all(&directory)?;
+        context.report_options().directory(directory);
+    }
+    // The cohort is drawn from the loaded population before any per-person events are reported
+    if let Some(fraction) = report_sample_fraction {
+        sample_report_cohort(context, fraction);
+        info!("Restricting per-person reports to a sampled cohort of fraction {fraction}.");
+    }
     let mut report_count = 0;
 
     if let Some((name, period)) = get_period_report_name(&prevalence_report)? {
-        prevalence_report::init(context, name, period)?;
+        prevalence_report::init(
+            context,
+            &file_name(&prevalence_report, name),
+            period,
+            prevalence_report.include_cumulative,
+        )?;
         info!("Generating the prevalence report.");
         report_count += 1;
     }
     if let Some((name, period)) = get_period_report_name(&incidence_report)? {
-        incidence_report::init(context, name, period)?;
+        incidence_report::init(
+            context,
+            &file_name(&incidence_report, name),
+            period,
+            incidence_report.cumulative,
+            incidence_report.by_setting_type,
+            incidence_report.age_groups.as_deref(),
+        )?;
         info!("Generating the incidence report.");
         report_count += 1;
     }
     if let Some(name) = get_report_name(&transmission_report)? {
-        transmission_report::init(context, name)?;
+        transmission_report::init(context, &file_name(&transmission_report, name))?;
         info!("Generating the transmission report.");
         report_count += 1;
     }
 
+    if let Some(report_params) = &symptomatic_report {
+        if let Some((name, period)) = get_period_report_name(report_params)? {
+            symptomatic_report::init(context, &file_name(report_params, name), period)?;
+            info!("Generating the symptomatic fraction report.");
+            report_count += 1;
+        }
+    }
+    if let Some(report_params) = &natural_history_report {
+        if let Some(name) = get_report_name(report_params)? {
+            natural_history_report::init(context, &file_name(report_params, name))?;
+            info!("Generating the natural history report.");
+            report_count += 1;
+        }
+    }
+    if let Some(report_params) = &setting_size_report {
+        if let Some((name, period)) = get_period_report_name(report_params)? {
+            setting_size_report::init(context, &file_name(report_params, name), period)?;
+            info!("Generating the setting size report.");
+            report_count += 1;
+        }
+    }
+    if let Some(report_params) = &observed_incidence_report {
+        if let Some((name, period)) = get_period_report_name(report_params)? {
+            observed_incidence_report::init(
+                context,
+                &file_name(report_params, name),
+                period,
+                case_ascertainment_probability.unwrap_or(1.0),
+                reporting_delay.unwrap_or(0.0),
+            )?;
+            info!("Generating the observed incidence report.");
+            report_count += 1;
+        }
+    }
+    if let Some(report_params) = &reproduction_report {
+        if let Some((name, period)) = get_period_report_name(report_params)? {
+            reproduction_report::init(context, &file_name(report_params, name), period)?;
+            info!("Generating the reproduction number report.");
+            report_count += 1;
+        }
+    }
+    if let Some(report_params) = &hospitalization_report {
+        if let Some((name, period)) = get_period_report_name(report_params)? {
+            hospitalization_report::init(context, &file_name(report_params, name), period)?;
+            info!("Generating the hospitalization report.");
+            report_count += 1;
+        }
+    }
+    if let Some(report_params) = &serial_interval_report {
+        if let Some(name) = get_report_name(report_params)? {
+            serial_interval_report::init(context, &file_name(report_params, name))?;
+            info!("Generating the serial interval report.");
+            report_count += 1;
+        }
+    }
+
     info!("Generating {report_count} report(s) in total.");
 
     Ok(())
 }
 
+/// Write the reports that can only be written once the simulation has ended, and finish the
+/// compressed reports.
+/// # Errors
+/// - If a compressed report cannot be finished
+pub fn finalize(context: &mut Context) -> Result<(), IxaError> {
+    reproduction_report::send_reproduction_numbers(context);
+    context.finish_compressed_reports()
+}
+
 #[cfg(test)]
 mod test {
 
-    use super::get_period_report_name;
+    use super::{
+        add_periodic_report_plan, append_seed_to_filename, append_to_filename,
+        get_period_report_name, resolve_output_directory,
+    };
+    use crate::infectiousness_manager::InfectionContextExt;
     use crate::reports::ReportParams;
     use crate::{
-        parameters::{ContextParametersExt, Params},
+        parameters::{ContextParametersExt, GlobalParams, Params},
         rate_fns::load_rate_fns,
     };
     use ixa::assert_almost_eq;
-    use ixa::{Context, ContextGlobalPropertiesExt, ContextRandomExt, IxaError};
+    use ixa::{
+        Context, ContextGlobalPropertiesExt, ContextPeopleExt, ContextRandomExt, ContextReportExt,
+        IxaError,
+    };
+    use std::cell::RefCell;
     use std::fs::File;
     use std::io::Write;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
     use tempfile::tempdir;
 
     fn setup_context_from_str(params_json: &str) -> Context {
@@ -179,6 +497,47 @@ mod test {
         assert_eq!(transmission_report.period, None);
     }
 
+    #[test]
+    fn test_resolve_output_directory() {
+        let params = Params {
+            seed: 42,
+            transmission_scale: Some(2.5),
+            output_directory: Some("out/scale_{transmission_scale}_seed_{seed}".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve_output_directory(&params).unwrap(),
+            Some(PathBuf::from("out/scale_2.5_seed_42"))
+        );
+        let params = Params {
+            output_directory: None,
+            ..Default::default()
+        };
+        assert_eq!(resolve_output_directory(&params).unwrap(), None);
+    }
+
+    #[test]
+    fn test_resolve_output_directory_unknown_placeholder() {
+        let params = Params {
+            output_directory: Some("out/r0_{r0}".to_string()),
+            ..Default::default()
+        };
+        let e = resolve_output_directory(&params).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "Placeholder {r0} in output directory template does not match a parameter"
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that the placeholder does not resolve. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, the output directory resolved."),
+        }
+    }
+
     #[test]
     fn test_get_period_report_name() {
         let name = "output.csv".to_string();
@@ -188,6 +547,11 @@ mod test {
             write: true,
             filename: Some(name.clone()),
             period: Some(period),
+            cumulative: false,
+            by_setting_type: false,
+            age_groups: None,
+            compress: None,
+            include_cumulative: false,
         };
 
         if let Some((expect_name, expect_period)) = get_period_report_name(&report).unwrap() {
@@ -207,6 +571,11 @@ mod test {
             write: false,
             filename: Some(name),
             period: Some(period),
+            cumulative: false,
+            by_setting_type: false,
+            age_groups: None,
+            compress: None,
+            include_cumulative: false,
         };
 
         assert_eq!(None, get_period_report_name(&report).unwrap());
@@ -220,6 +589,11 @@ mod test {
             write: true,
             filename: None,
             period: Some(period),
+            cumulative: false,
+            by_setting_type: false,
+            age_groups: None,
+            compress: None,
+            include_cumulative: false,
         };
 
         match get_period_report_name(&no_name_report).err() {
@@ -246,6 +620,11 @@ mod test {
             write: true,
             filename: Some(name),
             period: Some(bad_period),
+            cumulative: false,
+            by_setting_type: false,
+            age_groups: None,
+            compress: None,
+            include_cumulative: false,
         };
 
         match get_period_report_name(&bad_period_report).err() {
@@ -262,4 +641,191 @@ mod test {
             None => panic!("Expected an error. Instead validation passed with no errors."),
         }
     }
+
+    #[test]
+    fn test_error_infinite_period() {
+        let report = ReportParams {
+            write: true,
+            filename: Some("output.csv".to_string()),
+            period: Some(f64::INFINITY),
+            cumulative: false,
+            by_setting_type: false,
+            age_groups: None,
+            compress: None,
+            include_cumulative: false,
+        };
+
+        match get_period_report_name(&report).err() {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "The report period must be finite, found period inf for output.csv instead."
+                        .to_string()
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that the period must be finite. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead validation passed with no errors."),
+        }
+    }
+
+    fn record_periodic_report_times(start_time: Option<f64>, max_time: f64) -> Vec<f64> {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        if let Some(start_time) = start_time {
+            context.set_start_time(start_time);
+        }
+        let times = Rc::new(RefCell::new(Vec::new()));
+        let times_clone = Rc::clone(&times);
+        add_periodic_report_plan(&mut context, 0.1, move |context| {
+            times_clone.borrow_mut().push(context.get_current_time());
+        });
+        context.execute();
+        times.take()
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_periodic_report_times_do_not_drift() {
+        let times = record_periodic_report_times(None, 1.0);
+        assert_eq!(times[10], 1.0);
+        let expected: Vec<f64> = (0..11).map(|n| f64::from(n) * 0.1).collect();
+        assert_eq!(times, expected);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_periodic_reports_follow_start_time() {
+        // Reports start at the start time of a simulation that starts after time zero
+        let times = record_periodic_report_times(Some(2.0), 3.0);
+        let expected: Vec<f64> = (0..11).map(|n| 2.0 + f64::from(n) * 0.1).collect();
+        assert_eq!(times, expected);
+        // A simulation that starts before time zero is reported from time zero
+        let times = record_periodic_report_times(Some(-5.0), 1.0);
+        let expected: Vec<f64> = (0..11).map(|n| f64::from(n) * 0.1).collect();
+        assert_eq!(times, expected);
+    }
+
+    #[test]
+    fn test_append_seed_to_filename() {
+        assert_eq!(
+            append_seed_to_filename("incidence.csv", 42),
+            "incidence_seed42.csv"
+        );
+        assert_eq!(append_seed_to_filename("incidence", 42), "incidence_seed42");
+        assert_eq!(
+            append_seed_to_filename("reports/incidence.csv", 7),
+            Path::new("reports")
+                .join("incidence_seed7.csv")
+                .to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn test_append_to_filename_keeps_compressed_extension() {
+        assert_eq!(
+            append_to_filename("incidence.csv.gz", "_infections"),
+            "incidence_infections.csv.gz"
+        );
+        assert_eq!(
+            append_to_filename("incidence.csv", "_infections"),
+            "incidence_infections.csv"
+        );
+    }
+
+    fn init_transmission_report_with_seed(seed: u64, directory: &Path) {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                crate::parameters::GlobalParams,
+                Params {
+                    seed,
+                    transmission_report: ReportParams {
+                        write: true,
+                        filename: Some("transmission.csv".to_string()),
+                        period: None,
+                        cumulative: false,
+                        by_setting_type: false,
+                        age_groups: None,
+                        compress: None,
+                        include_cumulative: false,
+                    },
+                    seed_in_report_filenames: Some(true),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(seed);
+        context.report_options().directory(directory.to_path_buf());
+        super::init(&mut context).unwrap();
+    }
+
+    #[test]
+    fn test_different_seeds_write_differently_named_reports() {
+        let temp_dir = tempdir().unwrap();
+        init_transmission_report_with_seed(1, temp_dir.path());
+        init_transmission_report_with_seed(2, temp_dir.path());
+
+        assert!(temp_dir.path().join("transmission_seed1.csv").exists());
+        assert!(temp_dir.path().join("transmission_seed2.csv").exists());
+        assert!(!temp_dir.path().join("transmission.csv").exists());
+    }
+
+    #[test]
+    fn test_compressed_report_is_gzipped_after_seed() {
+        let temp_dir = tempdir().unwrap();
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                crate::parameters::GlobalParams,
+                Params {
+                    seed: 3,
+                    max_time: 2.0,
+                    transmission_report: ReportParams {
+                        write: true,
+                        filename: Some("transmission.csv".to_string()),
+                        period: None,
+                        cumulative: false,
+                        by_setting_type: false,
+                        age_groups: None,
+                        compress: Some(true),
+                        include_cumulative: false,
+                    },
+                    seed_in_report_filenames: Some(true),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(3);
+        load_rate_fns(&mut context).unwrap();
+        context
+            .report_options()
+            .directory(temp_dir.path().to_path_buf());
+        super::init(&mut context).unwrap();
+        let source = context.add_person(()).unwrap();
+        let target = context.add_person(()).unwrap();
+        context.infect_person(source, None, None, None);
+        context.add_plan(1.0, move |context| {
+            context.infect_person(target, Some(source), None, None);
+        });
+        context.execute();
+        super::finalize(&mut context).unwrap();
+
+        let path = temp_dir.path().join("transmission_seed3.csv.gz");
+        assert!(path.exists());
+        let mut reader =
+            csv::Reader::from_reader(flate2::read::GzDecoder::new(File::open(path).unwrap()));
+        assert_eq!(&reader.headers().unwrap()[1], "target_id");
+        assert_eq!(reader.records().count(), 1);
+    }
 }
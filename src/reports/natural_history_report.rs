@@ -0,0 +1,135 @@
+use crate::infectiousness_manager::{InfectionData, InfectionDataValue};
+use crate::natural_history_parameter_manager::ContextNaturalHistoryParameterExt;
+use crate::rate_fns::RateFn;
+use crate::reports::{is_in_report_cohort, report_sink::ContextReportSinkExt};
+use ixa::profiling::open_span;
+use ixa::{define_report, Context, IxaError, PersonId, PersonPropertyChangeEvent};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct NaturalHistoryReport {
+    time: f64,
+    person_id: PersonId,
+    rate_fn_id: usize,
+}
+
+define_report!(NaturalHistoryReport);
+
+/// Record the id of the infectiousness rate function from the natural history library that each
+/// infected person is assigned, at the time they become infectious. The id is assigned once per
+/// person, so a reinfected person is reported with the same id as their first infection.
+/// # Errors
+///
+/// Will return `IxaError` if the report cannot be added
+pub fn init(context: &mut Context, file_name: &str) -> Result<(), IxaError> {
+    context.add_report_with_sink::<NaturalHistoryReport>(file_name)?;
+    context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionData>>(|context, event| {
+        let _span = open_span("natural_history_report");
+        if let InfectionDataValue::Infectious { infection_time, .. } = event.current {
+            if !is_in_report_cohort(context, event.person_id) {
+                return;
+            }
+            let rate_fn_id = context.get_parameter_id(RateFn, event.person_id);
+            context.send_report_to_sink(NaturalHistoryReport {
+                time: infection_time,
+                person_id: event.person_id,
+                rate_fn_id,
+            });
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        infectiousness_manager::{InfectionContextExt, InfectionData, InfectionDataValue},
+        natural_history_parameter_manager::{
+            ContextNaturalHistoryParameterExt, NaturalHistoryParameterLibrary,
+        },
+        parameters::{ContextParametersExt, GlobalParams, Params},
+        rate_fns::{ConstantRate, InfectiousnessRateExt, RateFn},
+        reports::{
+            report_sink::{ContextReportSinkExt, ReportSink},
+            ReportParams,
+        },
+    };
+    use ixa::{Context, ContextGlobalPropertiesExt, ContextPeopleExt, ContextRandomExt};
+
+    #[test]
+    fn test_natural_history_ids_in_range_and_stable_across_infections() {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 10.0,
+                    natural_history_report: Some(ReportParams {
+                        write: true,
+                        filename: Some("natural_history.csv".to_string()),
+                        period: None,
+                        cumulative: false,
+                        by_setting_type: false,
+                        age_groups: None,
+                        compress: None,
+                        include_cumulative: false,
+                    }),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(context.get_params().seed);
+        for duration in 1..=5 {
+            context.add_rate_fn(ConstantRate::new(1.0, f64::from(duration)).unwrap());
+        }
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink(
+            "natural_history.csv",
+            ReportSink::Memory(Rc::clone(&buffer)),
+        );
+        crate::reports::init(&mut context).unwrap();
+
+        // Everyone is infected, recovers, loses their immunity, and is infected again
+        let people: Vec<_> = (0..20).map(|_| context.add_person(()).unwrap()).collect();
+        for &person in &people {
+            context.add_plan(1.0, move |context| {
+                context.infect_person(person, None, None, None);
+            });
+            context.add_plan(2.0, move |context| {
+                context.recover_person(person);
+            });
+            context.add_plan(3.0, move |context| {
+                context.set_person_property(person, InfectionData, InfectionDataValue::Susceptible);
+            });
+            context.add_plan(4.0, move |context| {
+                context.infect_person(person, None, None, None);
+            });
+        }
+        context.execute();
+        let library_size = RateFn.library_size(&context);
+        let assigned_ids: Vec<usize> = people
+            .iter()
+            .map(|&person| context.get_parameter_id(RateFn, person))
+            .collect();
+        std::mem::drop(context);
+
+        let buffer = buffer.borrow();
+        let mut reader = csv::Reader::from_reader(buffer.as_slice());
+        let records: Vec<super::NaturalHistoryReport> =
+            reader.deserialize().map(Result::unwrap).collect();
+        assert_eq!(records.len(), 2 * people.len());
+        for (i, &person) in people.iter().enumerate() {
+            let person_records: Vec<_> = records
+                .iter()
+                .filter(|record| record.person_id == person)
+                .collect();
+            assert_eq!(person_records.len(), 2);
+            for record in person_records {
+                assert!(record.rate_fn_id < library_size);
+                assert_eq!(record.rate_fn_id, assigned_ids[i]);
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{
+    infectiousness_manager::{InfectionStatus, InfectionStatusValue},
+    reports::{add_periodic_report_plan, report_sink::ContextReportSinkExt},
+};
+use ixa::{
+    define_data_plugin, define_report, define_rng, Context, ContextRandomExt, IxaError,
+    PersonPropertyChangeEvent,
+};
+use serde::{Deserialize, Serialize};
+
+define_rng!(CaseAscertainmentRng);
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct ObservedIncidenceReport {
+    t_upper: f64,
+    true_incidence: u32,
+    observed_incidence: u32,
+}
+
+define_report!(ObservedIncidenceReport);
+
+#[derive(Default)]
+struct ObservedIncidenceContainer {
+    true_incidence: u32,
+    observed_incidence: u32,
+}
+
+define_data_plugin!(
+    ObservedIncidencePlugin,
+    ObservedIncidenceContainer,
+    ObservedIncidenceContainer::default()
+);
+
+fn update_observed_incidence(
+    context: &mut Context,
+    event: PersonPropertyChangeEvent<InfectionStatus>,
+    case_ascertainment_probability: f64,
+    reporting_delay: f64,
+) {
+    if event.current != InfectionStatusValue::Infectious {
+        return;
+    }
+    context.get_data_mut(ObservedIncidencePlugin).true_incidence += 1;
+    if !context.sample_bool(CaseAscertainmentRng, case_ascertainment_probability) {
+        return;
+    }
+    if reporting_delay > 0.0 {
+        context.add_plan(
+            context.get_current_time() + reporting_delay,
+            |context: &mut Context| {
+                context
+                    .get_data_mut(ObservedIncidencePlugin)
+                    .observed_incidence += 1;
+            },
+        );
+    } else {
+        context
+            .get_data_mut(ObservedIncidencePlugin)
+            .observed_incidence += 1;
+    }
+}
+
+fn send_observed_incidence(context: &mut Context) {
+    let container = context.get_data_mut(ObservedIncidencePlugin);
+    let true_incidence = std::mem::take(&mut container.true_incidence);
+    let observed_incidence = std::mem::take(&mut container.observed_incidence);
+    context.send_report_to_sink(ObservedIncidenceReport {
+        t_upper: context.get_current_time(),
+        true_incidence,
+        observed_incidence,
+    });
+}
+
+/// Record the number of new infections each `period` alongside the number that surveillance
+/// observes. Each infection is detected with probability `case_ascertainment_probability`, and a
+/// detected infection is counted in the period in which it is reported, `reporting_delay` after
+/// the person becomes infectious.
+/// # Errors
+///
+/// Will return `IxaError` if the report cannot be added
+pub fn init(
+    context: &mut Context,
+    file_name: &str,
+    period: f64,
+    case_ascertainment_probability: f64,
+    reporting_delay: f64,
+) -> Result<(), IxaError> {
+    context.add_report_with_sink::<ObservedIncidenceReport>(file_name)?;
+
+    context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionStatus>>(
+        move |context, event| {
+            update_observed_incidence(
+                context,
+                event,
+                case_ascertainment_probability,
+                reporting_delay,
+            );
+        },
+    );
+    add_periodic_report_plan(context, period, move |context: &mut Context| {
+        send_observed_incidence(context);
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        infectiousness_manager::InfectionContextExt,
+        parameters::{ContextParametersExt, GlobalParams, Params},
+        rate_fns::load_rate_fns,
+        reports::{
+            report_sink::{ContextReportSinkExt, ReportSink},
+            ReportParams,
+        },
+    };
+    use ixa::{Context, ContextGlobalPropertiesExt, ContextPeopleExt, ContextRandomExt};
+
+    fn run_observed_incidence_report(
+        case_ascertainment_probability: f64,
+        reporting_delay: f64,
+    ) -> Vec<super::ObservedIncidenceReport> {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 4.0,
+                    observed_incidence_report: Some(ReportParams {
+                        write: true,
+                        filename: Some("observed_incidence.csv".to_string()),
+                        period: Some(1.0),
+                        cumulative: false,
+                        by_setting_type: false,
+                        age_groups: None,
+                        compress: None,
+                        include_cumulative: false,
+                    }),
+                    case_ascertainment_probability: Some(case_ascertainment_probability),
+                    reporting_delay: Some(reporting_delay),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(context.get_params().seed);
+        load_rate_fns(&mut context).unwrap();
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink(
+            "observed_incidence.csv",
+            ReportSink::Memory(Rc::clone(&buffer)),
+        );
+        crate::reports::init(&mut context).unwrap();
+
+        for _ in 0..10_000 {
+            let person = context.add_person(()).unwrap();
+            context.add_plan(0.5, move |context| {
+                context.infect_person(person, None, None, None);
+            });
+        }
+        context.add_plan(4.0, Context::shutdown);
+        context.execute();
+        std::mem::drop(context);
+
+        let buffer = buffer.borrow();
+        let mut reader = csv::Reader::from_reader(buffer.as_slice());
+        reader.deserialize().map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn test_observed_incidence_is_ascertainment_fraction_of_true_incidence() {
+        let records = run_observed_incidence_report(0.3, 0.0);
+        let true_incidence: u32 = records.iter().map(|record| record.true_incidence).sum();
+        let observed_incidence: u32 = records.iter().map(|record| record.observed_incidence).sum();
+        assert_eq!(true_incidence, 10_000);
+        let observed_fraction = f64::from(observed_incidence) / f64::from(true_incidence);
+        assert!(
+            (observed_fraction - 0.3).abs() < 0.02,
+            "Observed fraction {observed_fraction} is not close to 0.3"
+        );
+        // Without a reporting delay, cases are observed in the period they occur
+        for record in &records {
+            assert!(record.observed_incidence <= record.true_incidence);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_reporting_delay_shifts_observed_incidence() {
+        let records = run_observed_incidence_report(1.0, 2.0);
+        for record in &records {
+            if record.t_upper == 1.0 {
+                assert_eq!(record.true_incidence, 10_000);
+                assert_eq!(record.observed_incidence, 0);
+            } else if record.t_upper == 3.0 {
+                assert_eq!(record.true_incidence, 0);
+                assert_eq!(record.observed_incidence, 10_000);
+            } else {
+                assert_eq!(record.observed_incidence, 0);
+            }
+        }
+    }
+}
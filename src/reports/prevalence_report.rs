@@ -2,10 +2,11 @@ use crate::{
     hospitalizations::Hospitalized,
     infectiousness_manager::{InfectionStatus, InfectionStatusValue},
     population_loader::{Age, Alive},
+    reports::{add_periodic_report_plan, report_sink::ContextReportSinkExt},
     symptom_progression::{SymptomValue, Symptoms},
 };
 use ixa::prelude::*;
-use ixa::{ExecutionPhase, HashMap, PersonPropertyChangeEvent};
+use ixa::{HashMap, PersonPropertyChangeEvent};
 // use ixa::{
 //     define_data_plugin, define_derived_property, define_report, report::ContextReportExt, Context,
 //     ContextPeopleExt, ExecutionPhase, HashMap, IxaError, PersonPropertyChangeEvent,
@@ -20,6 +21,9 @@ struct PersonPropertyReport {
     infection_status: InfectionStatusValue,
     hospitalized: bool,
     count: usize,
+    // Only written when the report includes cumulative infections
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cumulative_infections: Option<usize>,
 }
 
 define_report!(PersonPropertyReport);
@@ -48,6 +52,9 @@ define_derived_property!(
 
 struct PropertyReportDataContainer {
     report_map_container: HashMap<PersonPropertyReportValues, usize>,
+    // The number of times anyone has become infectious. Only tracked when the report includes
+    // cumulative infections.
+    cumulative_infections: usize,
 }
 
 define_data_plugin!(
@@ -55,6 +62,7 @@ define_data_plugin!(
     PropertyReportDataContainer,
     PropertyReportDataContainer {
         report_map_container: HashMap::default(),
+        cumulative_infections: 0,
     }
 );
 
@@ -76,22 +84,38 @@ fn update_property_change_counts(context: &mut Context, event: ReportEvent) {
         .or_insert(0);
 }
 
-fn send_property_counts(context: &mut Context) {
+fn update_cumulative_infections(
+    context: &mut Context,
+    event: PersonPropertyChangeEvent<InfectionStatus>,
+) {
+    if event.current == InfectionStatusValue::Infectious {
+        context
+            .get_data_mut(PropertyReportDataPlugin)
+            .cumulative_infections += 1;
+    }
+}
+
+fn send_property_counts(context: &mut Context, include_cumulative: bool) {
     let report_container = context.get_data(PropertyReportDataPlugin);
+    let cumulative_infections =
+        include_cumulative.then_some(report_container.cumulative_infections);
 
     for (values, count_property) in &report_container.report_map_container {
-        context.send_report(PersonPropertyReport {
+        context.send_report_to_sink(PersonPropertyReport {
             t: context.get_current_time(),
             age: values.age,
             infection_status: values.infection_status,
             symptoms: values.symptoms,
             hospitalized: values.hospitalized,
             count: *count_property,
+            cumulative_infections,
         });
     }
 }
 
-/// Count initial number of people per property status and subscribe to cahnges
+/// Count initial number of people per property status and subscribe to cahnges. If
+/// `include_cumulative` is true, each row also records the number of infections so far, the number
+/// of times anyone has become infectious, counting each reinfection.
 /// # Errors
 ///
 /// Will return `IxaError` if the report cannot be added
@@ -99,8 +123,13 @@ fn send_property_counts(context: &mut Context) {
 /// # Panics
 ///
 /// Will panic if symptom value string is not listed in enum
-pub fn init(context: &mut Context, file_name: &str, period: f64) -> Result<(), IxaError> {
-    context.add_report::<PersonPropertyReport>(file_name)?;
+pub fn init(
+    context: &mut Context,
+    file_name: &str,
+    period: f64,
+    include_cumulative: bool,
+) -> Result<(), IxaError> {
+    context.add_report_with_sink::<PersonPropertyReport>(file_name)?;
 
     let mut map_counts = HashMap::default();
     context.with_query_results((Alive, true), &mut |current_people| {
@@ -121,13 +150,23 @@ pub fn init(context: &mut Context, file_name: &str, period: f64) -> Result<(), I
         update_property_change_counts(context, event);
     });
 
-    context.add_periodic_plan_with_phase(
-        period,
-        move |context: &mut Context| {
-            send_property_counts(context);
-        },
-        ExecutionPhase::Last,
-    );
+    if include_cumulative {
+        // People who are already infectious have entered `Infectious` before the report started
+        let infectious_people =
+            context.query_people_count((InfectionStatus, InfectionStatusValue::Infectious));
+        context
+            .get_data_mut(PropertyReportDataPlugin)
+            .cumulative_infections = infectious_people;
+        context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionStatus>>(
+            |context, event| {
+                update_cumulative_infections(context, event);
+            },
+        );
+    }
+
+    add_periodic_report_plan(context, period, move |context: &mut Context| {
+        send_property_counts(context, include_cumulative);
+    });
     Ok(())
 }
 
@@ -137,13 +176,16 @@ mod test {
         infectiousness_manager::InfectionContextExt,
         parameters::{ContextParametersExt, GlobalParams, Params},
         rate_fns::load_rate_fns,
-        reports::ReportParams,
+        reports::{
+            report_sink::{ContextReportSinkExt, ReportSink},
+            ReportParams,
+        },
         Age,
     };
     use ixa::{
         Context, ContextGlobalPropertiesExt, ContextPeopleExt, ContextRandomExt, ContextReportExt,
     };
-    use std::path::PathBuf;
+    use std::{cell::RefCell, path::PathBuf, rc::Rc};
     use tempfile::tempdir;
 
     fn setup_context_with_report(prevalence_report: ReportParams) -> Context {
@@ -169,6 +211,11 @@ mod test {
             write: true,
             filename: Some("output.csv".to_string()),
             period: Some(2.0),
+            cumulative: false,
+            by_setting_type: false,
+            age_groups: None,
+            compress: None,
+            include_cumulative: false,
         });
 
         let temp_dir = tempdir().unwrap();
@@ -224,4 +271,52 @@ mod test {
 
         assert_eq!(actual, expected, "CSV file should contain the correct data");
     }
+
+    #[test]
+    fn test_prevalence_report_includes_cumulative_infections() {
+        let mut context = setup_context_with_report(ReportParams {
+            write: true,
+            filename: Some("output.csv".to_string()),
+            period: Some(2.0),
+            cumulative: false,
+            by_setting_type: false,
+            age_groups: None,
+            compress: None,
+            include_cumulative: true,
+        });
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink("output.csv", ReportSink::Memory(Rc::clone(&buffer)));
+
+        let source = context.add_person((Age, 42)).unwrap();
+        let target = context.add_person((Age, 43)).unwrap();
+        context.infect_person(source, None, None, None);
+        crate::reports::init(&mut context).unwrap();
+
+        context.add_plan(1.0, move |context| {
+            context.infect_person(target, Some(source), None, None);
+        });
+        // The source recovers but is still counted, and their reinfection is counted again
+        context.add_plan(1.5, move |context| {
+            context.recover_person(source);
+        });
+        context.add_plan(1.75, move |context| {
+            context.infect_person(source, None, None, None);
+        });
+        context.execute();
+        std::mem::drop(context);
+
+        let buffer = buffer.borrow();
+        let mut reader = csv::Reader::from_reader(buffer.as_slice());
+        assert_eq!(
+            reader.headers().unwrap().iter().next_back(),
+            Some("cumulative_infections")
+        );
+        let records: Vec<super::PersonPropertyReport> =
+            reader.deserialize().map(Result::unwrap).collect();
+        assert!(!records.is_empty());
+        for record in records {
+            let expected = if record.t < 2.0 { 1 } else { 3 };
+            assert_eq!(record.cumulative_infections, Some(expected));
+        }
+    }
 }
@@ -0,0 +1,154 @@
+use std::{any::TypeId, cell::RefCell, fs::File, io::Write, rc::Rc};
+
+use flate2::{write::GzEncoder, Compression};
+use ixa::{
+    define_data_plugin,
+    report::{ContextReportExt, Report},
+    Context, HashMap, IxaError, PluginContext,
+};
+use serde::Serialize;
+
+/// Where the rows of a report are written.
+#[derive(Clone, Debug, Default)]
+#[allow(dead_code)]
+pub enum ReportSink {
+    /// Write the report to a CSV file in the report output directory. This is the default. If the
+    /// file name ends in `.gz`, the file is gzip-compressed.
+    #[default]
+    File,
+    /// Write the report as CSV to a shared in-memory buffer, e.g., when embedding the simulation
+    /// in a larger program or test harness where writing to disk is unwanted.
+    Memory(Rc<RefCell<Vec<u8>>>),
+}
+
+/// A `Write` implementation that appends to a buffer shared with the caller of `set_report_sink`
+/// so that the caller can read the report after (or while) the simulation runs.
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct ReportSinkContainer {
+    // Sinks requested for a report by its file name. These are resolved when the report is added.
+    sinks: HashMap<String, ReportSink>,
+    // Writers for each report type that is directed to memory rather than to a file
+    memory_writers: HashMap<TypeId, RefCell<csv::Writer<SharedBuffer>>>,
+    // Writers for each report type that is written to a gzip-compressed file. The compressed
+    // stream is finished by `finish_compressed_reports`.
+    gzip_writers: HashMap<TypeId, RefCell<csv::Writer<GzEncoder<File>>>>,
+}
+
+define_data_plugin!(
+    ReportSinkPlugin,
+    ReportSinkContainer,
+    ReportSinkContainer::default()
+);
+
+pub trait ContextReportSinkExt: PluginContext {
+    /// Direct the report that would otherwise be written to `file_name` to `sink`. This must be
+    /// called before the report is added (i.e., before `reports::init`).
+    #[allow(dead_code)]
+    fn set_report_sink(&mut self, file_name: &str, sink: ReportSink);
+
+    /// Add a report of type `T` that is written to the sink registered for `file_name`, or to the
+    /// file `file_name` if no sink has been registered. The file is gzip-compressed if
+    /// `file_name` ends in `.gz`.
+    /// # Errors
+    /// - If the report is written to a file and the file cannot be created
+    /// - If the report is written to a compressed file that already exists and overwriting is off
+    fn add_report_with_sink<T: Report + 'static>(
+        &mut self,
+        file_name: &str,
+    ) -> Result<(), IxaError>;
+
+    /// Send a row of a report of type `T` to wherever the report is being written.
+    fn send_report_to_sink<T: Report + Serialize>(&self, report: T);
+
+    /// Flush the gzip-compressed reports and finish their compressed streams. No more rows can be
+    /// sent to these reports afterwards.
+    /// # Errors
+    /// - If a compressed report cannot be flushed or its compressed stream cannot be finished
+    fn finish_compressed_reports(&mut self) -> Result<(), IxaError>;
+}
+
+impl ContextReportSinkExt for Context {
+    fn set_report_sink(&mut self, file_name: &str, sink: ReportSink) {
+        self.get_data_mut(ReportSinkPlugin)
+            .sinks
+            .insert(file_name.to_string(), sink);
+    }
+
+    fn add_report_with_sink<T: Report + 'static>(
+        &mut self,
+        file_name: &str,
+    ) -> Result<(), IxaError> {
+        let sink = self
+            .get_data(ReportSinkPlugin)
+            .sinks
+            .get(file_name)
+            .cloned()
+            .unwrap_or_default();
+        match sink {
+            ReportSink::File if file_name.ends_with(".gz") => {
+                let options = self.report_options();
+                let path = options
+                    .output_dir
+                    .join(format!("{}{file_name}", options.file_prefix));
+                if path.exists() && !options.overwrite {
+                    return Err(IxaError::IxaError(format!(
+                        "The report file {} already exists.",
+                        path.display()
+                    )));
+                }
+                let file = File::create(path)?;
+                let writer = csv::Writer::from_writer(GzEncoder::new(file, Compression::default()));
+                self.get_data_mut(ReportSinkPlugin)
+                    .gzip_writers
+                    .insert(TypeId::of::<T>(), RefCell::new(writer));
+                Ok(())
+            }
+            ReportSink::File => self.add_report::<T>(file_name),
+            ReportSink::Memory(buffer) => {
+                let writer = csv::Writer::from_writer(SharedBuffer(buffer));
+                self.get_data_mut(ReportSinkPlugin)
+                    .memory_writers
+                    .insert(TypeId::of::<T>(), RefCell::new(writer));
+                Ok(())
+            }
+        }
+    }
+
+    fn send_report_to_sink<T: Report + Serialize>(&self, report: T) {
+        let container = self.get_data(ReportSinkPlugin);
+        if let Some(writer) = container.memory_writers.get(&TypeId::of::<T>()) {
+            let mut writer = writer.borrow_mut();
+            writer.serialize(report).unwrap();
+            // Flush after every row so the buffer is always readable by the caller
+            writer.flush().unwrap();
+        } else if let Some(writer) = container.gzip_writers.get(&TypeId::of::<T>()) {
+            writer.borrow_mut().serialize(report).unwrap();
+        } else {
+            self.send_report(report);
+        }
+    }
+
+    fn finish_compressed_reports(&mut self) -> Result<(), IxaError> {
+        let gzip_writers = std::mem::take(&mut self.get_data_mut(ReportSinkPlugin).gzip_writers);
+        for writer in gzip_writers.into_values() {
+            let encoder = writer
+                .into_inner()
+                .into_inner()
+                .map_err(csv::IntoInnerError::into_error)?;
+            encoder.finish()?;
+        }
+        Ok(())
+    }
+}
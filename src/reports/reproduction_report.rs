@@ -0,0 +1,193 @@
+use crate::{
+    infectiousness_manager::{InfectionData, InfectionDataValue},
+    reports::report_sink::ContextReportSinkExt,
+};
+use ixa::{
+    define_data_plugin, define_report, Context, HashMap, IxaError, PersonId,
+    PersonPropertyChangeEvent,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct ReproductionReport {
+    t: f64,
+    mean_secondary_cases: f64,
+    n_infectors: usize,
+}
+
+define_report!(ReproductionReport);
+
+#[derive(Default)]
+struct ReproductionContainer {
+    // The report period, or `None` if the report is not being generated
+    period: Option<f64>,
+    // The period in which each person's most recent infection began
+    infection_periods: HashMap<PersonId, i64>,
+    // The number of infections that began in each period and the secondary cases they caused
+    cases_by_period: BTreeMap<i64, (usize, usize)>,
+}
+
+define_data_plugin!(
+    ReproductionPlugin,
+    ReproductionContainer,
+    ReproductionContainer::default()
+);
+
+fn update_reproduction_counts(
+    context: &mut Context,
+    event: PersonPropertyChangeEvent<InfectionData>,
+) {
+    let InfectionDataValue::Infectious {
+        infection_time,
+        infected_by,
+        ..
+    } = event.current
+    else {
+        return;
+    };
+    let container = context.get_data_mut(ReproductionPlugin);
+    let Some(period) = container.period else {
+        return;
+    };
+    #[allow(clippy::cast_possible_truncation)]
+    let infection_period = (infection_time / period).floor() as i64;
+    container
+        .infection_periods
+        .insert(event.person_id, infection_period);
+    container
+        .cases_by_period
+        .entry(infection_period)
+        .or_default()
+        .0 += 1;
+    // Secondary cases are attributed to the period in which the infector's infection began
+    if let Some(infector_period) =
+        infected_by.and_then(|infector| container.infection_periods.get(&infector).copied())
+    {
+        container
+            .cases_by_period
+            .entry(infector_period)
+            .or_default()
+            .1 += 1;
+    }
+}
+
+/// Send a row for each period in which at least one infection began, with the mean number of
+/// secondary cases caused by those infections. Infections that are still ongoing are included with
+/// their secondary cases to date, so this is sent once the simulation has ended.
+#[allow(clippy::cast_precision_loss)]
+pub fn send_reproduction_numbers(context: &Context) {
+    let container = context.get_data(ReproductionPlugin);
+    let Some(period) = container.period else {
+        return;
+    };
+    for (&infection_period, &(n_infectors, secondary_cases)) in &container.cases_by_period {
+        context.send_report_to_sink(ReproductionReport {
+            t: infection_period as f64 * period,
+            mean_secondary_cases: secondary_cases as f64 / n_infectors as f64,
+            n_infectors,
+        });
+    }
+}
+
+/// Record the realized effective reproduction number of the infections that began in each
+/// `period`: the mean number of secondary cases caused by each infection, grouped by the period
+/// in which the infector was infected. The report is written by `send_reproduction_numbers` once
+/// the simulation has ended.
+/// # Errors
+///
+/// Will return `IxaError` if the report cannot be added
+pub fn init(context: &mut Context, file_name: &str, period: f64) -> Result<(), IxaError> {
+    context.add_report_with_sink::<ReproductionReport>(file_name)?;
+    context.get_data_mut(ReproductionPlugin).period = Some(period);
+    context.subscribe_to_event(update_reproduction_counts);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        infectiousness_manager::InfectionContextExt,
+        parameters::{GlobalParams, Params},
+        rate_fns::load_rate_fns,
+        reports::{
+            report_sink::{ContextReportSinkExt, ReportSink},
+            ReportParams,
+        },
+    };
+    use ixa::{
+        assert_almost_eq, Context, ContextGlobalPropertiesExt, ContextPeopleExt, ContextRandomExt,
+    };
+
+    use super::ReproductionReport;
+
+    #[test]
+    fn test_secondary_cases_are_grouped_by_infector_period() {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 10.0,
+                    reproduction_report: Some(ReportParams {
+                        write: true,
+                        filename: Some("reproduction.csv".to_string()),
+                        period: Some(2.0),
+                        cumulative: false,
+                        by_setting_type: false,
+                        age_groups: None,
+                        compress: None,
+                        include_cumulative: false,
+                    }),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        load_rate_fns(&mut context).unwrap();
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink("reproduction.csv", ReportSink::Memory(Rc::clone(&buffer)));
+        crate::reports::init(&mut context).unwrap();
+
+        let people: Vec<_> = (0..6).map(|_| context.add_person(()).unwrap()).collect();
+        let [first, second, third, fourth, fifth, sixth] = people[..] else {
+            unreachable!()
+        };
+        // Two people are infected in the first period, and the first of them infects two people in
+        // the second period
+        context.infect_person(first, None, None, None);
+        context.infect_person(second, None, None, None);
+        context.add_plan(3.0, move |context| {
+            context.infect_person(third, Some(first), None, None);
+            context.infect_person(fourth, Some(first), None, None);
+        });
+        // A secondary case caused after the infector's period has ended still counts toward it, so
+        // the first person's third secondary case counts toward the first period
+        context.add_plan(5.0, move |context| {
+            context.infect_person(fifth, Some(first), None, None);
+            context.infect_person(sixth, Some(third), None, None);
+        });
+        context.execute();
+        super::send_reproduction_numbers(&context);
+        std::mem::drop(context);
+
+        let buffer = buffer.borrow();
+        let mut reader = csv::Reader::from_reader(buffer.as_slice());
+        let records: Vec<ReproductionReport> = reader.deserialize().map(Result::unwrap).collect();
+        assert_eq!(records.len(), 3);
+        // The first period: 3 secondary cases from 2 infectors
+        assert_almost_eq!(records[0].t, 0.0, 0.0);
+        assert_eq!(records[0].n_infectors, 2);
+        assert_almost_eq!(records[0].mean_secondary_cases, 1.5, 0.0);
+        // The second period: 1 secondary case from 2 infectors
+        assert_almost_eq!(records[1].t, 2.0, 0.0);
+        assert_eq!(records[1].n_infectors, 2);
+        assert_almost_eq!(records[1].mean_secondary_cases, 0.5, 0.0);
+        // The third period: no secondary cases from 2 infectors
+        assert_almost_eq!(records[2].t, 4.0, 0.0);
+        assert_eq!(records[2].n_infectors, 2);
+        assert_almost_eq!(records[2].mean_secondary_cases, 0.0, 0.0);
+    }
+}
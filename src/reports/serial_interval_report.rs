@@ -0,0 +1,231 @@
+use crate::{
+    infectiousness_manager::{InfectionData, InfectionDataValue},
+    reports::{is_in_report_cohort, report_sink::ContextReportSinkExt},
+    symptom_progression::SymptomRecord,
+};
+use ixa::{
+    define_data_plugin, define_report, Context, HashMap, IxaError, PersonId,
+    PersonPropertyChangeEvent,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct SerialIntervalReport {
+    infector: PersonId,
+    infectee: PersonId,
+    serial_interval: f64,
+}
+
+define_report!(SerialIntervalReport);
+
+#[derive(Default)]
+struct SerialIntervalContainer {
+    // The symptom onset time of each person's current infection, once they develop symptoms
+    symptom_onsets: HashMap<PersonId, f64>,
+    // The infector of each infectee whose current infection has not yet led to symptoms
+    presymptomatic_infectees: HashMap<PersonId, PersonId>,
+    // The infectees who have developed symptoms, and when, for each infector who has not yet
+    // developed symptoms
+    infectees_awaiting_infector_onset: HashMap<PersonId, Vec<(PersonId, f64)>>,
+}
+
+define_data_plugin!(
+    SerialIntervalPlugin,
+    SerialIntervalContainer,
+    SerialIntervalContainer::default()
+);
+
+fn record_serial_interval(
+    context: &Context,
+    infector: PersonId,
+    infectee: PersonId,
+    serial_interval: f64,
+) {
+    // With a sampled report cohort, only pairs involving a cohort member are reported
+    if is_in_report_cohort(context, infector) || is_in_report_cohort(context, infectee) {
+        context.send_report_to_sink(SerialIntervalReport {
+            infector,
+            infectee,
+            serial_interval,
+        });
+    }
+}
+
+fn record_infection(context: &mut Context, event: PersonPropertyChangeEvent<InfectionData>) {
+    let InfectionDataValue::Infectious { infected_by, .. } = event.current else {
+        return;
+    };
+    let container = context.get_data_mut(SerialIntervalPlugin);
+    // Symptoms from a previous infection do not count toward this one, and neither do infectees
+    // who are still waiting on symptoms from the previous infection
+    container.symptom_onsets.remove(&event.person_id);
+    container
+        .infectees_awaiting_infector_onset
+        .remove(&event.person_id);
+    if let Some(infector) = infected_by {
+        container
+            .presymptomatic_infectees
+            .insert(event.person_id, infector);
+    } else {
+        container.presymptomatic_infectees.remove(&event.person_id);
+    }
+}
+
+fn record_symptom_onset(context: &mut Context, event: PersonPropertyChangeEvent<SymptomRecord>) {
+    let Some(record) = event.current else {
+        return;
+    };
+    // The record is also updated when symptoms escalate or resolve, which keeps the start time
+    #[allow(clippy::float_cmp)]
+    let same_episode = event
+        .previous
+        .is_some_and(|previous| previous.symptom_start == record.symptom_start);
+    if same_episode {
+        return;
+    }
+    let person_id = event.person_id;
+    let onset = record.symptom_start;
+    let container = context.get_data_mut(SerialIntervalPlugin);
+    container.symptom_onsets.insert(person_id, onset);
+
+    let mut serial_intervals = Vec::new();
+    // The person is an infectee whose infector may already have developed symptoms
+    if let Some(infector) = container.presymptomatic_infectees.remove(&person_id) {
+        if let Some(&infector_onset) = container.symptom_onsets.get(&infector) {
+            serial_intervals.push((infector, person_id, onset - infector_onset));
+        } else {
+            container
+                .infectees_awaiting_infector_onset
+                .entry(infector)
+                .or_default()
+                .push((person_id, onset));
+        }
+    }
+    // The person is an infector whose infectees have already developed symptoms
+    if let Some(infectees) = container
+        .infectees_awaiting_infector_onset
+        .remove(&person_id)
+    {
+        for (infectee, infectee_onset) in infectees {
+            serial_intervals.push((person_id, infectee, infectee_onset - onset));
+        }
+    }
+    for (infector, infectee, serial_interval) in serial_intervals {
+        record_serial_interval(context, infector, infectee, serial_interval);
+    }
+}
+
+/// Record the serial interval of each transmission, the time from the infector's symptom onset to
+/// the infectee's symptom onset. A row is written once both have developed symptoms, so pairs in
+/// which either person is asymptomatic are never written.
+/// # Errors
+///
+/// Will return `IxaError` if the report cannot be added
+pub fn init(context: &mut Context, file_name: &str) -> Result<(), IxaError> {
+    context.add_report_with_sink::<SerialIntervalReport>(file_name)?;
+    context.subscribe_to_event(record_infection);
+    context.subscribe_to_event(record_symptom_onset);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        infectiousness_manager::InfectionContextExt,
+        parameters::{GlobalParams, Params},
+        rate_fns::load_rate_fns,
+        reports::{
+            report_sink::{ContextReportSinkExt, ReportSink},
+            ReportParams,
+        },
+        symptom_progression::{SymptomRecord, SymptomRecordValue, SymptomValue},
+    };
+    use ixa::{
+        assert_almost_eq, Context, ContextGlobalPropertiesExt, ContextPeopleExt, ContextRandomExt,
+        PersonId,
+    };
+
+    use super::SerialIntervalReport;
+
+    fn develop_symptoms(context: &mut Context, person_id: PersonId, symptom_start: f64) {
+        context.add_plan(symptom_start, move |context| {
+            context.set_person_property(
+                person_id,
+                SymptomRecord,
+                Some(SymptomRecordValue {
+                    category: SymptomValue::Category2,
+                    symptom_start,
+                    symptom_end: None,
+                    severe: false,
+                }),
+            );
+        });
+    }
+
+    fn infect_at(context: &mut Context, time: f64, infectee: PersonId, infector: PersonId) {
+        context.add_plan(time, move |context| {
+            context.infect_person(infectee, Some(infector), None, None);
+        });
+    }
+
+    #[test]
+    fn test_serial_intervals_skip_asymptomatic_pairs() {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 10.0,
+                    serial_interval_report: Some(ReportParams {
+                        write: true,
+                        filename: Some("serial_interval.csv".to_string()),
+                        period: None,
+                        cumulative: false,
+                        by_setting_type: false,
+                        age_groups: None,
+                        compress: None,
+                        include_cumulative: false,
+                    }),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(0);
+        load_rate_fns(&mut context).unwrap();
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink(
+            "serial_interval.csv",
+            ReportSink::Memory(Rc::clone(&buffer)),
+        );
+        crate::reports::init(&mut context).unwrap();
+
+        let people: Vec<_> = (0..5).map(|_| context.add_person(()).unwrap()).collect();
+        let (a, b, c, d, e) = (people[0], people[1], people[2], people[3], people[4]);
+        context.infect_person(a, None, None, None);
+        // `a` infects `b` before developing symptoms, and `b` develops symptoms later
+        infect_at(&mut context, 1.0, b, a);
+        develop_symptoms(&mut context, a, 2.0);
+        develop_symptoms(&mut context, b, 4.5);
+        // `c` never develops symptoms, so neither the pair of `a` and `c` nor the pair of `c` and
+        // `d` is written
+        infect_at(&mut context, 3.0, c, a);
+        infect_at(&mut context, 4.0, d, c);
+        develop_symptoms(&mut context, d, 6.0);
+        // `b` infects `e` after developing symptoms
+        infect_at(&mut context, 5.0, e, b);
+        develop_symptoms(&mut context, e, 5.5);
+        context.execute();
+        std::mem::drop(context);
+
+        let buffer = buffer.borrow();
+        let mut reader = csv::Reader::from_reader(buffer.as_slice());
+        let records: Vec<SerialIntervalReport> = reader.deserialize().map(Result::unwrap).collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!((records[0].infector, records[0].infectee), (a, b));
+        assert_almost_eq!(records[0].serial_interval, 2.5, 0.0);
+        assert_eq!((records[1].infector, records[1].infectee), (b, e));
+        assert_almost_eq!(records[1].serial_interval, 1.0, 0.0);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{
+    reports::{add_periodic_report_plan, report_sink::ContextReportSinkExt},
+    settings::ContextSettingExt,
+};
+use ixa::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct SettingSizeReport {
+    t: f64,
+    setting_type: String,
+    occupied_settings: usize,
+    mean_active_members: f64,
+}
+
+define_report!(SettingSizeReport);
+
+fn send_setting_sizes(context: &mut Context) {
+    let t = context.get_current_time();
+    for (setting_type, occupied_settings, mean_active_members) in
+        context.get_mean_active_setting_sizes()
+    {
+        context.send_report_to_sink(SettingSizeReport {
+            t,
+            setting_type,
+            occupied_settings,
+            mean_active_members,
+        });
+    }
+}
+
+/// Record the mean number of active members per occupied setting of each setting type every
+/// `period`
+/// # Errors
+///
+/// Will return `IxaError` if the report cannot be added
+pub fn init(context: &mut Context, file_name: &str, period: f64) -> Result<(), IxaError> {
+    context.add_report_with_sink::<SettingSizeReport>(file_name)?;
+
+    add_periodic_report_plan(context, period, move |context: &mut Context| {
+        send_setting_sizes(context);
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        parameters::{CoreSettingsTypes, GlobalParams, ItinerarySpecificationType, Params},
+        reports::{
+            report_sink::{ContextReportSinkExt, ReportSink},
+            ReportParams,
+        },
+        settings::{
+            ContextSettingExt, Home, ItineraryEntry, ItineraryModifiers, SettingId,
+            SettingProperties, Workplace,
+        },
+    };
+    use ixa::{assert_almost_eq, Context, ContextGlobalPropertiesExt, ContextPeopleExt, HashMap};
+
+    use super::SettingSizeReport;
+
+    #[test]
+    fn test_isolation_reduces_mean_workplace_size() {
+        let mut context = Context::new();
+        let setting_properties = SettingProperties {
+            alpha: 0.5,
+            itinerary_specification: Some(ItinerarySpecificationType::Constant { ratio: 0.5 }),
+        };
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 3.0,
+                    settings_properties: HashMap::from_iter([
+                        (CoreSettingsTypes::Home, setting_properties),
+                        (CoreSettingsTypes::Workplace, setting_properties),
+                    ]),
+                    setting_size_report: Some(ReportParams {
+                        write: true,
+                        filename: Some("setting_size.csv".to_string()),
+                        period: Some(1.0),
+                        cumulative: false,
+                        by_setting_type: false,
+                        age_groups: None,
+                        compress: None,
+                        include_cumulative: false,
+                    }),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        crate::settings::init(&mut context).unwrap();
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink("setting_size.csv", ReportSink::Memory(Rc::clone(&buffer)));
+        crate::reports::init(&mut context).unwrap();
+
+        // Ten workers who each live alone share a workplace
+        let mut workers = vec![];
+        for home_id in 0..10 {
+            let person = context.add_person(()).unwrap();
+            context
+                .add_itinerary(
+                    person,
+                    vec![
+                        ItineraryEntry::new(SettingId::new(Home, home_id), 0.5),
+                        ItineraryEntry::new(SettingId::new(Workplace, 0), 0.5),
+                    ],
+                )
+                .unwrap();
+            workers.push(person);
+        }
+        // Six of the workers isolate at home partway through the simulation
+        context.add_plan(1.5, move |context| {
+            for &worker in &workers[..6] {
+                context
+                    .modify_itinerary(
+                        worker,
+                        ItineraryModifiers::RestrictTo {
+                            setting: &Home,
+                            setting_id: None,
+                        },
+                    )
+                    .unwrap();
+            }
+        });
+        context.execute();
+        std::mem::drop(context);
+
+        let buffer = buffer.borrow();
+        let mut reader = csv::Reader::from_reader(buffer.as_slice());
+        let records: Vec<SettingSizeReport> = reader.deserialize().map(Result::unwrap).collect();
+        let workplace_sizes: Vec<(f64, f64)> = records
+            .iter()
+            .filter(|record| record.setting_type == "Workplace")
+            .map(|record| (record.t, record.mean_active_members))
+            .collect();
+        assert_eq!(
+            workplace_sizes.iter().map(|&(t, _)| t).collect::<Vec<_>>(),
+            vec![0.0, 1.0, 2.0, 3.0]
+        );
+        for (t, mean_active_members) in workplace_sizes {
+            let expected = if t < 1.5 { 10.0 } else { 4.0 };
+            assert_almost_eq!(mean_active_members, expected, 0.0);
+        }
+        // Everyone is still active at home
+        for record in records
+            .iter()
+            .filter(|record| record.setting_type == "Home")
+        {
+            assert_eq!(record.occupied_settings, 10);
+            assert_almost_eq!(record.mean_active_members, 1.0, 0.0);
+        }
+    }
+}
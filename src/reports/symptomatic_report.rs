@@ -0,0 +1,153 @@
+use crate::{
+    population_loader::Alive,
+    reports::{add_periodic_report_plan, report_sink::ContextReportSinkExt},
+    symptom_progression::PresentingWithSymptoms,
+};
+use ixa::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct SymptomaticFractionReport {
+    t: f64,
+    symptomatic: usize,
+    population: usize,
+    fraction: f64,
+}
+
+define_report!(SymptomaticFractionReport);
+
+#[allow(clippy::cast_precision_loss)]
+fn send_symptomatic_fraction(context: &mut Context) {
+    let population = context.query_people_count((Alive, true));
+    let symptomatic = context.query_people_count(((PresentingWithSymptoms, true), (Alive, true)));
+    let fraction = if population == 0 {
+        0.0
+    } else {
+        symptomatic as f64 / population as f64
+    };
+    context.send_report_to_sink(SymptomaticFractionReport {
+        t: context.get_current_time(),
+        symptomatic,
+        population,
+        fraction,
+    });
+}
+
+/// Record the fraction of the living population currently presenting with symptoms every `period`
+/// # Errors
+///
+/// Will return `IxaError` if the report cannot be added
+pub fn init(context: &mut Context, file_name: &str, period: f64) -> Result<(), IxaError> {
+    context.add_report_with_sink::<SymptomaticFractionReport>(file_name)?;
+
+    add_periodic_report_plan(context, period, move |context: &mut Context| {
+        send_symptomatic_fraction(context);
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        infectiousness_manager::{InfectionContextExt, InfectionStatus, InfectionStatusValue},
+        parameters::{ContextParametersExt, GlobalParams, Params},
+        rate_fns::load_rate_fns,
+        reports::{
+            add_periodic_report_plan,
+            report_sink::{ContextReportSinkExt, ReportSink},
+            ReportParams,
+        },
+        symptom_progression::{SymptomValue, Symptoms},
+    };
+    use ixa::{
+        assert_almost_eq, Context, ContextGlobalPropertiesExt, ContextPeopleExt, ContextRandomExt,
+    };
+
+    #[test]
+    fn test_symptomatic_fraction_tracks_shifted_infectious_fraction() {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 10.0,
+                    symptomatic_report: Some(ReportParams {
+                        write: true,
+                        filename: Some("symptomatic.csv".to_string()),
+                        period: Some(1.0),
+                        cumulative: false,
+                        by_setting_type: false,
+                        age_groups: None,
+                        compress: None,
+                        include_cumulative: false,
+                    }),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(context.get_params().seed);
+        load_rate_fns(&mut context).unwrap();
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink("symptomatic.csv", ReportSink::Memory(Rc::clone(&buffer)));
+        crate::reports::init(&mut context).unwrap();
+
+        // One person is infected every day and develops symptoms after an incubation period,
+        // and nobody recovers over the simulation
+        let incubation_period = 2.0;
+        for i in 0..10 {
+            let person = context.add_person(()).unwrap();
+            if i < 6 {
+                let infection_time = f64::from(i) + 0.5;
+                context.add_plan(infection_time, move |context| {
+                    context.infect_person(person, None, None, None);
+                });
+                context.add_plan(infection_time + incubation_period, move |context| {
+                    context.set_person_property(person, Symptoms, Some(SymptomValue::Category1));
+                });
+            }
+        }
+        let infectious_fractions = Rc::new(RefCell::new(Vec::new()));
+        let infectious_fractions_clone = Rc::clone(&infectious_fractions);
+        add_periodic_report_plan(&mut context, 1.0, move |context| {
+            #[allow(clippy::cast_precision_loss)]
+            let fraction = context
+                .query_people_count((InfectionStatus, InfectionStatusValue::Infectious))
+                as f64
+                / 10.0;
+            infectious_fractions_clone.borrow_mut().push(fraction);
+        });
+        context.add_plan(10.0, Context::shutdown);
+        context.execute();
+        std::mem::drop(context);
+
+        let buffer = buffer.borrow();
+        let mut reader = csv::Reader::from_reader(buffer.as_slice());
+        let symptomatic_fractions: Vec<(f64, f64)> = reader
+            .records()
+            .map(|record| {
+                let record = record.unwrap();
+                (record[0].parse().unwrap(), record[3].parse().unwrap())
+            })
+            .collect();
+        let infectious_fractions = infectious_fractions.borrow();
+        assert_eq!(symptomatic_fractions.len(), infectious_fractions.len());
+        assert!(symptomatic_fractions
+            .iter()
+            .any(|&(_, fraction)| fraction > 0.0));
+        // The symptomatic fraction at time t is the infectious fraction at t - incubation period
+        for (i, &(t, fraction)) in symptomatic_fractions.iter().enumerate() {
+            #[allow(clippy::cast_precision_loss)]
+            let expected_t = i as f64;
+            assert_almost_eq!(t, expected_t, 0.0);
+            if t < incubation_period {
+                assert_almost_eq!(fraction, 0.0, 0.0);
+            } else {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let shifted = infectious_fractions[(t - incubation_period) as usize];
+                assert_almost_eq!(fraction, shifted, 1e-12);
+            }
+        }
+    }
+}
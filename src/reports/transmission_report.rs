@@ -1,8 +1,7 @@
 use crate::infectiousness_manager::{InfectionData, InfectionDataValue};
+use crate::reports::{is_in_report_cohort, report_sink::ContextReportSinkExt};
 use ixa::profiling::open_span;
-use ixa::{
-    define_report, report::ContextReportExt, Context, IxaError, PersonId, PersonPropertyChangeEvent,
-};
+use ixa::{define_report, Context, IxaError, PersonId, PersonPropertyChangeEvent};
 use serde::{Deserialize, Serialize};
 use std::string::ToString;
 
@@ -13,6 +12,7 @@ struct TransmissionReport {
     infected_by: Option<PersonId>,
     infection_setting_type: Option<String>,
     infection_setting_id: Option<usize>,
+    generation_interval: Option<f64>,
 }
 
 define_report!(TransmissionReport);
@@ -23,14 +23,20 @@ fn record_transmission_event(
     infected_by: Option<PersonId>,
     infection_setting_type: Option<String>,
     infection_setting_id: Option<usize>,
+    generation_interval: Option<f64>,
 ) {
-    if infected_by.is_some() {
-        context.send_report(TransmissionReport {
+    let Some(infector) = infected_by else {
+        return;
+    };
+    // With a sampled report cohort, only transmission involving a cohort member is reported
+    if is_in_report_cohort(context, target_id) || is_in_report_cohort(context, infector) {
+        context.send_report_to_sink(TransmissionReport {
             time: context.get_current_time(),
             target_id,
             infected_by,
             infection_setting_type,
             infection_setting_id,
+            generation_interval,
         });
     }
 }
@@ -39,13 +45,14 @@ fn record_transmission_event(
 ///
 /// Will return `IxaError` if the report cannot be added
 pub fn init(context: &mut Context, file_name: &str) -> Result<(), IxaError> {
-    context.add_report::<TransmissionReport>(file_name)?;
+    context.add_report_with_sink::<TransmissionReport>(file_name)?;
     context.subscribe_to_event::<PersonPropertyChangeEvent<InfectionData>>(|context, event| {
         let _span = open_span("transmission_report");
         if let InfectionDataValue::Infectious {
             infected_by,
             infection_setting_type,
             infection_setting_id,
+            generation_interval,
             ..
         } = event.current
         {
@@ -55,6 +62,7 @@ pub fn init(context: &mut Context, file_name: &str) -> Result<(), IxaError> {
                 infected_by,
                 infection_setting_type.map(ToString::to_string),
                 infection_setting_id,
+                generation_interval,
             );
         }
     });
@@ -67,13 +75,16 @@ mod test {
         infectiousness_manager::InfectionContextExt,
         parameters::{ContextParametersExt, GlobalParams, Params},
         rate_fns::load_rate_fns,
-        reports::ReportParams,
+        reports::{
+            report_sink::{ContextReportSinkExt, ReportSink},
+            InReportCohort, ReportParams,
+        },
     };
     use ixa::assert_almost_eq;
     use ixa::{
         Context, ContextGlobalPropertiesExt, ContextPeopleExt, ContextRandomExt, ContextReportExt,
     };
-    use std::path::PathBuf;
+    use std::{cell::RefCell, path::PathBuf, rc::Rc};
     use tempfile::tempdir;
 
     fn setup_context_with_report(transmission_report: ReportParams) -> Context {
@@ -99,6 +110,11 @@ mod test {
             write: true,
             filename: Some("output.csv".to_string()),
             period: None,
+            cumulative: false,
+            by_setting_type: false,
+            age_groups: None,
+            compress: None,
+            include_cumulative: false,
         });
 
         let temp_dir = tempdir().unwrap();
@@ -146,8 +162,80 @@ mod test {
                 Some("test_setting".to_string())
             );
             assert_eq!(record.infection_setting_id, setting_id);
+            // The source was infected at time 0
+            assert_almost_eq!(record.generation_interval.unwrap(), infection_time, 0.0);
             line_count += 1;
         }
         assert_eq!(line_count, 1);
     }
+
+    #[test]
+    fn test_report_sample_fraction_restricts_transmission_report_to_cohort() {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 10.0,
+                    transmission_report: ReportParams {
+                        write: true,
+                        filename: Some("transmission.csv".to_string()),
+                        period: None,
+                        cumulative: false,
+                        by_setting_type: false,
+                        age_groups: None,
+                        compress: None,
+                        include_cumulative: false,
+                    },
+                    report_sample_fraction: Some(0.3),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        context.init_random(context.get_params().seed);
+        load_rate_fns(&mut context).unwrap();
+        // The cohort is sampled from the people who exist when the reports are initialized
+        let people: Vec<_> = (0..200).map(|_| context.add_person(()).unwrap()).collect();
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        context.set_report_sink("transmission.csv", ReportSink::Memory(Rc::clone(&buffer)));
+        crate::reports::init(&mut context).unwrap();
+
+        // Each person in the first half of the population infects one person in the second half
+        let (infectors, targets) = people.split_at(100);
+        for (&infector, &target) in infectors.iter().zip(targets) {
+            context.infect_person(infector, None, None, None);
+            context.add_plan(1.0, move |context| {
+                context.infect_person(target, Some(infector), None, None);
+            });
+        }
+        context.execute();
+        let expected_targets: Vec<_> = infectors
+            .iter()
+            .zip(targets)
+            .filter(|&(&infector, &target)| {
+                context.get_person_property(infector, InReportCohort)
+                    || context.get_person_property(target, InReportCohort)
+            })
+            .map(|(_, &target)| target)
+            .collect();
+        let cohort_size = people
+            .iter()
+            .filter(|&&person| context.get_person_property(person, InReportCohort))
+            .count();
+        std::mem::drop(context);
+
+        assert!(cohort_size > 0 && cohort_size < people.len());
+        let buffer = buffer.borrow();
+        let mut reader = csv::Reader::from_reader(buffer.as_slice());
+        let reported_targets: Vec<_> = reader
+            .deserialize()
+            .map(|result| {
+                let record: super::TransmissionReport = result.unwrap();
+                record.target_id
+            })
+            .collect();
+        assert!(!reported_targets.is_empty());
+        assert!(reported_targets.len() < infectors.len());
+        assert_eq!(reported_targets, expected_targets);
+    }
 }
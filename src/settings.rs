@@ -1,6 +1,7 @@
 use crate::parameters::{
     ContextParametersExt, CoreSettingsTypes, ItinerarySpecificationType, Params,
 };
+use crate::utils::calendar::{day_of_week, is_weekend, ContextCalendarExt};
 
 use indexmap::set::IndexSet;
 use ixa::{
@@ -9,7 +10,7 @@ use ixa::{
 };
 use serde::{Deserialize, Serialize};
 
-use std::{any::TypeId, hash::Hash};
+use std::{any::TypeId, hash::Hash, path::PathBuf};
 
 use dyn_clone::DynClone;
 
@@ -22,6 +23,122 @@ pub struct SettingProperties {
     pub itinerary_specification: Option<ItinerarySpecificationType>,
 }
 
+/// A global calendar of non-activity days on which workplaces and schools (including classrooms)
+/// are closed. Day `d` covers the simulation times in `[d, d + 1)`.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ActivityCalendar {
+    /// Whether workplaces and schools are closed on weekends, Saturday and Sunday given the
+    /// `start_day_of_week` of day 0
+    pub weekends: bool,
+    /// Additional days on which workplaces and schools are closed, in increasing order
+    pub holidays: Vec<usize>,
+}
+
+impl ActivityCalendar {
+    /// Whether `time` falls on a non-activity day when day 0 falls on `start_day_of_week`
+    #[allow(clippy::cast_possible_truncation)]
+    #[must_use]
+    pub fn is_non_activity_day(&self, time: f64, start_day_of_week: u8) -> bool {
+        if self.weekends && is_weekend(day_of_week(time, start_day_of_week)) {
+            return true;
+        }
+        let day = time.floor() as i64;
+        usize::try_from(day).is_ok_and(|day| self.holidays.binary_search(&day).is_ok())
+    }
+}
+
+/// The properties the model uses for a registered setting type once every setting input has been
+/// applied, for checking the configuration before the simulation runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedSettingProperties {
+    pub setting_type: String,
+    pub alpha: f64,
+    /// The constant ratio of the setting type in default itineraries, if it has one
+    pub ratio: Option<f64>,
+    /// The explicit contact process that replaces the `(N - 1)^alpha` multiplier, if any
+    pub contact_rate: Option<SettingContactRate>,
+    /// Whether a daily activity profile scales the setting type's multiplier
+    pub daily_activity_profile: bool,
+    /// Whether settings of the type are closed on the non-activity days of the activity calendar
+    pub activity_calendar: bool,
+}
+
+/// An activity profile over the 24 hours of each day that scales the infectiousness multiplier of
+/// a setting category (e.g., workplaces that are only active from 9 to 5). Hour `h` of day `d`
+/// covers the simulation times in `[d + h / 24, d + (h + 1) / 24)`.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct DailyActivityProfile {
+    /// The multiplier of each hour of the day, between 0 and 1
+    pub hourly_multipliers: Vec<f64>,
+}
+
+impl DailyActivityProfile {
+    /// The multiplier of the hour of the day that `time` falls in
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    #[must_use]
+    pub fn multiplier(&self, time: f64) -> f64 {
+        let hour = ((time.rem_euclid(1.0) * 24.0).floor() as usize).min(23);
+        self.hourly_multipliers[hour]
+    }
+}
+
+/// An explicit contact process for a setting category, used instead of the `(N - 1)^alpha`
+/// multiplier. People in a setting of the category make `contacts_per_day` contacts per unit time
+/// with the other members, and each contact transmits with probability `transmission_probability`
+/// scaled by the infector's intrinsic infectiousness.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub struct SettingContactRate {
+    pub contacts_per_day: f64,
+    pub transmission_probability: f64,
+}
+
+/// A kernel that weights contacts with people in other census tracts by the distance between the
+/// tracts. Contacts in a person's own tract, at distance zero, have a weight of one.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub enum DistanceKernel {
+    /// A weight of `exp(-distance / length_scale)`
+    Exponential { length_scale: f64 },
+    /// A weight of `(1 + distance / length_scale)^(-exponent)`
+    PowerLaw { length_scale: f64, exponent: f64 },
+}
+
+impl DistanceKernel {
+    /// The weight of contacts with people in a tract at `distance`
+    #[must_use]
+    pub fn weight(&self, distance: f64) -> f64 {
+        match *self {
+            DistanceKernel::Exponential { length_scale } => (-distance / length_scale).exp(),
+            DistanceKernel::PowerLaw {
+                length_scale,
+                exponent,
+            } => (1.0 + distance / length_scale).powf(-exponent),
+        }
+    }
+}
+
+/// The coordinates of the census tracts and the kernel that weights contacts across tracts by
+/// the distance between them
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct CensusTractKernel {
+    /// A CSV file with `tract_id`, `x`, and `y` columns giving the coordinates of each tract
+    pub coordinates_file: PathBuf,
+    pub kernel: DistanceKernel,
+}
+
+#[derive(Deserialize, Debug)]
+struct TractCoordinates {
+    tract_id: usize,
+    x: f64,
+    y: f64,
+}
+
+// The tracts listed in the census tract coordinates file and, for each of them, the cumulative
+// kernel weights of contacts in each listed tract
+struct TractContactWeights {
+    tract_ids: Vec<usize>,
+    cumulative_weights: HashMap<usize, Vec<f64>>,
+}
+
 pub trait SettingCategory: std::fmt::Debug + 'static {
     fn get_type_id(&self) -> std::any::TypeId;
 }
@@ -40,7 +157,7 @@ where
     fn id(&self) -> usize;
     fn calculate_multiplier(
         &self,
-        members: &IndexSet<PersonId>,
+        member_count: usize,
         setting_properties: SettingProperties,
     ) -> f64;
     fn get_category_id(&self) -> &'static str;
@@ -62,10 +179,10 @@ impl<T: SettingCategory + Clone> AnySettingId for SettingId<T> {
     #[allow(clippy::cast_precision_loss)]
     fn calculate_multiplier(
         &self,
-        members: &IndexSet<PersonId>,
+        member_count: usize,
         setting_properties: SettingProperties,
     ) -> f64 {
-        ((members.len() - 1) as f64).powf(setting_properties.alpha)
+        ((member_count - 1) as f64).powf(setting_properties.alpha)
     }
     fn get_category_id(&self) -> &'static str {
         std::any::type_name::<T>()
@@ -104,11 +221,34 @@ impl ItineraryEntry {
 #[allow(dead_code)]
 pub enum ItineraryModifiers<'a> {
     // Replace itinerary with a new vector of itinerary entries
-    ReplaceWith { itinerary: Vec<ItineraryEntry> },
-    // Reduce the current itinerary to a setting type (e.g., Home)
-    RestrictTo { setting: &'a dyn SettingCategory },
+    ReplaceWith {
+        itinerary: Vec<ItineraryEntry>,
+    },
+    // Reduce the current itinerary to a setting type (e.g., Home), or to a single setting of that
+    // type when a setting id is provided (e.g., a person's primary home)
+    RestrictTo {
+        setting: &'a dyn SettingCategory,
+        setting_id: Option<usize>,
+    },
+    // Like `RestrictTo`, but every other setting keeps its ratio scaled by `leakage` (e.g., leaky
+    // isolation at home), and the time freed up is spent in the kept settings in proportion to
+    // their ratios. The ratios still sum to one, so the person's transmission in every other
+    // setting is exactly `leakage` times its value in the default itinerary.
+    LeakyRestrictTo {
+        setting: &'a dyn SettingCategory,
+        setting_id: Option<usize>,
+        leakage: f64,
+    },
     // Exclude setting types from current itinerary (e.g., Workplace)
-    Exclude { setting: &'a dyn SettingCategory },
+    Exclude {
+        setting: &'a dyn SettingCategory,
+    },
+    // Scale the ratio of a setting type in the current itinerary by a non-negative factor
+    // (e.g., partially reopened workplaces). The itinerary is renormalized after scaling.
+    ScaleSetting {
+        setting: &'a dyn SettingCategory,
+        factor: f64,
+    },
 }
 
 pub fn append_itinerary_entry(
@@ -134,6 +274,83 @@ pub fn append_itinerary_entry(
     Ok(())
 }
 
+/// Append entries for a classroom and its parent school to an itinerary, so that a student is a
+/// member of both and transmission occurs at both levels. Ratios default to the constant ratios
+/// of the `School` and `Classroom` categories if not specified. As in `append_itinerary_entry`,
+/// entries of unregistered setting categories are skipped.
+/// # Errors
+/// - If the classroom has not been linked to a school
+/// - If a ratio is not specified and the category has no itinerary specification
+#[allow(dead_code)]
+pub fn append_classroom_itinerary_entries(
+    itinerary: &mut Vec<ItineraryEntry>,
+    context: &Context,
+    classroom_id: usize,
+    school_ratio: Option<f64>,
+    classroom_ratio: Option<f64>,
+) -> Result<(), IxaError> {
+    let Some(school_id) = context.get_classroom_school_id(classroom_id) else {
+        return Err(IxaError::IxaError(format!(
+            "Classroom {classroom_id} is not linked to a school"
+        )));
+    };
+    append_itinerary_entry(
+        itinerary,
+        context,
+        SettingId::new(School, school_id),
+        school_ratio,
+    )?;
+    append_itinerary_entry(
+        itinerary,
+        context,
+        SettingId::new(Classroom, classroom_id),
+        classroom_ratio,
+    )
+}
+
+/// Build the default itinerary of a person in the core settings: their home, census tract, and,
+/// if they have them, their school and workplace. As in `append_itinerary_entry`, entries of
+/// unregistered setting categories are skipped and ratios are the constant ratios of each
+/// category. The ratios of the returned itinerary are normalized to sum to one.
+/// # Errors
+/// - If a registered category has no itinerary specification
+pub fn build_default_itinerary(
+    context: &Context,
+    home_id: usize,
+    workplace_id: Option<usize>,
+    tract_id: usize,
+    school_id: Option<usize>,
+) -> Result<Vec<ItineraryEntry>, IxaError> {
+    let mut itinerary = vec![];
+    append_itinerary_entry(&mut itinerary, context, SettingId::new(Home, home_id), None)?;
+    append_itinerary_entry(
+        &mut itinerary,
+        context,
+        SettingId::new(CensusTract, tract_id),
+        None,
+    )?;
+    if let Some(school_id) = school_id {
+        append_itinerary_entry(
+            &mut itinerary,
+            context,
+            SettingId::new(School, school_id),
+            None,
+        )?;
+    }
+    if let Some(workplace_id) = workplace_id {
+        append_itinerary_entry(
+            &mut itinerary,
+            context,
+            SettingId::new(Workplace, workplace_id),
+            None,
+        )?;
+    }
+    if itinerary.is_empty() {
+        return Ok(itinerary);
+    }
+    context.normalize_default_itinerary(itinerary)
+}
+
 // In the future, this method could take the person id as an argument for making individual-level
 // itineraries.
 fn get_itinerary_ratio(context: &Context, setting: &dyn AnySettingId) -> Result<f64, IxaError> {
@@ -163,6 +380,29 @@ struct SettingDataContainer {
     all_members: HashMap<(TypeId, usize), IndexSet<PersonId>>,
     itineraries: HashMap<PersonId, Vec<ItineraryEntry>>,
     modified_itineraries: HashMap<PersonId, Vec<ItineraryEntry>>,
+    // The default itinerary ratios of people whose setting activity has been toggled, so that the
+    // ratio of a setting can be restored when it is reactivated
+    base_ratios: HashMap<PersonId, Vec<f64>>,
+    // People whose itineraries are held fixed against itinerary modifiers
+    pinned: HashSet<PersonId>,
+    // The modified itinerary each person who has moved into a setting (e.g., a hospital ward)
+    // would otherwise have, which is restored when they return from the setting
+    held_itineraries: HashMap<PersonId, Option<Vec<ItineraryEntry>>>,
+    // People who have been removed from all of their settings for the rest of the simulation
+    cleared: HashSet<PersonId>,
+    // The id of the parent school of each classroom
+    classroom_schools: HashMap<usize, usize>,
+    // The days on which workplaces and schools are closed
+    activity_calendar: Option<ActivityCalendar>,
+    // The activity profile over each day of setting types with diurnal activity
+    daily_activity_profiles: HashMap<TypeId, DailyActivityProfile>,
+    // The explicit contact process of setting types parameterized by contacts per unit time
+    contact_rates: HashMap<TypeId, SettingContactRate>,
+    // The name of each registered setting type (e.g., "Home")
+    setting_category_names: HashMap<TypeId, String>,
+    // The weights used to sample the tract of contacts made in a census tract, if census tract
+    // contacts are modulated by a distance kernel
+    tract_contact_weights: Option<TractContactWeights>,
 }
 
 #[derive(Clone, Copy)]
@@ -207,6 +447,16 @@ impl SettingDataContainer {
     fn get_all_setting_members(&self, setting: &dyn AnySettingId) -> Option<&IndexSet<PersonId>> {
         self.all_members.get(&setting.get_tuple_id())
     }
+    // Reads the number of members directly from the membership map so that callers that only need
+    // the count don't have to hold onto the set of members
+    fn get_setting_member_count(
+        &self,
+        setting: &dyn AnySettingId,
+        selector: MembershipSelector,
+    ) -> usize {
+        self.get_setting_members(setting, selector)
+            .map_or(0, IndexSet::len)
+    }
     fn get_default_itinerary(&self, person_id: PersonId) -> Option<&Vec<ItineraryEntry>> {
         self.itineraries.get(&person_id)
     }
@@ -238,7 +488,7 @@ impl SettingDataContainer {
         membership_selector: MembershipSelector,
         mut callback: F,
     ) where
-        F: FnMut(&dyn AnySettingId, &SettingProperties, &IndexSet<PersonId>, f64),
+        F: FnMut(&dyn AnySettingId, &SettingProperties, usize, f64),
     {
         if let Some(itinerary) = self.get_itinerary(person_id, itinerary_selector) {
             for entry in itinerary {
@@ -247,11 +497,27 @@ impl SettingDataContainer {
                     .setting_properties
                     .get(&entry.setting.get_type_id())
                     .unwrap();
-                let members = self
-                    .get_setting_members(setting, membership_selector)
-                    .unwrap();
-                callback(setting, setting_props, members, entry.ratio);
+                let member_count = self.get_setting_member_count(setting, membership_selector);
+                callback(setting, setting_props, member_count, entry.ratio);
+            }
+        }
+    }
+    /// The infectiousness multiplier of a setting with `member_count` members. This is
+    /// `(N - 1)^alpha`, unless the setting type has a contact rate, in which case it is the
+    /// contacts per unit time times the per-contact transmission probability whenever there is
+    /// anyone else in the setting to contact.
+    fn calculate_setting_multiplier(
+        &self,
+        setting: &dyn AnySettingId,
+        setting_props: SettingProperties,
+        member_count: usize,
+    ) -> f64 {
+        match self.contact_rates.get(&setting.get_type_id()) {
+            Some(contact_rate) if member_count > 1 => {
+                contact_rate.contacts_per_day * contact_rate.transmission_probability
             }
+            Some(_) => 0.0,
+            None => setting.calculate_multiplier(member_count, setting_props),
         }
     }
     fn activate_itinerary(
@@ -328,6 +594,68 @@ impl SettingDataContainer {
         }
     }
 
+    /// Make `itinerary` the default itinerary of `person_id`, replacing any previous default
+    /// itinerary. The itinerary must already be validated and normalized.
+    fn set_default_itinerary(&mut self, person_id: PersonId, itinerary: Vec<ItineraryEntry>) {
+        // Clean up settings that from previous itinerary, if there is one
+        if let Some(previous_itinerary) = self.itineraries.get(&person_id) {
+            self.deactivate_itinerary(person_id, previous_itinerary.clone());
+        }
+
+        // A modified itinerary determines membership until it is removed, at which point the new
+        // default itinerary is activated. Setting types were checked when the itineraries were
+        // validated, so activation cannot fail.
+        let active_itinerary = self
+            .modified_itineraries
+            .get(&person_id)
+            .cloned()
+            .unwrap_or_else(|| itinerary.clone());
+        self.activate_itinerary(person_id, &active_itinerary)
+            .expect("Itinerary setting types are registered");
+        self.itineraries.insert(person_id, itinerary);
+        self.base_ratios.remove(&person_id);
+    }
+
+    /// Make each itinerary the default itinerary of a person who does not have an itinerary yet.
+    /// Memberships are grouped by setting so that the member sets of each setting are looked up
+    /// once, and people are added to each set in the same order as sequential activation would.
+    fn add_new_default_itineraries(&mut self, itineraries: Vec<(PersonId, Vec<ItineraryEntry>)>) {
+        let _span = open_span("add new default itineraries");
+        let mut memberships: HashMap<(TypeId, usize), Vec<(PersonId, bool)>> = HashMap::new();
+        for (person_id, itinerary) in &itineraries {
+            for entry in itinerary {
+                memberships
+                    .entry(entry.setting.get_tuple_id())
+                    .or_default()
+                    .push((*person_id, entry.ratio > 0.0));
+            }
+        }
+        for (setting_identifier, members) in memberships {
+            let active = self.active_members.entry(setting_identifier).or_default();
+            active.extend(
+                members
+                    .iter()
+                    .filter(|(_, is_active)| *is_active)
+                    .map(|(person_id, _)| *person_id),
+            );
+            let inactive = self.inactive_members.entry(setting_identifier).or_default();
+            inactive.extend(
+                members
+                    .iter()
+                    .filter(|(_, is_active)| !*is_active)
+                    .map(|(person_id, _)| *person_id),
+            );
+            self.all_members
+                .entry(setting_identifier)
+                .or_default()
+                .extend(members.into_iter().map(|(person_id, _)| person_id));
+        }
+        for (person_id, itinerary) in itineraries {
+            self.base_ratios.remove(&person_id);
+            self.itineraries.insert(person_id, itinerary);
+        }
+    }
+
     fn deactivate_itinerary(&mut self, person_id: PersonId, itinerary: Vec<ItineraryEntry>) {
         let _span = open_span("deactivate itinerary");
         for itinerary_entry in itinerary {
@@ -354,6 +682,10 @@ define_setting_category!(Home);
 define_setting_category!(CensusTract);
 define_setting_category!(School);
 define_setting_category!(Workplace);
+// A cohort of students within a school, linked to its parent school
+define_setting_category!(Classroom);
+// A hospital ward, which people only join while they are hospitalized
+define_setting_category!(Hospital);
 
 define_data_plugin!(
     SettingDataPlugin,
@@ -361,7 +693,7 @@ define_data_plugin!(
     SettingDataContainer::default()
 );
 
-trait ContextSettingInternalExt: PluginContext + ContextRandomExt {
+trait ContextSettingInternalExt: PluginContext + ContextRandomExt + ContextCalendarExt {
     /// Takes an itinerary and adds makes it the modified itinerary of `person id`
     /// This modified itinerary is used as the person's itinerary instead of default itinerary
     /// for as long as modified itinerary exists in the container.
@@ -382,6 +714,18 @@ trait ContextSettingInternalExt: PluginContext + ContextRandomExt {
         }
         let container = self.get_data_mut(SettingDataPlugin);
 
+        // People who have moved into a setting stay there, and the modified itinerary is applied
+        // when they return from it
+        if let Some(held_itinerary) = container.held_itineraries.get_mut(&person_id) {
+            if held_itinerary.is_some() {
+                return Err(IxaError::from(
+                    "Can't modify itinerary because a modified itinerary is already present. Remove and add new modified itinerary.",
+                ));
+            }
+            *held_itinerary = Some(itinerary);
+            return Ok(());
+        }
+
         // If there's a modified itinerary present, replace with this
         if container.modified_itineraries.contains_key(&person_id) {
             return Err(IxaError::from(
@@ -409,6 +753,83 @@ trait ContextSettingInternalExt: PluginContext + ContextRandomExt {
         Ok(())
     }
 
+    /// Toggle whether a person is active in the settings at `indices` of their default itinerary.
+    /// See `set_setting_active`.
+    fn set_itinerary_entries_active(
+        &mut self,
+        person_id: PersonId,
+        indices: &[usize],
+        active: bool,
+    ) -> Result<(), IxaError> {
+        let container = self.get_data_mut(SettingDataPlugin);
+        let Some(itinerary) = container.itineraries.get(&person_id) else {
+            return Err(IxaError::from("Can't find itinerary for person"));
+        };
+        let mut is_active: Vec<bool> = itinerary.iter().map(|entry| entry.ratio > 0.0).collect();
+        for &index in indices {
+            is_active[index] = active;
+        }
+
+        let mut base_ratios = container
+            .base_ratios
+            .get(&person_id)
+            .cloned()
+            .unwrap_or_else(|| itinerary.iter().map(|entry| entry.ratio).collect());
+        for &index in indices {
+            if active && base_ratios[index] <= 0.0 {
+                match container
+                    .setting_properties
+                    .get(&itinerary[index].setting.get_type_id())
+                    .and_then(|properties| properties.itinerary_specification)
+                {
+                    Some(ItinerarySpecificationType::Constant { ratio }) => {
+                        base_ratios[index] = ratio;
+                    }
+                    None => {
+                        return Err(IxaError::from(
+                            "Can't activate a setting without a ratio to restore when its itinerary specification is None",
+                        ))
+                    }
+                }
+            }
+        }
+
+        let total_ratio: f64 = base_ratios
+            .iter()
+            .zip(&is_active)
+            .filter(|(_, is_active)| **is_active)
+            .map(|(ratio, _)| ratio)
+            .sum();
+        if total_ratio <= 0.0 {
+            return Err(IxaError::from(
+                "Can't deactivate every setting in a person's itinerary",
+            ));
+        }
+        let new_ratios: Vec<f64> = base_ratios
+            .iter()
+            .zip(&is_active)
+            .map(|(ratio, &is_active)| if is_active { ratio / total_ratio } else { 0.0 })
+            .collect();
+        container.base_ratios.insert(person_id, base_ratios);
+
+        let itinerary = container.itineraries.get_mut(&person_id).unwrap();
+        for (entry, &ratio) in itinerary.iter_mut().zip(&new_ratios) {
+            entry.ratio = ratio;
+        }
+        // A modified itinerary determines membership until it is removed, at which point the
+        // updated default itinerary is reactivated
+        if !container.modified_itineraries.contains_key(&person_id) {
+            let toggled: Vec<(f64, (TypeId, usize))> = indices
+                .iter()
+                .map(|&index| (new_ratios[index], itinerary[index].setting.get_tuple_id()))
+                .collect();
+            for (ratio, setting_identifier) in toggled {
+                container.set_member_activity(person_id, ratio, setting_identifier);
+            }
+        }
+        Ok(())
+    }
+
     fn exclude_setting_from_itinerary(
         &mut self,
         person_id: PersonId,
@@ -437,22 +858,105 @@ trait ContextSettingInternalExt: PluginContext + ContextRandomExt {
             }
         }
     }
+    /// Scale the ratio of a specified setting type (e.g., Workplace) by `factor`
+    /// The ratios of the rest of the settings are unchanged before renormalization
+    fn scale_setting_in_itinerary(
+        &mut self,
+        person_id: PersonId,
+        setting: &dyn SettingCategory,
+        factor: f64,
+    ) -> Result<(), IxaError> {
+        if factor < 0.0 {
+            return Err(IxaError::from("Setting scale factor must be non-negative"));
+        }
+        let container = self.get_data_mut(SettingDataPlugin);
+        match container.itineraries.get(&person_id) {
+            None => Err(IxaError::from("Can't find itinerary for person")),
+            Some(itinerary_vector) => {
+                let mut modified_itinerary = Vec::<ItineraryEntry>::new();
+                for entry in itinerary_vector {
+                    let mut new_entry = entry.clone();
+                    if entry.setting.get_type_id() == setting.get_type_id() {
+                        new_entry.ratio *= factor;
+                    }
+                    modified_itinerary.push(new_entry);
+                }
+                if modified_itinerary.iter().all(|entry| entry.ratio <= 0.0) {
+                    return Err(IxaError::from(
+                        "Scale setting resulted in an itinerary without time in any setting",
+                    ));
+                }
+
+                self.add_modified_itinerary(person_id, modified_itinerary, false)?;
+                Ok(())
+            }
+        }
+    }
     /// Limit the current itinerary to a specified setting type (e.g., Home)
+    /// If a setting id is provided, only the setting of that type with that id is kept
     /// The proportion of the rest of the settings remains unchanged
     fn limit_itinerary_by_setting_category(
         &mut self,
         person_id: PersonId,
         setting: &dyn SettingCategory,
+        setting_id: Option<usize>,
+    ) -> Result<(), IxaError> {
+        self.limit_itinerary_with_leakage(person_id, setting, setting_id, 0.0)
+    }
+    /// Limit the current itinerary to a specified setting type as in
+    /// `limit_itinerary_by_setting_category`, but scale the ratios of the rest of the settings by
+    /// `leakage` instead of removing them. The time freed up is added to the kept settings in
+    /// proportion to their ratios, so the ratios of the default itinerary still sum to one.
+    fn limit_itinerary_with_leakage(
+        &mut self,
+        person_id: PersonId,
+        setting: &dyn SettingCategory,
+        setting_id: Option<usize>,
+        leakage: f64,
     ) -> Result<(), IxaError> {
+        if !(0.0..=1.0).contains(&leakage) {
+            return Err(IxaError::from("Itinerary leakage must be between 0 and 1"));
+        }
         let container = self.get_data_mut(SettingDataPlugin);
         match container.itineraries.get(&person_id) {
             None => Err(IxaError::from("Can't find itinerary for person")),
             Some(itineraries) => {
+                if let Some(setting_id) = setting_id {
+                    if !itineraries.iter().any(|entry| {
+                        entry.setting.get_type_id() == setting.get_type_id()
+                            && entry.setting.id() == setting_id
+                    }) {
+                        return Err(IxaError::from(
+                            "Can't restrict itinerary to a setting id not in the person's itinerary",
+                        ));
+                    }
+                }
+                let is_kept = |entry: &ItineraryEntry| {
+                    entry.setting.get_type_id() == setting.get_type_id()
+                        && setting_id.is_none_or(|id| entry.setting.id() == id)
+                };
+                let kept_ratio: f64 = itineraries
+                    .iter()
+                    .filter(|entry| is_kept(entry))
+                    .map(|entry| entry.ratio)
+                    .sum();
+                let freed_ratio: f64 = itineraries
+                    .iter()
+                    .filter(|entry| !is_kept(entry))
+                    .map(|entry| entry.ratio * (1.0 - leakage))
+                    .sum();
+                if leakage > 0.0 && kept_ratio <= 0.0 {
+                    return Err(IxaError::from(
+                        "Can't restrict itinerary with leakage to a setting type without time in the person's itinerary",
+                    ));
+                }
                 let mut modified_itinerary = Vec::<ItineraryEntry>::new();
                 for entry in itineraries {
                     let mut new_entry = entry.clone();
-                    if entry.setting.get_type_id() != setting.get_type_id() {
-                        new_entry.ratio = 0.0;
+                    if !is_kept(entry) {
+                        new_entry.ratio *= leakage;
+                    } else if kept_ratio > 0.0 {
+                        new_entry.ratio *= 1.0 + freed_ratio / kept_ratio;
                     }
                     modified_itinerary.push(new_entry);
                 }
@@ -493,6 +997,31 @@ trait ContextSettingInternalExt: PluginContext + ContextRandomExt {
         Ok(())
     }
 
+    /// Validate a default itinerary and normalize its ratios to sum to one
+    fn normalize_default_itinerary(
+        &self,
+        mut itinerary: Vec<ItineraryEntry>,
+    ) -> Result<Vec<ItineraryEntry>, IxaError> {
+        self.validate_itinerary(&itinerary)?;
+        let setting_categories = &self.get_data(SettingDataPlugin).setting_categories;
+        if itinerary
+            .iter()
+            .any(|entry| !setting_categories.contains(&entry.setting.get_type_id()))
+        {
+            return Err(IxaError::from(
+                "Itinerary entry setting type not registered",
+            ));
+        }
+
+        let total_ratio: f64 = itinerary.iter().map(|entry| entry.ratio).sum();
+        // If we passed validation, we know setting entries aren't all zero, so we can divide by
+        // total_ratio without worrying about dividing by zero.
+        for entry in &mut itinerary {
+            entry.ratio /= total_ratio;
+        }
+        Ok(itinerary)
+    }
+
     fn get_setting_members_internal(
         &self,
         setting: &dyn AnySettingId,
@@ -502,6 +1031,21 @@ trait ContextSettingInternalExt: PluginContext + ContextRandomExt {
             .get_setting_members(setting, selector)
     }
 
+    /// Sample the census tract of a contact made in `tract_id` according to the distance kernel.
+    /// Returns `None` if there is no kernel or the tract is not in the coordinates file, in which
+    /// case the contact is made in the tract itself.
+    fn sample_contact_tract(&self, tract_id: usize) -> Option<usize> {
+        let weights = self
+            .get_data(SettingDataPlugin)
+            .tract_contact_weights
+            .as_ref()?;
+        let cumulative_weights = weights.cumulative_weights.get(&tract_id)?;
+        let total_weight = *cumulative_weights.last()?;
+        let draw = self.sample_range(SettingsRng, 0.0..total_weight);
+        let index = cumulative_weights.partition_point(|&weight| weight <= draw);
+        Some(weights.tract_ids[index])
+    }
+
     fn sample_active_setting_members(&self, setting: &dyn AnySettingId) -> Option<PersonId> {
         if let Some(members) = self
             .get_data(SettingDataPlugin)
@@ -524,9 +1068,41 @@ trait ContextSettingInternalExt: PluginContext + ContextRandomExt {
         self.get_data(SettingDataPlugin)
             .get_itinerary(person_id, selector)
     }
+
+    /// Whether the current time falls on a non-activity day of the activity calendar
+    fn is_non_activity_day(&self) -> bool {
+        self.get_data(SettingDataPlugin)
+            .activity_calendar
+            .as_ref()
+            .is_some_and(|calendar| {
+                calendar.is_non_activity_day(self.get_current_time(), self.get_start_day_of_week())
+            })
+    }
+
+    /// The multiplier of each setting type with a daily activity profile at the current time
+    fn get_daily_activity_multipliers(&self) -> HashMap<TypeId, f64> {
+        let current_time = self.get_current_time();
+        self.get_data(SettingDataPlugin)
+            .daily_activity_profiles
+            .iter()
+            .map(|(type_id, profile)| (*type_id, profile.multiplier(current_time)))
+            .collect()
+    }
 }
 impl ContextSettingInternalExt for Context {}
 
+/// Whether a setting is closed on the non-activity days of the activity calendar
+fn follows_activity_calendar(setting: &dyn AnySettingId) -> bool {
+    type_follows_activity_calendar(setting.get_type_id())
+}
+
+/// Whether settings of a type are closed on the non-activity days of the activity calendar
+fn type_follows_activity_calendar(type_id: TypeId) -> bool {
+    type_id == TypeId::of::<Workplace>()
+        || type_id == TypeId::of::<School>()
+        || type_id == TypeId::of::<Classroom>()
+}
+
 fn identical_settings(
     itinerary_0: &Vec<ItineraryEntry>,
     itinerary_1: &Vec<ItineraryEntry>,
@@ -580,6 +1156,9 @@ pub trait ContextSettingExt:
         container
             .setting_properties
             .insert(setting.get_type_id(), setting_props);
+        container
+            .setting_category_names
+            .insert(setting.get_type_id(), format!("{setting:?}"));
         Ok(())
     }
 
@@ -587,6 +1166,15 @@ pub trait ContextSettingExt:
         let _span = open_span("remove_modified_itinerary");
 
         let container = self.get_data_mut(SettingDataPlugin);
+        if container.pinned.contains(&person_id) || container.cleared.contains(&person_id) {
+            trace!("Person {person_id} has a pinned or cleared itinerary, so its modifier is not removed");
+            return Ok(());
+        }
+        if let Some(held_itinerary) = container.held_itineraries.get_mut(&person_id) {
+            trace!("Person {person_id} has moved into a setting, so its held modifier is removed");
+            *held_itinerary = None;
+            return Ok(());
+        }
 
         // If there's a modified itinerary present, remove
         if let Some(previous_mod_itinerary) = container.modified_itineraries.get(&person_id) {
@@ -612,26 +1200,51 @@ pub trait ContextSettingExt:
         itinerary_modifier: ItineraryModifiers,
     ) -> Result<(), IxaError> {
         let _span = open_span("modify_itinerary");
+        if self.is_itinerary_pinned(person_id) || self.is_itinerary_cleared(person_id) {
+            trace!("Person {person_id} has a pinned or cleared itinerary, so it is not modified");
+            return Ok(());
+        }
         let result = match itinerary_modifier {
             ItineraryModifiers::ReplaceWith { itinerary } => {
                 trace!("ItineraryModifier::Replace person {person_id} --  {itinerary:?}");
 
                 // The model currtently assumes that people cannot change the settings of their itinerary, only the ratios of those settings.
-                // We therefore assert that the settings are identical for this `ItineraryModifiers::ReplaceWith` as it is the only
+                // We therefore require that the settings are identical for this `ItineraryModifiers::ReplaceWith` as it is the only
                 // modifier that could feasibly change settings ids between default and modified itineraries.
-                assert!(identical_settings(
+                let Some(default_itinerary) =
                     self.get_itinerary(person_id, ItinerarySelector::Default)
-                        .unwrap(),
-                    &itinerary
-                ));
+                else {
+                    return Err(IxaError::from(
+                        "Can't replace the itinerary of a person without a default itinerary",
+                    ));
+                };
+                if !identical_settings(default_itinerary, &itinerary) {
+                    return Err(IxaError::IxaError(format!(
+                        "The replacement itinerary for person {person_id} must contain the same settings as their default itinerary."
+                    )));
+                }
                 self.add_modified_itinerary(person_id, itinerary, true)
             }
-            ItineraryModifiers::RestrictTo { setting } => {
+            ItineraryModifiers::RestrictTo {
+                setting,
+                setting_id,
+            } => {
+                trace!(
+                    "ItineraryModifier::RestrictTo person {person_id} -- {:?} {setting_id:?}",
+                    setting.get_type_id()
+                );
+                self.limit_itinerary_by_setting_category(person_id, setting, setting_id)
+            }
+            ItineraryModifiers::LeakyRestrictTo {
+                setting,
+                setting_id,
+                leakage,
+            } => {
                 trace!(
-                    "ItineraryModifier::RestrictTo person {person_id} -- {:?}",
+                    "ItineraryModifier::LeakyRestrictTo person {person_id} -- {:?} {setting_id:?} {leakage}",
                     setting.get_type_id()
                 );
-                self.limit_itinerary_by_setting_category(person_id, setting)
+                self.limit_itinerary_with_leakage(person_id, setting, setting_id, leakage)
             }
             ItineraryModifiers::Exclude { setting } => {
                 trace!(
@@ -640,6 +1253,13 @@ pub trait ContextSettingExt:
                 );
                 self.exclude_setting_from_itinerary(person_id, setting)
             }
+            ItineraryModifiers::ScaleSetting { setting, factor } => {
+                trace!(
+                    "ItineraryModifier::ScaleSetting person {person_id} -- {:?} {factor}",
+                    setting.get_type_id()
+                );
+                self.scale_setting_in_itinerary(person_id, setting, factor)
+            }
         };
         result
     }
@@ -666,32 +1286,69 @@ pub trait ContextSettingExt:
         }
     }
 
+    /// `get_primary_setting_id` returns the id of a person's primary setting of a given type,
+    /// which is the first setting of that type in their default itinerary. People may belong to
+    /// multiple settings of the same type (e.g., more than one home), but the primary one is
+    /// where they go when their itinerary is restricted to that type (e.g., isolating at home).
+    fn get_primary_setting_id(
+        &self,
+        person_id: PersonId,
+        setting_category: &dyn SettingCategory,
+    ) -> Option<usize> {
+        self.get_itinerary(person_id, ItinerarySelector::Default)?
+            .iter()
+            .find(|entry| entry.setting.get_type_id() == setting_category.get_type_id())
+            .map(|entry| entry.setting.id())
+    }
+
     fn add_itinerary(
         &mut self,
         person_id: PersonId,
         itinerary: Vec<ItineraryEntry>,
     ) -> Result<(), IxaError> {
         let _span = open_span("add_itinerary");
-        // Normalize itinerary ratios
-        self.validate_itinerary(&itinerary)?;
+        let itinerary = self.normalize_default_itinerary(itinerary)?;
+        self.get_data_mut(SettingDataPlugin)
+            .set_default_itinerary(person_id, itinerary);
+        Ok(())
+    }
 
-        let total_ratio: f64 = itinerary.iter().map(|entry| entry.ratio).sum();
-        // If we passed validation, we know setting entries aren't all zero, so we can divide by
-        // total_ratio without worrying about dividing by zero.
-        let mut itinerary = itinerary;
-        for entry in &mut itinerary {
-            entry.ratio /= total_ratio;
+    /// Add the default itineraries of many people at once, e.g., when loading a population. The
+    /// resulting memberships and itineraries are the same as calling `add_itinerary` for each
+    /// person in order, but the memberships of people without a previous itinerary are inserted
+    /// in batches.
+    /// # Errors
+    /// - If any itinerary is invalid or contains an unregistered setting type, in which case no
+    ///   itineraries are added
+    fn add_itineraries_bulk(
+        &mut self,
+        entries: Vec<(PersonId, Vec<ItineraryEntry>)>,
+    ) -> Result<(), IxaError> {
+        let _span = open_span("add_itineraries_bulk");
+        let mut itineraries = Vec::with_capacity(entries.len());
+        for (person_id, itinerary) in entries {
+            itineraries.push((person_id, self.normalize_default_itinerary(itinerary)?));
         }
-        let container = self.get_data_mut(SettingDataPlugin);
-
-        // Clean up settings that from previous itinerary, if there is one
 
-        if let Some(previous_itinerary) = container.itineraries.get(&person_id) {
-            container.deactivate_itinerary(person_id, previous_itinerary.clone());
+        let container = self.get_data_mut(SettingDataPlugin);
+        let mut batch = vec![];
+        let mut batch_people = HashSet::new();
+        for (person_id, itinerary) in itineraries {
+            if container.itineraries.contains_key(&person_id)
+                || container.modified_itineraries.contains_key(&person_id)
+                || batch_people.contains(&person_id)
+            {
+                // Replacing an itinerary depends on the memberships added before it, so add the
+                // batch so far and then replace this itinerary as `add_itinerary` would
+                container.add_new_default_itineraries(std::mem::take(&mut batch));
+                batch_people.clear();
+                container.set_default_itinerary(person_id, itinerary);
+            } else {
+                batch_people.insert(person_id);
+                batch.push((person_id, itinerary));
+            }
         }
-
-        container.activate_itinerary(person_id, &itinerary)?;
-        container.itineraries.insert(person_id, itinerary);
+        container.add_new_default_itineraries(batch);
 
         Ok(())
     }
@@ -701,38 +1358,522 @@ pub trait ContextSettingExt:
         self.get_itinerary(person_id, ItinerarySelector::Current)
     }
 
-    #[allow(dead_code)]
-    fn get_setting_members(&self, setting: &dyn AnySettingId) -> Option<&IndexSet<PersonId>> {
-        self.get_setting_members_internal(setting, MembershipSelector::Active)
+    /// Get the settings in a person's default itinerary as pairs of the name of the setting
+    /// category (e.g., "Home") and the setting id
+    fn get_default_itinerary_settings(&self, person_id: PersonId) -> Vec<(&'static str, usize)> {
+        self.get_itinerary(person_id, ItinerarySelector::Default)
+            .map(|itinerary| {
+                itinerary
+                    .iter()
+                    .map(|entry| (entry.setting.get_category_id(), entry.setting.id()))
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 
-    /// Get the total current infectiousness multiplier for a person
-    /// This is the sum of the infectiousness multipliers for each setting derived from the itinerary
-    /// with members filtered as Active and in the Current itinerary
-    /// These are generated without modification from the general formula of ratio * (N - 1) ^ alpha
-    /// where N is the number of active members in the setting
-    fn calculate_current_infectiousness_multiplier_for_person(&self, person_id: PersonId) -> f64 {
+    /// Get the proportion of time a person spends in each setting category according to their
+    /// default itinerary, keyed by the name of the setting category (e.g., "Home")
+    fn get_default_itinerary_category_proportions(
+        &self,
+        person_id: PersonId,
+    ) -> HashMap<&'static str, f64> {
+        let mut proportions = HashMap::new();
+        if let Some(itinerary) = self.get_itinerary(person_id, ItinerarySelector::Default) {
+            let total_ratio: f64 = itinerary.iter().map(|entry| entry.ratio).sum();
+            for entry in itinerary {
+                *proportions
+                    .entry(entry.setting.get_category_id())
+                    .or_insert(0.0) += entry.ratio / total_ratio;
+            }
+        }
+        proportions
+    }
+
+    /// Get the names of the registered setting types (e.g., "Home"), sorted by name
+    fn get_setting_category_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .get_data(SettingDataPlugin)
+            .setting_category_names
+            .values()
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Get the resolved properties of each registered setting type, sorted by the name of the
+    /// setting type: its `alpha` and itinerary ratio, and whether a contact rate, a daily activity
+    /// profile, or the activity calendar applies to it.
+    fn get_resolved_setting_properties(&self) -> Vec<ResolvedSettingProperties> {
         let container = self.get_data(SettingDataPlugin);
-        let mut collector = 0.0;
-        container.with_itinerary(
-            person_id,
-            ItinerarySelector::Current,
-            MembershipSelector::Active,
-            |setting, setting_props, members, ratio| {
-                let multiplier: f64 = if members.is_empty() {
+        let mut resolved: Vec<ResolvedSettingProperties> = container
+            .setting_properties
+            .iter()
+            .map(|(type_id, properties)| ResolvedSettingProperties {
+                setting_type: container.setting_category_names[type_id].clone(),
+                alpha: properties.alpha,
+                ratio: properties
+                    .itinerary_specification
+                    .map(|ItinerarySpecificationType::Constant { ratio }| ratio),
+                contact_rate: container.contact_rates.get(type_id).copied(),
+                daily_activity_profile: container.daily_activity_profiles.contains_key(type_id),
+                activity_calendar: container.activity_calendar.is_some()
+                    && type_follows_activity_calendar(*type_id),
+            })
+            .collect();
+        resolved.sort_by(|a, b| a.setting_type.cmp(&b.setting_type));
+        resolved
+    }
+
+    /// Get the proportion of the members of each setting type for whom `predicate` is true,
+    /// sorted by the name of the setting type. Members of all settings of the type are included,
+    /// whether or not they are currently active, and people who belong to several settings of the
+    /// same type are counted once for that type.
+    #[allow(clippy::cast_precision_loss)]
+    fn get_setting_category_member_proportions(
+        &self,
+        predicate: impl Fn(PersonId) -> bool,
+    ) -> Vec<(String, f64)> {
+        let container = self.get_data(SettingDataPlugin);
+        let mut members_by_category: HashMap<TypeId, HashSet<PersonId>> = HashMap::new();
+        for ((type_id, _), members) in &container.all_members {
+            members_by_category
+                .entry(*type_id)
+                .or_default()
+                .extend(members.iter().copied());
+        }
+        let mut proportions: Vec<(String, f64)> = members_by_category
+            .into_iter()
+            .filter(|(_, members)| !members.is_empty())
+            .map(|(type_id, members)| {
+                let matching = members.iter().filter(|&&person| predicate(person)).count();
+                (
+                    container.setting_category_names[&type_id].clone(),
+                    matching as f64 / members.len() as f64,
+                )
+            })
+            .collect();
+        proportions.sort_by(|a, b| a.0.cmp(&b.0));
+        proportions
+    }
+
+    /// Get the number of occupied settings of each setting type and the mean number of active
+    /// members per occupied setting, sorted by the name of the setting type. A setting is occupied
+    /// if it has at least one active member. Setting types without any occupied settings have a
+    /// mean of zero.
+    #[allow(clippy::cast_precision_loss)]
+    fn get_mean_active_setting_sizes(&self) -> Vec<(String, usize, f64)> {
+        let container = self.get_data(SettingDataPlugin);
+        let mut sizes_by_category: HashMap<TypeId, (usize, usize)> = container
+            .setting_categories
+            .iter()
+            .map(|type_id| (*type_id, (0, 0)))
+            .collect();
+        for ((type_id, _), members) in &container.active_members {
+            if members.is_empty() {
+                continue;
+            }
+            let (occupied, total_members) = sizes_by_category.entry(*type_id).or_default();
+            *occupied += 1;
+            *total_members += members.len();
+        }
+        let mut sizes: Vec<(String, usize, f64)> = sizes_by_category
+            .into_iter()
+            .map(|(type_id, (occupied, total_members))| {
+                let mean = if occupied == 0 {
                     0.0
                 } else {
-                    setting.calculate_multiplier(members, *setting_props)
+                    total_members as f64 / occupied as f64
                 };
-                collector += ratio * multiplier;
-            },
+                (
+                    container.setting_category_names[&type_id].clone(),
+                    occupied,
+                    mean,
+                )
+            })
+            .collect();
+        sizes.sort_by(|a, b| a.0.cmp(&b.0));
+        sizes
+    }
+
+    /// Get the number of distinct settings a person is currently active in, i.e., the settings
+    /// in their current itinerary in which they spend some time
+    fn get_active_setting_count(&self, person_id: PersonId) -> usize {
+        let mut settings = HashSet::new();
+        if let Some(itinerary) = self.get_itinerary(person_id, ItinerarySelector::Current) {
+            for entry in itinerary {
+                if entry.ratio > 0.0 {
+                    settings.insert(entry.setting.get_tuple_id());
+                }
+            }
+        }
+        settings.len()
+    }
+
+    #[allow(dead_code)]
+    fn get_setting_members(&self, setting: &dyn AnySettingId) -> Option<&IndexSet<PersonId>> {
+        self.get_setting_members_internal(setting, MembershipSelector::Active)
+    }
+
+    /// Toggle whether a person is active in a single setting of their default itinerary without
+    /// replacing the itinerary. Deactivating a setting sets its ratio to zero, and activating it
+    /// restores the ratio it had when the itinerary was added (or the setting category's constant
+    /// ratio if it was added with a ratio of zero). The itinerary is then renormalized.
+    /// If the person has a modified itinerary, it continues to govern their setting memberships,
+    /// and the change takes effect when the modified itinerary is removed.
+    /// # Errors
+    /// - If the person does not have an itinerary or the setting is not in it
+    /// - If the setting has no ratio to restore and its category has no constant ratio
+    /// - If deactivating the setting would leave the person without any active settings
+    #[allow(dead_code)]
+    fn set_setting_active(
+        &mut self,
+        person_id: PersonId,
+        setting: &dyn AnySettingId,
+        active: bool,
+    ) -> Result<(), IxaError> {
+        let container = self.get_data(SettingDataPlugin);
+        let Some(itinerary) = container.itineraries.get(&person_id) else {
+            return Err(IxaError::from("Can't find itinerary for person"));
+        };
+        let Some(index) = itinerary
+            .iter()
+            .position(|entry| entry.setting.get_tuple_id() == setting.get_tuple_id())
+        else {
+            return Err(IxaError::from(
+                "Can't set the activity of a setting not in the person's itinerary",
+            ));
+        };
+        self.set_itinerary_entries_active(person_id, &[index], active)
+    }
+
+    /// Toggle whether a person is active in every setting of the given categories in their
+    /// default itinerary at once (e.g., a school and its classrooms), as in `set_setting_active`.
+    /// Either every setting is toggled or, if there is an error, none are. People without a
+    /// setting of the categories are unaffected.
+    /// # Errors
+    /// - If the person does not have an itinerary
+    /// - If a setting has no ratio to restore and its category has no constant ratio
+    /// - If deactivating the settings would leave the person without any active settings
+    fn set_setting_categories_active(
+        &mut self,
+        person_id: PersonId,
+        categories: &[&dyn SettingCategory],
+        active: bool,
+    ) -> Result<(), IxaError> {
+        let container = self.get_data(SettingDataPlugin);
+        let Some(itinerary) = container.itineraries.get(&person_id) else {
+            return Err(IxaError::from("Can't find itinerary for person"));
+        };
+        let indices: Vec<usize> = itinerary
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| {
+                categories
+                    .iter()
+                    .any(|category| category.get_type_id() == entry.setting.get_type_id())
+            })
+            .map(|(index, _)| index)
+            .collect();
+        if indices.is_empty() {
+            return Ok(());
+        }
+        self.set_itinerary_entries_active(person_id, &indices, active)
+    }
+
+    /// Replace every setting of category `from` in a person's default itinerary with the setting
+    /// `to`, e.g., to move a student from their school to a workplace. The itinerary is rebuilt
+    /// from the ratios its settings had when it was added, so settings that were deactivated with
+    /// `set_setting_active` keep the ratio to restore and stay inactive. The new setting takes the
+    /// constant ratio of its category on the same scale as those ratios, so the person's time is
+    /// split as if the itinerary had been added with the new setting. If either category has no
+    /// itinerary specification, the new setting takes the ratio of the replaced setting instead.
+    /// If `to` is `None`, the settings are removed.
+    /// # Errors
+    /// - If the person does not have an itinerary
+    /// - If the resulting itinerary is invalid (e.g., it has no time in any setting)
+    fn replace_setting_category_in_itinerary(
+        &mut self,
+        person_id: PersonId,
+        from: &dyn SettingCategory,
+        to: Option<impl AnySettingId>,
+    ) -> Result<(), IxaError> {
+        let container = self.get_data(SettingDataPlugin);
+        let Some(itinerary) = container.itineraries.get(&person_id) else {
+            return Err(IxaError::from("Can't find itinerary for person"));
+        };
+        let base_ratios = container
+            .base_ratios
+            .get(&person_id)
+            .cloned()
+            .unwrap_or_else(|| itinerary.iter().map(|entry| entry.ratio).collect());
+        let constant_ratio = |type_id: TypeId| {
+            container
+                .setting_properties
+                .get(&type_id)
+                .and_then(|properties| properties.itinerary_specification)
+                .map(|ItinerarySpecificationType::Constant { ratio }| ratio)
+        };
+
+        let mut new_itinerary = Vec::new();
+        let mut inactive = Vec::new();
+        let mut replaced_ratio = None;
+        for (entry, &base_ratio) in itinerary.iter().zip(&base_ratios) {
+            if entry.setting.get_type_id() == from.get_type_id() {
+                replaced_ratio.get_or_insert(base_ratio);
+                continue;
+            }
+            if entry.ratio <= 0.0 && base_ratio > 0.0 {
+                inactive.push(new_itinerary.len());
+            }
+            new_itinerary.push(ItineraryEntry {
+                setting: entry.setting.clone(),
+                ratio: base_ratio,
+            });
+        }
+        if let (Some(to), Some(replaced_ratio)) = (to, replaced_ratio) {
+            let ratio = match (
+                constant_ratio(from.get_type_id()),
+                constant_ratio(to.get_type_id()),
+            ) {
+                (Some(from_ratio), Some(to_ratio)) if from_ratio > 0.0 => {
+                    replaced_ratio * to_ratio / from_ratio
+                }
+                _ => replaced_ratio,
+            };
+            new_itinerary.push(ItineraryEntry::new(to, ratio));
+        }
+        self.add_itinerary(person_id, new_itinerary)?;
+        if inactive.is_empty() {
+            return Ok(());
+        }
+        self.set_itinerary_entries_active(person_id, &inactive, false)
+    }
+
+    /// Move a person into a single setting outside their default itinerary (e.g., a hospital
+    /// ward), in which they are active until their modified itinerary is removed. The setting is
+    /// added to their default itinerary with a ratio of zero, so they remain an inactive member of
+    /// it afterwards, and their modified itinerary gives every other setting a ratio of zero. The
+    /// setting takes precedence over other itinerary modifiers: any previous modified itinerary
+    /// (e.g., isolating at home) is held, modifiers applied or removed while the person is in the
+    /// setting change the held itinerary instead, and `return_from_setting` applies it again.
+    /// People with a pinned itinerary are not moved.
+    /// # Errors
+    /// - If the person does not have an itinerary
+    /// - If the setting type is not registered
+    fn move_to_setting(
+        &mut self,
+        person_id: PersonId,
+        setting: impl AnySettingId,
+    ) -> Result<(), IxaError> {
+        if self.is_itinerary_pinned(person_id) || self.is_itinerary_cleared(person_id) {
+            trace!("Person {person_id} has a pinned or cleared itinerary, so they are not moved");
+            return Ok(());
+        }
+        let Some(itinerary) = self.get_data(SettingDataPlugin).itineraries.get(&person_id) else {
+            return Err(IxaError::from("Can't find itinerary for person"));
+        };
+        let mut default_itinerary = itinerary.clone();
+        // A person moving between settings keeps the modified itinerary held when they first moved
+        let container = self.get_data_mut(SettingDataPlugin);
+        let held_itinerary = match container.held_itineraries.remove(&person_id) {
+            Some(held_itinerary) => held_itinerary,
+            None => container.modified_itineraries.get(&person_id).cloned(),
+        };
+        self.remove_modified_itinerary(person_id)?;
+        let target = setting.get_tuple_id();
+        if !default_itinerary
+            .iter()
+            .any(|entry| entry.setting.get_tuple_id() == target)
+        {
+            default_itinerary.push(ItineraryEntry::new(setting, 0.0));
+            self.add_itinerary(person_id, default_itinerary.clone())?;
+        }
+        let itinerary = default_itinerary
+            .into_iter()
+            .map(|entry| {
+                let ratio = if entry.setting.get_tuple_id() == target {
+                    1.0
+                } else {
+                    0.0
+                };
+                ItineraryEntry {
+                    setting: entry.setting,
+                    ratio,
+                }
+            })
+            .collect();
+        self.modify_itinerary(person_id, ItineraryModifiers::ReplaceWith { itinerary })?;
+        self.get_data_mut(SettingDataPlugin)
+            .held_itineraries
+            .insert(person_id, held_itinerary);
+        Ok(())
+    }
+
+    /// Return a person who moved into a setting with `move_to_setting` to their other settings.
+    /// The modified itinerary held while they were in the setting is applied again, or they return
+    /// to their default itinerary if they have none. People with a pinned or cleared itinerary
+    /// stay where they are.
+    /// # Errors
+    /// - If the person does not have an itinerary
+    fn return_from_setting(&mut self, person_id: PersonId) -> Result<(), IxaError> {
+        if self.is_itinerary_pinned(person_id) || self.is_itinerary_cleared(person_id) {
+            trace!("Person {person_id} has a pinned or cleared itinerary, so they do not return");
+            return Ok(());
+        }
+        let held_itinerary = self
+            .get_data_mut(SettingDataPlugin)
+            .held_itineraries
+            .remove(&person_id)
+            .flatten();
+        self.remove_modified_itinerary(person_id)?;
+        if let Some(itinerary) = held_itinerary {
+            self.add_modified_itinerary(person_id, itinerary, false)?;
+        }
+        Ok(())
+    }
+
+    /// Get the ids of all settings of a category that have had members, in ascending order
+    fn get_category_setting_ids(&self, setting_category: &dyn SettingCategory) -> Vec<usize> {
+        let mut ids: Vec<usize> = self
+            .get_data(SettingDataPlugin)
+            .all_members
+            .keys()
+            .filter(|(type_id, _)| *type_id == setting_category.get_type_id())
+            .map(|(_, id)| *id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Link a classroom to its parent school. Classrooms are settings of category `Classroom`
+    /// whose members also belong to the parent school, typically with a higher contact intensity.
+    /// # Errors
+    /// - If the classroom is already linked to a different school
+    #[allow(dead_code)]
+    fn link_classroom_to_school(
+        &mut self,
+        classroom_id: usize,
+        school_id: usize,
+    ) -> Result<(), IxaError> {
+        let classroom_schools = &mut self.get_data_mut(SettingDataPlugin).classroom_schools;
+        match classroom_schools.get(&classroom_id) {
+            Some(&linked_school_id) if linked_school_id != school_id => Err(IxaError::IxaError(
+                format!("Classroom {classroom_id} is already linked to school {linked_school_id}"),
+            )),
+            _ => {
+                classroom_schools.insert(classroom_id, school_id);
+                Ok(())
+            }
+        }
+    }
+
+    /// Get the id of the parent school of a classroom, if it has been linked to one
+    fn get_classroom_school_id(&self, classroom_id: usize) -> Option<usize> {
+        self.get_data(SettingDataPlugin)
+            .classroom_schools
+            .get(&classroom_id)
+            .copied()
+    }
+
+    /// Hold a person's current itinerary fixed: itinerary modifiers (e.g., those applied by
+    /// isolation policies when a person property changes) are neither applied to nor removed from
+    /// the person until the itinerary is unpinned. Explicitly adding a default itinerary is not
+    /// affected.
+    #[allow(dead_code)]
+    fn pin_itinerary(&mut self, person_id: PersonId) {
+        self.get_data_mut(SettingDataPlugin)
+            .pinned
+            .insert(person_id);
+    }
+
+    /// Allow itinerary modifiers to apply to a person whose itinerary was pinned
+    #[allow(dead_code)]
+    fn unpin_itinerary(&mut self, person_id: PersonId) {
+        self.get_data_mut(SettingDataPlugin)
+            .pinned
+            .remove(&person_id);
+    }
+
+    /// Remove a person from all of their settings for the rest of the simulation (e.g., when they
+    /// permanently leave their workplace). They remain inactive members of the settings in their
+    /// default itinerary, and itinerary modifiers no longer apply to them, so they are not made
+    /// active again. Unlike pinning, clearing an itinerary cannot be undone.
+    fn clear_itinerary(&mut self, person_id: PersonId) {
+        let container = self.get_data_mut(SettingDataPlugin);
+        if let Some(modified_itinerary) = container.modified_itineraries.remove(&person_id) {
+            container.deactivate_itinerary(person_id, modified_itinerary);
+        }
+        if let Some(default_itinerary) = container.itineraries.get(&person_id) {
+            container.deactivate_itinerary(person_id, default_itinerary.clone());
+        }
+        container.held_itineraries.remove(&person_id);
+        container.cleared.insert(person_id);
+    }
+
+    /// Whether a person's itinerary is pinned against itinerary modifiers
+    fn is_itinerary_pinned(&self, person_id: PersonId) -> bool {
+        self.get_data(SettingDataPlugin).pinned.contains(&person_id)
+    }
+
+    /// Whether a person has been removed from all of their settings with `clear_itinerary`
+    fn is_itinerary_cleared(&self, person_id: PersonId) -> bool {
+        self.get_data(SettingDataPlugin)
+            .cleared
+            .contains(&person_id)
+    }
+
+    /// Whether a person is currently an active member of a setting
+    #[allow(dead_code)]
+    fn is_setting_active(&self, person_id: PersonId, setting: &dyn AnySettingId) -> bool {
+        self.get_setting_members_internal(setting, MembershipSelector::Active)
+            .is_some_and(|members| members.contains(&person_id))
+    }
+
+    /// Get the number of active members of a setting without borrowing the set of members
+    #[allow(dead_code)]
+    fn active_member_count(&self, setting: &dyn AnySettingId) -> usize {
+        self.get_data(SettingDataPlugin)
+            .get_setting_member_count(setting, MembershipSelector::Active)
+    }
+
+    /// Get the total current infectiousness multiplier for a person
+    /// This is the sum of the infectiousness multipliers for each setting derived from the itinerary
+    /// with members filtered as Active and in the Current itinerary
+    /// These are generated without modification from the general formula of ratio * (N - 1) ^ alpha
+    /// where N is the number of active members in the setting, except that workplaces and schools
+    /// have a multiplier of zero on the non-activity days of the activity calendar, and settings
+    /// with a daily activity profile are scaled by the profile's multiplier for the current hour
+    fn calculate_current_infectiousness_multiplier_for_person(&self, person_id: PersonId) -> f64 {
+        let container = self.get_data(SettingDataPlugin);
+        let non_activity_day = self.is_non_activity_day();
+        let daily_activity_multipliers = self.get_daily_activity_multipliers();
+        let mut collector = 0.0;
+        container.with_itinerary(
+            person_id,
+            ItinerarySelector::Current,
+            MembershipSelector::Active,
+            |setting, setting_props, member_count, ratio| {
+                let multiplier: f64 = if member_count == 0
+                    || (non_activity_day && follows_activity_calendar(setting))
+                {
+                    0.0
+                } else {
+                    container.calculate_setting_multiplier(setting, *setting_props, member_count)
+                        * daily_activity_multipliers
+                            .get(&setting.get_type_id())
+                            .unwrap_or(&1.0)
+                };
+                collector += ratio * multiplier;
+            },
         );
         collector
     }
     /// Get the maximum infectiousness multiplier for a person across all settings
     /// derived from both the default and modified itineraries of the person.
     /// These are generated without modification from the general formula of ratio * (N - 1) ^ alpha
-    /// where N is the number of all active and inactive members in the setting
+    /// where N is the number of all active and inactive members in the setting, or from the
+    /// contact rate of setting types that have one
     fn calculate_max_infectiousness_multiplier_for_person(&self, person_id: PersonId) -> f64 {
         let container = self.get_data(SettingDataPlugin);
         let mut collector = 0.0;
@@ -740,8 +1881,9 @@ pub trait ContextSettingExt:
             person_id,
             ItinerarySelector::Default,
             MembershipSelector::Union,
-            |setting, setting_props, members, _ratio| {
-                let multiplier: f64 = setting.calculate_multiplier(members, *setting_props);
+            |setting, setting_props, member_count, _ratio| {
+                let multiplier: f64 =
+                    container.calculate_setting_multiplier(setting, *setting_props, member_count);
                 // We want to identify the max at the setting level, not itinerary level, so that we sample at the true maximum possible rate
                 collector = f64::max(collector, multiplier);
             },
@@ -755,6 +1897,16 @@ pub trait ContextSettingExt:
         setting: &dyn AnySettingId,
     ) -> Result<Option<PersonId>, IxaError> {
         let _span = open_span("get_contact");
+        // Contacts made in a census tract may be drawn from a neighboring tract instead
+        if setting.get_type_id() == TypeId::of::<CensusTract>() {
+            if let Some(tract_id) = self.sample_contact_tract(setting.id()) {
+                if tract_id != setting.id() {
+                    return Ok(self
+                        .sample_active_setting_members(&SettingId::new(CensusTract, tract_id))
+                        .filter(|&contact_id| contact_id != person_id));
+                }
+            }
+        }
         if let Some(members) =
             self.get_setting_members_internal(setting, MembershipSelector::Active)
         {
@@ -774,19 +1926,28 @@ pub trait ContextSettingExt:
         Err(IxaError::from("Group membership is None"))
     }
 
-    fn sample_current_setting(&self, person_id: PersonId) -> Option<&dyn AnySettingId> {
+    // Setting ids are `'static`, so callers can clone the sampled setting to use it after the
+    // borrow of the context ends
+    fn sample_current_setting(&self, person_id: PersonId) -> Option<&(dyn AnySettingId + 'static)> {
         let _span = open_span("sample_setting");
         let container = self.get_data(SettingDataPlugin);
+        let non_activity_day = self.is_non_activity_day();
+        let daily_activity_multipliers = self.get_daily_activity_multipliers();
         let mut itinerary_multiplier = Vec::new();
         container.with_itinerary(
             person_id,
             ItinerarySelector::Current,
             MembershipSelector::Active,
-            |setting, setting_props, members, ratio| {
-                let multiplier = if members.is_empty() {
+            |setting, setting_props, member_count, ratio| {
+                let multiplier = if member_count == 0
+                    || (non_activity_day && follows_activity_calendar(setting))
+                {
                     0.0
                 } else {
-                    setting.calculate_multiplier(members, *setting_props)
+                    container.calculate_setting_multiplier(setting, *setting_props, member_count)
+                        * daily_activity_multipliers
+                            .get(&setting.get_type_id())
+                            .unwrap_or(&1.0)
                 };
                 itinerary_multiplier.push(ratio * multiplier);
             },
@@ -804,13 +1965,90 @@ pub trait ContextSettingExt:
 }
 impl ContextSettingExt for Context {}
 
-pub fn init(context: &mut Context) {
+/// Load the census tract coordinates and, for each tract, compute the cumulative kernel weights
+/// of contacts in every tract in the file.
+/// # Errors
+/// - If the coordinates file cannot be read or a row cannot be parsed
+/// - If a tract appears more than once in the coordinates file
+fn load_census_tract_kernel(
+    context: &mut Context,
+    census_tract_kernel: &CensusTractKernel,
+) -> Result<(), IxaError> {
+    let mut reader = csv::Reader::from_path(&census_tract_kernel.coordinates_file)?;
+    let mut tracts: Vec<TractCoordinates> = vec![];
+    let mut tract_ids = HashSet::new();
+    for record in reader.deserialize() {
+        let tract: TractCoordinates = record?;
+        if !tract_ids.insert(tract.tract_id) {
+            return Err(IxaError::IxaError(format!(
+                "Census tract {} appears more than once in the coordinates file.",
+                tract.tract_id
+            )));
+        }
+        tracts.push(tract);
+    }
+    let mut cumulative_weights = HashMap::new();
+    for tract in &tracts {
+        let mut total_weight = 0.0;
+        let weights = tracts
+            .iter()
+            .map(|other| {
+                let distance = (tract.x - other.x).hypot(tract.y - other.y);
+                total_weight += census_tract_kernel.kernel.weight(distance);
+                total_weight
+            })
+            .collect();
+        cumulative_weights.insert(tract.tract_id, weights);
+    }
+    context
+        .get_data_mut(SettingDataPlugin)
+        .tract_contact_weights = Some(TractContactWeights {
+        tract_ids: tracts.iter().map(|tract| tract.tract_id).collect(),
+        cumulative_weights,
+    });
+    Ok(())
+}
+
+/// The type id of the setting category of a core setting type
+fn core_setting_type_id(setting_category: CoreSettingsTypes) -> TypeId {
+    match setting_category {
+        CoreSettingsTypes::Home => TypeId::of::<Home>(),
+        CoreSettingsTypes::CensusTract => TypeId::of::<CensusTract>(),
+        CoreSettingsTypes::School => TypeId::of::<School>(),
+        CoreSettingsTypes::Workplace => TypeId::of::<Workplace>(),
+        CoreSettingsTypes::Hospital => TypeId::of::<Hospital>(),
+    }
+}
+
+/// # Errors
+/// - If the census tract kernel is specified and its coordinates file cannot be loaded
+pub fn init(context: &mut Context) -> Result<(), IxaError> {
     let Params {
         settings_properties,
+        activity_calendar,
+        daily_activity_profiles,
+        setting_contact_rates,
+        census_tract_kernel,
         ..
-    } = context.get_params();
+    } = context.get_params().clone();
+    context.get_data_mut(SettingDataPlugin).activity_calendar = activity_calendar;
+    for (setting_category, profile) in daily_activity_profiles.unwrap_or_default() {
+        context
+            .get_data_mut(SettingDataPlugin)
+            .daily_activity_profiles
+            .insert(core_setting_type_id(setting_category), profile);
+    }
+    for (setting_category, contact_rate) in setting_contact_rates.unwrap_or_default() {
+        context
+            .get_data_mut(SettingDataPlugin)
+            .contact_rates
+            .insert(core_setting_type_id(setting_category), contact_rate);
+    }
+    if let Some(census_tract_kernel) = &census_tract_kernel {
+        load_census_tract_kernel(context, census_tract_kernel)?;
+    }
 
-    for (setting_category, setting_properties) in settings_properties.clone() {
+    for (setting_category, setting_properties) in settings_properties {
         match setting_category {
             CoreSettingsTypes::Home => {
                 context
@@ -832,7 +2070,53 @@ pub fn init(context: &mut Context) {
                     .register_setting_category(&Workplace, setting_properties)
                     .unwrap();
             }
+            CoreSettingsTypes::Hospital => {
+                context
+                    .register_setting_category(&Hospital, setting_properties)
+                    .unwrap();
+            }
+        }
+    }
+    if context
+        .get_params()
+        .setting_properties_summary
+        .unwrap_or(false)
+    {
+        print_resolved_setting_properties(context);
+    }
+    Ok(())
+}
+
+/// Prints the resolved properties of each registered setting type, so that the configuration the
+/// model will actually use can be checked before the simulation runs
+fn print_resolved_setting_properties(context: &Context) {
+    println!("Resolved Setting Properties:");
+    for resolved in context.get_resolved_setting_properties() {
+        let ratio = resolved
+            .ratio
+            .map_or_else(|| "none".to_string(), |ratio| ratio.to_string());
+        let mut overrides = vec![];
+        if let Some(contact_rate) = resolved.contact_rate {
+            overrides.push(format!(
+                "{} contacts per day with transmission probability {}",
+                contact_rate.contacts_per_day, contact_rate.transmission_probability
+            ));
+        }
+        if resolved.daily_activity_profile {
+            overrides.push("daily activity profile".to_string());
         }
+        if resolved.activity_calendar {
+            overrides.push("activity calendar".to_string());
+        }
+        let overrides = if overrides.is_empty() {
+            "none".to_string()
+        } else {
+            overrides.join(", ")
+        };
+        println!(
+            "  {}: alpha {}, ratio {ratio}, overrides: {overrides}",
+            resolved.setting_type, resolved.alpha
+        );
     }
 }
 
@@ -844,7 +2128,8 @@ mod test {
         settings::ContextSettingExt,
     };
     use ixa::{
-        assert_almost_eq, define_person_property, ContextGlobalPropertiesExt, ContextPeopleExt,
+        assert_almost_eq, define_person_property, define_person_property_with_default,
+        ContextGlobalPropertiesExt, ContextPeopleExt, PersonPropertyChangeEvent,
     };
 
     define_setting_category!(Community);
@@ -1043,6 +2328,122 @@ mod test {
         }
     }
 
+    fn generated_itineraries(people: &[PersonId]) -> Vec<(PersonId, Vec<ItineraryEntry>)> {
+        people
+            .iter()
+            .enumerate()
+            .map(|(i, &person)| {
+                let mut itinerary = vec![
+                    ItineraryEntry::new(SettingId::new(Home, i / 3), 0.5),
+                    ItineraryEntry::new(SettingId::new(CensusTract, i / 50), 0.1),
+                ];
+                if i % 5 < 3 {
+                    #[allow(clippy::cast_precision_loss)]
+                    let ratio = (i % 4) as f64 * 0.1;
+                    itinerary.push(ItineraryEntry::new(SettingId::new(Workplace, i % 7), ratio));
+                } else {
+                    itinerary.push(ItineraryEntry::new(SettingId::new(School, i % 3), 0.0));
+                }
+                (person, itinerary)
+            })
+            .collect()
+    }
+
+    type MembershipState = Vec<((TypeId, usize), Vec<PersonId>)>;
+
+    fn membership_state(context: &Context) -> Vec<MembershipState> {
+        let container = context.get_data(SettingDataPlugin);
+        [
+            &container.active_members,
+            &container.inactive_members,
+            &container.all_members,
+        ]
+        .into_iter()
+        .map(|members| {
+            let mut state: MembershipState = members
+                .iter()
+                .map(|(id, people)| (*id, people.iter().copied().collect()))
+                .collect();
+            state.sort_by_key(|(id, _)| *id);
+            state
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_bulk_itineraries_match_sequential_itineraries() {
+        let mut sequential = Context::new();
+        let mut bulk = Context::new();
+        let mut people = vec![];
+        for context in [&mut sequential, &mut bulk] {
+            register_default_settings(context);
+            people = (0..300).map(|_| context.add_person(()).unwrap()).collect();
+            // One person already has an itinerary, which is replaced by the generated one
+            context
+                .add_itinerary(
+                    people[0],
+                    vec![ItineraryEntry::new(SettingId::new(Home, 1000), 1.0)],
+                )
+                .unwrap();
+        }
+        let mut itineraries = generated_itineraries(&people);
+        // One person's itinerary is given twice, so the second replaces the first
+        itineraries.push((
+            people[10],
+            vec![ItineraryEntry::new(SettingId::new(Workplace, 100), 1.0)],
+        ));
+        itineraries.extend(generated_itineraries(&people[250..]));
+
+        for (person, itinerary) in itineraries.clone() {
+            sequential.add_itinerary(person, itinerary).unwrap();
+        }
+        bulk.add_itineraries_bulk(itineraries).unwrap();
+
+        assert_eq!(membership_state(&sequential), membership_state(&bulk));
+        for &person in &people {
+            let entries = |context: &Context| -> Vec<((TypeId, usize), f64)> {
+                context.get_data(SettingDataPlugin).itineraries[&person]
+                    .iter()
+                    .map(|entry| (entry.setting.get_tuple_id(), entry.ratio))
+                    .collect()
+            };
+            assert_eq!(entries(&sequential), entries(&bulk));
+        }
+    }
+
+    #[test]
+    fn test_bulk_itineraries_unregistered_setting_adds_nothing() {
+        let mut context = Context::new();
+        register_default_settings(&mut context);
+        let person = context.add_person(()).unwrap();
+        let other = context.add_person(()).unwrap();
+        let e = context
+            .add_itineraries_bulk(vec![
+                (
+                    person,
+                    vec![ItineraryEntry::new(SettingId::new(Home, 0), 1.0)],
+                ),
+                (
+                    other,
+                    vec![ItineraryEntry::new(SettingId::new(Community, 0), 1.0)],
+                ),
+            ])
+            .err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(msg, "Itinerary entry setting type not registered");
+            }
+            Some(ue) => panic!(
+                "Expected an error that the setting type is not registered. Instead got: {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, the itineraries were added."),
+        }
+        assert!(context
+            .get_setting_members(&SettingId::new(Home, 0))
+            .is_none());
+    }
+
     #[test]
     fn test_feasible_itinerary_setting() {
         let mut context = Context::new();
@@ -1314,6 +2715,62 @@ mod test {
         assert_eq!(w_members.len(), 0);
     }
 
+    #[test]
+    fn test_replace_with_different_settings_returns_error() {
+        let mut context = Context::new();
+        register_default_settings(&mut context);
+        let person = context.add_person(()).unwrap();
+        let itinerary = vec![
+            ItineraryEntry::new(SettingId::new(Home, 0), 1.0),
+            ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0),
+        ];
+        context.add_itinerary(person, itinerary).unwrap();
+
+        let replacement = vec![
+            ItineraryEntry::new(SettingId::new(Home, 0), 1.0),
+            ItineraryEntry::new(SettingId::new(Workplace, 1), 1.0),
+        ];
+        let e = context
+            .modify_itinerary(
+                person,
+                ItineraryModifiers::ReplaceWith {
+                    itinerary: replacement,
+                },
+            )
+            .err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    format!("The replacement itinerary for person {person} must contain the same settings as their default itinerary.")
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that the settings differ. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, replacing the itinerary passed."),
+        }
+
+        // The default itinerary stays active
+        assert!(context
+            .get_data(SettingDataPlugin)
+            .get_modified_itinerary(person)
+            .is_none());
+        assert_eq!(
+            context
+                .get_setting_members(&SettingId::new(Workplace, 0))
+                .map_or(0, IndexSet::len),
+            1
+        );
+        assert_eq!(
+            context
+                .get_setting_members(&SettingId::new(Workplace, 1))
+                .map_or(0, IndexSet::len),
+            0
+        );
+    }
+
     #[allow(clippy::too_many_lines)]
     #[test]
     fn test_itinerary_modifiers_replace() {
@@ -1435,21 +2892,272 @@ mod test {
     }
 
     #[test]
-    fn test_limited_itinerary_modifier() {
-        /* H(0) = [0, 1, 2]
-          W(0) = [0,3,4] -
-         Person 0 isolates using limited itinerary by setting type.
-        */
+    fn test_pinned_itinerary_ignores_property_driven_modifiers() {
+        define_person_property_with_default!(Restricted, bool, false);
         let mut context = Context::new();
         context.init_random(42);
         register_default_settings(&mut context);
+        // Restricted people are limited to their home
+        context.subscribe_to_event(|context, event: PersonPropertyChangeEvent<Restricted>| {
+            if event.current {
+                context
+                    .modify_itinerary(
+                        event.person_id,
+                        ItineraryModifiers::RestrictTo {
+                            setting: &Home,
+                            setting_id: None,
+                        },
+                    )
+                    .unwrap();
+            } else {
+                context.remove_modified_itinerary(event.person_id).unwrap();
+            }
+        });
+
+        let pinned = context.add_person(()).unwrap();
+        let unpinned = context.add_person(()).unwrap();
+        for person in [pinned, unpinned] {
+            context
+                .add_itinerary(
+                    person,
+                    vec![
+                        ItineraryEntry::new(SettingId::new(Home, 0), 0.5),
+                        ItineraryEntry::new(SettingId::new(Workplace, 0), 0.5),
+                    ],
+                )
+                .unwrap();
+        }
+        context.pin_itinerary(pinned);
+        assert!(context.is_itinerary_pinned(pinned));
+        assert!(!context.is_itinerary_pinned(unpinned));
+
+        context.add_plan(1.0, move |context| {
+            context.set_person_property(pinned, Restricted, true);
+            context.set_person_property(unpinned, Restricted, true);
+        });
+        context.execute();
+
+        assert!(context.is_setting_active(pinned, &SettingId::new(Workplace, 0)));
+        assert!(context.is_setting_active(pinned, &SettingId::new(Home, 0)));
+        assert_eq!(
+            context
+                .get_itinerary(pinned, ItinerarySelector::Current)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(!context.is_setting_active(unpinned, &SettingId::new(Workplace, 0)));
+        assert!(context.is_setting_active(unpinned, &SettingId::new(Home, 0)));
+
+        // Once unpinned, the person responds to property-driven modifiers again
+        context.unpin_itinerary(pinned);
+        context.add_plan(2.0, move |context| {
+            context.set_person_property(pinned, Restricted, false);
+            context.set_person_property(pinned, Restricted, true);
+        });
+        context.execute();
+        assert!(!context.is_setting_active(pinned, &SettingId::new(Workplace, 0)));
+    }
+
+    #[test]
+    fn test_replaced_setting_category_matches_native_itinerary() {
+        let mut context = Context::new();
+        for (category, ratio) in [
+            (&Home as &dyn SettingCategory, 0.6),
+            (&School, 0.2),
+            (&Workplace, 0.4),
+            (&CensusTract, 0.2),
+        ] {
+            context
+                .register_setting_category(
+                    category,
+                    SettingProperties {
+                        alpha: 0.1,
+                        itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                            ratio,
+                        }),
+                    },
+                )
+                .unwrap();
+        }
+        let worker = context.add_person(()).unwrap();
+        context
+            .add_itinerary(
+                worker,
+                vec![
+                    ItineraryEntry::new(SettingId::new(Home, 0), 0.6),
+                    ItineraryEntry::new(SettingId::new(CensusTract, 0), 0.2),
+                    ItineraryEntry::new(SettingId::new(Workplace, 0), 0.4),
+                ],
+            )
+            .unwrap();
+        let student = context.add_person(()).unwrap();
+        context
+            .add_itinerary(
+                student,
+                vec![
+                    ItineraryEntry::new(SettingId::new(Home, 1), 0.6),
+                    ItineraryEntry::new(SettingId::new(CensusTract, 0), 0.2),
+                    ItineraryEntry::new(SettingId::new(School, 0), 0.2),
+                ],
+            )
+            .unwrap();
+        // A setting that is inactive when the student graduates stays inactive
+        context
+            .set_setting_active(student, &SettingId::new(CensusTract, 0), false)
+            .unwrap();
+
+        context
+            .replace_setting_category_in_itinerary(
+                student,
+                &School,
+                Some(SettingId::new(Workplace, 0)),
+            )
+            .unwrap();
+        assert!(!context.is_setting_active(student, &SettingId::new(CensusTract, 0)));
+        assert!(context.is_setting_active(student, &SettingId::new(Workplace, 0)));
+
+        // Once reactivated, the graduate splits their time like the native worker
+        context
+            .set_setting_active(student, &SettingId::new(CensusTract, 0), true)
+            .unwrap();
+        let ratios = |person| -> Vec<f64> {
+            context
+                .get_itinerary(person, ItinerarySelector::Default)
+                .unwrap()
+                .iter()
+                .map(|entry| entry.ratio)
+                .collect()
+        };
+        let (worker_ratios, student_ratios) = (ratios(worker), ratios(student));
+        assert_eq!(worker_ratios.len(), student_ratios.len());
+        for (worker_ratio, student_ratio) in worker_ratios.iter().zip(&student_ratios) {
+            assert_almost_eq!(*worker_ratio, *student_ratio, 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_moved_person_returns_to_held_modified_itinerary() {
+        let mut context = Context::new();
+        context.init_random(42);
+        register_default_settings(&mut context);
+        context
+            .register_setting_category(
+                &Hospital,
+                SettingProperties {
+                    alpha: 0.1,
+                    itinerary_specification: None,
+                },
+            )
+            .unwrap();
+        let restrict_to_home = || ItineraryModifiers::RestrictTo {
+            setting: &Home,
+            setting_id: None,
+        };
+        let home = SettingId::new(Home, 0);
+        let workplace = SettingId::new(Workplace, 0);
+        let ward = SettingId::new(Hospital, 0);
+        let person = context.add_person(()).unwrap();
+        context
+            .add_itinerary(
+                person,
+                vec![
+                    ItineraryEntry::new(home, 0.5),
+                    ItineraryEntry::new(workplace, 0.5),
+                ],
+            )
+            .unwrap();
+
+        // A person isolating at home moves into the ward and ignores modifiers while there
+        context
+            .modify_itinerary(person, restrict_to_home())
+            .unwrap();
+        context.move_to_setting(person, ward).unwrap();
+        context.remove_modified_itinerary(person).unwrap();
+        context
+            .modify_itinerary(person, restrict_to_home())
+            .unwrap();
+        assert!(context.is_setting_active(person, &ward));
+        assert!(!context.is_setting_active(person, &home));
+        assert!(!context.is_setting_active(person, &workplace));
+        // A second modifier is rejected as it would be outside the ward
+        assert!(context
+            .modify_itinerary(person, restrict_to_home())
+            .is_err());
+
+        // On return, the person is still isolating
+        context.return_from_setting(person).unwrap();
+        assert!(!context.is_setting_active(person, &ward));
+        assert!(context.is_setting_active(person, &home));
+        assert!(!context.is_setting_active(person, &workplace));
+
+        // A modifier removed while in the ward is not restored on return
+        context.move_to_setting(person, ward).unwrap();
+        context.remove_modified_itinerary(person).unwrap();
+        context.return_from_setting(person).unwrap();
+        assert!(!context.is_setting_active(person, &ward));
+        assert!(context.is_setting_active(person, &home));
+        assert!(context.is_setting_active(person, &workplace));
+    }
+
+    #[test]
+    fn test_cleared_itinerary_is_not_restored_by_unpinning() {
+        let mut context = Context::new();
+        context.init_random(42);
+        register_default_settings(&mut context);
+        let person = context.add_person(()).unwrap();
+        context
+            .add_itinerary(
+                person,
+                vec![
+                    ItineraryEntry::new(SettingId::new(Home, 0), 0.5),
+                    ItineraryEntry::new(SettingId::new(Workplace, 0), 0.5),
+                ],
+            )
+            .unwrap();
+        context.clear_itinerary(person);
+        assert!(context.is_itinerary_cleared(person));
+        assert!(!context.is_itinerary_pinned(person));
+
+        // Neither unpinning nor itinerary modifiers make the person active again
+        context.unpin_itinerary(person);
+        context
+            .modify_itinerary(
+                person,
+                ItineraryModifiers::RestrictTo {
+                    setting: &Home,
+                    setting_id: None,
+                },
+            )
+            .unwrap();
+        context.remove_modified_itinerary(person).unwrap();
+        assert!(!context.is_setting_active(person, &SettingId::new(Home, 0)));
+        assert!(!context.is_setting_active(person, &SettingId::new(Workplace, 0)));
+        assert_eq!(
+            context
+                .get_setting_members(&SettingId::new(Home, 0))
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_limited_itinerary_modifier() {
+        /* H(0) = [0, 1, 2]
+          W(0) = [0,3,4] -
+         Person 0 isolates using limited itinerary by setting type.
+        */
+        let mut context = Context::new();
+        context.init_random(42);
+        register_default_settings(&mut context);
+
+        let person = context.add_person(()).unwrap();
+        let itinerary = vec![
+            ItineraryEntry::new(SettingId::new(Home, 0), 1.0),
+            ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0),
+        ];
 
-        let person = context.add_person(()).unwrap();
-        let itinerary = vec![
-            ItineraryEntry::new(SettingId::new(Home, 0), 1.0),
-            ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0),
-        ];
-
         let _ = context.add_itinerary(person, itinerary.clone());
 
         for _ in 0..2 {
@@ -1477,7 +3185,13 @@ mod test {
         println!("WORK MEMBERS (limit default): {w_members:?}");
 
         // Reduce itinerary to only Home
-        let _ = context.modify_itinerary(person, ItineraryModifiers::RestrictTo { setting: &Home });
+        let _ = context.modify_itinerary(
+            person,
+            ItineraryModifiers::RestrictTo {
+                setting: &Home,
+                setting_id: None,
+            },
+        );
 
         // Check membership
         let h_members = context
@@ -1505,6 +3219,106 @@ mod test {
         println!("WORK MEMBERS (limit isolation): {w_members:?}");
     }
 
+    #[test]
+    fn test_restrict_itinerary_to_specific_home() {
+        /* H(0) = [0, 1]
+          H(1) = [0, 2]
+          W(0) = [0, 3]
+         Person 0 has two homes and isolates at only one of them.
+        */
+        let mut context = Context::new();
+        context.init_random(42);
+        register_default_settings(&mut context);
+
+        let person = context.add_person(()).unwrap();
+        let itinerary = vec![
+            ItineraryEntry::new(SettingId::new(Home, 0), 1.0),
+            ItineraryEntry::new(SettingId::new(Home, 1), 1.0),
+            ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0),
+        ];
+        context.add_itinerary(person, itinerary).unwrap();
+        for home_id in 0..2 {
+            let p_id = context.add_person(()).unwrap();
+            context
+                .add_itinerary(
+                    p_id,
+                    vec![ItineraryEntry::new(SettingId::new(Home, home_id), 1.0)],
+                )
+                .unwrap();
+        }
+        let p_id = context.add_person(()).unwrap();
+        context
+            .add_itinerary(
+                p_id,
+                vec![ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0)],
+            )
+            .unwrap();
+
+        // The primary home is the first home in the default itinerary
+        assert_eq!(context.get_primary_setting_id(person, &Home), Some(0));
+        assert_eq!(context.get_primary_setting_id(person, &School), None);
+
+        context
+            .modify_itinerary(
+                person,
+                ItineraryModifiers::RestrictTo {
+                    setting: &Home,
+                    setting_id: Some(1),
+                },
+            )
+            .unwrap();
+
+        let h_zero_members = context
+            .get_setting_members(&SettingId::new(Home, 0))
+            .unwrap();
+        let h_one_members = context
+            .get_setting_members(&SettingId::new(Home, 1))
+            .unwrap();
+        let w_members = context
+            .get_setting_members(&SettingId::new(Workplace, 0))
+            .unwrap();
+        assert_eq!(h_zero_members.len(), 1);
+        assert!(!h_zero_members.contains(&person));
+        assert_eq!(h_one_members.len(), 2);
+        assert!(h_one_members.contains(&person));
+        assert_eq!(w_members.len(), 1);
+
+        // All of the person's time is now spent in the one home
+        let current_itinerary = context.get_current_itinerary(person).unwrap();
+        for entry in current_itinerary {
+            if entry.setting.get_type_id() == TypeId::of::<Home>() && entry.setting.id() == 1 {
+                assert_almost_eq!(entry.ratio, 1.0, 0.0);
+            } else {
+                assert_almost_eq!(entry.ratio, 0.0, 0.0);
+            }
+        }
+
+        // Restricting to a home that is not in the itinerary is an error
+        context.remove_modified_itinerary(person).unwrap();
+        let e = context
+            .modify_itinerary(
+                person,
+                ItineraryModifiers::RestrictTo {
+                    setting: &Home,
+                    setting_id: Some(2),
+                },
+            )
+            .err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "Can't restrict itinerary to a setting id not in the person's itinerary"
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that the setting id is not in the itinerary. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, the itinerary was modified."),
+        }
+    }
+
     #[test]
     fn test_exclude_setting_from_itinerary() {
         /* H(0) = [0, 1, 2]
@@ -1581,6 +3395,126 @@ mod test {
         println!("WORK MEMBERS (exclude post-isolation): {w_members:?}");
     }
 
+    #[test]
+    fn test_leaky_restrict_itinerary() {
+        let mut context = Context::new();
+        register_default_settings(&mut context);
+        let person = context.add_person(()).unwrap();
+        let itinerary = vec![
+            ItineraryEntry::new(SettingId::new(Home, 0), 0.5),
+            ItineraryEntry::new(SettingId::new(Workplace, 0), 0.5),
+        ];
+        context.add_itinerary(person, itinerary).unwrap();
+
+        // The workplace keeps a tenth of its ratio, and the rest of the time is spent at home
+        context
+            .modify_itinerary(
+                person,
+                ItineraryModifiers::LeakyRestrictTo {
+                    setting: &Home,
+                    setting_id: Some(0),
+                    leakage: 0.1,
+                },
+            )
+            .unwrap();
+        let ratios: Vec<f64> = context
+            .get_current_itinerary(person)
+            .unwrap()
+            .iter()
+            .map(|entry| entry.ratio)
+            .collect();
+        assert_almost_eq!(ratios[0], 0.95, 1e-12);
+        assert_almost_eq!(ratios[1], 0.05, 1e-12);
+        context.remove_modified_itinerary(person).unwrap();
+
+        let e = context
+            .modify_itinerary(
+                person,
+                ItineraryModifiers::LeakyRestrictTo {
+                    setting: &Home,
+                    setting_id: None,
+                    leakage: 1.5,
+                },
+            )
+            .err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(msg, "Itinerary leakage must be between 0 and 1");
+            }
+            Some(ue) => panic!(
+                "Expected an error that the leakage is invalid. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, restricted the itinerary."),
+        }
+    }
+
+    #[test]
+    fn test_scale_setting_in_itinerary() {
+        let mut context = Context::new();
+        register_default_settings(&mut context);
+        let person = context.add_person(()).unwrap();
+        let itinerary = vec![
+            ItineraryEntry::new(SettingId::new(Home, 0), 0.5),
+            ItineraryEntry::new(SettingId::new(Workplace, 0), 0.5),
+        ];
+        context.add_itinerary(person, itinerary).unwrap();
+
+        // Halving the workplace ratio leaves the person spending a third of their time at work
+        context
+            .modify_itinerary(
+                person,
+                ItineraryModifiers::ScaleSetting {
+                    setting: &Workplace,
+                    factor: 0.5,
+                },
+            )
+            .unwrap();
+        let ratios: Vec<f64> = context
+            .get_current_itinerary(person)
+            .unwrap()
+            .iter()
+            .map(|entry| entry.ratio)
+            .collect();
+        assert_almost_eq!(ratios[0], 2.0 / 3.0, 1e-12);
+        assert_almost_eq!(ratios[1], 1.0 / 3.0, 1e-12);
+        assert!(context.is_setting_active(person, &SettingId::new(Workplace, 0)));
+        context.remove_modified_itinerary(person).unwrap();
+
+        // A factor of zero is the same as excluding the setting
+        context
+            .modify_itinerary(
+                person,
+                ItineraryModifiers::ScaleSetting {
+                    setting: &Workplace,
+                    factor: 0.0,
+                },
+            )
+            .unwrap();
+        assert!(!context.is_setting_active(person, &SettingId::new(Workplace, 0)));
+        context.remove_modified_itinerary(person).unwrap();
+
+        let e = context
+            .modify_itinerary(
+                person,
+                ItineraryModifiers::ScaleSetting {
+                    setting: &Workplace,
+                    factor: -1.0,
+                },
+            )
+            .err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(msg, "Setting scale factor must be non-negative");
+            }
+            Some(ue) => panic!(
+                "Expected an error that the scale factor is negative. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, scaled the setting by a negative factor."),
+        }
+    }
+
     #[test]
     fn test_setting_registration() {
         let mut context = Context::new();
@@ -1725,7 +3659,7 @@ mod test {
         let setting_type = &SettingId::new(Home, home_id);
 
         let inf_multiplier = setting_type.calculate_multiplier(
-            members,
+            members.len(),
             SettingProperties {
                 alpha: 0.1,
                 itinerary_specification: None,
@@ -1736,6 +3670,202 @@ mod test {
         assert_almost_eq!(inf_multiplier, f64::from(6 - 1).powf(0.1), 0.0);
     }
 
+    #[test]
+    fn test_set_setting_active() {
+        let mut context = Context::new();
+        register_default_settings(&mut context);
+        let person = context.add_person(()).unwrap();
+        let home = SettingId::new(Home, 0);
+        let workplace = SettingId::new(Workplace, 0);
+        let itinerary = vec![
+            ItineraryEntry::new(home, 0.5),
+            ItineraryEntry::new(workplace, 0.5),
+        ];
+        context.add_itinerary(person, itinerary).unwrap();
+        assert!(context.is_setting_active(person, &workplace));
+
+        // Toggle the workplace off
+        context
+            .set_setting_active(person, &workplace, false)
+            .unwrap();
+        assert!(!context.is_setting_active(person, &workplace));
+        assert!(context.is_setting_active(person, &home));
+        assert!(context.get_setting_members(&workplace).unwrap().is_empty());
+        let ratios: Vec<f64> = context
+            .get_current_itinerary(person)
+            .unwrap()
+            .iter()
+            .map(|entry| entry.ratio)
+            .collect();
+        assert_eq!(ratios, vec![1.0, 0.0]);
+
+        // The home can't also be turned off because there would be no active settings
+        let e = context.set_setting_active(person, &home, false).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "Can't deactivate every setting in a person's itinerary"
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that every setting would be deactivated. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, deactivated every setting."),
+        }
+
+        // Toggle the workplace back on
+        context
+            .set_setting_active(person, &workplace, true)
+            .unwrap();
+        assert!(context.is_setting_active(person, &workplace));
+        assert!(context
+            .get_setting_members(&workplace)
+            .unwrap()
+            .contains(&person));
+        let ratios: Vec<f64> = context
+            .get_current_itinerary(person)
+            .unwrap()
+            .iter()
+            .map(|entry| entry.ratio)
+            .collect();
+        assert_eq!(ratios, vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_add_itinerary_with_modified_itinerary() {
+        let mut context = Context::new();
+        register_default_settings(&mut context);
+        let person = context.add_person(()).unwrap();
+        let home = SettingId::new(Home, 0);
+        let school = SettingId::new(School, 0);
+        let workplace = SettingId::new(Workplace, 0);
+        context
+            .add_itinerary(
+                person,
+                vec![
+                    ItineraryEntry::new(home, 0.5),
+                    ItineraryEntry::new(school, 0.5),
+                ],
+            )
+            .unwrap();
+        context
+            .modify_itinerary(
+                person,
+                ItineraryModifiers::RestrictTo {
+                    setting: &Home,
+                    setting_id: None,
+                },
+            )
+            .unwrap();
+
+        // Replacing the default itinerary does not change membership while the modified itinerary
+        // is in place
+        context
+            .add_itinerary(
+                person,
+                vec![
+                    ItineraryEntry::new(home, 0.5),
+                    ItineraryEntry::new(workplace, 0.5),
+                ],
+            )
+            .unwrap();
+        assert!(context.is_setting_active(person, &home));
+        assert!(!context.is_setting_active(person, &school));
+        assert!(!context.is_setting_active(person, &workplace));
+
+        // The new default itinerary is activated once the modified itinerary is removed
+        context.remove_modified_itinerary(person).unwrap();
+        assert!(context.is_setting_active(person, &home));
+        assert!(!context.is_setting_active(person, &school));
+        assert!(context.is_setting_active(person, &workplace));
+        assert_eq!(context.active_member_count(&school), 0);
+    }
+
+    #[test]
+    fn test_set_setting_active_with_modified_itinerary() {
+        let mut context = Context::new();
+        register_default_settings(&mut context);
+        let person = context.add_person(()).unwrap();
+        let workplace = SettingId::new(Workplace, 0);
+        let itinerary = vec![
+            ItineraryEntry::new(SettingId::new(Home, 0), 0.5),
+            ItineraryEntry::new(workplace, 0.5),
+        ];
+        context.add_itinerary(person, itinerary).unwrap();
+        context
+            .modify_itinerary(
+                person,
+                ItineraryModifiers::RestrictTo {
+                    setting: &Home,
+                    setting_id: None,
+                },
+            )
+            .unwrap();
+        assert!(!context.is_setting_active(person, &workplace));
+
+        // Toggling the default itinerary does not change membership while the modified itinerary
+        // is in place
+        context
+            .set_setting_active(person, &workplace, false)
+            .unwrap();
+        assert!(!context.is_setting_active(person, &workplace));
+        context
+            .set_setting_active(person, &workplace, true)
+            .unwrap();
+        assert!(!context.is_setting_active(person, &workplace));
+
+        context
+            .set_setting_active(person, &workplace, false)
+            .unwrap();
+        context.remove_modified_itinerary(person).unwrap();
+        assert!(!context.is_setting_active(person, &workplace));
+    }
+
+    #[test]
+    fn test_active_member_count() {
+        let mut context = Context::new();
+        register_default_settings(&mut context);
+        for _ in 0..3 {
+            let person = context.add_person(()).unwrap();
+            let itinerary = vec![
+                ItineraryEntry::new(SettingId::new(Home, 0), 0.5),
+                ItineraryEntry::new(SettingId::new(Workplace, 0), 0.5),
+            ];
+            context.add_itinerary(person, itinerary).unwrap();
+        }
+        // Make one person inactive in the workplace
+        let person = context.add_person(()).unwrap();
+        let itinerary = vec![
+            ItineraryEntry::new(SettingId::new(Home, 1), 0.5),
+            ItineraryEntry::new(SettingId::new(Workplace, 0), 0.5),
+        ];
+        context.add_itinerary(person, itinerary).unwrap();
+        context
+            .modify_itinerary(
+                person,
+                ItineraryModifiers::Exclude {
+                    setting: &Workplace,
+                },
+            )
+            .unwrap();
+
+        for (setting, expected) in [(SettingId::new(Home, 0), 3), (SettingId::new(Home, 1), 1)] {
+            assert_eq!(context.active_member_count(&setting), expected);
+            assert_eq!(
+                context.get_setting_members(&setting).unwrap().len(),
+                expected
+            );
+        }
+        // The excluded person is no longer an active member of the workplace
+        let workplace = SettingId::new(Workplace, 0);
+        assert_eq!(context.active_member_count(&workplace), 3);
+        assert_eq!(context.get_setting_members(&workplace).unwrap().len(), 3);
+        // Settings that no one has joined have no members
+        assert_eq!(context.active_member_count(&SettingId::new(School, 0)), 0);
+    }
+
     #[test]
     fn test_total_infectiousness_multiplier() {
         // Go through all the settings and compute infectiousness multiplier
@@ -1915,6 +4045,96 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_link_classroom_to_different_school_errors() {
+        let mut context = Context::new();
+        context.link_classroom_to_school(0, 0).unwrap();
+        // Linking to the same school again is fine
+        context.link_classroom_to_school(0, 0).unwrap();
+        assert_eq!(context.get_classroom_school_id(0), Some(0));
+        assert_eq!(context.get_classroom_school_id(1), None);
+
+        let e = context.link_classroom_to_school(0, 1).err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(msg, "Classroom 0 is already linked to school 0");
+            }
+            Some(ue) => panic!(
+                "Expected an error that the classroom is already linked. Instead got {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, linking the classroom passed."),
+        }
+    }
+
+    #[test]
+    fn test_classroom_mates_contacted_more_than_schoolmates() {
+        let mut context = Context::new();
+        context.init_random(42);
+        for category in [&School as &dyn SettingCategory, &Classroom] {
+            context
+                .register_setting_category(
+                    category,
+                    SettingProperties {
+                        alpha: 0.0,
+                        itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                            ratio: 0.5,
+                        }),
+                    },
+                )
+                .unwrap();
+        }
+        // One school with two classrooms of ten students each
+        context.link_classroom_to_school(0, 0).unwrap();
+        context.link_classroom_to_school(1, 0).unwrap();
+        let mut classrooms = HashMap::new();
+        let mut students = vec![];
+        for classroom_id in [0, 1] {
+            for _ in 0..10 {
+                let person = context.add_person(()).unwrap();
+                students.push(person);
+                let mut itinerary = vec![];
+                append_classroom_itinerary_entries(
+                    &mut itinerary,
+                    &context,
+                    classroom_id,
+                    None,
+                    None,
+                )
+                .unwrap();
+                assert_eq!(itinerary.len(), 2);
+                context.add_itinerary(person, itinerary).unwrap();
+                classrooms.insert(person, classroom_id);
+            }
+        }
+
+        // Contacts (and so infection attempts) of a student in classroom 0 occur in both the
+        // school and the classroom
+        let student = students[0];
+        let num_contacts = 10_000;
+        let mut classmate_contacts = 0;
+        let mut schoolmate_contacts = 0;
+        for _ in 0..num_contacts {
+            let setting = context.sample_current_setting(student).unwrap();
+            let contact = context
+                .sample_from_setting_with_exclusion(student, setting)
+                .unwrap()
+                .unwrap();
+            if classrooms[&contact] == classrooms[&student] {
+                classmate_contacts += 1;
+            } else {
+                schoolmate_contacts += 1;
+            }
+        }
+        // Each of the 9 classmates is contacted with probability 0.5 / 19 + 0.5 / 9, and each of
+        // the 10 schoolmates in the other classroom with probability 0.5 / 19
+        let classmate_rate = f64::from(classmate_contacts) / 9.0 / f64::from(num_contacts);
+        let schoolmate_rate = f64::from(schoolmate_contacts) / 10.0 / f64::from(num_contacts);
+        assert!(classmate_rate > schoolmate_rate);
+        assert_almost_eq!(classmate_rate, 0.5 / 19.0 + 0.5 / 9.0, 0.005);
+        assert_almost_eq!(schoolmate_rate, 0.5 / 19.0, 0.005);
+    }
+
     define_person_property!(Age, usize);
 
     #[test]
@@ -1995,6 +4215,50 @@ mod test {
         assert_almost_eq!(itinerary[2].ratio, 1.0, 0.0);
     }
 
+    #[test]
+    fn test_build_default_itinerary_matches_manual_itinerary() {
+        let mut context = Context::new();
+        // School is not registered, so school entries are skipped
+        for (category, ratio) in [
+            (&Home as &dyn SettingCategory, 1.0),
+            (&CensusTract, 0.5),
+            (&Workplace, 0.5),
+        ] {
+            context
+                .register_setting_category(
+                    category,
+                    SettingProperties {
+                        alpha: 0.1,
+                        itinerary_specification: Some(ItinerarySpecificationType::Constant {
+                            ratio,
+                        }),
+                    },
+                )
+                .unwrap();
+        }
+
+        let itinerary = build_default_itinerary(&context, 1, Some(4), 2, Some(3)).unwrap();
+        let manual_itinerary = vec![
+            ItineraryEntry::new(SettingId::new(Home, 1), 0.5),
+            ItineraryEntry::new(SettingId::new(CensusTract, 2), 0.25),
+            ItineraryEntry::new(SettingId::new(Workplace, 4), 0.25),
+        ];
+        assert_eq!(itinerary.len(), manual_itinerary.len());
+        for (entry, manual_entry) in itinerary.iter().zip(&manual_itinerary) {
+            assert_eq!(
+                entry.setting.get_tuple_id(),
+                manual_entry.setting.get_tuple_id()
+            );
+            assert_almost_eq!(entry.ratio, manual_entry.ratio, 1e-12);
+        }
+
+        // Without a workplace, only the home and census tract remain
+        let itinerary = build_default_itinerary(&context, 1, None, 2, None).unwrap();
+        assert_eq!(itinerary.len(), 2);
+        assert_almost_eq!(itinerary[0].ratio, 2.0 / 3.0, 1e-12);
+        assert_almost_eq!(itinerary[1].ratio, 1.0 / 3.0, 1e-12);
+    }
+
     #[test]
     fn test_get_itinerary_ratio() {
         let mut context = Context::new();
@@ -2015,6 +4279,274 @@ mod test {
         assert_almost_eq!(ratio, 0.5, 0.0);
     }
 
+    #[test]
+    fn test_distance_kernel_weights() {
+        let exponential = DistanceKernel::Exponential { length_scale: 2.0 };
+        assert_almost_eq!(exponential.weight(0.0), 1.0, 0.0);
+        assert_almost_eq!(exponential.weight(2.0), (-1.0_f64).exp(), 1e-12);
+        let power_law = DistanceKernel::PowerLaw {
+            length_scale: 1.0,
+            exponent: 2.0,
+        };
+        assert_almost_eq!(power_law.weight(0.0), 1.0, 0.0);
+        assert_almost_eq!(power_law.weight(3.0), 1.0 / 16.0, 1e-12);
+    }
+
+    #[test]
+    fn test_census_tract_kernel_cross_tract_contacts() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"tract_id,x,y\n1,0.0,0.0\n2,3.0,4.0\n").unwrap();
+        let mut context = Context::new();
+        context.init_random(0);
+        context
+            .register_setting_category(
+                &CensusTract,
+                SettingProperties {
+                    alpha: 0.0,
+                    itinerary_specification: None,
+                },
+            )
+            .unwrap();
+        let kernel = DistanceKernel::Exponential { length_scale: 2.5 };
+        load_census_tract_kernel(
+            &mut context,
+            &CensusTractKernel {
+                coordinates_file: file.path().to_path_buf(),
+                kernel,
+            },
+        )
+        .unwrap();
+        let mut people = vec![];
+        for tract in [1, 2] {
+            for _ in 0..50 {
+                let person = context.add_person(()).unwrap();
+                context
+                    .add_itinerary(
+                        person,
+                        vec![ItineraryEntry::new(SettingId::new(CensusTract, tract), 1.0)],
+                    )
+                    .unwrap();
+                people.push(person);
+            }
+        }
+
+        // The tracts are at a distance of 5.0, so a contact made in tract 1 is in tract 2 with
+        // probability w / (1 + w), where w is the kernel weight at that distance
+        let num_contacts = 20_000;
+        let tract_2 = context
+            .get_setting_members(&SettingId::new(CensusTract, 2))
+            .unwrap()
+            .clone();
+        let mut cross_tract_contacts = 0;
+        for _ in 0..num_contacts {
+            let contact = context
+                .sample_from_setting_with_exclusion(people[0], &SettingId::new(CensusTract, 1))
+                .unwrap()
+                .unwrap();
+            assert_ne!(contact, people[0]);
+            if tract_2.contains(&contact) {
+                cross_tract_contacts += 1;
+            }
+        }
+        let weight = kernel.weight(5.0);
+        #[allow(clippy::cast_precision_loss)]
+        let cross_tract_fraction = f64::from(cross_tract_contacts) / num_contacts as f64;
+        assert_almost_eq!(cross_tract_fraction, weight / (1.0 + weight), 0.01);
+    }
+
+    #[test]
+    fn test_census_tract_kernel_rejects_duplicate_tracts() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"tract_id,x,y\n1,0.0,0.0\n1,3.0,4.0\n").unwrap();
+        let mut context = Context::new();
+        let e = load_census_tract_kernel(
+            &mut context,
+            &CensusTractKernel {
+                coordinates_file: file.path().to_path_buf(),
+                kernel: DistanceKernel::Exponential { length_scale: 1.0 },
+            },
+        )
+        .err();
+        match e {
+            Some(IxaError::IxaError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "Census tract 1 appears more than once in the coordinates file."
+                );
+            }
+            Some(ue) => panic!(
+                "Expected an error that a tract is duplicated. Instead got: {:?}",
+                ue.to_string()
+            ),
+            None => panic!("Expected an error. Instead, the coordinates file was loaded."),
+        }
+    }
+
+    #[test]
+    fn test_activity_calendar_non_activity_days() {
+        let calendar = ActivityCalendar {
+            weekends: true,
+            holidays: vec![2, 9],
+        };
+        let closed_days: Vec<i32> = (-2..14)
+            .filter(|&day| calendar.is_non_activity_day(f64::from(day) + 0.5, 0))
+            .collect();
+        assert_eq!(closed_days, vec![-2, -1, 2, 5, 6, 9, 12, 13]);
+    }
+
+    #[test]
+    fn test_resolved_setting_properties_reflect_alpha_override() {
+        let setting_properties = SettingProperties {
+            alpha: 0.5,
+            itinerary_specification: Some(ItinerarySpecificationType::Constant { ratio: 0.5 }),
+        };
+        let mut parameters = Params {
+            max_time: 10.0,
+            settings_properties: HashMap::from_iter([
+                (CoreSettingsTypes::Home, setting_properties),
+                (CoreSettingsTypes::Workplace, setting_properties),
+                (CoreSettingsTypes::School, setting_properties),
+            ]),
+            setting_contact_rates: Some(HashMap::from_iter([(
+                CoreSettingsTypes::School,
+                SettingContactRate {
+                    contacts_per_day: 10.0,
+                    transmission_probability: 0.1,
+                },
+            )])),
+            activity_calendar: Some(ActivityCalendar {
+                weekends: true,
+                holidays: vec![],
+            }),
+            setting_properties_summary: Some(true),
+            ..Default::default()
+        };
+        // Override the workplace alpha of the input, as a command-line override would
+        parameters.settings_properties.insert(
+            CoreSettingsTypes::Workplace,
+            SettingProperties {
+                alpha: 0.25,
+                itinerary_specification: None,
+            },
+        );
+        let mut context = Context::new();
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        init(&mut context).unwrap();
+
+        let resolved = context.get_resolved_setting_properties();
+        let setting_types: Vec<&str> = resolved
+            .iter()
+            .map(|resolved| resolved.setting_type.as_str())
+            .collect();
+        assert_eq!(setting_types, vec!["Home", "School", "Workplace"]);
+        assert_almost_eq!(resolved[0].alpha, 0.5, 0.0);
+        assert_eq!(resolved[0].ratio, Some(0.5));
+        assert!(!resolved[0].activity_calendar);
+        assert_eq!(
+            resolved[1].contact_rate,
+            Some(SettingContactRate {
+                contacts_per_day: 10.0,
+                transmission_probability: 0.1,
+            })
+        );
+        assert!(resolved[1].activity_calendar);
+        assert_almost_eq!(resolved[2].alpha, 0.25, 0.0);
+        assert_eq!(resolved[2].ratio, None);
+        assert!(resolved[2].contact_rate.is_none());
+        assert!(!resolved[2].daily_activity_profile);
+        assert!(resolved[2].activity_calendar);
+    }
+
+    #[test]
+    fn test_no_workplace_transmission_on_holidays() {
+        let mut context = Context::new();
+        let setting_properties = SettingProperties {
+            alpha: 0.0,
+            itinerary_specification: Some(ItinerarySpecificationType::Constant { ratio: 1.0 }),
+        };
+        let parameters = Params {
+            max_time: 10.0,
+            settings_properties: HashMap::from_iter([
+                (CoreSettingsTypes::Home, setting_properties),
+                (CoreSettingsTypes::Workplace, setting_properties),
+            ]),
+            activity_calendar: Some(ActivityCalendar {
+                weekends: false,
+                holidays: vec![2],
+            }),
+            ..Default::default()
+        };
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        context.init_random(0);
+        init(&mut context).unwrap();
+
+        // A worker who lives with one person and works with another
+        let worker = context.add_person(()).unwrap();
+        let housemate = context.add_person(()).unwrap();
+        let coworker = context.add_person(()).unwrap();
+        context
+            .add_itinerary(
+                worker,
+                vec![
+                    ItineraryEntry::new(SettingId::new(Home, 0), 1.0),
+                    ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0),
+                ],
+            )
+            .unwrap();
+        context
+            .add_itinerary(
+                housemate,
+                vec![ItineraryEntry::new(SettingId::new(Home, 0), 1.0)],
+            )
+            .unwrap();
+        context
+            .add_itinerary(
+                coworker,
+                vec![ItineraryEntry::new(SettingId::new(Workplace, 0), 1.0)],
+            )
+            .unwrap();
+
+        for (time, holiday) in [(1.5, false), (2.0, true), (2.5, true), (3.0, false)] {
+            context.add_plan(time, move |context| {
+                let workplace_samples = (0..100)
+                    .filter(|_| {
+                        context
+                            .sample_current_setting(worker)
+                            .unwrap()
+                            .get_type_id()
+                            == TypeId::of::<Workplace>()
+                    })
+                    .count();
+                if holiday {
+                    // Only the home contributes to the worker's infectiousness
+                    assert_almost_eq!(
+                        context.calculate_current_infectiousness_multiplier_for_person(worker),
+                        0.5,
+                        0.0
+                    );
+                    assert_almost_eq!(
+                        context.calculate_current_infectiousness_multiplier_for_person(coworker),
+                        0.0,
+                        0.0
+                    );
+                    assert_eq!(workplace_samples, 0);
+                } else {
+                    assert_almost_eq!(
+                        context.calculate_current_infectiousness_multiplier_for_person(worker),
+                        1.0,
+                        0.0
+                    );
+                    assert!(workplace_samples > 0);
+                }
+            });
+        }
+        context.execute();
+    }
+
     #[test]
     fn test_only_include_registered_settings_in_itineraries() {
         let mut context = Context::new();
@@ -2039,7 +4571,7 @@ mod test {
             .set_global_property_value(GlobalParams, parameters)
             .unwrap();
 
-        init(&mut context);
+        init(&mut context).unwrap();
         let mut iitinerary = vec![];
         append_itinerary_entry(
             &mut iitinerary,
@@ -1,9 +1,10 @@
 use ixa::rand::Rng;
 use ixa::{
-    define_derived_property, define_person_property_with_default, define_rng, Context,
-    ContextPeopleExt, ContextRandomExt, HashMap, IxaError, PersonPropertyChangeEvent,
+    define_data_plugin, define_derived_property, define_person_property_with_default, define_rng,
+    trace, Context, ContextPeopleExt, ContextRandomExt, HashMap, IxaError, PersonId,
+    PersonPropertyChangeEvent,
 };
-use rand_distr::Weibull;
+use rand_distr::{Exp, Weibull};
 use serde::{Deserialize, Serialize};
 
 use crate::interventions::{ContextTransmissionModifierExt, TransmissionModifier};
@@ -13,11 +14,12 @@ use crate::natural_history_parameter_manager::{
 use crate::parameters::{ContextParametersExt, RateFnType};
 use crate::rate_fns::RateFn;
 use crate::{
-    infectiousness_manager::{InfectionStatus, InfectionStatusValue},
+    infectiousness_manager::{InfectionStatus, InfectionStatusValue, WithinMinReinfectionInterval},
     property_progression_manager::{load_progressions, ContextPropertyProgressionExt, Progression},
 };
 
 define_rng!(SymptomRng);
+define_rng!(SymptomEscalationRng);
 
 #[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize, Eq, Hash)]
 pub enum SymptomValue {
@@ -46,6 +48,40 @@ pub struct SymptomRecordValue {
 
 define_person_property_with_default!(SymptomRecord, Option<SymptomRecordValue>, None);
 
+// The symptom category of each registered symptom progression, indexed in the same order as the
+// progressions are registered so that the index matches a person's `Symptoms` parameter id.
+#[derive(Default)]
+struct SymptomCategoryContainer {
+    categories: Vec<SymptomValue>,
+}
+
+define_data_plugin!(
+    SymptomCategoryPlugin,
+    SymptomCategoryContainer,
+    SymptomCategoryContainer::default()
+);
+
+/// Whether a symptom category is considered severe.
+#[must_use]
+pub fn is_severe(category: SymptomValue) -> bool {
+    category == SymptomValue::Category1
+}
+
+/// Returns the symptom category of the symptom progression assigned to a person. The category is
+/// known as soon as the person is assigned a progression, before they develop symptoms. Returns
+/// `None` if the assigned progression was not registered from symptom data.
+pub fn get_assigned_symptom_category(
+    context: &Context,
+    person_id: PersonId,
+) -> Option<SymptomValue> {
+    let id = context.get_parameter_id(Symptoms, person_id);
+    context
+        .get_data(SymptomCategoryPlugin)
+        .categories
+        .get(id)
+        .copied()
+}
+
 /// Stores information about a symptom progression (presymptomatic -> category{1..=4} -> None)
 /// for a person.
 /// Includes an incubation period and the time to symptom improvement distribution.
@@ -173,6 +209,10 @@ impl SymptomData {
             time_to_symptom_improvement,
         };
         context.register_property_progression(Symptoms, progression);
+        context
+            .get_data_mut(SymptomCategoryPlugin)
+            .categories
+            .push(category);
         Ok(())
     }
 }
@@ -226,11 +266,15 @@ fn schedule_recovery(data: &SymptomData, context: &Context) -> (Option<SymptomVa
 struct AsymptomaticInfectiousnessModifier;
 impl TransmissionModifier for AsymptomaticInfectiousnessModifier {
     fn get_relative_transmission(&self, context: &Context, person_id: ixa::PersonId) -> f64 {
-        let relative_infectiousness = context.get_params().relative_infectiousness_asymptomatics;
+        let params = context.get_params();
+        let relative_infectiousness = params.relative_infectiousness_asymptomatics;
         let symptoms = context.get_person_property(person_id, Symptoms);
         match symptoms {
-            // Some can be either are Some(Presymptmatic) or Some(Category{1..=4})
-            // Presymptomatic individuals are not asymptomatic -- they will eventually develop symptoms
+            // Presymptomatic individuals are not asymptomatic -- they will eventually develop
+            // symptoms -- but they may transmit less before their symptoms start
+            Some(SymptomValue::Presymptomatic) => {
+                params.presymptomatic_infectiousness_factor.unwrap_or(1.0)
+            }
             Some(_) => 1.0,
             // None means person is fully asymptomatic -- will never develop symptoms
             None => relative_infectiousness,
@@ -249,7 +293,7 @@ pub fn init(context: &mut Context) -> Result<(), IxaError> {
     // Register to symptom changes to record symptom data
     record_symptom_changes(context);
 
-    // Register that asymptomatics have an infectiousness modifier
+    // Register that asymptomatics and presymptomatics have an infectiousness modifier
     context.register_transmission_modifier_fn(
         InfectionStatusValue::Infectious,
         AsymptomaticInfectiousnessModifier,
@@ -257,10 +301,12 @@ pub fn init(context: &mut Context) -> Result<(), IxaError> {
 
     // For isolation guidance, each rate function has a corresponding symptom improvement time
     // distribution, so we enforce a 1:1 relationship between the two.
-    // If the rate function is constant, we sample a symptom category from the symptom library.
+    // If the rate function is constant or from a JSON library, we sample a symptom category from the symptom library.
     let rate_of_infection = context.get_params().infectiousness_rate_fn.clone();
     match rate_of_infection {
-        RateFnType::Constant { .. } => {
+        RateFnType::Constant { .. }
+        | RateFnType::LibraryFromJson { .. }
+        | RateFnType::ViralLoad { .. } => {
             context.register_parameter_id_assigner(Symptoms, |context, _person_id| {
                 let library_size = Symptoms.library_size(context);
                 context.sample_range(SymptomRng, 0..library_size)
@@ -279,6 +325,15 @@ fn subscribe_to_becoming_infected(context: &mut Context) {
     context.subscribe_to_event(
         |context, event: PersonPropertyChangeEvent<InfectionStatus>| {
             if event.current == InfectionStatusValue::Infectious {
+                // A reinfection soon after recovery does not start a new symptom episode, which
+                // could otherwise overlap the previous one
+                if context.get_person_property(event.person_id, WithinMinReinfectionInterval) {
+                    trace!(
+                        "Person {}: No new symptom episode within the minimum reinfection interval",
+                        event.person_id
+                    );
+                    return;
+                }
                 let prop_asymptomatic = context.get_params().proportion_asymptomatic;
                 // We parameterize the model in terms of proportion asymptomatic, but we only do
                 // something if the person is symptomatic, so hence p = 1.0 - prop_asymptomatic.
@@ -298,6 +353,33 @@ fn subscribe_to_becoming_infected(context: &mut Context) {
     );
 }
 
+/// Schedule a person whose symptoms started mild to escalate to moderate symptoms at an
+/// exponentially distributed time with the escalation rate. The escalation only happens if the
+/// same symptom episode is still ongoing at that time. Escalating updates the person's symptom
+/// record to the moderate category, so that the isolation guidance treats them as severe and they
+/// are evaluated for hospitalization again, but their `Symptoms` progression and its recovery time
+/// are unchanged.
+fn schedule_symptom_escalation(context: &mut Context, person_id: PersonId, escalation_rate: f64) {
+    let symptom_start = context.get_current_time();
+    let time_to_escalation = context.scale_duration(
+        context.sample_distr(SymptomEscalationRng, Exp::new(escalation_rate).unwrap()),
+    );
+    context.add_plan(symptom_start + time_to_escalation, move |context| {
+        let Some(mut record_value) = context.get_person_property(person_id, SymptomRecord) else {
+            return;
+        };
+        // The symptom start time identifies the episode the escalation was drawn for
+        #[allow(clippy::float_cmp)]
+        let same_episode = record_value.symptom_start == symptom_start;
+        if same_episode && record_value.symptom_end.is_none() {
+            trace!("Person {person_id}: Symptoms escalated to moderate");
+            record_value.category = SymptomValue::Category1;
+            record_value.severe = true;
+            context.set_person_property(person_id, SymptomRecord, Some(record_value));
+        }
+    });
+}
+
 fn record_symptom_changes(context: &mut Context) {
     context.subscribe_to_event(
         |context, event: PersonPropertyChangeEvent<PresentingWithSymptoms>| {
@@ -305,16 +387,11 @@ fn record_symptom_changes(context: &mut Context) {
                 // If the person is presenting with symptoms, we record the symptom data.
                 let category = context.get_person_property(event.person_id, Symptoms);
                 if let Some(category) = category {
-                    let severe = match category {
-                        SymptomValue::Category1 => true,
-                        SymptomValue::Category2
-                        | SymptomValue::Category3
-                        | SymptomValue::Category4 => false,
-                        SymptomValue::Presymptomatic => {
-                            // Presymptomatic is not severe, but we don't record it in the symptom record
-                            return;
-                        }
-                    };
+                    if category == SymptomValue::Presymptomatic {
+                        // Presymptomatic is not severe, but we don't record it in the symptom record
+                        return;
+                    }
+                    let severe = is_severe(category);
                     let record_value = SymptomRecordValue {
                         category,
                         symptom_start: context.get_current_time(),
@@ -322,6 +399,11 @@ fn record_symptom_changes(context: &mut Context) {
                         severe,
                     };
                     context.set_person_property(event.person_id, SymptomRecord, Some(record_value));
+                    if let Some(escalation_rate) = context.get_params().symptom_escalation_rate {
+                        if !severe {
+                            schedule_symptom_escalation(context, event.person_id, escalation_rate);
+                        }
+                    }
                 }
             } else if let Some(mut record_value) =
                 context.get_person_property(event.person_id, SymptomRecord)
@@ -338,7 +420,7 @@ fn record_symptom_changes(context: &mut Context) {
 mod test {
     use std::{cell::RefCell, rc::Rc};
 
-    use super::{init, SymptomData, SymptomValue};
+    use super::{init, record_symptom_changes, SymptomData, SymptomRecord, SymptomValue};
     use crate::{
         infectiousness_manager::InfectionContextExt,
         interventions::ContextTransmissionModifierExt,
@@ -710,6 +792,57 @@ mod test {
         );
     }
 
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn test_fraction_of_mild_cases_escalate_to_severe() {
+        let escalation_rate = 0.5;
+        let symptom_duration = 2.0;
+        let mut context = Context::new();
+        let parameters = Params {
+            max_time: 10.0,
+            symptom_escalation_rate: Some(escalation_rate),
+            ..Default::default()
+        };
+        context.init_random(parameters.seed);
+        context
+            .set_global_property_value(GlobalParams, parameters)
+            .unwrap();
+        record_symptom_changes(&mut context);
+
+        // Everyone develops mild symptoms at the same time and recovers after the same duration
+        let num_people = 2000;
+        let people: Vec<_> = (0..num_people)
+            .map(|_| context.add_person(()).unwrap())
+            .collect();
+        for &person in &people {
+            context.add_plan(1.0, move |context| {
+                context.set_person_property(person, Symptoms, Some(SymptomValue::Category2));
+            });
+            context.add_plan(1.0 + symptom_duration, move |context| {
+                context.set_person_property(person, Symptoms, None);
+            });
+        }
+        context.execute();
+
+        let records: Vec<_> = people
+            .iter()
+            .map(|&person| context.get_person_property(person, SymptomRecord).unwrap())
+            .collect();
+        // Escalated cases follow the moderate pathway, and nobody escalates after their symptoms
+        // have resolved
+        for record in &records {
+            assert_eq!(record.severe, record.category == SymptomValue::Category1);
+            assert!(record.severe || record.category == SymptomValue::Category2);
+            assert_almost_eq!(record.symptom_end.unwrap(), 1.0 + symptom_duration, 0.0);
+        }
+        let escalated = records.iter().filter(|record| record.severe).count();
+        assert_almost_eq!(
+            escalated as f64 / num_people as f64,
+            1.0 - (-escalation_rate * symptom_duration).exp(),
+            0.05
+        );
+    }
+
     #[test]
     fn test_asymptomatic_infectiousness_modifier() {
         // Make a simulation where all people are asymptomatic
@@ -0,0 +1,90 @@
+use ixa::{ContextGlobalPropertiesExt, PluginContext};
+
+use crate::parameters::GlobalParams;
+
+/// The number of days in a week
+pub const DAYS_PER_WEEK: u8 = 7;
+
+/// The day of the epidemic that `time` falls in, where day `d` covers the simulation times in
+/// `[d, d + 1)`. Times before the start of the simulation (e.g., when initial infections are
+/// seeded) fall on day 0.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+#[must_use]
+pub fn day_of_epidemic(time: f64) -> u64 {
+    time.floor().max(0.0) as u64
+}
+
+/// The day of the week that `time` falls in, from 0 (Monday) to 6 (Sunday), when day 0 of the
+/// epidemic falls on `start_day_of_week`. Times before the start of the simulation continue the
+/// weekly cycle backwards.
+#[allow(clippy::cast_possible_truncation)]
+#[must_use]
+pub fn day_of_week(time: f64, start_day_of_week: u8) -> u8 {
+    let day = time.floor() as i64 + i64::from(start_day_of_week);
+    day.rem_euclid(i64::from(DAYS_PER_WEEK)) as u8
+}
+
+/// Whether the day of the week is a weekend day (Saturday or Sunday)
+#[must_use]
+pub fn is_weekend(day_of_week: u8) -> bool {
+    day_of_week >= 5
+}
+
+/// Maps the current simulation time to calendar days, so that all calendar-aware features agree
+/// on which day it is. The day of the week of day 0 is `start_day_of_week`, or Monday if it is not
+/// specified or the parameters have not been set.
+pub trait ContextCalendarExt: PluginContext + ContextGlobalPropertiesExt {
+    /// The day of the week of day 0 of the epidemic
+    fn get_start_day_of_week(&self) -> u8 {
+        self.get_global_property_value(GlobalParams)
+            .and_then(|params| params.start_day_of_week)
+            .unwrap_or(0)
+    }
+    /// The day of the epidemic that the current time falls in
+    fn current_day(&self) -> u64 {
+        day_of_epidemic(self.get_current_time())
+    }
+    /// The day of the week that the current time falls in, from 0 (Monday) to 6 (Sunday)
+    fn current_day_of_week(&self) -> u8 {
+        day_of_week(self.get_current_time(), self.get_start_day_of_week())
+    }
+}
+impl ContextCalendarExt for ixa::Context {}
+
+#[cfg(test)]
+mod test {
+    use ixa::{Context, ContextGlobalPropertiesExt};
+
+    use super::{day_of_epidemic, day_of_week, ContextCalendarExt};
+    use crate::parameters::{GlobalParams, Params};
+
+    #[test]
+    fn test_day_of_week_cycles_from_start_offset() {
+        let mut context = Context::new();
+        context
+            .set_global_property_value(
+                GlobalParams,
+                Params {
+                    max_time: 20.0,
+                    // Day 0 is a Wednesday
+                    start_day_of_week: Some(2),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        for day in 0..15u32 {
+            context.add_plan(f64::from(day) + 0.5, move |context| {
+                assert_eq!(context.current_day(), u64::from(day));
+                assert_eq!(
+                    context.current_day_of_week(),
+                    u8::try_from((day + 2) % 7).unwrap()
+                );
+            });
+        }
+        context.execute();
+        assert_eq!(day_of_week(4.0, 2), 6);
+        assert_eq!(day_of_week(5.0, 2), 0);
+        assert_eq!(day_of_week(-1.0, 2), 1);
+        assert_eq!(day_of_epidemic(-0.5), 0);
+    }
+}
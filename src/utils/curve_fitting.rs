@@ -1,3 +1,5 @@
+use super::calendar::day_of_epidemic;
+
 /// Linear interpolation between two points. Returns the average y value when `x1 == x2`.
 #[must_use]
 pub fn linear_interpolation(x1: f64, x2: f64, y1: f64, y2: f64, xp: f64) -> f64 {
@@ -10,11 +12,90 @@ pub fn linear_interpolation(x1: f64, x2: f64, y1: f64, y2: f64, xp: f64) -> f64
     }
     y1 + (y2 - y1) / (x2 - x1) * (xp - x1)
 }
+
+/// Fits exponential growth to a series of event times (e.g., infections or hospital admissions)
+/// and returns the growth rate per unit time. The events are counted by day of the epidemic, and
+/// the growth rate is the least-squares slope of the log daily counts over the growth phase, from
+/// the first day with events to the day with the most events. Days without events in the growth
+/// phase are skipped. Returns `None` if fewer than two days in the growth phase have events.
+#[allow(clippy::cast_precision_loss)]
+#[must_use]
+pub fn exponential_growth_rate(event_times: &[f64]) -> Option<f64> {
+    let mut daily_counts: Vec<usize> = Vec::new();
+    for &time in event_times {
+        let day = usize::try_from(day_of_epidemic(time)).ok()?;
+        if day >= daily_counts.len() {
+            daily_counts.resize(day + 1, 0);
+        }
+        daily_counts[day] += 1;
+    }
+    // The first day with the most events ends the growth phase
+    let peak_day = daily_counts
+        .iter()
+        .enumerate()
+        .rev()
+        .max_by_key(|&(_, &count)| count)?
+        .0;
+    let points: Vec<(f64, f64)> = daily_counts[..=peak_day]
+        .iter()
+        .enumerate()
+        .filter(|&(_, &count)| count > 0)
+        .map(|(day, &count)| (day as f64, (count as f64).ln()))
+        .collect();
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_day = points.iter().map(|&(day, _)| day).sum::<f64>() / n;
+    let mean_log_count = points.iter().map(|&(_, log_count)| log_count).sum::<f64>() / n;
+    let covariance: f64 = points
+        .iter()
+        .map(|&(day, log_count)| (day - mean_day) * (log_count - mean_log_count))
+        .sum();
+    let variance: f64 = points
+        .iter()
+        .map(|&(day, _)| (day - mean_day).powi(2))
+        .sum();
+    Some(covariance / variance)
+}
+
+/// The time for a series of events growing exponentially to double, from the growth rate fit by
+/// `exponential_growth_rate`. Returns `None` if the growth rate cannot be fit or is not positive.
+#[must_use]
+pub fn doubling_time(event_times: &[f64]) -> Option<f64> {
+    exponential_growth_rate(event_times)
+        .filter(|&growth_rate| growth_rate > 0.0)
+        .map(|growth_rate| std::f64::consts::LN_2 / growth_rate)
+}
 #[cfg(test)]
 mod test {
     use ixa::assert_almost_eq;
 
-    use super::linear_interpolation;
+    use super::{doubling_time, exponential_growth_rate, linear_interpolation};
+
+    /// Event times with `initial * growth^day` events (rounded down) spread evenly over each day
+    /// before `days`, delayed by `lag` and thinned to every `stride`th event
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn exponential_event_times(
+        initial: f64,
+        growth: f64,
+        days: i32,
+        lag: f64,
+        stride: usize,
+    ) -> Vec<f64> {
+        let mut times = Vec::new();
+        for day in 0..days {
+            let count = (initial * growth.powi(day)).floor() as usize;
+            for i in 0..count {
+                times.push(f64::from(day) + lag + i as f64 / count as f64);
+            }
+        }
+        times.into_iter().step_by(stride).collect()
+    }
 
     #[test]
     fn test_linear_interpolation_simple() {
@@ -33,4 +114,36 @@ mod test {
         let result = linear_interpolation(1.0, 1.0, 3.0, 6.0, 1.0);
         assert_almost_eq!(result, 4.5, 0.0);
     }
+
+    #[test]
+    fn test_exponential_growth_rate_of_doubling_counts() {
+        // 1, 2, 4, 8 events on days 0 to 3, then a decline that is not part of the fit
+        let times = [
+            0.5, 1.2, 1.7, 2.1, 2.3, 2.5, 2.7, 3.0, 3.1, 3.2, 3.3, 3.4, 3.5, 3.6, 3.7, 4.5,
+        ];
+        assert_almost_eq!(
+            exponential_growth_rate(&times).unwrap(),
+            std::f64::consts::LN_2,
+            1e-12
+        );
+        assert_almost_eq!(doubling_time(&times).unwrap(), 1.0, 1e-12);
+        assert!(exponential_growth_rate(&[0.5, 0.7]).is_none());
+    }
+
+    #[test]
+    fn test_lagged_admissions_have_infection_doubling_time() {
+        // Admissions are one in ten infections a week later, so in the exponential phase they
+        // double at the same rate as infections
+        let infections = exponential_event_times(50.0, 1.15, 25, 0.0, 1);
+        let admissions = exponential_event_times(50.0, 1.15, 25, 7.0, 10);
+        let infection_doubling_time = doubling_time(&infections).unwrap();
+        let admission_doubling_time = doubling_time(&admissions).unwrap();
+        let expected = std::f64::consts::LN_2 / 1.15_f64.ln();
+        assert_almost_eq!(infection_doubling_time, expected, 0.05 * expected);
+        assert_almost_eq!(
+            admission_doubling_time,
+            infection_doubling_time,
+            0.05 * infection_doubling_time
+        );
+    }
 }
@@ -1,6 +1,15 @@
+pub mod calendar;
+pub use calendar::ContextCalendarExt;
+
 pub mod curve_fitting;
+pub use curve_fitting::doubling_time;
 pub use curve_fitting::linear_interpolation;
 
 pub mod numeric_integrators;
 pub use numeric_integrators::cumulative_trapezoid_integral;
 pub use numeric_integrators::trapezoid_integral;
+
+#[cfg(test)]
+pub mod seed_context;
+#[cfg(test)]
+pub use seed_context::with_seed_context;
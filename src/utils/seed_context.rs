@@ -0,0 +1,60 @@
+//! Test support for reproducing failures of stochastic tests. Tests that loop over seeds can wrap
+//! the body of each iteration in `with_seed_context` so that a failed assertion reports the seed
+//! of the offending simulation, which can then be replayed on its own.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Runs `f`, the part of a test that simulates with the given `seed`. If `f` panics (e.g., on a
+/// failed `assert_almost_eq!`), the panic is re-raised with the seed prepended to its message.
+/// # Panics
+/// - If `f` panics
+pub fn with_seed_context<T>(seed: u64, f: impl FnOnce() -> T) -> T {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(value) => value,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| (*message).to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            panic!("Failure with seed {seed}: {message}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::panic::catch_unwind;
+
+    use ixa::{assert_almost_eq, Context, ContextRandomExt};
+
+    use super::with_seed_context;
+
+    ixa::define_rng!(SeedContextRng);
+
+    #[test]
+    fn test_with_seed_context_returns_value() {
+        let value = with_seed_context(3, || {
+            let mut context = Context::new();
+            context.init_random(3);
+            context.sample_range(SeedContextRng, 0..10)
+        });
+        assert!(value < 10);
+    }
+
+    #[test]
+    fn test_with_seed_context_reports_seed_on_failure() {
+        let result = catch_unwind(|| {
+            for seed in 0..10 {
+                with_seed_context(seed, || {
+                    // Force a failure on one of the seeds
+                    let value = if seed == 7 { 2.0 } else { 1.0 };
+                    assert_almost_eq!(value, 1.0, 0.1);
+                });
+            }
+        });
+        let payload = result.expect_err("Expected the forced failure to panic");
+        let message = payload.downcast_ref::<String>().unwrap();
+        assert!(message.starts_with("Failure with seed 7: "));
+    }
+}